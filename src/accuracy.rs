@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::clone::find_milestone;
+use crate::models::{WorkItem, WorkLog};
+use crate::{
+    client, get_available_boards, get_available_milestones, get_json, get_work_items, output, Id,
+};
+
+#[derive(Args, Debug)]
+pub struct AccuracyArguments {
+    /// Milestone whose work items are compared
+    #[arg(long)]
+    milestone: String,
+
+    /// Also compare work items that aren't completed yet
+    #[arg(long)]
+    include_open: bool,
+
+    /// Also write the comparison per work item to a CSV file
+    #[arg(long, value_name = "FILE")]
+    csv: Option<PathBuf>,
+}
+
+/// Estimated and logged hours of one work item
+struct Row<'a> {
+    work_item: &'a WorkItem,
+    estimated: f32,
+    logged: f32,
+    /// Logged hours by username
+    logged_by: BTreeMap<String, f32>,
+}
+
+/// Compares the estimates of the work items in a milestone to the time logged on them, per work
+/// item and per person
+pub async fn run(accuracy_arguments: &AccuracyArguments) {
+    let client = client();
+    let spinner = output::spinner("Fetching work items and logged time from Hack'n'Plan");
+    let milestone_id = find_milestone(
+        &accuracy_arguments.milestone,
+        &get_available_milestones(&client).await,
+    );
+    let board_ids = get_available_boards(&client)
+        .await
+        .into_iter()
+        .filter(|(_, _, board_milestone_id)| *board_milestone_id == Some(milestone_id))
+        .map(|(id, _, _)| id)
+        .collect::<Vec<Id>>();
+
+    // NOTE: Stories log their time on their children, so only those are compared
+    let work_items = get_work_items::<WorkItem>(&client)
+        .await
+        .into_iter()
+        .filter(|work_item| {
+            work_item
+                .board
+                .as_ref()
+                .is_some_and(|board| board_ids.contains(&board.board_id))
+        })
+        .filter(|work_item| !work_item.is_story)
+        .filter(|work_item| accuracy_arguments.include_open || work_item.is_completed())
+        .collect::<Vec<WorkItem>>();
+
+    let mut rows = vec![];
+    for work_item in &work_items {
+        let work_logs: Vec<WorkLog> = serde_json::from_value(
+            get_json(
+                &client,
+                &format!("/workitems/{}/worklogs", work_item.work_item_id),
+            )
+            .await,
+        )
+        .unwrap_or_else(|error| panic!("To deserialize work logs: {error}"));
+
+        let mut logged_by = BTreeMap::new();
+        for work_log in &work_logs {
+            let user_name = work_log
+                .user
+                .as_ref()
+                .map_or("unknown".to_owned(), |user| user.username.clone());
+            *logged_by.entry(user_name).or_insert(0.0) += work_log.value;
+        }
+
+        rows.push(Row {
+            work_item,
+            estimated: work_item.estimated_cost.unwrap_or_default(),
+            logged: work_logs.iter().map(|work_log| work_log.value).sum(),
+            logged_by,
+        });
+    }
+    spinner.finish_and_clear();
+
+    if rows.is_empty() {
+        output::warn(format!(
+            "No {}work items in \"{}\" to compare",
+            if accuracy_arguments.include_open {
+                ""
+            } else {
+                "completed "
+            },
+            accuracy_arguments.milestone
+        ));
+        return;
+    }
+
+    println!(
+        "{:<8} {:<40} {:>9} {:>9} {:>9}",
+        "ID", "Title", "Estimated", "Logged", "Ratio"
+    );
+    for row in &rows {
+        println!(
+            "{:<8} {:<40} {:>9} {:>9} {:>9}",
+            format!("#{}", row.work_item.work_item_id),
+            truncate(&row.work_item.title, 40),
+            hours(row.estimated),
+            hours(row.logged),
+            ratio(row.estimated, row.logged)
+        );
+    }
+
+    println!();
+    println!(
+        "{:<20} {:>9} {:>9} {:>9} {:>9}",
+        "Person", "Items", "Estimated", "Logged", "Ratio"
+    );
+    for (user_name, (items, estimated, logged)) in per_person(&rows) {
+        println!(
+            "{:<20} {:>9} {:>9} {:>9} {:>9}",
+            format!("@{user_name}"),
+            items,
+            hours(estimated),
+            hours(logged),
+            ratio(estimated, logged)
+        );
+    }
+
+    let estimated = rows.iter().map(|row| row.estimated).sum::<f32>();
+    let logged = rows.iter().map(|row| row.logged).sum::<f32>();
+    println!();
+    output::info(format!(
+        "{} work item(s) estimated at {} took {}, {} of the estimate",
+        rows.len(),
+        hours(estimated),
+        hours(logged),
+        ratio(estimated, logged)
+    ));
+
+    if let Some(path) = &accuracy_arguments.csv {
+        fs::write(path, csv(&rows)).unwrap_or_else(|_| panic!("To write to {}", path.display()));
+        output::success(format!("Wrote the comparison to {}", path.display()));
+    }
+}
+
+/// Number of work items, estimated and logged hours by username. The estimate of a work item is
+/// split evenly among its assignees, the logged time goes to whoever logged it.
+fn per_person(rows: &[Row]) -> BTreeMap<String, (usize, f32, f32)> {
+    let mut people: BTreeMap<String, (usize, f32, f32)> = BTreeMap::new();
+
+    for row in rows {
+        let assignees = &row.work_item.assigned_users;
+        for assigned_user in assignees {
+            let person = people
+                .entry(assigned_user.user.username.clone())
+                .or_default();
+            person.0 += 1;
+            person.1 += row.estimated / assignees.len() as f32;
+        }
+
+        for (user_name, logged) in &row.logged_by {
+            people.entry(user_name.to_owned()).or_default().2 += logged;
+        }
+    }
+
+    people
+}
+
+fn csv(rows: &[Row]) -> String {
+    let mut csv = "id,title,assignees,estimated,logged,difference\n".to_owned();
+
+    for row in rows {
+        let assignees = row
+            .work_item
+            .assigned_users
+            .iter()
+            .map(|assigned_user| assigned_user.user.username.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ");
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            row.work_item.work_item_id,
+            csv_field(&row.work_item.title),
+            csv_field(&assignees),
+            row.estimated,
+            row.logged,
+            row.logged - row.estimated
+        ));
+    }
+
+    csv
+}
+
+/// `field` quoted when it has commas, quotes or newlines
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn hours(hours: f32) -> String {
+    format!("{:.1}h", hours)
+}
+
+/// Logged time as a percentage of the estimate, `—` without an estimate
+fn ratio(estimated: f32, logged: f32) -> String {
+    if estimated > 0.0 {
+        format!("{:.0}%", logged / estimated * 100.0)
+    } else {
+        "—".to_owned()
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_owned()
+    } else {
+        format!("{}…", text.chars().take(width - 1).collect::<String>())
+    }
+}
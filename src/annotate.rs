@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{text, Id};
+
+lazy_static! {
+    /// `<!-- hnp: 1234 -->` anywhere in a ticket, the marker `hnp sync` and `hnp export github`
+    /// use as well
+    static ref COMMENT_MATCHER: Regex =
+        Regex::new(r"[ \t]*<!--\s*hnp:\s*\d+\s*-->").expect("Comment Regex to compile");
+    /// `[HNP-1234]` in front of a title
+    static ref PREFIX_MATCHER: Regex =
+        Regex::new(r"^(?<leading>\s*)\[HNP-\d+\][ \t]*").expect("Prefix Regex to compile");
+}
+
+/// How `--annotate` marks the tickets in the file with the work items created for them
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Annotation {
+    /// Write `<!-- hnp: 1234 -->` behind the title, hidden when the Markdown is rendered
+    Comment,
+    /// Write `[HNP-1234]` in front of the title
+    Prefix,
+}
+
+/// Leaves the annotations out of `text`, so annotated tickets read the same as before
+pub fn strip(text: &str) -> String {
+    let text = COMMENT_MATCHER.replace_all(text, "");
+    PREFIX_MATCHER.replace(&text, "$leading").into_owned()
+}
+
+/// Marks the title on each line of `created` in the file at `path` with the id of its work
+/// item. Lines count from 1, an annotation already on the line is replaced.
+pub fn write(path: &Path, annotation: Annotation, created: &[(usize, Id)]) {
+    let (contents, encoding) = text::read_as_is(path);
+
+    let contents = contents
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            let Some((_, work_item_id)) = created.iter().find(|(number, _)| *number == index + 1)
+            else {
+                return line.to_owned();
+            };
+
+            let content = line.trim_end_matches(['\n', '\r']);
+            let ending = &line[content.len()..];
+            let content = strip(content);
+            let (leading, title) = content.split_at(content.len() - content.trim_start().len());
+
+            match annotation {
+                Annotation::Comment => {
+                    format!(
+                        "{leading}{} <!-- hnp: {work_item_id} -->{ending}",
+                        title.trim_end()
+                    )
+                }
+                Annotation::Prefix => format!("{leading}[HNP-{work_item_id}] {title}{ending}"),
+            }
+        })
+        .collect::<String>();
+
+    text::write(path, &contents, encoding);
+}
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::i18n::{Action, Message};
+use crate::{client, get_json, output, patch_json, post_json, Arguments, Id};
+
+#[derive(Args, Debug)]
+pub struct ApplyArguments {
+    /// Spec file declaring the categories, tags, importance levels, milestones and boards
+    spec: PathBuf,
+
+    /// Apply the changes without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// Declarative setup of a project, see `example_project.toml`
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Spec {
+    categories: Vec<Named>,
+    tags: Vec<TagSpec>,
+    importance_levels: Vec<Named>,
+    milestones: Vec<MilestoneSpec>,
+    boards: Vec<BoardSpec>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Named {
+    name: String,
+    /// Names it might still have on Hack'n'Plan, which then get renamed to `name`
+    #[serde(default)]
+    previous_names: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct TagSpec {
+    #[serde(flatten)]
+    named: Named,
+    color: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct MilestoneSpec {
+    #[serde(flatten)]
+    named: Named,
+    description: Option<String>,
+    start_date: Option<toml::value::Datetime>,
+    due_date: Option<toml::value::Datetime>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct BoardSpec {
+    #[serde(flatten)]
+    named: Named,
+    description: Option<String>,
+    /// Name of the milestone the board belongs to
+    milestone: Option<String>,
+}
+
+/// Something in the spec together with the fields it should have on Hack'n'Plan
+struct Desired<'a> {
+    named: &'a Named,
+    fields: Map<String, Value>,
+    milestone: Option<&'a str>,
+}
+
+enum Change {
+    Create(Map<String, Value>),
+    Update(Id, Map<String, Value>),
+}
+
+/// Collections in the order they're applied in, so milestones exist before their boards
+const COLLECTIONS: [(&str, &str, &str); 5] = [
+    ("categories", "categoryId", "category"),
+    ("tags", "tagId", "tag"),
+    ("importancelevels", "importanceLevelId", "importance level"),
+    ("milestones", "milestoneId", "milestone"),
+    ("boards", "boardId", "board"),
+];
+
+pub async fn run(arguments: &Arguments, apply_arguments: &ApplyArguments) {
+    let contents = fs::read_to_string(&apply_arguments.spec)
+        .unwrap_or_else(|_| panic!("To read spec {}", apply_arguments.spec.display()));
+    let spec: Spec = toml::from_str(&contents).unwrap_or_else(|error| {
+        panic!("To parse spec {}: {error}", apply_arguments.spec.display())
+    });
+
+    let client = client();
+
+    let spinner = output::spinner("Fetching project setup from Hack'n'Plan");
+    let mut existing: HashMap<&str, Vec<Value>> = HashMap::new();
+    for (collection, _, _) in COLLECTIONS {
+        let values = get_json(&client, &format!("/{collection}")).await;
+        existing.insert(
+            collection,
+            values
+                .as_array()
+                .unwrap_or_else(|| panic!("{collection} results to be an array"))
+                .to_owned(),
+        );
+    }
+    spinner.finish_and_clear();
+
+    let desired = desired(&spec);
+
+    let mut changes: Vec<(&str, &str, &str, Change)> = vec![];
+    for (collection, id_field, label) in COLLECTIONS {
+        for desired in &desired[collection] {
+            let change = diff(
+                desired,
+                &existing[collection],
+                id_field,
+                &existing["milestones"],
+            );
+
+            if let Some(change) = change {
+                changes.push((collection, id_field, label, change));
+            }
+        }
+    }
+
+    if changes.is_empty() {
+        output::success("Hack'n'Plan already matches the spec");
+        return;
+    }
+
+    for (collection, id_field, label, change) in &changes {
+        match change {
+            Change::Create(fields) => output::info(format!(
+                "Create {label} \"{}\"{}",
+                fields["name"].as_str().unwrap_or_default(),
+                describe(fields)
+            )),
+            Change::Update(id, fields) => {
+                let current = existing[collection]
+                    .iter()
+                    .find(|value| value.get(*id_field).and_then(Value::as_i64) == Some(*id))
+                    .and_then(|value| value.get("name"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+
+                match fields.get("name").and_then(Value::as_str) {
+                    Some(name) => output::info(format!(
+                        "Rename {label} \"{current}\" to \"{name}\"{}",
+                        describe(fields)
+                    )),
+                    None => {
+                        output::info(format!("Update {label} \"{current}\"{}", describe(fields)))
+                    }
+                }
+            }
+        }
+    }
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if apply_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Changed));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmApply(changes.len()))
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Changed));
+        return;
+    }
+
+    // NOTE: Milestones created by this run are looked up by name when their boards are created
+    let mut milestone_ids = existing["milestones"]
+        .iter()
+        .filter_map(|milestone| {
+            Some((
+                milestone.get("name")?.as_str()?.to_lowercase(),
+                milestone.get("milestoneId")?.as_i64()?,
+            ))
+        })
+        .collect::<HashMap<String, Id>>();
+
+    let progress = output::progress(changes.len(), "Applying spec");
+    for (collection, id_field, _, change) in changes {
+        match change {
+            Change::Create(fields) => {
+                let name = fields["name"].as_str().unwrap_or_default().to_lowercase();
+                let body = resolve_milestone(fields, &milestone_ids);
+                let id = post_json(&client, &format!("/{collection}"), &body, id_field).await;
+
+                if collection == "milestones" {
+                    milestone_ids.insert(name, id);
+                }
+            }
+            Change::Update(id, fields) => {
+                if let Some(name) = fields.get("name").and_then(Value::as_str) {
+                    if collection == "milestones" {
+                        milestone_ids.insert(name.to_lowercase(), id);
+                    }
+                }
+
+                let body = resolve_milestone(fields, &milestone_ids);
+                patch_json(&client, &format!("/{collection}/{id}"), &body).await;
+            }
+        }
+
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    output::success("Applied the spec to Hack'n'Plan");
+}
+
+fn desired(spec: &Spec) -> HashMap<&'static str, Vec<Desired<'_>>> {
+    let named = |named| Desired {
+        named,
+        fields: Map::new(),
+        milestone: None,
+    };
+    // NOTE: Milestones only have a day, so a time written in the spec is left out
+    let date = |date: &Option<toml::value::Datetime>| {
+        date.as_ref()
+            .and_then(|date| date.date)
+            .map(|date| json!(format!("{date}T00:00:00")))
+    };
+
+    let mut desired = HashMap::new();
+    desired.insert(
+        "categories",
+        spec.categories.iter().map(named).collect::<Vec<Desired>>(),
+    );
+    desired.insert(
+        "importancelevels",
+        spec.importance_levels.iter().map(named).collect(),
+    );
+    desired.insert(
+        "tags",
+        spec.tags
+            .iter()
+            .map(|tag| Desired {
+                fields: optional_fields([("color", tag.color.as_ref().map(|color| json!(color)))]),
+                ..named(&tag.named)
+            })
+            .collect(),
+    );
+    desired.insert(
+        "milestones",
+        spec.milestones
+            .iter()
+            .map(|milestone| Desired {
+                fields: optional_fields([
+                    (
+                        "description",
+                        milestone.description.as_ref().map(|text| json!(text)),
+                    ),
+                    ("startDate", date(&milestone.start_date)),
+                    ("dueDate", date(&milestone.due_date)),
+                ]),
+                ..named(&milestone.named)
+            })
+            .collect(),
+    );
+    desired.insert(
+        "boards",
+        spec.boards
+            .iter()
+            .map(|board| Desired {
+                fields: optional_fields([(
+                    "description",
+                    board.description.as_ref().map(|text| json!(text)),
+                )]),
+                milestone: board.milestone.as_deref(),
+                ..named(&board.named)
+            })
+            .collect(),
+    );
+
+    desired
+}
+
+fn optional_fields<const N: usize>(fields: [(&str, Option<Value>); N]) -> Map<String, Value> {
+    fields
+        .into_iter()
+        .filter_map(|(field, value)| Some((field.to_owned(), value?)))
+        .collect()
+}
+
+/// What has to change on Hack'n'Plan for `desired` to be there, if anything
+fn diff(
+    desired: &Desired,
+    existing: &[Value],
+    id_field: &str,
+    milestones: &[Value],
+) -> Option<Change> {
+    let name_of = |value: &Value| value.get("name").and_then(Value::as_str).map(str::to_owned);
+
+    let current = existing
+        .iter()
+        .find(|value| {
+            name_of(value).is_some_and(|name| name.eq_ignore_ascii_case(&desired.named.name))
+        })
+        .or_else(|| {
+            existing.iter().find(|value| {
+                name_of(value).is_some_and(|name| {
+                    desired
+                        .named
+                        .previous_names
+                        .iter()
+                        .any(|previous| previous.eq_ignore_ascii_case(&name))
+                })
+            })
+        });
+
+    let mut fields = desired.fields.clone();
+    if let Some(milestone) = desired.milestone {
+        // NOTE: Resolved into an id when applying, the milestone might not exist yet
+        fields.insert("milestoneId".to_owned(), json!(milestone));
+    }
+
+    let Some(current) = current else {
+        fields.insert("name".to_owned(), json!(desired.named.name));
+        return Some(Change::Create(fields));
+    };
+
+    let current_milestone = current
+        .get("milestoneId")
+        .and_then(Value::as_i64)
+        .and_then(|id| {
+            milestones
+                .iter()
+                .find(|milestone| milestone.get("milestoneId").and_then(Value::as_i64) == Some(id))
+        })
+        .and_then(name_of);
+
+    fields.retain(|field, value| match field.as_str() {
+        "milestoneId" => current_milestone.as_ref().is_none_or(|current| {
+            !value
+                .as_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case(current))
+        }),
+        _ => current.get(field) != Some(value),
+    });
+
+    if name_of(current).as_deref() != Some(desired.named.name.as_str()) {
+        fields.insert("name".to_owned(), json!(desired.named.name));
+    }
+
+    if fields.is_empty() {
+        return None;
+    }
+
+    let id = current
+        .get(id_field)
+        .and_then(Value::as_i64)
+        .unwrap_or_else(|| panic!("{id_field} to be available"));
+
+    Some(Change::Update(id, fields))
+}
+
+/// Replaces the milestone name in `fields` by its id
+fn resolve_milestone(mut fields: Map<String, Value>, milestone_ids: &HashMap<String, Id>) -> Value {
+    if let Some(Value::String(milestone)) = fields.get("milestoneId") {
+        let id = milestone_ids
+            .get(&milestone.to_lowercase())
+            .unwrap_or_else(|| panic!("To find milestone \"{milestone}\""));
+        fields.insert("milestoneId".to_owned(), json!(id));
+    }
+
+    Value::Object(fields)
+}
+
+/// Fields besides the name that get set, e.g. ` (color: #fff, milestone: Sprint 13)`
+fn describe(fields: &Map<String, Value>) -> String {
+    let details = fields
+        .iter()
+        .filter(|(field, _)| *field != "name")
+        .map(|(field, value)| {
+            let field = if field == "milestoneId" {
+                "milestone"
+            } else {
+                field
+            };
+
+            match value {
+                Value::String(text) => format!("{field}: {text}"),
+                value => format!("{field}: {value}"),
+            }
+        })
+        .collect::<Vec<String>>();
+
+    if details.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", details.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{desired, Spec};
+
+    #[test]
+    fn leaves_the_time_out_of_milestone_dates() {
+        let spec = toml::from_str::<Spec>(
+            "[[milestones]]\nname = \"0.4.0\"\nstart_date = 2026-10-15\n\
+             due_date = 2026-10-28T17:00:00+02:00\n",
+        )
+        .expect("To parse the spec");
+
+        let desired = desired(&spec);
+        let fields = &desired["milestones"][0].fields;
+
+        assert_eq!(fields["startDate"], json!("2026-10-15T00:00:00"));
+        assert_eq!(fields["dueDate"], json!("2026-10-28T00:00:00"));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::config::Config;
+use crate::models::WorkItem;
+use crate::{core, estimate_with_sub_tasks, find_user, get_work_items, output, Id, Ticket};
+
+/// How `--auto-assign` picks a user for each ticket without mentions
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /// Take turns in the order of the pool
+    RoundRobin,
+    /// Give each ticket to whoever has the least estimated work open, counting the tickets
+    /// assigned before it
+    LeastLoaded,
+}
+
+/// Assigns the tickets without mentions to the users in `assignment.pool` of the config
+pub async fn run(
+    client: &reqwest::Client,
+    tickets: &mut [Ticket],
+    metadata: &core::Metadata,
+    config: &Config,
+    strategy: Strategy,
+) {
+    if config.assignment.pool.is_empty() {
+        output::error("--auto-assign needs usernames in `assignment.pool` of the config");
+        output::exit_with_error();
+    }
+
+    divide(client, tickets, metadata, &config.assignment.pool, strategy).await;
+}
+
+/// Assigns the tickets without mentions to the users in `pool`, by username
+pub async fn divide(
+    client: &reqwest::Client,
+    tickets: &mut [Ticket],
+    metadata: &core::Metadata,
+    pool: &[String],
+    strategy: Strategy,
+) {
+    let pool = pool
+        .iter()
+        .map(|user_name| find_user(user_name, &metadata.users))
+        .collect::<Vec<&(Id, String, String)>>();
+
+    // NOTE: Round robin doesn't look at the workload, so it doesn't need the work items
+    let mut loads = vec![0.0; pool.len()];
+    if strategy == Strategy::LeastLoaded {
+        for work_item in get_work_items::<WorkItem>(client)
+            .await
+            .iter()
+            .filter(|work_item| !work_item.is_completed())
+        {
+            let assigned_user_ids = work_item.assigned_user_ids();
+            for (load, (id, _, _)) in loads.iter_mut().zip(&pool) {
+                if assigned_user_ids.contains(id) {
+                    *load += work_item.estimated_cost.unwrap_or_default();
+                }
+            }
+        }
+    }
+
+    // NOTE: Tickets that mention someone are theirs as well
+    for ticket in tickets.iter() {
+        for (load, (id, _, _)) in loads.iter_mut().zip(&pool) {
+            if ticket.assigned_user_ids.contains(id) {
+                *load += estimate_with_sub_tasks(ticket);
+            }
+        }
+    }
+
+    for (turn, ticket) in tickets
+        .iter_mut()
+        .filter(|ticket| ticket.assigned_user_ids.is_empty())
+        .enumerate()
+    {
+        let index = match strategy {
+            Strategy::RoundRobin => turn % pool.len(),
+            // NOTE: Ties go to whoever comes first in the pool
+            Strategy::LeastLoaded => loads
+                .iter()
+                .enumerate()
+                .min_by(|(_, left), (_, right)| left.total_cmp(right))
+                .map_or(0, |(index, _)| index),
+        };
+
+        let (id, _, user_name) = pool[index];
+        loads[index] += estimate_with_sub_tasks(ticket);
+        ticket.assigned_user_ids.push(*id);
+
+        output::info(format!("Assigning \"{}\" to @{user_name}", ticket.title));
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::clone::{parents_first, set_stage, to_sub_tasks, to_ticket};
+use crate::i18n::{Action, Message};
+use crate::models::{self, WorkItem};
+use crate::{
+    client, complete_sub_tasks, current_project_id, get_json, get_work_items, output, patch_json,
+    post_json, upload_ticket, Arguments, Id,
+};
+
+/// Project wide collections in a snapshot, in the order they have to be restored in, with the
+/// field holding their id and the fields copied when restoring them
+pub const COLLECTIONS: [(&str, &str, &[&str]); 8] = [
+    ("categories", "categoryId", &["name", "icon"]),
+    ("tags", "tagId", &["name", "color"]),
+    (
+        "importancelevels",
+        "importanceLevelId",
+        &["name", "isDefault"],
+    ),
+    (
+        "stages",
+        "stageId",
+        &["name", "status", "isUnblocker", "icon"],
+    ),
+    (
+        "milestones",
+        "milestoneId",
+        &["name", "description", "startDate", "dueDate"],
+    ),
+    (
+        "boards",
+        "boardId",
+        &["name", "description", "startDate", "dueDate"],
+    ),
+    (
+        "designelementtypes",
+        "designElementTypeId",
+        &["name", "icon", "color"],
+    ),
+    (
+        "designelements",
+        "designElementId",
+        &["name", "description"],
+    ),
+];
+
+const WORK_ITEMS_DIRECTORY: &str = "workitems";
+
+/// Work item as saved in a snapshot, together with its sub tasks and comments
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotWorkItem {
+    #[serde(flatten)]
+    work_item: WorkItem,
+    #[serde(default)]
+    sub_tasks: Vec<models::SubTask>,
+    #[serde(default)]
+    comments: Vec<Comment>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Comment {
+    text: String,
+}
+
+#[derive(Args, Debug)]
+pub struct BackupArguments {
+    /// Directory to write the snapshot to, one JSON file per collection and per work item
+    #[arg(short, long)]
+    out: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct RestoreArguments {
+    /// Directory with a snapshot made by `hnp backup`
+    snapshot: PathBuf,
+
+    /// Restore without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub async fn backup(backup_arguments: &BackupArguments) {
+    let client = client();
+    let out = &backup_arguments.out;
+
+    fs::create_dir_all(out.join(WORK_ITEMS_DIRECTORY))
+        .unwrap_or_else(|_| panic!("To create snapshot directory {}", out.display()));
+
+    let spinner = output::spinner("Fetching project from Hack'n'Plan");
+    write_json(&out.join("project.json"), &get_json(&client, "").await);
+    write_json(&out.join("users.json"), &get_json(&client, "/users").await);
+    for (collection, _, _) in COLLECTIONS {
+        let values = get_json(&client, &format!("/{collection}")).await;
+        write_json(&out.join(format!("{collection}.json")), &values);
+    }
+
+    // NOTE: Saved exactly as the API returns them, so nothing gets lost that hnp doesn't model
+    let work_items = get_work_items::<Value>(&client).await;
+    spinner.finish_and_clear();
+
+    let progress = output::progress(work_items.len(), "Saving work items");
+    for mut work_item in work_items {
+        let id = work_item
+            .get("workItemId")
+            .and_then(Value::as_i64)
+            .expect("workItemId to be available");
+        let sub_tasks = get_json(&client, &format!("/workitems/{id}/subtasks")).await;
+        let comments = items(get_json(&client, &format!("/workitems/{id}/comments")).await);
+
+        work_item["subTasks"] = sub_tasks;
+        work_item["comments"] = Value::Array(comments);
+
+        write_json(
+            &out.join(WORK_ITEMS_DIRECTORY).join(format!("{id}.json")),
+            &work_item,
+        );
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    output::success(format!("Saved snapshot to {}", out.display()));
+}
+
+pub async fn restore(arguments: &Arguments, restore_arguments: &RestoreArguments) {
+    let client = client();
+    let snapshot = &restore_arguments.snapshot;
+
+    let project = read_json(&snapshot.join("project.json"));
+    let work_items = read_work_items(snapshot);
+
+    output::info(format!(
+        "Restoring \"{}\" with {} work item(s) into project {PROJECT_ID}",
+        project
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default(),
+        work_items.len(),
+        PROJECT_ID = current_project_id()
+    ));
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if restore_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Restored));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmRestore)
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Restored));
+        return;
+    }
+
+    let ids = restore_collections(&client, &COLLECTIONS, |collection| {
+        read_json(&snapshot.join(format!("{collection}.json")))
+    })
+    .await;
+
+    let users = map_users(
+        &read_json(&snapshot.join("users.json")),
+        &get_json(&client, "/users").await,
+    );
+
+    let snapshot_work_items = work_items
+        .iter()
+        .map(|snapshot_work_item| {
+            (
+                snapshot_work_item.work_item.work_item_id,
+                snapshot_work_item,
+            )
+        })
+        .collect::<HashMap<Id, &SnapshotWorkItem>>();
+    let work_item_references = work_items
+        .iter()
+        .map(|snapshot_work_item| &snapshot_work_item.work_item)
+        .collect::<Vec<&WorkItem>>();
+    let mut restored: HashMap<Id, Id> = HashMap::new();
+
+    let progress = output::progress(work_items.len(), "Restoring work items");
+    for work_item in parents_first(&work_item_references) {
+        let snapshot_work_item = snapshot_work_items[&work_item.work_item_id];
+        let sub_tasks = to_sub_tasks(&snapshot_work_item.sub_tasks, true);
+
+        let remap =
+            |collection: &str, id: Id| ids[collection].get(&id).copied().unwrap_or_default();
+
+        let mut ticket = to_ticket(work_item, sub_tasks);
+        ticket.parent_id = restored.get(&ticket.parent_id).copied().unwrap_or_default();
+        ticket.category_id = remap("categories", ticket.category_id);
+        ticket.importance_level_id = remap("importancelevels", ticket.importance_level_id);
+        ticket.board_id = remap("boards", ticket.board_id);
+        ticket.tag_ids = ticket
+            .tag_ids
+            .iter()
+            .filter_map(|id| ids["tags"].get(id).copied())
+            .collect();
+        ticket.assigned_user_ids = ticket
+            .assigned_user_ids
+            .iter()
+            .filter_map(|id| users.get(id).copied())
+            .collect();
+        ticket.design_element_id = work_item
+            .design_element
+            .as_ref()
+            .and_then(|design_element| ids["designelements"].get(&design_element.design_element_id))
+            .copied();
+
+        let id = upload_ticket(&client, &ticket).await;
+        complete_sub_tasks(&client, id, &ticket).await;
+
+        if let Some(stage_id) = work_item
+            .stage
+            .as_ref()
+            .and_then(|stage| ids["stages"].get(&stage.stage_id))
+        {
+            set_stage(&client, id, *stage_id).await;
+        }
+
+        for comment in &snapshot_work_item.comments {
+            let path = format!("/workitems/{id}/comments");
+            post_json(&client, &path, &json!(comment.text), "id").await;
+        }
+
+        restored.insert(work_item.work_item_id, id);
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    // NOTE: Work items can depend on ones restored after them, so dependencies are added once
+    // all of them exist
+    for work_item in &work_item_references {
+        let dependency_ids = work_item
+            .dependency_ids
+            .iter()
+            .filter_map(|id| restored.get(id).copied())
+            .collect::<Vec<Id>>();
+        if dependency_ids.is_empty() {
+            continue;
+        }
+
+        let path = format!("/workitems/{}", restored[&work_item.work_item_id]);
+        patch_json(&client, &path, &json!({ "dependencyIds": dependency_ids })).await;
+    }
+
+    output::success(format!(
+        "Restored {} work item(s) from {}",
+        restored.len(),
+        snapshot.display()
+    ));
+}
+
+/// Creates the entries of `collections` read from a snapshot by `read` that don't exist by name
+/// yet. Ids differ between projects, so this returns the ids in the project by the id in the
+/// snapshot for each collection.
+pub async fn restore_collections(
+    client: &reqwest::Client,
+    collections: &[(&'static str, &str, &[&str])],
+    read: impl Fn(&str) -> Value,
+) -> HashMap<&'static str, HashMap<Id, Id>> {
+    let mut ids: HashMap<&str, HashMap<Id, Id>> = HashMap::new();
+    for (collection, id_field, fields) in collections {
+        let values = read(collection);
+        let existing = get_json(client, &format!("/{collection}")).await;
+
+        let mut mapping = HashMap::new();
+        for value in parents_first_by(&items(values), id_field) {
+            let Some(id) = value.get(id_field).and_then(Value::as_i64) else {
+                continue;
+            };
+
+            let mut body = fields
+                .iter()
+                .filter_map(|field| Some((field.to_string(), value.get(*field)?.clone())))
+                .collect::<Map<String, Value>>();
+
+            // NOTE: Boards refer to their milestone, design elements to their type and parent
+            if let Some(milestone_id) = value
+                .get("milestoneId")
+                .and_then(Value::as_i64)
+                .filter(|_| *collection == "boards")
+            {
+                body.insert(
+                    "milestoneId".to_owned(),
+                    json!(ids.get("milestones").and_then(|ids| ids.get(&milestone_id))),
+                );
+            }
+            if let Some(type_id) = value.get("typeId").and_then(Value::as_i64) {
+                body.insert(
+                    "typeId".to_owned(),
+                    json!(ids
+                        .get("designelementtypes")
+                        .and_then(|ids| ids.get(&type_id))),
+                );
+            }
+            if let Some(parent_id) = value.get("parentId").and_then(Value::as_i64) {
+                body.insert("parentId".to_owned(), json!(mapping.get(&parent_id)));
+            }
+
+            let restored_id = match find_by_name(&existing, &value, id_field) {
+                Some(restored_id) => restored_id,
+                None => {
+                    let path = format!("/{collection}");
+                    post_json(client, &path, &Value::Object(body), id_field).await
+                }
+            };
+            mapping.insert(id, restored_id);
+        }
+
+        ids.insert(collection, mapping);
+    }
+
+    ids
+}
+
+/// Assigned users can't be created, so users are matched on their username
+fn map_users(snapshot: &Value, existing: &Value) -> HashMap<Id, Id> {
+    let username = |user: &Value| {
+        user.pointer("/user/username")
+            .and_then(Value::as_str)
+            .map(str::to_lowercase)
+    };
+    let id = |user: &Value| user.pointer("/user/id").and_then(Value::as_i64);
+
+    let existing = items(existing.clone());
+    items(snapshot.clone())
+        .iter()
+        .filter_map(|user| {
+            let restored = existing.iter().find(|existing| {
+                username(existing).is_some() && username(existing) == username(user)
+            });
+
+            match restored {
+                Some(restored) => Some((id(user)?, id(restored)?)),
+                None => {
+                    output::warn(format!(
+                        "User \"{}\" isn't part of the project, their assignments are skipped",
+                        username(user).unwrap_or_default()
+                    ));
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Id of the value in `existing` with the same name as `value`, so restoring twice reuses them
+fn find_by_name(existing: &Value, value: &Value, id_field: &str) -> Option<Id> {
+    let name = value.get("name").and_then(Value::as_str)?;
+
+    existing
+        .as_array()?
+        .iter()
+        .find(|existing| {
+            existing
+                .get("name")
+                .and_then(Value::as_str)
+                .is_some_and(|existing| existing.eq_ignore_ascii_case(name))
+        })?
+        .get(id_field)?
+        .as_i64()
+}
+
+/// Orders `values` so those referred to by a `parentId` come first
+fn parents_first_by(values: &[Value], id_field: &str) -> Vec<Value> {
+    let mut ordered: Vec<Value> = vec![];
+    let mut pending = values.to_vec();
+
+    while !pending.is_empty() {
+        let placed = ordered
+            .iter()
+            .filter_map(|value| value.get(id_field).and_then(Value::as_i64))
+            .collect::<Vec<Id>>();
+        let known = values
+            .iter()
+            .filter_map(|value| value.get(id_field).and_then(Value::as_i64))
+            .collect::<Vec<Id>>();
+
+        let (ready, waiting): (Vec<Value>, Vec<Value>) = pending.into_iter().partition(|value| {
+            value
+                .get("parentId")
+                .and_then(Value::as_i64)
+                .is_none_or(|parent_id| !known.contains(&parent_id) || placed.contains(&parent_id))
+        });
+
+        if ready.is_empty() {
+            panic!("To not find a loop in the parents of the snapshot");
+        }
+
+        ordered.extend(ready);
+        pending = waiting;
+    }
+
+    ordered
+}
+
+fn read_work_items(snapshot: &Path) -> Vec<SnapshotWorkItem> {
+    let directory = snapshot.join(WORK_ITEMS_DIRECTORY);
+
+    let mut work_items = fs::read_dir(&directory)
+        .unwrap_or_else(|_| panic!("To read work items from {}", directory.display()))
+        .map(|entry| entry.expect("To read snapshot directory entry").path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .map(|path| {
+            serde_json::from_value(read_json(&path)).unwrap_or_else(|error| {
+                panic!(
+                    "To parse work item in snapshot file {}: {error}",
+                    path.display()
+                )
+            })
+        })
+        .collect::<Vec<SnapshotWorkItem>>();
+
+    // NOTE: Restores in the order the work items were created in
+    work_items.sort_by_key(|snapshot_work_item| snapshot_work_item.work_item.work_item_id);
+
+    work_items
+}
+
+/// Elements of a paginated (`{ items: [...] }`) or plain array response
+fn items(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(values) => values,
+        Value::Object(mut object) => match object.remove("items") {
+            Some(Value::Array(values)) => values,
+            _ => vec![],
+        },
+        _ => vec![],
+    }
+}
+
+fn read_json(path: &Path) -> Value {
+    let contents = fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("To read snapshot file {}", path.display()));
+
+    serde_json::from_str(&contents)
+        .unwrap_or_else(|error| panic!("To parse snapshot file {}: {error}", path.display()))
+}
+
+fn write_json(path: &Path, value: &Value) {
+    let contents = serde_json::to_string_pretty(value).expect("To serialize snapshot");
+
+    fs::write(path, contents + "\n")
+        .unwrap_or_else(|_| panic!("To write snapshot file {}", path.display()));
+}
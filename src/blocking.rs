@@ -0,0 +1,94 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::client::{user_agent, DEFAULT_API_ENDPOINT, DEFAULT_TIMEOUT};
+use crate::models;
+use crate::Id;
+
+/// Synchronous version of [`crate::client::Client`], for build scripts and plugins that can't
+/// run an async runtime. Don't use it from within one, reqwest panics when it's blocked on.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::blocking::Client,
+    endpoint: String,
+    api_key: String,
+    project_id: Id,
+}
+
+impl Client {
+    pub fn new(api_key: impl Into<String>, project_id: Id) -> Client {
+        Client {
+            http: reqwest::blocking::Client::builder()
+                .user_agent(user_agent(None))
+                .timeout(DEFAULT_TIMEOUT)
+                .build()
+                .expect("To build the HTTP client"),
+            endpoint: DEFAULT_API_ENDPOINT.to_owned(),
+            api_key: api_key.into(),
+            project_id,
+        }
+    }
+
+    /// Sends requests to `endpoint` instead of Hack'n'Plan's own API, for proxies and mocks
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Client {
+        self.endpoint = endpoint.into().trim_end_matches('/').to_owned();
+        self
+    }
+
+    pub fn project_id(&self) -> Id {
+        self.project_id
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::blocking::RequestBuilder {
+        self.http
+            .request(
+                method,
+                format!("{}/projects/{}{path}", self.endpoint, self.project_id),
+            )
+            .header("Authorization", format!("ApiKey {}", self.api_key))
+    }
+
+    /// A collection of the project like `categories`, `tags` or `boards`, deserialized into its
+    /// model
+    pub fn collection<T: DeserializeOwned>(&self, collection: &str) -> reqwest::Result<T> {
+        self.request(reqwest::Method::GET, &format!("/{collection}"))
+            .send()?
+            .error_for_status()?
+            .json::<T>()
+    }
+
+    /// All work items in the project, fetched page by page
+    pub fn work_items(&self) -> reqwest::Result<Vec<models::WorkItem>> {
+        const PAGE_SIZE: usize = 100;
+
+        let mut work_items = vec![];
+        loop {
+            let page = self
+                .request(reqwest::Method::GET, "/workitems")
+                .query(&[("offset", work_items.len()), ("limit", PAGE_SIZE)])
+                .send()?
+                .error_for_status()?
+                .json::<models::Page<models::WorkItem>>()?;
+
+            let count = page.items.len();
+            work_items.extend(page.items);
+
+            if count < PAGE_SIZE {
+                return Ok(work_items);
+            }
+        }
+    }
+
+    /// Creates a work item from `work_item`, a body like `{"title": ..., "categoryId": ...}` as
+    /// the API expects it
+    pub fn create_work_item(
+        &self,
+        work_item: &impl Serialize,
+    ) -> reqwest::Result<models::WorkItem> {
+        self.request(reqwest::Method::POST, "/workitems")
+            .json(work_item)
+            .send()?
+            .error_for_status()?
+            .json::<models::WorkItem>()
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use clap::{Args, Subcommand};
+use serde_json::json;
+
+use crate::clone::{clone_work_items, find_milestone, CloneOptions};
+use crate::i18n::{Action, Message};
+use crate::models::{self, WorkItem};
+use crate::recording::SendRecorded;
+use crate::{
+    api_endpoint, client, current_project_id, get_available_boards, get_available_milestones,
+    get_work_items, output, Arguments, Id, API_KEY,
+};
+
+#[derive(Subcommand, Debug)]
+pub enum BoardCommand {
+    /// Copy a board together with all of its work items
+    Clone(BoardCloneArguments),
+}
+
+#[derive(Args, Debug)]
+pub struct BoardCloneArguments {
+    /// Name of the board to clone
+    board: String,
+
+    /// Name of the new board
+    #[arg(long = "as", value_name = "NAME")]
+    name: String,
+
+    /// Milestone of the new board, defaults to the milestone of the original
+    #[arg(long, value_name = "MILESTONE")]
+    into_milestone: Option<String>,
+
+    /// Start all work items in the first stage and with their sub tasks unchecked
+    #[arg(long)]
+    reset_stages: bool,
+
+    /// Also copy the sub tasks
+    #[arg(long)]
+    sub_tasks: bool,
+
+    /// Clone the board without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub async fn run(arguments: &Arguments, command: &BoardCommand) {
+    match command {
+        BoardCommand::Clone(clone_arguments) => clone(arguments, clone_arguments).await,
+    }
+}
+
+async fn clone(arguments: &Arguments, clone_arguments: &BoardCloneArguments) {
+    let client = client();
+
+    let spinner = output::spinner("Fetching boards and work items from Hack'n'Plan");
+    let available_boards = get_available_boards(&client).await;
+    let available_milestones = get_available_milestones(&client).await;
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    let Some((board_id, board_name, milestone_id)) = available_boards
+        .iter()
+        .find(|(_, name, _)| name.eq_ignore_ascii_case(&clone_arguments.board))
+    else {
+        output::error(format!(
+            "Could not find board \"{}\" on Hack'n'Plan",
+            clone_arguments.board
+        ));
+        output::exit_with_error();
+    };
+
+    if available_boards
+        .iter()
+        .any(|(_, name, _)| name.eq_ignore_ascii_case(&clone_arguments.name))
+    {
+        output::error(format!(
+            "A board named \"{}\" already exists, nothing was cloned",
+            clone_arguments.name
+        ));
+        output::exit_with_error();
+    }
+
+    let milestone_id = clone_arguments
+        .into_milestone
+        .as_ref()
+        .map(|milestone| find_milestone(milestone, &available_milestones))
+        .or(*milestone_id);
+
+    let board_work_items = work_items
+        .iter()
+        .filter(|work_item| work_item.board.as_ref().map(|board| board.board_id) == Some(*board_id))
+        .collect::<Vec<&WorkItem>>();
+
+    output::info(format!(
+        "Cloning board \"{board_name}\" as \"{}\" with {} work item(s)",
+        clone_arguments.name,
+        board_work_items.len()
+    ));
+    for work_item in &board_work_items {
+        println!("    #{} {}", work_item.work_item_id, work_item.title);
+    }
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if clone_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Cloned));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmCloneBoard {
+            board: board_name,
+            work_items: board_work_items.len(),
+        })
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Cloned));
+        return;
+    }
+
+    let new_board_id = create_board(&client, &clone_arguments.name, milestone_id).await;
+
+    let options = CloneOptions {
+        sub_tasks: clone_arguments.sub_tasks,
+        reset_stages: clone_arguments.reset_stages,
+        estimates: HashMap::new(),
+        from_project: None,
+    };
+    clone_work_items(&client, &board_work_items, Some(new_board_id), &options).await;
+
+    output::success(format!(
+        "Cloned board \"{board_name}\" as \"{}\" with {} work item(s)",
+        clone_arguments.name,
+        board_work_items.len()
+    ));
+}
+
+async fn create_board(client: &reqwest::Client, name: &str, milestone_id: Option<Id>) -> Id {
+    client
+        .post(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}/boards",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
+        ))
+        .header(
+            "Authorization",
+            format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+        )
+        .json(&json!({ "name": name, "milestoneId": milestone_id }))
+        .send_recorded()
+        .await
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|error| panic!("to create board \"{name}\": {error}"))
+        .json::<models::Board>()
+        .await
+        .unwrap_or_else(|error| panic!("To deserialize created board \"{name}\": {error}"))
+        .board_id
+}
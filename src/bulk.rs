@@ -0,0 +1,378 @@
+use clap::Args;
+use serde_json::json;
+
+use crate::config::Config;
+use crate::filter::Filter;
+use crate::i18n::{Action, Message};
+use crate::models::WorkItem;
+use crate::recording::SendRecorded;
+use crate::{
+    api_endpoint, client, current_project_id, find_importance_level, find_user,
+    get_available_categories, get_available_importance_levels, get_available_tags,
+    get_available_users, get_work_items, normalize_name, output, Arguments, Id, API_KEY,
+};
+
+#[derive(Args, Debug)]
+pub struct BulkArguments {
+    /// Work items to change, e.g. `tag:audio stage:Backlog -is:completed @bram`
+    #[arg(short, long, required_unless_present = "ids")]
+    filter: Option<String>,
+
+    /// Ids of the work items to change, separated by commas or `-` to read them from stdin, e.g.
+    /// `hnp search crash -q | hnp bulk --ids - --add-tag needs-triage`. Together with --filter
+    /// only the work items matching both are changed.
+    #[arg(long, value_name = "IDS")]
+    ids: Option<String>,
+
+    #[arg(long, value_name = "TAG")]
+    add_tag: Vec<String>,
+
+    #[arg(long, value_name = "TAG")]
+    remove_tag: Vec<String>,
+
+    #[arg(long, value_name = "IMPORTANCE")]
+    set_importance: Option<String>,
+
+    #[arg(long, value_name = "CATEGORY")]
+    set_category: Option<String>,
+
+    /// Users to assign, by username or (part of) their name, the `@` is optional
+    #[arg(long, value_name = "USER")]
+    assign: Vec<String>,
+
+    #[arg(long, value_name = "USER")]
+    unassign: Vec<String>,
+
+    /// Apply the changes without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// Changes to make to a single work item, only containing what isn't already the case
+#[derive(Debug, Default)]
+pub struct Changes {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub estimated_cost: Option<f32>,
+    pub add_tags: Vec<(Id, String)>,
+    pub remove_tags: Vec<(Id, String)>,
+    pub assign: Vec<(Id, String)>,
+    pub unassign: Vec<(Id, String)>,
+    pub importance_level: Option<(Id, String)>,
+    pub category: Option<(Id, String)>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.estimated_cost.is_none()
+            && self.add_tags.is_empty()
+            && self.remove_tags.is_empty()
+            && self.assign.is_empty()
+            && self.unassign.is_empty()
+            && self.importance_level.is_none()
+            && self.category.is_none()
+    }
+
+    pub fn describe(&self) -> Vec<String> {
+        let names = |items: &[(Id, String)]| {
+            items
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        };
+
+        let mut lines = vec![];
+        if let Some(title) = &self.title {
+            lines.push(format!("title: {title}"));
+        }
+        if self.description.is_some() {
+            lines.push("description".to_owned());
+        }
+        if let Some(estimated_cost) = &self.estimated_cost {
+            lines.push(format!("estimate: {estimated_cost}"));
+        }
+        if !self.add_tags.is_empty() {
+            lines.push(format!("add tags: {}", names(&self.add_tags)));
+        }
+        if !self.remove_tags.is_empty() {
+            lines.push(format!("remove tags: {}", names(&self.remove_tags)));
+        }
+        if !self.assign.is_empty() {
+            lines.push(format!("assign: {}", names(&self.assign)));
+        }
+        if !self.unassign.is_empty() {
+            lines.push(format!("unassign: {}", names(&self.unassign)));
+        }
+        if let Some((_, name)) = &self.importance_level {
+            lines.push(format!("importance: {name}"));
+        }
+        if let Some((_, name)) = &self.category {
+            lines.push(format!("category: {name}"));
+        }
+
+        lines
+    }
+}
+
+pub async fn run(arguments: &Arguments, bulk_arguments: &BulkArguments, config: &Config) {
+    let filter = Filter::parse(bulk_arguments.filter.as_deref().unwrap_or_default())
+        .unwrap_or_else(|error| {
+            output::error(format!("Invalid filter: {error}"));
+            output::exit_with_error();
+        });
+    let ids = bulk_arguments.ids.as_deref().map(|ids| {
+        read_ids(ids).unwrap_or_else(|error| {
+            output::error(error);
+            output::exit_with_error();
+        })
+    });
+
+    let client = client();
+
+    let spinner = output::spinner(Message::FetchingMetadata);
+    let available_categories = get_available_categories(&client).await;
+    let available_tags = get_available_tags(&client).await;
+    let available_users = get_available_users(&client).await;
+    let available_importance_levels = get_available_importance_levels(&client).await;
+    spinner.finish_and_clear();
+
+    let find_tag = |tag: &String| {
+        available_tags
+            .iter()
+            .find(|(_, name)| normalize_name(name) == normalize_name(tag))
+            .cloned()
+            .unwrap_or_else(|| panic!("To find tag \"{tag}\" on Hack'n'Plan"))
+    };
+
+    let find_assignee = |user: &String| {
+        let (id, name, _) = find_user(user.trim_start_matches('@'), &available_users);
+        (*id, name.clone())
+    };
+
+    let add_tags = bulk_arguments
+        .add_tag
+        .iter()
+        .map(find_tag)
+        .collect::<Vec<_>>();
+    let remove_tags = bulk_arguments
+        .remove_tag
+        .iter()
+        .map(find_tag)
+        .collect::<Vec<_>>();
+    let assign = bulk_arguments
+        .assign
+        .iter()
+        .map(find_assignee)
+        .collect::<Vec<_>>();
+    let unassign = bulk_arguments
+        .unassign
+        .iter()
+        .map(find_assignee)
+        .collect::<Vec<_>>();
+
+    let importance_level = bulk_arguments.set_importance.as_ref().map(|importance| {
+        let id = find_importance_level(importance, &available_importance_levels, config);
+        let (_, name, _) = available_importance_levels
+            .iter()
+            .find(|(level_id, _, _)| *level_id == id)
+            .expect("importance level to be available");
+
+        (id, name.clone())
+    });
+
+    let category = bulk_arguments.set_category.as_ref().map(|category| {
+        available_categories
+            .iter()
+            .find(|(_, name)| normalize_name(name) == normalize_name(category))
+            .cloned()
+            .unwrap_or_else(|| panic!("To find category \"{category}\" on Hack'n'Plan"))
+    });
+
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    let mut planned: Vec<(&WorkItem, Changes)> = vec![];
+    let mut unchanged = 0;
+    for work_item in work_items
+        .iter()
+        .filter(|work_item| filter.matches(work_item))
+        .filter(|work_item| {
+            ids.as_ref()
+                .is_none_or(|ids| ids.contains(&work_item.work_item_id))
+        })
+    {
+        let tag_ids = work_item.tag_ids();
+        let user_ids = work_item.assigned_user_ids();
+
+        let changes = Changes {
+            add_tags: add_tags
+                .iter()
+                .filter(|(id, _)| !tag_ids.contains(id))
+                .cloned()
+                .collect(),
+            remove_tags: remove_tags
+                .iter()
+                .filter(|(id, _)| tag_ids.contains(id))
+                .cloned()
+                .collect(),
+            assign: assign
+                .iter()
+                .filter(|(id, _)| !user_ids.contains(id))
+                .cloned()
+                .collect(),
+            unassign: unassign
+                .iter()
+                .filter(|(id, _)| user_ids.contains(id))
+                .cloned()
+                .collect(),
+            importance_level: importance_level.clone().filter(|(id, _)| {
+                work_item
+                    .importance_level
+                    .as_ref()
+                    .map(|importance_level| importance_level.importance_level_id)
+                    != Some(*id)
+            }),
+            category: category.clone().filter(|(id, _)| {
+                work_item
+                    .category
+                    .as_ref()
+                    .map(|category| category.category_id)
+                    != Some(*id)
+            }),
+            ..Default::default()
+        };
+
+        if changes.is_empty() {
+            unchanged += 1;
+        } else {
+            planned.push((work_item, changes));
+        }
+    }
+
+    if planned.is_empty() {
+        let selection = match &bulk_arguments.filter {
+            Some(filter) => format!("matched \"{filter}\""),
+            None => "were selected".to_owned(),
+        };
+        output::info(format!(
+            "Nothing to change, {unchanged} work item(s) {selection} and are already up to date"
+        ));
+        return;
+    }
+
+    for (work_item, changes) in &planned {
+        output::info(format!("#{} {}", work_item.work_item_id, work_item.title));
+
+        for line in changes.describe() {
+            println!("    {line}");
+        }
+    }
+
+    output::info(format!(
+        "{} work item(s) will be changed, {unchanged} matching work item(s) are already up to date",
+        planned.len()
+    ));
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if bulk_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Changed));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmBulk(planned.len()))
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Changed));
+        return;
+    }
+
+    let progress = output::progress(planned.len(), "Updating work items");
+    for (work_item, changes) in &planned {
+        apply(&client, work_item.work_item_id, changes).await;
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    output::success(Message::ChangedWorkItems(planned.len()));
+}
+
+pub async fn apply(client: &reqwest::Client, id: Id, changes: &Changes) {
+    let url = format!(
+        "{API_ENDPOINT}/projects/{PROJECT_ID}/workitems/{id}",
+        API_ENDPOINT = api_endpoint(),
+        PROJECT_ID = current_project_id()
+    );
+    let authorization = format!("ApiKey {API_KEY}", API_KEY = *API_KEY);
+
+    let mut requests = vec![];
+    for (tag_id, _) in &changes.add_tags {
+        requests.push(client.post(format!("{url}/tags")).json(tag_id));
+    }
+    for (tag_id, _) in &changes.remove_tags {
+        requests.push(client.delete(format!("{url}/tags/{tag_id}")));
+    }
+    for (user_id, _) in &changes.assign {
+        requests.push(client.post(format!("{url}/users")).json(user_id));
+    }
+    for (user_id, _) in &changes.unassign {
+        requests.push(client.delete(format!("{url}/users/{user_id}")));
+    }
+
+    let mut patch = serde_json::Map::new();
+    if let Some(title) = &changes.title {
+        patch.insert("title".to_owned(), json!(title));
+    }
+    if let Some(description) = &changes.description {
+        patch.insert("description".to_owned(), json!(description));
+    }
+    if let Some(estimated_cost) = &changes.estimated_cost {
+        patch.insert("estimatedCost".to_owned(), json!(estimated_cost));
+    }
+    if let Some((importance_level_id, _)) = &changes.importance_level {
+        patch.insert("importanceLevelId".to_owned(), json!(importance_level_id));
+    }
+    if let Some((category_id, _)) = &changes.category {
+        patch.insert("categoryId".to_owned(), json!(category_id));
+    }
+    if !patch.is_empty() {
+        requests.push(client.patch(&url).json(&patch));
+    }
+
+    for request in requests {
+        request
+            .header("Authorization", &authorization)
+            .send_recorded()
+            .await
+            .unwrap_or_else(|error| panic!("To update work item #{id}: {error}"))
+            .error_for_status()
+            .unwrap_or_else(|error| panic!("To update work item #{id}: {error}"));
+    }
+}
+
+/// Ids separated by commas, or by whitespace on stdin when `ids` is `-`. A leading `#` is
+/// allowed, so ids can be copied from the output of other commands.
+fn read_ids(ids: &str) -> Result<Vec<Id>, String> {
+    let ids = if ids == "-" {
+        std::io::read_to_string(std::io::stdin())
+            .map_err(|error| format!("Could not read ids from stdin: {error}"))?
+    } else {
+        ids.replace(',', " ")
+    };
+
+    ids.split_whitespace()
+        .map(|id| {
+            id.trim_start_matches('#')
+                .parse::<Id>()
+                .map_err(|_| format!("\"{id}\" isn't the id of a work item"))
+        })
+        .collect()
+}
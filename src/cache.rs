@@ -0,0 +1,139 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::recording::SendRecorded;
+use crate::{api_endpoint, current_project_id, paths, API_KEY};
+
+/// Response kept on disk together with the validators to ask Hack'n'Plan whether it changed
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: Value,
+}
+
+/// `collection` of the project, sent as a conditional request when an earlier response is
+/// cached so an unchanged collection (304) is served from disk
+pub async fn get_collection(client: &reqwest::Client, collection: &str) -> Value {
+    let path = path(collection);
+    let cached = path.as_ref().and_then(|path| {
+        let contents = fs::read_to_string(path).ok()?;
+        serde_json::from_str::<Entry>(&contents).ok()
+    });
+
+    let mut request = client
+        .get(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}/{collection}",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
+        ))
+        .header(
+            "Authorization",
+            format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+        );
+
+    if let Some(entry) = &cached {
+        if let Some(etag) = &entry.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request
+        .send_recorded()
+        .await
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|error| panic!("To get {collection} from Hack'n'Plan: {error}"));
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        if let Some(entry) = cached {
+            return entry.body;
+        }
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    let etag = header(reqwest::header::ETAG);
+    let last_modified = header(reqwest::header::LAST_MODIFIED);
+
+    let body = response
+        .json::<Value>()
+        .await
+        .unwrap_or_else(|_| panic!("To deserialize {collection} into JSON"));
+
+    // NOTE: Failing to cache only makes the next run slower, so errors are ignored
+    if let Some(path) = path.filter(|_| etag.is_some() || last_modified.is_some()) {
+        let entry = Entry {
+            etag,
+            last_modified,
+            body,
+        };
+
+        if let Some(directory) = path.parent() {
+            let _ = fs::create_dir_all(directory);
+        }
+        if let Ok(contents) = serde_json::to_string(&entry) {
+            let _ = fs::write(&path, contents);
+        }
+
+        return entry.body;
+    }
+
+    body
+}
+
+/// Name of each cached collection of the project with when it was cached, `None` for entries
+/// that can't be read and are replaced on the next request
+pub fn entries() -> Vec<(String, Option<SystemTime>)> {
+    let Some(Ok(read_dir)) = directory().map(fs::read_dir) else {
+        return vec![];
+    };
+
+    let mut entries = read_dir
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .map(|path| {
+            let collection = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let is_valid = fs::read_to_string(&path)
+                .is_ok_and(|contents| serde_json::from_str::<Entry>(&contents).is_ok());
+            let modified = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .filter(|_| is_valid);
+
+            (collection, modified)
+        })
+        .collect::<Vec<(String, Option<SystemTime>)>>();
+    entries.sort();
+
+    entries
+}
+
+/// Where the responses of the project are cached
+pub fn directory() -> Option<PathBuf> {
+    Some(paths::cache_dir()?.join(current_project_id().to_string()))
+}
+
+/// Cached response for `collection`, `~/.cache/hnp/<project>/<collection>.json` on Linux
+fn path(collection: &str) -> Option<PathBuf> {
+    Some(directory()?.join(format!("{collection}.json")))
+}
@@ -0,0 +1,102 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::config::{Config, EstimateMode};
+use crate::{assigned_estimate, core, estimate_with_sub_tasks, get_models, models, output, Ticket};
+
+/// Warns when `tickets` add up to more hours than the team has left in `milestone`, per user and
+/// in total, exiting before anything is uploaded when `strict`
+pub async fn check(
+    client: &reqwest::Client,
+    tickets: &[Ticket],
+    metadata: &core::Metadata,
+    config: &Config,
+    milestone: &str,
+    strict: bool,
+) {
+    if config.estimates.mode == EstimateMode::Points {
+        output::warn(
+            "Not checking the capacity, it's in hours while the project estimates in points",
+        );
+        return;
+    }
+
+    let milestone = get_models::<models::Milestone>(client, "milestones")
+        .await
+        .into_iter()
+        .find(|available_milestone| available_milestone.name.eq_ignore_ascii_case(milestone))
+        .unwrap_or_else(|| panic!("To find a milestone for \"{milestone}\""));
+    let working_days = working_days_left(&milestone, config);
+
+    let mut problems = vec![];
+    for (id, _, user_name) in &metadata.users {
+        let planned = assigned_estimate(tickets, *id);
+        let available = working_days * config.calendar.daily_capacity(user_name);
+
+        if planned > available {
+            problems.push(format!(
+                "@{user_name} is planned for {planned}h but has {available}h left in \"{}\"",
+                milestone.name
+            ));
+        }
+    }
+
+    let planned = tickets.iter().map(estimate_with_sub_tasks).sum::<f32>();
+    let available = metadata
+        .users
+        .iter()
+        .map(|(_, _, user_name)| working_days * config.calendar.daily_capacity(user_name))
+        .sum::<f32>();
+    if planned > available {
+        problems.push(format!(
+            "The tickets add up to {planned}h, more than the {available}h the team has left in \"{}\"",
+            milestone.name
+        ));
+    }
+
+    for problem in &problems {
+        let diagnostic = output::Diagnostic {
+            code: "capacity",
+            message: problem.to_owned(),
+            ..Default::default()
+        };
+        if strict {
+            output::error_at(diagnostic);
+        } else {
+            output::warn_at(diagnostic);
+        }
+    }
+
+    if strict && !problems.is_empty() {
+        output::error(
+            "Running --strict with too much work for the milestone, nothing was uploaded",
+        );
+        output::exit_with_error();
+    }
+}
+
+/// Working days from today or the start of `milestone`, whichever is later, until its due date
+pub fn working_days_left(milestone: &models::Milestone, config: &Config) -> f32 {
+    let date = |date: &Option<String>| {
+        date.as_deref()
+            .and_then(|date| date.get(..10))
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    };
+    let due = date(&milestone.due_date)
+        .unwrap_or_else(|| panic!("To have a due date on milestone \"{}\"", milestone.name));
+
+    // NOTE: Only the days left count, a milestone that already started has less room
+    let today = config.calendar.today();
+    let start = date(&milestone.start_date).map_or(today, |start| start.max(today));
+    let working_days = start
+        .iter_days()
+        .take_while(|day| *day <= due)
+        .filter(|day| (day.weekday().num_days_from_monday() as f32) < config.calendar.days_per_week)
+        .count() as f32;
+
+    output::info(format!(
+        "Milestone \"{}\" has {working_days} working day(s) left from {start} until {due}",
+        milestone.name
+    ));
+
+    working_days
+}
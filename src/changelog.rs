@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use clap::Args;
+
+use crate::config::Config;
+use crate::graph::Dependencies;
+use crate::i18n::Message;
+use crate::{client, normalize_name, output};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ChangelogFormat {
+    /// Markdown with a heading per group
+    Md,
+    /// Plain text with a line per group
+    Text,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Category,
+    /// A work item with several tags is listed below each of them
+    Tag,
+}
+
+#[derive(Args, Debug)]
+pub struct ChangelogArguments {
+    /// Milestone whose completed work items are listed
+    #[arg(long)]
+    milestone: String,
+
+    #[arg(long, value_enum, default_value_t = ChangelogFormat::Md)]
+    format: ChangelogFormat,
+
+    #[arg(long, value_enum, default_value_t = GroupBy::Category)]
+    group_by: GroupBy,
+
+    /// Only list work items with one of these tags, on top of `changelog.include_tags`
+    #[arg(long, value_name = "TAG")]
+    include_tag: Vec<String>,
+
+    /// Leave out work items with these tags, on top of `changelog.exclude_tags`
+    #[arg(long, value_name = "TAG")]
+    exclude_tag: Vec<String>,
+}
+
+/// Prints the completed work items of a milestone grouped by category or tag, to paste into
+/// release notes
+pub async fn run(changelog_arguments: &ChangelogArguments, config: &Config) {
+    let client = client();
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let dependencies = Dependencies::fetch(&client, &changelog_arguments.milestone).await;
+    spinner.finish_and_clear();
+
+    let normalized = |tags: &[String]| {
+        tags.iter()
+            .map(|tag| normalize_name(tag.trim_start_matches('#')))
+            .collect::<Vec<String>>()
+    };
+    let include = normalized(
+        &[
+            config.changelog.include_tags.clone(),
+            changelog_arguments.include_tag.clone(),
+        ]
+        .concat(),
+    );
+    let exclude = normalized(
+        &[
+            config.changelog.exclude_tags.clone(),
+            changelog_arguments.exclude_tag.clone(),
+        ]
+        .concat(),
+    );
+
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for work_item in dependencies
+        .in_milestone
+        .iter()
+        .map(|id| dependencies.work_item(*id))
+        .filter(|work_item| work_item.is_completed())
+    {
+        let tags = work_item
+            .tags
+            .iter()
+            .map(|tag| normalize_name(&tag.name))
+            .collect::<Vec<String>>();
+        if tags.iter().any(|tag| exclude.contains(tag))
+            || (!include.is_empty() && !tags.iter().any(|tag| include.contains(tag)))
+        {
+            continue;
+        }
+
+        let mut group_names = match changelog_arguments.group_by {
+            GroupBy::Category => work_item
+                .category
+                .iter()
+                .map(|category| category.name.clone())
+                .collect::<Vec<String>>(),
+            GroupBy::Tag => work_item
+                .tags
+                .iter()
+                .map(|tag| tag.name.clone())
+                .collect::<Vec<String>>(),
+        };
+        if group_names.is_empty() {
+            group_names.push("Other".to_owned());
+        }
+
+        for group_name in group_names {
+            groups
+                .entry(group_name)
+                .or_default()
+                .push(work_item.title.trim().to_owned());
+        }
+    }
+
+    if groups.is_empty() {
+        output::warn(format!(
+            "No completed work items in \"{}\" to list",
+            changelog_arguments.milestone
+        ));
+        return;
+    }
+
+    let sections = groups
+        .into_iter()
+        .map(|(group, mut titles)| {
+            titles.sort_by_key(|title| title.to_lowercase());
+
+            let items = titles
+                .iter()
+                .map(|title| format!("- {title}"))
+                .collect::<Vec<String>>()
+                .join("\n");
+            match changelog_arguments.format {
+                ChangelogFormat::Md => format!("### {group}\n\n{items}"),
+                ChangelogFormat::Text => format!("{group}:\n{items}"),
+            }
+        })
+        .collect::<Vec<String>>();
+
+    println!(
+        "{}\n\n{}",
+        match changelog_arguments.format {
+            ChangelogFormat::Md => format!("## {}", changelog_arguments.milestone),
+            ChangelogFormat::Text => changelog_arguments.milestone.to_owned(),
+        },
+        sections.join("\n\n")
+    );
+}
@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::models;
+use crate::Id;
+
+pub const DEFAULT_API_ENDPOINT: &str = "https://api.hacknplan.com/v0";
+
+/// Version of the API hnp is made for
+pub const API_VERSION: &str = "v0";
+
+/// How long a request may take, unless the builder is given another timeout
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Builder for an HTTP client with the settings hnp uses: timeouts, a pool of kept alive
+/// connections, gzip and a user agent naming this version of hnp, followed by
+/// `user_agent_suffix` to tell apart traffic that goes through a proxy
+pub fn http_client_builder(user_agent_suffix: Option<&str>) -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(user_agent(user_agent_suffix))
+        .timeout(DEFAULT_TIMEOUT)
+        .connect_timeout(Duration::from_secs(10))
+        .gzip(true)
+        .pool_max_idle_per_host(8)
+        .pool_idle_timeout(Duration::from_secs(90))
+        .tcp_keepalive(Duration::from_secs(60))
+}
+
+/// `hnp/<version>`, followed by `suffix` when given
+pub fn user_agent(suffix: Option<&str>) -> String {
+    let user_agent = concat!("hnp/", env!("CARGO_PKG_VERSION"));
+    match suffix.map(str::trim).filter(|suffix| !suffix.is_empty()) {
+        Some(suffix) => format!("{user_agent} {suffix}"),
+        None => user_agent.to_owned(),
+    }
+}
+
+/// Client for one Hack'n'Plan project
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    endpoint: String,
+    api_key: String,
+    project_id: Id,
+}
+
+impl Client {
+    pub fn new(api_key: impl Into<String>, project_id: Id) -> Client {
+        Client {
+            http: http_client_builder(None)
+                .build()
+                .expect("To build the HTTP client"),
+            endpoint: DEFAULT_API_ENDPOINT.to_owned(),
+            api_key: api_key.into(),
+            project_id,
+        }
+    }
+
+    /// Sends requests to `endpoint` instead of Hack'n'Plan's own API, for proxies and mocks
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Client {
+        self.endpoint = endpoint.into().trim_end_matches('/').to_owned();
+        self
+    }
+
+    /// Sends requests through `http`, to share one pool of connections with other code
+    pub fn with_http(mut self, http: reqwest::Client) -> Client {
+        self.http = http;
+        self
+    }
+
+    pub fn project_id(&self) -> Id {
+        self.project_id
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.http
+            .request(
+                method,
+                format!("{}/projects/{}{path}", self.endpoint, self.project_id),
+            )
+            .header("Authorization", format!("ApiKey {}", self.api_key))
+    }
+
+    /// A collection of the project like `categories`, `tags` or `boards`, deserialized into its
+    /// model
+    pub async fn collection<T: DeserializeOwned>(&self, collection: &str) -> reqwest::Result<T> {
+        self.request(reqwest::Method::GET, &format!("/{collection}"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<T>()
+            .await
+    }
+
+    /// All work items in the project, fetched page by page
+    pub async fn work_items(&self) -> reqwest::Result<Vec<models::WorkItem>> {
+        const PAGE_SIZE: usize = 100;
+
+        let mut work_items = vec![];
+        loop {
+            let page = self
+                .request(reqwest::Method::GET, "/workitems")
+                .query(&[("offset", work_items.len()), ("limit", PAGE_SIZE)])
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<models::Page<models::WorkItem>>()
+                .await?;
+
+            let count = page.items.len();
+            work_items.extend(page.items);
+
+            if count < PAGE_SIZE {
+                return Ok(work_items);
+            }
+        }
+    }
+
+    /// Creates a work item from `work_item`, a body like `{"title": ..., "categoryId": ...}` as
+    /// the API expects it
+    pub async fn create_work_item(
+        &self,
+        work_item: &impl Serialize,
+    ) -> reqwest::Result<models::WorkItem> {
+        self.request(reqwest::Method::POST, "/workitems")
+            .json(work_item)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<models::WorkItem>()
+            .await
+    }
+}
+
+#[cfg(feature = "core")]
+impl crate::core::Backend for Client {
+    fn collection(
+        &self,
+        collection: &str,
+    ) -> impl std::future::Future<Output = Result<serde_json::Value, String>> {
+        let collection = collection.to_owned();
+
+        async move {
+            Client::collection::<serde_json::Value>(self, &collection)
+                .await
+                .map_err(|error| format!("To get {collection} from Hack'n'Plan: {error}"))
+        }
+    }
+}
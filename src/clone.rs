@@ -0,0 +1,512 @@
+use std::collections::{HashMap, HashSet};
+
+use clap::Args;
+use serde_json::json;
+
+use crate::config::Config;
+use crate::i18n::{Action, Message};
+use crate::models::{self, WorkItem};
+use crate::recording::SendRecorded;
+use crate::{
+    api_endpoint, check_access, client, complete_sub_tasks, core, current_project_id, find_profile,
+    get_available_boards, get_available_milestones, get_metadata, get_sub_tasks, get_work_items,
+    in_project, normalize_name, output, project_name, upload_ticket, Arguments, Id, SubTask,
+    Ticket, API_KEY,
+};
+
+#[derive(Args, Debug)]
+pub struct CloneArguments {
+    /// Id of the work item to clone
+    work_item: Id,
+
+    /// Put the clone on the (first) board of this milestone
+    #[arg(long, value_name = "MILESTONE", conflicts_with = "into_board")]
+    into_milestone: Option<String>,
+
+    /// Put the clone on this board instead of the board of the original
+    #[arg(long, value_name = "BOARD")]
+    into_board: Option<String>,
+
+    /// Also copy the sub tasks
+    #[arg(long)]
+    sub_tasks: bool,
+
+    /// Copy to another project, by the name of its profile in the config or its id. Categories,
+    /// tags, users and importance levels are matched by name there.
+    #[arg(long, value_name = "PROJECT")]
+    to_project: Option<String>,
+
+    /// Category of the other project to use for a category of the original, like
+    /// `Programming=Code`
+    #[arg(long, value_name = "FROM=TO", value_parser = parse_mapping, requires = "to_project")]
+    map_category: Vec<(String, String)>,
+
+    /// Tag of the other project to use for a tag of the original, like `bug=defect`
+    #[arg(long, value_name = "FROM=TO", value_parser = parse_mapping, requires = "to_project")]
+    map_tag: Vec<(String, String)>,
+
+    /// Clone without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub struct CloneOptions {
+    pub sub_tasks: bool,
+    /// Start the clones in the first stage instead of the stage of the original
+    pub reset_stages: bool,
+    /// Estimates to give the clones instead of the estimate of their original, by original id
+    pub estimates: HashMap<Id, f32>,
+    /// Project the originals are in, when they're cloned to another one
+    pub from_project: Option<Id>,
+}
+
+pub async fn run(arguments: &Arguments, clone_arguments: &CloneArguments, config: &Config) {
+    let client = client();
+
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    let Some(work_item) = work_items
+        .iter()
+        .find(|work_item| work_item.work_item_id == clone_arguments.work_item)
+    else {
+        output::error(format!(
+            "Could not find work item #{} on Hack'n'Plan",
+            clone_arguments.work_item
+        ));
+        output::exit_with_error();
+    };
+
+    // NOTE: From here on the boards and everything the clones refer to come from the project
+    // they're copied to
+    let original_project_id = current_project_id();
+    let project_id = match &clone_arguments.to_project {
+        Some(project) => project
+            .parse::<Id>()
+            .unwrap_or_else(|_| find_profile(config, project)),
+        None => original_project_id,
+    };
+
+    in_project(project_id, async {
+        if clone_arguments.to_project.is_some() {
+            check_access(&client).await;
+            output::info(format!(
+                "Copying to project \"{}\" (#{project_id})",
+                project_name(&client).await
+            ));
+        }
+
+        let spinner = output::spinner("Fetching boards from Hack'n'Plan");
+        let available_boards = get_available_boards(&client).await;
+        let available_milestones = get_available_milestones(&client).await;
+        spinner.finish_and_clear();
+
+        let board_id = if let Some(board) = &clone_arguments.into_board {
+            Some(
+                available_boards
+                    .iter()
+                    .find(|(_, name, _)| name.eq_ignore_ascii_case(board))
+                    .unwrap_or_else(|| panic!("To find a board for \"{board}\""))
+                    .0,
+            )
+        } else if let Some(milestone) = &clone_arguments.into_milestone {
+            let milestone_id = find_milestone(milestone, &available_milestones);
+
+            Some(
+                available_boards
+                    .iter()
+                    .find(|(_, _, board_milestone_id)| *board_milestone_id == Some(milestone_id))
+                    .unwrap_or_else(|| panic!("To find a board in milestone \"{milestone}\""))
+                    .0,
+            )
+        } else {
+            None
+        };
+
+        // NOTE: Stories are cloned together with the work items below them
+        let mut to_clone = vec![work_item];
+        let mut index = 0;
+        while let Some(parent) = to_clone.get(index) {
+            let parent_id = parent.work_item_id;
+            to_clone.extend(
+                work_items
+                    .iter()
+                    .filter(|work_item| work_item.parent_story_id() == Some(parent_id)),
+            );
+            index += 1;
+        }
+
+        for work_item in &to_clone {
+            output::info(format!(
+                "Cloning #{} {}",
+                work_item.work_item_id, work_item.title
+            ));
+
+            // NOTE: Attachments can't be copied through the API, so at least say they're left out
+            let attachments = in_project(
+                original_project_id,
+                get_attachments(&client, work_item.work_item_id),
+            )
+            .await;
+            if !attachments.is_empty() {
+                output::warn(format!(
+                    "Leaving the attachments of #{} off the clone, copy them by hand: {}",
+                    work_item.work_item_id,
+                    attachments
+                        .iter()
+                        .map(|attachment| attachment.name.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                ));
+            }
+        }
+
+        let copies;
+        let to_clone = if clone_arguments.to_project.is_some() {
+            let metadata = get_metadata(&client).await;
+            let ids = to_clone
+                .iter()
+                .map(|work_item| work_item.work_item_id)
+                .collect::<HashSet<Id>>();
+            copies = to_clone
+                .iter()
+                .map(|work_item| to_project(work_item, &ids, &metadata, clone_arguments))
+                .collect::<Vec<WorkItem>>();
+
+            copies.iter().collect::<Vec<&WorkItem>>()
+        } else {
+            to_clone
+        };
+
+        if arguments.dry_run {
+            return;
+        }
+
+        let confirmed = if clone_arguments.yes {
+            true
+        } else if arguments.non_interactive {
+            output::error(Message::NonInteractiveWithoutYes(Action::Cloned));
+            output::exit_with_error();
+        } else {
+            output::confirm(Message::ConfirmClone(to_clone.len()))
+        };
+
+        if !confirmed {
+            output::info(Message::NothingWas(Action::Cloned));
+            return;
+        }
+
+        let options = CloneOptions {
+            sub_tasks: clone_arguments.sub_tasks,
+            reset_stages: true,
+            estimates: HashMap::new(),
+            from_project: clone_arguments
+                .to_project
+                .as_ref()
+                .map(|_| original_project_id),
+        };
+        let clones = clone_work_items(&client, &to_clone, board_id, &options).await;
+
+        output::success(format!(
+            "Cloned #{} as #{}{}",
+            clone_arguments.work_item,
+            clones[&clone_arguments.work_item],
+            match clones.len() - 1 {
+                0 => String::new(),
+                children => format!(" together with {children} work item(s) below it"),
+            }
+        ));
+    })
+    .await;
+}
+
+/// Clones `work_items` onto `board_id` (or their own board when `None`), creating parent stories
+/// before the work items below them so the clones keep the same structure. Returns the ids of the
+/// clones by the id of their original.
+pub async fn clone_work_items(
+    client: &reqwest::Client,
+    work_items: &[&WorkItem],
+    board_id: Option<Id>,
+    options: &CloneOptions,
+) -> HashMap<Id, Id> {
+    let mut clones: HashMap<Id, Id> = HashMap::new();
+
+    let progress = output::progress(work_items.len(), "Cloning work items");
+    for work_item in parents_first(work_items) {
+        let sub_tasks = if options.sub_tasks {
+            let sub_tasks = get_sub_tasks(client, work_item.work_item_id);
+            let sub_tasks = match options.from_project {
+                Some(project_id) => in_project(project_id, sub_tasks).await,
+                None => sub_tasks.await,
+            };
+
+            to_sub_tasks(&sub_tasks, !options.reset_stages)
+        } else {
+            vec![]
+        };
+
+        let mut ticket = to_ticket(work_item, sub_tasks);
+        ticket.parent_id = clones
+            .get(&ticket.parent_id)
+            .copied()
+            .unwrap_or(ticket.parent_id);
+        ticket.board_id = board_id.unwrap_or(ticket.board_id);
+        if let Some(estimate) = options.estimates.get(&work_item.work_item_id) {
+            ticket.estimated_cost = *estimate;
+        }
+
+        let clone_id = upload_ticket(client, &ticket).await;
+        complete_sub_tasks(client, clone_id, &ticket).await;
+
+        if !options.reset_stages {
+            if let Some(stage) = &work_item.stage {
+                set_stage(client, clone_id, stage.stage_id).await;
+            }
+        }
+
+        clones.insert(work_item.work_item_id, clone_id);
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    clones
+}
+
+/// `work_item` with the category, tags, users and importance level of the project it's copied to,
+/// matched by name. It leaves behind its board and any parent story that isn't copied along.
+fn to_project(
+    work_item: &WorkItem,
+    copied_ids: &HashSet<Id>,
+    metadata: &core::Metadata,
+    clone_arguments: &CloneArguments,
+) -> WorkItem {
+    let mapped = |mappings: &[(String, String)], name: &str| {
+        mappings
+            .iter()
+            .find(|(from, _)| normalize_name(from) == normalize_name(name))
+            .map_or(name.to_owned(), |(_, to)| to.to_owned())
+    };
+
+    let mut copy = work_item.clone();
+    copy.parent_story_id = work_item
+        .parent_story_id()
+        .filter(|parent_id| copied_ids.contains(parent_id));
+    copy.board = None;
+    copy.stage = None;
+    copy.design_element = None;
+    copy.dependency_ids = vec![];
+
+    if let Some(category) = &mut copy.category {
+        let name = mapped(&clone_arguments.map_category, &category.name);
+        let Some((category_id, _)) = metadata
+            .categories
+            .iter()
+            .find(|(_, candidate)| normalize_name(candidate) == normalize_name(&name))
+        else {
+            output::error(format!(
+                "Project {} has no category \"{name}\" for #{}, map it with \
+                 --map-category \"{}=<category>\"",
+                current_project_id(),
+                work_item.work_item_id,
+                category.name
+            ));
+            output::exit_with_error();
+        };
+        category.category_id = *category_id;
+    }
+
+    copy.tags.retain_mut(|tag| {
+        let name = mapped(&clone_arguments.map_tag, &tag.name);
+        match metadata
+            .tags
+            .iter()
+            .find(|(_, candidate)| normalize_name(candidate) == normalize_name(&name))
+        {
+            Some((tag_id, _)) => {
+                tag.tag_id = *tag_id;
+                true
+            }
+            None => {
+                output::warn(format!(
+                    "Leaving tag \"{name}\" off #{}, project {} has no such tag",
+                    work_item.work_item_id,
+                    current_project_id()
+                ));
+                false
+            }
+        }
+    });
+
+    copy.assigned_users.retain_mut(|assigned_user| {
+        match metadata
+            .users
+            .iter()
+            .find(|(_, _, username)| *username == assigned_user.user.username)
+        {
+            Some((user_id, _, _)) => {
+                assigned_user.user.id = *user_id;
+                true
+            }
+            None => {
+                output::warn(format!(
+                    "Not assigning @{} to #{}, they aren't a member of project {}",
+                    assigned_user.user.username,
+                    work_item.work_item_id,
+                    current_project_id()
+                ));
+                false
+            }
+        }
+    });
+
+    // NOTE: Importance levels missing in the other project fall back to its default one
+    if let Some(importance_level) = &mut copy.importance_level {
+        let found = metadata
+            .importance_levels
+            .iter()
+            .find(|(_, name, _)| normalize_name(name) == normalize_name(&importance_level.name))
+            .or_else(|| {
+                metadata
+                    .importance_levels
+                    .iter()
+                    .find(|(_, _, is_default)| *is_default)
+            });
+        match found {
+            Some((importance_level_id, _, _)) => {
+                importance_level.importance_level_id = *importance_level_id
+            }
+            None => copy.importance_level = None,
+        }
+    }
+
+    copy
+}
+
+/// `FROM=TO` of a `--map-*` argument
+fn parse_mapping(mapping: &str) -> Result<(String, String), String> {
+    match mapping.split_once('=') {
+        Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => {
+            Ok((from.trim().to_owned(), to.trim().to_owned()))
+        }
+        _ => Err(format!("expected FROM=TO, got \"{mapping}\"")),
+    }
+}
+
+/// Orders `work_items` so parent stories come before the work items below them
+pub fn parents_first<'a>(work_items: &[&'a WorkItem]) -> Vec<&'a WorkItem> {
+    let ids = work_items
+        .iter()
+        .map(|work_item| work_item.work_item_id)
+        .collect::<HashSet<Id>>();
+
+    let mut ordered: Vec<&WorkItem> = vec![];
+    let mut placed: HashSet<Id> = HashSet::new();
+    let mut pending = work_items.to_vec();
+
+    while !pending.is_empty() {
+        let (ready, waiting): (Vec<&WorkItem>, Vec<&WorkItem>) =
+            pending.into_iter().partition(|work_item| {
+                work_item.parent_story_id().is_none_or(|parent_id| {
+                    !ids.contains(&parent_id) || placed.contains(&parent_id)
+                })
+            });
+
+        if ready.is_empty() {
+            panic!("To not find a loop in the parent stories of the work items");
+        }
+
+        placed.extend(ready.iter().map(|work_item| work_item.work_item_id));
+        ordered.extend(ready);
+        pending = waiting;
+    }
+
+    ordered
+}
+
+/// Ticket with the same contents, parent and board as `work_item`
+pub fn to_ticket(work_item: &WorkItem, sub_tasks: Vec<SubTask>) -> Ticket {
+    Ticket {
+        title: work_item.title.to_owned(),
+        description: work_item.description.clone().unwrap_or_default(),
+        parent_id: work_item.parent_story_id().unwrap_or_default(),
+        is_story: work_item.is_story,
+        category_id: work_item
+            .category
+            .as_ref()
+            .map(|category| category.category_id)
+            .unwrap_or_default(),
+        estimated_cost: work_item.estimated_cost.unwrap_or_default(),
+        importance_level_id: work_item
+            .importance_level
+            .as_ref()
+            .map(|importance_level| importance_level.importance_level_id)
+            .unwrap_or_default(),
+        board_id: work_item
+            .board
+            .as_ref()
+            .map(|board| board.board_id)
+            .unwrap_or_default(),
+        assigned_user_ids: work_item.assigned_user_ids(),
+        tag_ids: work_item.tag_ids(),
+        sub_tasks,
+        ..Default::default()
+    }
+}
+
+pub fn to_sub_tasks(sub_tasks: &[models::SubTask], keep_completion: bool) -> Vec<SubTask> {
+    sub_tasks
+        .iter()
+        .map(|sub_task| SubTask {
+            title: sub_task.title.to_owned(),
+            is_completed: keep_completion && sub_task.is_completed,
+            ..Default::default()
+        })
+        .collect::<Vec<SubTask>>()
+}
+
+pub fn find_milestone(milestone: &str, available_milestones: &[(Id, String)]) -> Id {
+    available_milestones
+        .iter()
+        .find(|(_, name)| name.eq_ignore_ascii_case(milestone))
+        .unwrap_or_else(|| panic!("To find a milestone for \"{milestone}\""))
+        .0
+}
+
+async fn get_attachments(client: &reqwest::Client, work_item_id: Id) -> Vec<models::Attachment> {
+    client
+        .get(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}/workitems/{work_item_id}/attachments",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
+        ))
+        .header(
+            "Authorization",
+            format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+        )
+        .send_recorded()
+        .await
+        .and_then(|response| response.error_for_status())
+        .expect("To get attachments from Hack'n'Plan")
+        .json::<Vec<models::Attachment>>()
+        .await
+        .unwrap_or_else(|error| panic!("To deserialize attachments: {error}"))
+}
+
+pub async fn set_stage(client: &reqwest::Client, work_item_id: Id, stage_id: Id) {
+    client
+        .patch(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}/workitems/{work_item_id}",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
+        ))
+        .header(
+            "Authorization",
+            format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+        )
+        .json(&json!({ "stageId": stage_id }))
+        .send_recorded()
+        .await
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|error| panic!("to move work item #{work_item_id} to its stage: {error}"));
+}
@@ -0,0 +1,631 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+
+use hnp::Id;
+
+use crate::paths;
+
+pub use hnp::core::{EstimateMode, DEFAULT_WORD_CHARACTERS};
+
+const CONFIG_FILE_NAME: &str = "hnp.toml";
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub api: Api,
+    pub calendar: Calendar,
+    pub estimates: Estimates,
+    pub importance: Importance,
+    pub tags: Tags,
+    pub markup: Markup,
+    pub references: References,
+    pub tokens: Vec<CustomToken>,
+    pub notion: Notion,
+    pub assignment: Assignment,
+    pub routing: Routing,
+    pub templates: Templates,
+    pub definition_of_done: DefinitionOfDone,
+    pub hooks: Hooks,
+    pub aliases: Aliases,
+    pub changelog: Changelog,
+    pub qa: Qa,
+    pub stats: Stats,
+    /// Projects to work in by name with `--profile`
+    pub profiles: HashMap<String, Profile>,
+}
+
+/// Where and how requests to Hack'n'Plan are made
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Api {
+    /// Base URL of the API, overridden by `--api-url`
+    pub url: Option<String>,
+    /// Version of the API like `v0`, replacing the one the base URL ends in
+    pub version: Option<String>,
+    /// Seconds a request may take, overridden by `--timeout`
+    pub timeout: Option<u64>,
+    /// Proxy for all requests instead of the one in HTTPS_PROXY, NO_PROXY still applies
+    pub proxy: Option<String>,
+    /// Text added to the user agent of every request, overridden by `--user-agent-suffix`
+    pub user_agent_suffix: Option<String>,
+}
+
+/// Who `--auto-assign` hands out tickets to
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Assignment {
+    /// Usernames of the users tickets without mentions are divided among
+    pub pool: Vec<String>,
+}
+
+/// Commands that tickets are piped through as JSON, run by the shell. Except for `post_upload`
+/// they print the JSON back, changed however they like.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    /// Gets `{"text": ...}` with the markup of each ticket before it's parsed
+    pub pre_parse: Option<String>,
+    /// Gets the payload of each ticket once its markup is resolved
+    pub post_resolve: Option<String>,
+    /// Gets the payload of each work item right before it's sent, in dry runs as well
+    pub pre_upload: Option<String>,
+    /// Gets the payload with the `workItemId` of each created work item, its output is ignored
+    pub post_upload: Option<String>,
+    /// Rhai script that can change every ticket after `post_resolve`, `./transform.rhai` is used
+    /// when it exists. Needs hnp to be built with the `scripting` feature.
+    pub transform: Option<PathBuf>,
+}
+
+impl Hooks {
+    pub fn transform_script(&self) -> Option<PathBuf> {
+        self.transform
+            .clone()
+            .or_else(|| Some(PathBuf::from("transform.rhai")).filter(|path| path.exists()))
+    }
+}
+
+/// Boards that tickets go to when the ticket itself doesn't name one
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Routing {
+    /// Boards by category name, e.g. `Art = "Art Pipeline"`, taking precedence over the board of
+    /// the file or import
+    pub categories: HashMap<String, String>,
+}
+
+impl Routing {
+    /// Name of the board that tickets in `category` are routed to
+    pub fn board(&self, category: &str) -> Option<&str> {
+        self.categories
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(category))
+            .map(|(_, board)| board.as_str())
+    }
+}
+
+/// Skeletons appended to the descriptions of tickets
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Templates {
+    /// Markdown files by category name, e.g. `Bug = "templates/bug.md"`
+    pub categories: HashMap<String, PathBuf>,
+}
+
+impl Templates {
+    /// Path of the template appended to descriptions of tickets in `category`
+    pub fn description(&self, category: &str) -> Option<&Path> {
+        self.categories
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(category))
+            .map(|(_, path)| path.as_path())
+    }
+}
+
+/// Sub tasks added to every created ticket, left out of a ticket with `!nodod`
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct DefinitionOfDone {
+    /// Sub tasks for every ticket, e.g. `["Code reviewed", "QA passed"]`
+    pub checklist: Vec<String>,
+    /// Sub tasks for tickets in a category on top of `checklist`, by category name
+    pub categories: HashMap<String, Vec<String>>,
+}
+
+impl DefinitionOfDone {
+    /// The sub tasks for a ticket in `category`
+    pub fn checklist(&self, category: Option<&str>) -> Vec<&str> {
+        let for_category = category.into_iter().flat_map(|category| {
+            self.categories
+                .iter()
+                .filter(move |(name, _)| name.eq_ignore_ascii_case(category))
+                .flat_map(|(_, checklist)| checklist)
+        });
+
+        self.checklist
+            .iter()
+            .chain(for_category)
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Which work items `hnp report changelog` lists, by tag name
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Changelog {
+    /// Only list work items with at least one of these tags, all when empty
+    pub include_tags: Vec<String>,
+    /// Leave out work items with any of these tags, e.g. `["internal"]`
+    pub exclude_tags: Vec<String>,
+}
+
+/// Statistics on the work items created, kept on this machine for `hnp stats`
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Stats {
+    /// Keep a count of the work items each run creates and their estimates, off by default
+    pub enabled: bool,
+}
+
+/// A project picked with `--profile <name>` instead of HACKNPLAN_PROJECT_ID
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub project_id: Id,
+    /// Make `bulk`, `rollover`, `restore` and `undo` on the project require `--profile` and its
+    /// name typed out
+    #[serde(default)]
+    pub protected: bool,
+}
+
+/// QA tasks `hnp qa generate` creates for completed work items. `{id}` and `{title}` in the text
+/// are those of the work item to test.
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Qa {
+    /// Usernames of the testers the QA tasks are divided among
+    pub pool: Vec<String>,
+    pub title: String,
+    pub description: String,
+    /// Sub tasks of every QA task
+    pub checklist: Vec<String>,
+    /// Category of the QA tasks instead of the category of the work item to test
+    pub category: Option<String>,
+}
+
+impl Default for Qa {
+    fn default() -> Self {
+        Qa {
+            pool: vec![],
+            title: "QA: {title}".to_owned(),
+            description: "Test #{id} {title} and note anything that's off in a comment.".to_owned(),
+            checklist: vec![],
+            category: None,
+        }
+    }
+}
+
+impl Qa {
+    /// `text` with the placeholders filled in for `work_item_id` and `title`
+    pub fn fill(text: &str, work_item_id: i64, title: &str) -> String {
+        text.replace("{id}", &work_item_id.to_string())
+            .replace("{title}", title)
+    }
+}
+
+/// Shorthands people write instead of the full name, resolved before looking the name up
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Aliases {
+    /// Users by mention, e.g. `bd = "Bram Dingelstad"` allows writing `@bd`
+    pub users: HashMap<String, String>,
+    /// Tags and categories by tag, e.g. `prog = "Programming"` allows writing `#prog`
+    pub tags: HashMap<String, String>,
+}
+
+impl Aliases {
+    pub fn user(&self, mention: &str) -> Option<&str> {
+        find_alias(&self.users, mention)
+    }
+
+    pub fn tag(&self, tag: &str) -> Option<&str> {
+        find_alias(&self.tags, tag)
+    }
+}
+
+fn find_alias<'a>(aliases: &'a HashMap<String, String>, alias: &str) -> Option<&'a str> {
+    aliases
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(alias))
+        .map(|(_, canonical)| canonical.as_str())
+}
+
+/// Working hours of the team, used to convert day and week estimates into hours
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Calendar {
+    pub hours_per_day: f32,
+    pub days_per_week: f32,
+    /// Hours per day for specific users (by username) that deviate from `hours_per_day`
+    pub capacity: HashMap<String, f32>,
+    /// Time zone the team counts days in, `local`, a name like `Europe/Amsterdam` or an offset
+    /// like `+02:00`
+    #[serde(deserialize_with = "deserialize_timezone")]
+    pub timezone: Timezone,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Calendar {
+            hours_per_day: 8.0,
+            days_per_week: 5.0,
+            capacity: HashMap::new(),
+            timezone: Timezone::Local,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub enum Timezone {
+    /// The time zone of the system, following its daylight saving time
+    #[default]
+    Local,
+    /// Time zone from the time zone database, following its daylight saving time
+    Named(chrono_tz::Tz),
+    Fixed(chrono::FixedOffset),
+}
+
+impl Timezone {
+    /// Date and time on the clock in this time zone at `utc`
+    fn at(&self, utc: chrono::DateTime<chrono::Utc>) -> chrono::NaiveDateTime {
+        match self {
+            Timezone::Local => utc.with_timezone(&chrono::Local).naive_local(),
+            Timezone::Named(timezone) => utc.with_timezone(timezone).naive_local(),
+            Timezone::Fixed(offset) => utc.with_timezone(offset).naive_local(),
+        }
+    }
+}
+
+impl Calendar {
+    /// Current date and time in the team's time zone
+    pub fn now(&self) -> chrono::NaiveDateTime {
+        self.timezone.at(chrono::Utc::now())
+    }
+
+    /// Today in the team's time zone
+    pub fn today(&self) -> chrono::NaiveDate {
+        self.now().date()
+    }
+
+    /// Day a timestamp from the API like `creationDate` falls on in the team's time zone. The API
+    /// leaves out the offset of timestamps in UTC, `null` and an empty string are no timestamp.
+    pub fn day_of(&self, timestamp: &Option<String>) -> Option<chrono::NaiveDate> {
+        let timestamp = timestamp
+            .as_deref()
+            .filter(|timestamp| !timestamp.is_empty())?;
+        let utc = match chrono::DateTime::parse_from_rfc3339(timestamp) {
+            Ok(timestamp) => timestamp.with_timezone(&chrono::Utc),
+            Err(_) => timestamp.parse::<chrono::NaiveDateTime>().ok()?.and_utc(),
+        };
+
+        Some(self.timezone.at(utc).date())
+    }
+
+    /// Available hours per day for the user with `username`
+    pub fn daily_capacity(&self, username: &str) -> f32 {
+        self.capacity
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(username))
+            .map(|(_, hours)| *hours)
+            .unwrap_or(self.hours_per_day)
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Estimates {
+    pub mode: EstimateMode,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Importance {
+    /// Shorthands for importance levels, e.g. `p0 = "Critical"` allows writing `!p0`
+    pub aliases: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tags {
+    /// Colors cycled through when creating tags that don't have a color in `colors`
+    pub palette: Vec<String>,
+    /// Colors for specific tags when they get created, e.g. `bug = "#e53935"`
+    pub colors: HashMap<String, String>,
+    /// Markup written instead of a tag, e.g. `ps5 = "#platform #ps5"`
+    pub expansions: HashMap<String, String>,
+}
+
+impl Tags {
+    /// What `#tag` expands to, if anything
+    pub fn expansion(&self, tag: &str) -> Option<&str> {
+        self.expansions
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(tag))
+            .map(|(_, expansion)| expansion.as_str())
+    }
+
+    /// Color for the `index`th newly created tag named `tag`, if any is configured
+    pub fn color(&self, tag: &str, index: usize) -> Option<&str> {
+        self.colors
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(tag))
+            .map(|(_, color)| color.as_str())
+            .or_else(|| {
+                (!self.palette.is_empty())
+                    .then(|| self.palette[index % self.palette.len()].as_str())
+            })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Markup {
+    /// Contents of a regex character class listing what may appear in tags and mentions
+    pub word_characters: String,
+    /// Whether to convert descriptions into the Markdown subset Hack'n'Plan renders
+    pub convert_markdown: bool,
+    /// Title of the created work items, see `--title-template`
+    pub title_template: Option<String>,
+}
+
+impl Default for Markup {
+    fn default() -> Self {
+        Markup {
+            word_characters: DEFAULT_WORD_CHARACTERS.to_owned(),
+            convert_markdown: true,
+            title_template: None,
+        }
+    }
+}
+
+/// How `#1234` references to existing work items in descriptions are handled
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct References {
+    /// Turn references into links to the work item
+    pub link: bool,
+    /// Check that referenced work items exist, skipping the ones that don't
+    pub validate: bool,
+    /// Link to a work item, `{project}` and `{id}` are replaced by their ids
+    pub link_template: String,
+}
+
+impl Default for References {
+    fn default() -> Self {
+        References {
+            link: false,
+            validate: false,
+            link_template: "https://app.hacknplan.com/p/{project}/kanban?taskId={id}".to_owned(),
+        }
+    }
+}
+
+impl References {
+    pub fn url(&self, project_id: i64, id: i64) -> String {
+        self.link_template
+            .replace("{project}", &project_id.to_string())
+            .replace("{id}", &id.to_string())
+    }
+}
+
+/// Extra markup defined by the user, evaluated after the built-in tokens
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CustomToken {
+    #[serde(deserialize_with = "deserialize_regex")]
+    pub pattern: Regex,
+    pub field: TokenField,
+    /// Value for `field`, capture groups of `pattern` can be referenced with `$1` or `${name}`
+    pub value: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenField {
+    Tag,
+    Category,
+    Importance,
+    Assignee,
+    Board,
+    Estimate,
+    /// The (first) board of the milestone, found by its name or id
+    Milestone,
+}
+
+/// How pages from a Notion export are turned into tickets
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Notion {
+    /// Field each database property fills in, by the property's name, e.g. `Status = "stage"`
+    pub properties: HashMap<String, PropertyField>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PropertyField {
+    Tag,
+    Category,
+    Importance,
+    Assignee,
+    Board,
+    Estimate,
+    Stage,
+    Start,
+    Due,
+}
+
+fn deserialize_timezone<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timezone, D::Error> {
+    let timezone = String::deserialize(deserializer)?;
+    if timezone.eq_ignore_ascii_case("local") {
+        return Ok(Timezone::Local);
+    }
+    if timezone.eq_ignore_ascii_case("utc") {
+        return Ok(Timezone::Named(chrono_tz::UTC));
+    }
+
+    timezone
+        .parse()
+        .map(Timezone::Fixed)
+        .or_else(|_| timezone.parse().map(Timezone::Named))
+        .map_err(|_| {
+            serde::de::Error::custom(format!(
+                "Expected timezone \"{timezone}\" to be `local`, a name like `Europe/Amsterdam` \
+                 or an offset like `+02:00`"
+            ))
+        })
+}
+
+fn deserialize_regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(serde::de::Error::custom)
+}
+
+impl Config {
+    /// Loads the config from `path` if given, otherwise from `./hnp.toml` or the user's config
+    /// directory, falling back to the defaults when neither exists.
+    pub fn load(path: Option<&Path>) -> Config {
+        match Self::find(path) {
+            Some(path) => Self::parse(&path)
+                .unwrap_or_else(|error| panic!("To load config file {}: {error}", path.display())),
+            None => Config::default(),
+        }
+    }
+
+    /// `path` when given, or else the first of the default locations that exists
+    pub fn find(path: Option<&Path>) -> Option<PathBuf> {
+        match path {
+            Some(path) => Some(path.to_path_buf()),
+            None => Self::default_paths().into_iter().find(|path| path.exists()),
+        }
+    }
+
+    pub fn parse(path: &Path) -> Result<Config, String> {
+        let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+        toml::from_str(&contents).map_err(|error| error.to_string())
+    }
+
+    /// The parts of the config that `hnp::core` needs to read the markup
+    pub fn markup_options(&self, scan_description: bool) -> hnp::core::Options {
+        hnp::core::Options {
+            scan_description,
+            estimate_mode: self.estimates.mode,
+            hours_per_day: self.calendar.hours_per_day,
+            days_per_week: self.calendar.days_per_week,
+            importance_aliases: self.importance.aliases.clone(),
+            strictness: hnp::core::Strictness::Normal,
+            today: Some(self.calendar.today()),
+        }
+    }
+
+    fn default_paths() -> Vec<PathBuf> {
+        let mut paths = vec![PathBuf::from(CONFIG_FILE_NAME)];
+
+        if let Some(config_dir) = paths::config_dir() {
+            paths.push(config_dir.join("config.toml"));
+        }
+        if let Some(legacy_config_dir) = paths::legacy_config_dir() {
+            paths.push(legacy_config_dir.join("config.toml"));
+        }
+
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::Calendar;
+
+    fn calendar(timezone: &str) -> Calendar {
+        toml::from_str(&format!("timezone = \"{timezone}\"")).expect("To parse the calendar")
+    }
+
+    fn day_of(calendar: &Calendar, timestamp: &str) -> Option<NaiveDate> {
+        calendar.day_of(&Some(timestamp.to_owned()))
+    }
+
+    fn day(date: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+    }
+
+    #[test]
+    fn follows_daylight_saving_time_into_summer() {
+        let amsterdam = calendar("Europe/Amsterdam");
+
+        // NOTE: Clocks in Amsterdam go from +01:00 to +02:00 at 01:00 UTC on March 29th 2026
+        assert_eq!(day_of(&amsterdam, "2026-03-28T22:30:00"), day("2026-03-28"));
+        assert_eq!(day_of(&amsterdam, "2026-03-28T23:30:00"), day("2026-03-29"));
+        assert_eq!(day_of(&amsterdam, "2026-03-29T21:30:00"), day("2026-03-29"));
+        assert_eq!(day_of(&amsterdam, "2026-03-29T22:30:00"), day("2026-03-30"));
+    }
+
+    #[test]
+    fn follows_daylight_saving_time_into_winter() {
+        let amsterdam = calendar("Europe/Amsterdam");
+
+        // NOTE: Clocks in Amsterdam go from +02:00 to +01:00 at 01:00 UTC on October 25th 2026
+        assert_eq!(day_of(&amsterdam, "2026-10-24T22:30:00"), day("2026-10-25"));
+        assert_eq!(day_of(&amsterdam, "2026-10-25T22:30:00"), day("2026-10-25"));
+        assert_eq!(day_of(&amsterdam, "2026-10-25T23:30:00"), day("2026-10-26"));
+
+        // NOTE: New York switches a week later, at 06:00 UTC on November 1st 2026
+        let new_york = calendar("America/New_York");
+        assert_eq!(day_of(&new_york, "2026-11-01T03:30:00"), day("2026-10-31"));
+        assert_eq!(day_of(&new_york, "2026-11-01T04:30:00"), day("2026-11-01"));
+        assert_eq!(day_of(&new_york, "2026-11-02T04:30:00"), day("2026-11-01"));
+    }
+
+    #[test]
+    fn keeps_fixed_offsets_all_year() {
+        let fixed = calendar("+01:00");
+
+        assert_eq!(day_of(&fixed, "2026-03-29T22:30:00"), day("2026-03-29"));
+        assert_eq!(day_of(&fixed, "2026-10-24T22:30:00"), day("2026-10-24"));
+        assert_eq!(
+            day_of(&calendar("UTC"), "2026-10-24T23:59:59"),
+            day("2026-10-24")
+        );
+    }
+
+    #[test]
+    fn reads_timestamps_with_offsets_and_fractions() {
+        let amsterdam = calendar("Europe/Amsterdam");
+
+        assert_eq!(
+            day_of(&amsterdam, "2026-10-16T23:30:00+02:00"),
+            day("2026-10-16")
+        );
+        assert_eq!(
+            day_of(&amsterdam, "2026-10-16T22:30:00Z"),
+            day("2026-10-17")
+        );
+        assert_eq!(
+            day_of(&amsterdam, "2026-10-16T22:30:00.123"),
+            day("2026-10-17")
+        );
+        assert_eq!(amsterdam.day_of(&Some(String::new())), None);
+        assert_eq!(amsterdam.day_of(&None), None);
+    }
+
+    #[test]
+    fn rejects_unknown_time_zones() {
+        assert!(toml::from_str::<Calendar>("timezone = \"Mars/Olympus_Mons\"").is_err());
+        assert!(toml::from_str::<Calendar>("timezone = \"+25:00\"").is_err());
+    }
+}
@@ -0,0 +1,1692 @@
+//! Parsing and resolution of hnp's ticket markup without any I/O, so it also compiles to
+//! wasm32 for editor extensions. Everything that needs Hack'n'Plan comes in through
+//! [`Metadata`], which can be fetched through any [`Backend`].
+//!
+//! Errors are expectations like the panics of the CLI (`To find a user for ...`), the CLI panics
+//! with them and [`lint`] reports them as `Expected to find a user for ...`.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{models, Id};
+
+/// Characters allowed in tags, categories and mentions when the config doesn't say otherwise
+pub const DEFAULT_WORD_CHARACTERS: &str = r"\p{L}\p{M}\p{N}_.\-";
+
+/// Whether the project's estimated cost is tracked in hours or in story points
+#[derive(Deserialize, Debug, Default, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EstimateMode {
+    #[default]
+    Hours,
+    Points,
+}
+
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum Tag {
+    Category(Id, String),
+    Tag(Id, String),
+    UnaddedTag(String),
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SubTask {
+    pub title: String,
+    pub is_completed: bool,
+    pub assigned_user_ids: Vec<Id>,
+    pub estimated_cost: f32,
+}
+
+impl SubTask {
+    /// Sub tasks can't have assignees or costs, so those with either become child work items
+    pub fn is_work_item(&self) -> bool {
+        !self.assigned_user_ids.is_empty() || self.estimated_cost > 0.0
+    }
+}
+
+/// Written in a title to leave the definition of done from the config out of the ticket
+pub const SKIP_DEFINITION_OF_DONE: &str = "!nodod";
+
+/// What the markup is resolved against, as tuples like the CLI passes them around
+#[derive(Debug, Default, Clone)]
+pub struct Metadata {
+    pub categories: Vec<(Id, String)>,
+    /// Users with their name and username
+    pub users: Vec<(Id, String, String)>,
+    pub tags: Vec<(Id, String)>,
+    /// Importance levels with whether they're the default one
+    pub importance_levels: Vec<(Id, String, bool)>,
+    /// Boards with the id of the milestone they belong to, if any
+    pub boards: Vec<(Id, String, Option<Id>)>,
+    pub stages: Vec<models::Stage>,
+}
+
+/// Source of a project's collections, like the HTTP API or a metadata snapshot
+pub trait Backend {
+    /// `collection` of the project (`categories`, `tags`, `users`, `importancelevels`, `boards`
+    /// or `stages`) as the API returns it
+    fn collection(
+        &self,
+        collection: &str,
+    ) -> impl Future<Output = Result<serde_json::Value, String>>;
+}
+
+impl Metadata {
+    pub async fn fetch(backend: &impl Backend) -> Result<Metadata, String> {
+        async fn models<T: serde::de::DeserializeOwned>(
+            backend: &impl Backend,
+            collection: &str,
+        ) -> Result<Vec<T>, String> {
+            serde_json::from_value(backend.collection(collection).await?)
+                .map_err(|error| format!("To deserialize {collection}: {error}"))
+        }
+
+        Ok(Metadata {
+            categories: models::<models::Category>(backend, "categories")
+                .await?
+                .into_iter()
+                .map(|category| (category.category_id, category.name))
+                .collect(),
+            users: models::<models::ProjectUser>(backend, "users")
+                .await?
+                .into_iter()
+                .map(|models::ProjectUser { user }| (user.id, user.name, user.username))
+                .collect(),
+            tags: models::<models::TagDef>(backend, "tags")
+                .await?
+                .into_iter()
+                .map(|tag| (tag.tag_id, tag.name))
+                .collect(),
+            importance_levels: models::<models::ImportanceLevel>(backend, "importancelevels")
+                .await?
+                .into_iter()
+                .map(|level| (level.importance_level_id, level.name, level.is_default))
+                .collect(),
+            boards: models::<models::Board>(backend, "boards")
+                .await?
+                .into_iter()
+                .map(|board| (board.board_id, board.name, board.milestone_id))
+                .collect(),
+            stages: models::<models::Stage>(backend, "stages").await?,
+        })
+    }
+}
+
+/// How estimates and importance levels are read, the parts of the config that matter here
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Also honor tags, estimates, importance and dates written in the description
+    pub scan_description: bool,
+    pub estimate_mode: EstimateMode,
+    pub hours_per_day: f32,
+    pub days_per_week: f32,
+    /// Shorthands for importance levels, e.g. `p0` for "Critical"
+    pub importance_aliases: HashMap<String, String>,
+    pub strictness: Strictness,
+    /// Day that relative dates like `due:friday` count from, they're an error without it
+    pub today: Option<chrono::NaiveDate>,
+}
+
+/// What happens to tokens that don't resolve and mentions that match several users
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Strictness {
+    /// Mentions matching several users are an error as well
+    Strict,
+    /// Unknown mentions and importance levels are an error, the first user matching a mention
+    /// is taken
+    #[default]
+    Normal,
+    /// Unknown mentions, tags and importance levels are kept in the text as is, see
+    /// [`Parsed::unresolved`]
+    Lenient,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            scan_description: false,
+            estimate_mode: EstimateMode::Hours,
+            hours_per_day: 8.0,
+            days_per_week: 5.0,
+            importance_aliases: HashMap::new(),
+            strictness: Strictness::Normal,
+            today: None,
+        }
+    }
+}
+
+impl Options {
+    fn importance_alias(&self, alias: &str) -> Option<&str> {
+        self.importance_aliases
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(alias))
+            .map(|(_, level)| level.as_str())
+    }
+}
+
+/// The matchers of the markup, tags and mentions depend on the configured word characters
+#[derive(Debug, Clone)]
+pub struct Markup {
+    // NOTE: Tokens can't follow a letter or digit, leaving e-mail addresses and `C#` alone
+    // NOTE: Separators have to be on a line of their own, so `|---|` in tables is left alone
+    pub ticket_separator: Regex,
+    pub title_separator: Regex,
+    pub hash_tag: Regex,
+    pub mention: Regex,
+    /// Matches `[] task` as well as Markdown task lists like `  - [ ] task` and `- [x] task`
+    pub sub_task: Regex,
+    pub estimate: Regex,
+    pub urgency: Regex,
+    pub reference: Regex,
+    pub dependency: Regex,
+    pub due_date: Regex,
+    pub start_date: Regex,
+    pub stage: Regex,
+    /// `type:bug` anywhere in the title, or `[bug]` at its start
+    pub work_item_type: Regex,
+}
+
+impl Markup {
+    pub fn new(word_characters: &str) -> Result<Markup, regex::Error> {
+        // NOTE: The name has to end in a letter or digit so trailing punctuation (`#bug.`) isn't
+        // part of it
+        let word = format!(r"[{word_characters}]*[[{word_characters}]&&\w]");
+
+        Ok(Markup {
+            ticket_separator: Regex::new(r"(?m)^[ \t]*---[ \t]*\r?$")?,
+            title_separator: Regex::new(r"(?m)^[ \t]*===[ \t]*\r?$")?,
+            hash_tag: Regex::new(&format!(r#"\B#(?:"[^"\n]+"|{word})"#))?,
+            mention: Regex::new(&format!(r"\B@{word}"))?,
+            sub_task: Regex::new(
+                r"(?m)^[ \t]*(?:[-*+][ \t]+)?\[(?<checked>[ xX]?)\](?<title>.*)$",
+            )?,
+            estimate: Regex::new(concat!(
+                r"~(?:(?<points>\d+(?:\.\d+)?)p|",
+                r"(?:(?<weeks>\d+(?:\.\d+)?)w)?(?:(?<days>\d+(?:\.\d+)?)d)?",
+                r"(?:(?<hours>\d+(?:\.\d+)?)h)?(?:(?<minutes>\d+(?:\.\d+)?)m)?",
+                r"(?:(?<seconds>\d+(?:\.\d+)?)s)?)"
+            ))?,
+            urgency: Regex::new(r"!\w+")?,
+            reference: Regex::new(r"\B#(?<id>\d+)\b")?,
+            dependency: Regex::new(r"(?i)\bdepends\s+on\s+#(?<id>\d+)\b")?,
+            // NOTE: `next` is followed by a space, as in `due:next week`. Punctuation after the
+            // date, like in `due:friday.`, isn't part of it, the `-` in `2024-05-31` is.
+            due_date: Regex::new(r"\bdue:(?<date>(?i:next)[ \t]+\w+|[^\s,.;:!?)]+)")?,
+            start_date: Regex::new(r"\bstart:(?<date>(?i:next)[ \t]+\w+|[^\s,.;:!?)]+)")?,
+            // NOTE: `>stage:Planned` reads like moving the work item, so the `>` is allowed
+            stage: Regex::new(r#">?\bstage:(?:"(?<quoted>[^"\n]+)"|(?<name>\S+))"#)?,
+            // NOTE: Brackets need two characters, so `[x]` isn't mistaken for one
+            work_item_type: Regex::new(
+                r"\btype:(?<name>\S+)|^\[(?<bracketed>[\p{L}\p{N}_-]{2,})\]",
+            )?,
+        })
+    }
+
+    /// The tickets in `contents` with the line they start on (counting from 0), leaving out
+    /// empty ones
+    pub fn split_tickets<'a>(&self, contents: &'a str) -> Vec<(usize, &'a str)> {
+        let mut start = 0;
+        let mut tickets = vec![];
+
+        for separator in self
+            .ticket_separator
+            .find_iter(contents)
+            .map(|separator| (separator.start(), separator.end()))
+            .chain([(contents.len(), contents.len())])
+        {
+            let text = &contents[start..separator.0];
+            if !text.trim().is_empty() {
+                // NOTE: Tickets start on the first line with text
+                let leading = &text[..text.len() - text.trim_start().len()];
+                let line = contents[..start].matches('\n').count() + leading.matches('\n').count();
+                tickets.push((line, text));
+            }
+            start = separator.1;
+        }
+
+        tickets
+    }
+
+    pub fn match_tags_and_categories(
+        &self,
+        title: &str,
+        available_categories: &[(Id, String)],
+        available_tags: &[(Id, String)],
+    ) -> Vec<Tag> {
+        self.hash_tag
+            .find_iter(title)
+            .filter(|hash_tag| !self.reference.is_match(hash_tag.as_str()))
+            .map(|hash_tag| {
+                let hash_tag = hash_tag
+                    .as_str()
+                    .replacen('#', "", 1)
+                    .trim_matches('"')
+                    .trim()
+                    .to_owned();
+
+                match_tag_or_category(&hash_tag, available_categories, available_tags)
+            })
+            .collect::<Vec<Tag>>()
+    }
+
+    /// The ids of the users mentioned in `string`, with the mentions that were left unresolved because of
+    /// [`Strictness::Lenient`]
+    pub fn match_mentions(
+        &self,
+        string: &str,
+        available_users: &[(Id, String, String)],
+        strictness: Strictness,
+    ) -> Result<(Vec<Id>, Vec<String>), String> {
+        let mut user_ids = vec![];
+        let mut unresolved = vec![];
+
+        for mention in self.mention.find_iter(string) {
+            let user_name = mention.as_str().replacen('@', "", 1).trim().to_lowercase();
+
+            match find_user(&user_name, available_users) {
+                Some(user) => {
+                    if strictness == Strictness::Strict {
+                        check_ambiguity(&user_name, available_users)?;
+                    }
+                    user_ids.push(user.0);
+                }
+                None if strictness == Strictness::Lenient => {
+                    unresolved.push(mention.as_str().to_owned())
+                }
+                None => return Err(user_not_found(&user_name, available_users)),
+            }
+        }
+
+        Ok((user_ids, unresolved))
+    }
+
+    pub fn strip_builtin_tokens(&self, title: &str) -> String {
+        self.strip_builtin_tokens_except(title, &[])
+    }
+
+    /// Like [`Markup::strip_builtin_tokens`], but leaves the tags, mentions, importance levels
+    /// and brackets in `keep` alone
+    pub fn strip_builtin_tokens_except(&self, title: &str, keep: &[String]) -> String {
+        let keep_or_strip = |captures: &regex::Captures| {
+            let token = captures.get(0).unwrap().as_str();
+            if keep.iter().any(|kept| kept == token) {
+                token.to_owned()
+            } else {
+                String::new()
+            }
+        };
+
+        // NOTE: First, as `[bug]` only counts at the start of the title
+        let title = self.work_item_type.replace_all(title, keep_or_strip);
+        let title = self
+            .hash_tag
+            .replace_all(&title, |captures: &regex::Captures| {
+                if self.reference.is_match(&captures[0]) {
+                    captures[0].to_owned()
+                } else {
+                    keep_or_strip(captures)
+                }
+            });
+        let title = self.mention.replace_all(&title, keep_or_strip);
+        let title = self.strip_estimates(&title);
+        let title = self.urgency.replace_all(&title, keep_or_strip);
+        let title = self.due_date.replace_all(&title, "");
+        let title = self.start_date.replace_all(&title, "");
+        let title = self.stage.replace_all(&title, "");
+
+        title.into_owned()
+    }
+
+    /// Tokens that are honored in the description with `--scan-description`, mentions are left
+    /// alone as those are kept in the description
+    pub fn strip_description_tokens(&self, description: &str) -> String {
+        let description = self.strip_hash_tags(description);
+        let description = self.strip_estimates(&description);
+        let description = self.urgency.replace_all(&description, "");
+        let description = self.due_date.replace_all(&description, "");
+        let description = self.start_date.replace_all(&description, "");
+
+        // NOTE: Only collapse spaces, newlines are part of the description's formatting
+        description
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .collect::<Vec<String>>()
+            .join("\n")
+            .trim()
+            .to_owned()
+    }
+
+    /// Removes tags and categories while leaving work item references like `#1234` alone
+    pub fn strip_hash_tags(&self, text: &str) -> String {
+        self.hash_tag
+            .replace_all(text, |captures: &regex::Captures| {
+                let hash_tag = captures.get(0).unwrap().as_str();
+
+                if self.reference.is_match(hash_tag) {
+                    hash_tag.to_owned()
+                } else {
+                    String::new()
+                }
+            })
+            .into_owned()
+    }
+
+    /// Removes estimates while leaving a lone `~` (like in `~/Documents`) alone
+    pub fn strip_estimates(&self, text: &str) -> String {
+        self.estimate
+            .replace_all(text, |captures: &regex::Captures| {
+                let estimate = captures.get(0).unwrap().as_str();
+
+                if estimate.len() > 1 {
+                    String::new()
+                } else {
+                    estimate.to_owned()
+                }
+            })
+            .into_owned()
+    }
+
+    /// Due date written as `due:2024-05-31` or relative to today like `due:friday`, in the
+    /// format Hack'n'Plan expects
+    pub fn due_date(&self, title: &str, options: &Options) -> Result<Option<String>, String> {
+        date(&self.due_date, title, "due date", options)
+    }
+
+    /// Start date written as `start:2024-05-01` or relative to today like `start:next week`, in
+    /// the format Hack'n'Plan expects
+    pub fn start_date(&self, title: &str, options: &Options) -> Result<Option<String>, String> {
+        date(&self.start_date, title, "start date", options)
+    }
+
+    /// Type written as `type:bug` or `[bug]` at the start of `title`. Brackets around anything
+    /// else, like `[WIP]`, are left alone.
+    pub fn work_item_type(
+        &self,
+        title: &str,
+        available_categories: &[(Id, String)],
+    ) -> Result<Option<WorkItemType>, String> {
+        let Some(captures) = self.work_item_type.captures(title) else {
+            return Ok(None);
+        };
+
+        match (captures.name("name"), captures.name("bracketed")) {
+            (Some(name), _) => find_work_item_type(name.as_str(), available_categories).map(Some),
+            (None, Some(name)) => Ok(find_work_item_type(name.as_str(), available_categories).ok()),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Stage written as `stage:Testing`, `>stage:Testing` or `stage:"In progress"`
+    pub fn stage(
+        &self,
+        title: &str,
+        available_stages: &[models::Stage],
+    ) -> Result<Option<Id>, String> {
+        let Some(captures) = self.stage.captures(title) else {
+            return Ok(None);
+        };
+        let Some(stage) = captures.name("quoted").or_else(|| captures.name("name")) else {
+            return Ok(None);
+        };
+
+        find_stage(stage.as_str(), available_stages).map(Some)
+    }
+
+    pub fn estimate(&self, title: &str, options: &Options) -> Result<f32, String> {
+        // NOTE: Skip lone `~` characters that match without any amount
+        let Some(captures) = self
+            .estimate
+            .captures_iter(title)
+            .find(|captures| captures.get(0).unwrap().len() > 1)
+        else {
+            return Ok(0.0);
+        };
+        let estimate = captures.get(0).unwrap().as_str();
+        let amount = |name: &str| {
+            captures.name(name).map_or(0.0, |amount| {
+                amount.as_str().parse::<f32>().unwrap_or_default()
+            })
+        };
+
+        if captures.name("points").is_some() {
+            if options.estimate_mode != EstimateMode::Points {
+                return Err(format!(
+                    "project to be configured for points-based estimation to use \"{estimate}\""
+                ));
+            }
+
+            return Ok(amount("points"));
+        }
+
+        let hours = amount("weeks") * options.days_per_week * options.hours_per_day
+            + amount("days") * options.hours_per_day
+            + amount("hours")
+            + amount("minutes") / 60.0
+            + amount("seconds") / 3600.0;
+
+        if hours > 0.0 && options.estimate_mode == EstimateMode::Points {
+            return Err(format!(
+                "estimate \"{estimate}\" to be written in points (e.g. ~3p) as the project is configured for points-based estimation"
+            ));
+        }
+
+        Ok(hours)
+    }
+
+    /// The first importance level in `text`, leaving out [`SKIP_DEFINITION_OF_DONE`] which
+    /// looks like one
+    pub fn find_urgency<'t>(&self, text: &'t str) -> Option<regex::Match<'t>> {
+        self.urgency.find_iter(text).find(|urgency| {
+            !urgency
+                .as_str()
+                .eq_ignore_ascii_case(SKIP_DEFINITION_OF_DONE)
+        })
+    }
+
+    /// Whether `title` has [`SKIP_DEFINITION_OF_DONE`]
+    pub fn skips_definition_of_done(&self, title: &str) -> bool {
+        self.urgency.find_iter(title).any(|urgency| {
+            urgency
+                .as_str()
+                .eq_ignore_ascii_case(SKIP_DEFINITION_OF_DONE)
+        })
+    }
+
+    pub fn importance_level(
+        &self,
+        title: &str,
+        available_importance_levels: &[(Id, String, bool)],
+        options: &Options,
+    ) -> Result<Id, String> {
+        if let Some(urgency) = self.find_urgency(title) {
+            let urgency = urgency.as_str().replace('!', "");
+
+            find_importance_level(&urgency, available_importance_levels, options)
+        } else {
+            available_importance_levels
+                .iter()
+                .find(
+                    |level| level.2, // NOTE: This is where the isDefault bool lives, will auto-filter
+                )
+                .map(|level| level.0)
+                .ok_or_else(|| "atleast one importance level to be default".to_owned())
+        }
+    }
+
+    /// The built-in tokens of a ticket in order, `text` being its title and description. Tokens
+    /// in the description only count where they're honored, see [`Options::scan_description`].
+    pub fn tokens(&self, text: &str, scan_description: bool) -> Vec<Token> {
+        let (title_end, description_start) = self
+            .title_separator
+            .find(text)
+            .map_or((text.len(), text.len()), |separator| {
+                (separator.start(), separator.end())
+            });
+
+        // NOTE: Where tokens overlap the kind listed first wins, so `depends on #12` isn't a
+        // reference as well and `#12` isn't a tag
+        let matchers = [
+            (TokenKind::Dependency, &self.dependency),
+            (TokenKind::Reference, &self.reference),
+            (TokenKind::Stage, &self.stage),
+            (TokenKind::WorkItemType, &self.work_item_type),
+            (TokenKind::DueDate, &self.due_date),
+            (TokenKind::StartDate, &self.start_date),
+            (TokenKind::Tag, &self.hash_tag),
+            (TokenKind::Mention, &self.mention),
+            (TokenKind::Estimate, &self.estimate),
+            (TokenKind::Importance, &self.urgency),
+        ];
+
+        let mut tokens: Vec<Token> = vec![];
+        fn add(tokens: &mut Vec<Token>, kind: TokenKind, start: usize, end: usize) {
+            if end > start
+                && !tokens
+                    .iter()
+                    .any(|token| start < token.end && token.start < end)
+            {
+                tokens.push(Token { kind, start, end });
+            }
+        }
+
+        // NOTE: References and dependencies are only read from the description, in the title
+        // they only keep `#12` from being read as a tag. The title is read from its first
+        // character on, so `[bug]` can be at its start.
+        let title_start = text[..title_end].len() - text[..title_end].trim_start().len();
+        for (kind, matcher) in &matchers[1..] {
+            for token in matcher.find_iter(&text[title_start..title_end]) {
+                // NOTE: A lone `~` (like in `~/Documents`) isn't an estimate
+                if *kind != TokenKind::Estimate || token.len() > 1 {
+                    add(
+                        &mut tokens,
+                        *kind,
+                        title_start + token.start(),
+                        title_start + token.end(),
+                    );
+                }
+            }
+        }
+        tokens.retain(|token| token.kind != TokenKind::Reference);
+
+        // NOTE: Sub tasks only take mentions and estimates, the rest of the description takes
+        // everything but the stage when it's scanned
+        let description = &text[description_start..];
+        let mut sub_task_lines = vec![];
+        for sub_task in self.sub_task.captures_iter(description) {
+            let (Some(line), Some(checked), Some(title)) = (
+                sub_task.get(0),
+                sub_task.name("checked"),
+                sub_task.name("title"),
+            ) else {
+                continue;
+            };
+            sub_task_lines.push((line.start(), line.end()));
+
+            let offset = description_start + title.start();
+            add(
+                &mut tokens,
+                TokenKind::SubTask,
+                description_start + checked.start() - 1,
+                description_start + checked.end() + 1,
+            );
+            for (kind, matcher) in [
+                (TokenKind::Mention, &self.mention),
+                (TokenKind::Estimate, &self.estimate),
+            ] {
+                for token in matcher.find_iter(title.as_str()) {
+                    if kind != TokenKind::Estimate || token.len() > 1 {
+                        add(
+                            &mut tokens,
+                            kind,
+                            offset + token.start(),
+                            offset + token.end(),
+                        );
+                    }
+                }
+            }
+        }
+
+        for (kind, matcher) in &matchers {
+            let honored = match kind {
+                TokenKind::Dependency | TokenKind::Reference | TokenKind::Mention => true,
+                TokenKind::Stage | TokenKind::WorkItemType => false,
+                _ => scan_description,
+            };
+            if !honored {
+                continue;
+            }
+
+            for token in matcher.find_iter(description) {
+                let in_sub_task = sub_task_lines
+                    .iter()
+                    .any(|(start, end)| *start <= token.start() && token.end() <= *end);
+                if !in_sub_task && (*kind != TokenKind::Estimate || token.len() > 1) {
+                    add(
+                        &mut tokens,
+                        *kind,
+                        description_start + token.start(),
+                        description_start + token.end(),
+                    );
+                }
+            }
+        }
+
+        tokens.sort_by_key(|token| token.start);
+        tokens
+    }
+}
+
+/// What a token of the markup is, see [`Markup::tokens`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Dependency,
+    Reference,
+    Tag,
+    Mention,
+    Estimate,
+    Importance,
+    DueDate,
+    StartDate,
+    Stage,
+    WorkItemType,
+    SubTask,
+}
+
+impl TokenKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenKind::Dependency => "dependency",
+            TokenKind::Reference => "reference",
+            TokenKind::Tag => "tag",
+            TokenKind::Mention => "mention",
+            TokenKind::Estimate => "estimate",
+            TokenKind::Importance => "importance",
+            TokenKind::DueDate => "due date",
+            TokenKind::StartDate => "start date",
+            TokenKind::Stage => "stage",
+            TokenKind::WorkItemType => "type",
+            TokenKind::SubTask => "sub task",
+        }
+    }
+}
+
+/// A token in a ticket's text, `start` and `end` are byte offsets
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// What `token`, written as a token of `kind`, resolves to, like `Category "Bug" (2)`
+pub fn resolve(
+    markup: &Markup,
+    kind: TokenKind,
+    token: &str,
+    metadata: &Metadata,
+    options: &Options,
+) -> Result<String, String> {
+    let name = |token: &str, prefix: char| {
+        token
+            .trim_start_matches(prefix)
+            .trim_matches('"')
+            .to_owned()
+    };
+
+    match kind {
+        TokenKind::Dependency | TokenKind::Reference => {
+            let id = token.rsplit('#').next().unwrap_or_default();
+            Ok(match kind {
+                TokenKind::Dependency => format!("Depends on work item {id}"),
+                _ => format!("Work item {id}"),
+            })
+        }
+        TokenKind::Tag => Ok(
+            match match_tag_or_category(&name(token, '#'), &metadata.categories, &metadata.tags) {
+                Tag::Category(id, name) => format!("Category \"{name}\" ({id})"),
+                Tag::Tag(id, name) => format!("Tag \"{name}\" ({id})"),
+                Tag::UnaddedTag(name) => format!(
+                    "Tag \"{name}\", created on upload{}",
+                    did_you_mean('#', &name, known_tags(metadata))
+                ),
+            },
+        ),
+        TokenKind::Mention => {
+            let user_name = name(token, '@').to_lowercase();
+            find_user(&user_name, &metadata.users)
+                .map(|(id, name, username)| format!("User \"{name}\" (@{username}, {id})"))
+                .ok_or_else(|| user_not_found(&user_name, &metadata.users))
+        }
+        TokenKind::Estimate => {
+            let estimate = markup.estimate(token, options)?;
+            Ok(match options.estimate_mode {
+                EstimateMode::Points => format!("{estimate} point(s)"),
+                EstimateMode::Hours => format!("{estimate} hour(s)"),
+            })
+        }
+        TokenKind::Importance if token.eq_ignore_ascii_case(SKIP_DEFINITION_OF_DONE) => {
+            Ok("Leaves out the definition of done".to_owned())
+        }
+        TokenKind::Importance => {
+            let id =
+                find_importance_level(&name(token, '!'), &metadata.importance_levels, options)?;
+            let name = metadata
+                .importance_levels
+                .iter()
+                .find(|(level_id, _, _)| *level_id == id)
+                .map_or("", |(_, name, _)| name.as_str());
+            Ok(format!("Importance level \"{name}\" ({id})"))
+        }
+        TokenKind::DueDate => Ok(format!(
+            "Due {}",
+            date_only(markup.due_date(token, options)?)
+        )),
+        TokenKind::StartDate => Ok(format!(
+            "Starts {}",
+            date_only(markup.start_date(token, options)?)
+        )),
+        TokenKind::Stage => {
+            let id = markup.stage(token, &metadata.stages)?.unwrap_or_default();
+            let name = metadata
+                .stages
+                .iter()
+                .find(|stage| stage.stage_id == id)
+                .map_or("", |stage| stage.name.as_str());
+            Ok(format!("Stage \"{name}\" ({id})"))
+        }
+        TokenKind::WorkItemType => {
+            Ok(match markup.work_item_type(token, &metadata.categories)? {
+                Some(WorkItemType::Story) => {
+                    "Story, sub tasks with assignees or estimates become its children".to_owned()
+                }
+                Some(WorkItemType::Task) => "Task".to_owned(),
+                Some(WorkItemType::Category(id, name)) => format!("Category \"{name}\" ({id})"),
+                None => "Text, not a work item type".to_owned(),
+            })
+        }
+        TokenKind::SubTask => Ok(if token.trim_matches(['[', ']', ' ']).is_empty() {
+            "Sub task".to_owned()
+        } else {
+            "Completed sub task".to_owned()
+        }),
+    }
+}
+
+/// `2024-05-31` of a date in the format Hack'n'Plan expects
+fn date_only(date: Option<String>) -> String {
+    date.unwrap_or_default()
+        .split('T')
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// A ticket with the built-in markup resolved. Custom tokens, references and the board are left
+/// to the caller, so `title` still has custom tokens in it.
+#[derive(Debug)]
+pub struct Parsed {
+    pub title: String,
+    pub categories_or_tags: Vec<Tag>,
+    pub mentions: Vec<Id>,
+    pub estimate: f32,
+    pub importance_level: Id,
+    pub due_date: Option<String>,
+    pub start_date: Option<String>,
+    pub stage_id: Option<Id>,
+    /// Whether `type:` made it a story or a task
+    pub is_story: Option<bool>,
+    pub description: String,
+    pub sub_tasks: Vec<SubTask>,
+    /// Tokens in the title kept as text because they didn't resolve, see [`Strictness::Lenient`]
+    pub unresolved: Vec<String>,
+    /// Whether the title has [`SKIP_DEFINITION_OF_DONE`]
+    pub skip_definition_of_done: bool,
+}
+
+impl Parsed {
+    pub fn category_id(&self) -> Option<Id> {
+        self.categories_or_tags
+            .iter()
+            .find_map(|entry| match entry {
+                Tag::Category(id, _) => Some(*id),
+                _ => None,
+            })
+    }
+}
+
+/// Resolves the built-in markup of a single ticket, `text` being its title and description
+pub fn parse_ticket(
+    markup: &Markup,
+    text: &str,
+    metadata: &Metadata,
+    options: &Options,
+) -> Result<Parsed, String> {
+    let mut chunks = markup.title_separator.splitn(text, 2);
+    let original_title = chunks.next().unwrap_or_default().trim().to_owned();
+
+    let lenient = options.strictness == Strictness::Lenient;
+
+    let mut categories_or_tags =
+        markup.match_tags_and_categories(&original_title, &metadata.categories, &metadata.tags);
+    let (mentions, mut unresolved) =
+        markup.match_mentions(&original_title, &metadata.users, options.strictness)?;
+    let mut estimate = markup.estimate(&original_title, options)?;
+    let mut importance_level =
+        match markup.importance_level(&original_title, &metadata.importance_levels, options) {
+            Err(_) if lenient => {
+                unresolved.extend(
+                    markup
+                        .find_urgency(&original_title)
+                        .map(|urgency| urgency.as_str().to_owned()),
+                );
+                markup.importance_level("", &metadata.importance_levels, options)?
+            }
+            result => result?,
+        };
+    let mut due_date = markup.due_date(&original_title, options)?;
+    let mut start_date = markup.start_date(&original_title, options)?;
+    let stage_id = markup.stage(&original_title, &metadata.stages)?;
+    let work_item_type = markup.work_item_type(&original_title, &metadata.categories)?;
+    // NOTE: Brackets that aren't a type, like `[WIP]`, stay in the title
+    let mut kept = unresolved.clone();
+    if work_item_type.is_none() {
+        kept.extend(
+            markup
+                .work_item_type
+                .find(&original_title)
+                .map(|bracketed| bracketed.as_str().to_owned()),
+        );
+    }
+    let is_story = match work_item_type {
+        Some(WorkItemType::Story) => Some(true),
+        Some(WorkItemType::Task) => Some(false),
+        // NOTE: The type is a deliberate choice, so it wins over the tags
+        Some(WorkItemType::Category(id, name)) => {
+            categories_or_tags.insert(0, Tag::Category(id, name));
+            None
+        }
+        None => None,
+    };
+
+    // NOTE: Tags that don't exist yet are created before parsing, so in lenient mode the ones
+    // left are kept as text
+    if lenient {
+        for tag in &categories_or_tags {
+            if let Tag::UnaddedTag(tag) = tag {
+                unresolved.extend(
+                    markup
+                        .hash_tag
+                        .find_iter(&original_title)
+                        .map(|hash_tag| hash_tag.as_str())
+                        .find(|hash_tag| hash_tag.trim_start_matches('#').trim_matches('"') == tag)
+                        .map(str::to_owned),
+                );
+            }
+        }
+        categories_or_tags.retain(|tag| !matches!(tag, Tag::UnaddedTag(_)));
+    }
+
+    let title = markup.strip_builtin_tokens_except(&original_title, &kept);
+
+    let description = chunks.next().unwrap_or("").trim().to_owned();
+
+    let mut unknown_user = None;
+    let description = markup
+        .mention
+        .replace_all(&description, |capture: &regex::Captures| {
+            let mention = capture
+                .get(0)
+                .unwrap()
+                .as_str()
+                .replacen('@', "", 1)
+                .to_lowercase();
+
+            match find_user(&mention, &metadata.users) {
+                // NOTE: This is the third entry in the tuple: the `user_name`
+                Some((_, _, user_name)) => format!("@{user_name}"),
+                None if lenient => capture[0].to_owned(),
+                None => {
+                    unknown_user.get_or_insert(mention);
+                    String::new()
+                }
+            }
+        })
+        .to_string();
+    if let Some(user_name) = unknown_user {
+        return Err(user_not_found(&user_name, &metadata.users));
+    }
+
+    let sub_tasks = markup
+        .sub_task
+        .captures_iter(&description)
+        .map(|sub_task| {
+            let title = &sub_task["title"];
+            let (assigned_users, unresolved_mentions) =
+                markup.match_mentions(title, &metadata.users, options.strictness)?;
+            let stripped_title = markup
+                .mention
+                .replace_all(title, |captures: &regex::Captures| {
+                    if unresolved_mentions
+                        .iter()
+                        .any(|mention| *mention == captures[0])
+                    {
+                        captures[0].to_owned()
+                    } else {
+                        String::new()
+                    }
+                });
+            let stripped_title = markup.estimate.replace_all(&stripped_title, "");
+
+            Ok(SubTask {
+                title: stripped_title
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+                    .join(" "),
+                is_completed: !sub_task["checked"].trim().is_empty(),
+                assigned_user_ids: assigned_users,
+                estimated_cost: markup.estimate(title, options)?,
+            })
+        })
+        .collect::<Result<Vec<SubTask>, String>>()?;
+
+    let description = markup
+        .sub_task
+        .replace_all(&description, "")
+        .trim()
+        .to_string();
+
+    // Metadata in the description only fills in what the title didn't specify
+    let description = if options.scan_description {
+        categories_or_tags.extend(markup.match_tags_and_categories(
+            &description,
+            &metadata.categories,
+            &metadata.tags,
+        ));
+
+        if estimate == 0.0 {
+            estimate = markup.estimate(&description, options)?;
+        }
+
+        if markup.find_urgency(&original_title).is_none()
+            && markup.find_urgency(&description).is_some()
+        {
+            importance_level =
+                markup.importance_level(&description, &metadata.importance_levels, options)?;
+        }
+
+        if due_date.is_none() {
+            due_date = markup.due_date(&description, options)?;
+        }
+        if start_date.is_none() {
+            start_date = markup.start_date(&description, options)?;
+        }
+
+        markup.strip_description_tokens(&description)
+    } else {
+        description
+    };
+
+    Ok(Parsed {
+        title,
+        categories_or_tags,
+        mentions,
+        estimate,
+        importance_level,
+        due_date,
+        start_date,
+        stage_id,
+        is_story,
+        description,
+        sub_tasks,
+        unresolved,
+        skip_definition_of_done: markup.skips_definition_of_done(&original_title),
+    })
+}
+
+// NOTE: Hack'n'Plan answers work items past these limits with a 400 that doesn't say which field
+// is wrong, the values are on the safe side of what it accepts
+/// Longest title of a work item, in characters
+pub const MAX_TITLE_LENGTH: usize = 255;
+/// Longest description of a work item, in characters
+pub const MAX_DESCRIPTION_LENGTH: usize = 20_000;
+/// Highest estimated cost of a work item, in hours or points
+pub const MAX_ESTIMATE: f32 = 9_999.0;
+/// Most users a work item can be assigned to
+pub const MAX_ASSIGNEES: usize = 50;
+
+/// Checks a work item against the limits of Hack'n'Plan's API, so it fails before uploading
+pub fn check_limits(
+    title: &str,
+    description: &str,
+    estimate: f32,
+    assignees: usize,
+) -> Result<(), String> {
+    let title_length = title.chars().count();
+    if title.trim().is_empty() {
+        return Err("title to be left after removing the tokens from it".to_owned());
+    }
+    if title_length > MAX_TITLE_LENGTH {
+        return Err(format!(
+            "title \"{}...\" to be at most {MAX_TITLE_LENGTH} characters instead of {title_length}",
+            title.chars().take(40).collect::<String>()
+        ));
+    }
+
+    let description_length = description.chars().count();
+    if description_length > MAX_DESCRIPTION_LENGTH {
+        return Err(format!(
+            "description of \"{title}\" to be at most {MAX_DESCRIPTION_LENGTH} characters instead of {description_length}"
+        ));
+    }
+
+    if !(0.0..=MAX_ESTIMATE).contains(&estimate) {
+        return Err(format!(
+            "estimate of \"{title}\" to be between 0 and {MAX_ESTIMATE} instead of {estimate}"
+        ));
+    }
+
+    if assignees > MAX_ASSIGNEES {
+        return Err(format!(
+            "\"{title}\" to have at most {MAX_ASSIGNEES} assignees instead of {assignees}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Something wrong with the markup, on a line of the contents (counting from 0)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub is_error: bool,
+    pub message: String,
+}
+
+/// Problems in `contents` that would stop the CLI from uploading it, or make it ask first
+pub fn lint(
+    markup: &Markup,
+    contents: &str,
+    metadata: &Metadata,
+    options: &Options,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    for (line, text) in markup.split_tickets(contents) {
+        let parsed = match parse_ticket(markup, text, metadata, options) {
+            Ok(parsed) => parsed,
+            Err(expectation) => {
+                diagnostics.push(Diagnostic {
+                    line,
+                    is_error: true,
+                    message: expected(&expectation),
+                });
+                continue;
+            }
+        };
+
+        let title = parsed
+            .title
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let limits = [check_limits(
+            &title,
+            &parsed.description,
+            parsed.estimate,
+            parsed.mentions.len(),
+        )]
+        .into_iter()
+        .chain(parsed.sub_tasks.iter().map(|sub_task| {
+            check_limits(
+                &sub_task.title,
+                "",
+                sub_task.estimated_cost,
+                sub_task.assigned_user_ids.len(),
+            )
+        }));
+        for expectation in limits.filter_map(Result::err) {
+            diagnostics.push(Diagnostic {
+                line,
+                is_error: true,
+                message: expected(&expectation),
+            });
+        }
+
+        for tag in &parsed.categories_or_tags {
+            if let Tag::UnaddedTag(tag) = tag {
+                diagnostics.push(Diagnostic {
+                    line,
+                    is_error: false,
+                    message: format!(
+                        "Tag \"{tag}\" doesn't exist yet, it gets created on upload{}",
+                        did_you_mean('#', tag, known_tags(metadata))
+                    ),
+                });
+            }
+        }
+
+        if parsed.category_id().is_none() {
+            diagnostics.push(Diagnostic {
+                line,
+                is_error: true,
+                message: format!(
+                    "Expected to have atleast one category available for ticket: {}",
+                    parsed.title.trim()
+                ),
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// An expectation as the CLI reports it, `To find ...` becomes `Expected to find ...`
+pub fn expected(expectation: &str) -> String {
+    let mut characters = expectation.chars();
+    match characters.next() {
+        Some(first) => format!(
+            "Expected {}",
+            first.to_lowercase().chain(characters).collect::<String>()
+        ),
+        None => String::new(),
+    }
+}
+
+pub fn match_tag_or_category(
+    hash_tag: &str,
+    available_categories: &[(Id, String)],
+    available_tags: &[(Id, String)],
+) -> Tag {
+    let name = normalize_name(hash_tag);
+
+    match available_categories
+        .iter()
+        .find(|(_, category)| normalize_name(category) == name)
+    {
+        Some((id, category)) => Tag::Category(*id, category.to_owned()),
+        None => match available_tags
+            .iter()
+            .find(|(_, tag)| normalize_name(tag) == name)
+        {
+            Some((id, tag)) => Tag::Tag(*id, tag.to_owned()),
+            None => Tag::UnaddedTag(hash_tag.to_owned()),
+        },
+    }
+}
+
+/// Names of the categories and tags, which are both written as `#name`
+pub fn known_tags(metadata: &Metadata) -> impl Iterator<Item = &str> {
+    metadata
+        .categories
+        .iter()
+        .chain(&metadata.tags)
+        .map(|(_, name)| name.as_str())
+}
+
+/// Lowercases `name` and treats `-`, `_` and whitespace alike, so `#level-design`,
+/// `#level_design` and `#"Level Design"` all refer to "Level Design"
+pub fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .split(|character: char| character == '-' || character == '_' || character.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// The expectation for a mention of `user_name` that no user matches, suggesting the closest
+/// usernames
+pub fn user_not_found(user_name: &str, available_users: &[(Id, String, String)]) -> String {
+    format!(
+        "To find a user for user_name: {user_name}{}",
+        did_you_mean(
+            '@',
+            user_name,
+            available_users
+                .iter()
+                .map(|(_, _, username)| username.as_str())
+        )
+    )
+}
+
+/// `, did you mean #design?` for the names that `name` is likely a typo of, nothing when none
+/// come close. `sigil` is the `#` or `@` the names are written with.
+pub fn did_you_mean<'a>(
+    sigil: char,
+    name: &str,
+    names: impl IntoIterator<Item = &'a str>,
+) -> String {
+    let suggestions = suggestions(name, names)
+        .into_iter()
+        .map(|suggestion| {
+            if suggestion.contains(char::is_whitespace) {
+                format!("{sigil}\"{suggestion}\"")
+            } else {
+                format!("{sigil}{suggestion}")
+            }
+        })
+        .collect::<Vec<String>>();
+
+    match suggestions.as_slice() {
+        [] => String::new(),
+        [suggestion] => format!(", did you mean {suggestion}?"),
+        [rest @ .., last] => format!(", did you mean {} or {last}?", rest.join(", ")),
+    }
+}
+
+/// Up to three of `names` within a few typos of `name`, closest first. Allows one typo for every
+/// three characters, and at least one.
+pub fn suggestions<'a>(name: &str, names: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let name = normalize_name(name);
+    let allowed = (name.chars().count() / 3).max(1);
+
+    let mut suggestions = names
+        .into_iter()
+        .map(|candidate| (edit_distance(&name, &normalize_name(candidate)), candidate))
+        .filter(|(distance, _)| (1..=allowed).contains(distance))
+        .collect::<Vec<(usize, &str)>>();
+    suggestions.sort_by_key(|(distance, _)| *distance);
+    suggestions.dedup_by_key(|(_, candidate)| normalize_name(candidate));
+
+    suggestions
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Number of characters to insert, remove, replace or swap with their neighbor to turn `left`
+/// into `right`. Swaps count as one edit, as `#desgin` is as likely a typo as `#desing`.
+fn edit_distance(left: &str, right: &str) -> usize {
+    let left = left.chars().collect::<Vec<char>>();
+    let right = right.chars().collect::<Vec<char>>();
+
+    // NOTE: distances[i][j] is the distance between the first i and j characters
+    let mut distances = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let cost = usize::from(left[i - 1] != right[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[left.len()][right.len()]
+}
+
+/// Fails when `user_name` isn't anyone's username and the names of several users contain it
+fn check_ambiguity(
+    user_name: &str,
+    available_users: &[(Id, String, String)],
+) -> Result<(), String> {
+    let lowercase_user_name = user_name.to_lowercase();
+    if available_users
+        .iter()
+        .any(|(_, _, username)| username.to_lowercase() == lowercase_user_name)
+    {
+        return Ok(());
+    }
+
+    let matches = available_users
+        .iter()
+        .filter(|(_, name, _)| name.to_lowercase().contains(&lowercase_user_name))
+        .map(|(_, _, username)| format!("@{username}"))
+        .collect::<Vec<String>>();
+    if matches.len() > 1 {
+        return Err(format!(
+            "To find one user for user_name: {user_name}, it matches {}",
+            matches.join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+/// The user with `user_name` as their username, or else the first whose name contains it
+pub fn find_user<'a>(
+    user_name: &str,
+    available_users: &'a [(Id, String, String)],
+) -> Option<&'a (Id, String, String)> {
+    let lowercase_user_name = user_name.to_lowercase();
+
+    available_users
+        .iter()
+        .find(|(_, _, username)| username.to_lowercase() == lowercase_user_name)
+        .or_else(|| {
+            available_users
+                .iter()
+                .find(|(_, name, _)| name.to_lowercase().contains(&lowercase_user_name))
+        })
+}
+
+/// What `type:` or `[...]` makes a work item. Hack'n'Plan only tells stories and tasks apart,
+/// the categories of a project are its types otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkItemType {
+    Story,
+    Task,
+    Category(Id, String),
+}
+
+pub fn find_work_item_type(
+    name: &str,
+    available_categories: &[(Id, String)],
+) -> Result<WorkItemType, String> {
+    match normalize_name(name).as_str() {
+        "story" => Ok(WorkItemType::Story),
+        "task" => Ok(WorkItemType::Task),
+        normalized => available_categories
+            .iter()
+            .find(|(_, category)| normalize_name(category) == normalized)
+            .map(|(id, category)| WorkItemType::Category(*id, category.to_owned()))
+            .ok_or_else(|| format!("To find a work item type for \"{name}\", see `hnp types`")),
+    }
+}
+
+pub fn find_stage(stage: &str, available_stages: &[models::Stage]) -> Result<Id, String> {
+    available_stages
+        .iter()
+        .find(|available_stage| normalize_name(&available_stage.name) == normalize_name(stage))
+        .map(|available_stage| available_stage.stage_id)
+        .ok_or_else(|| format!("To find a stage for \"{stage}\""))
+}
+
+pub fn find_importance_level(
+    urgency: &str,
+    available_importance_levels: &[(Id, String, bool)],
+    options: &Options,
+) -> Result<Id, String> {
+    let urgency = urgency.to_lowercase();
+
+    if let Some(level_name) = options.importance_alias(&urgency) {
+        return available_importance_levels
+            .iter()
+            .find(|(_, name, _)| name.eq_ignore_ascii_case(level_name))
+            .map(|level| level.0)
+            .ok_or_else(|| {
+                format!("to find importance level \"{level_name}\" for alias \"!{urgency}\"")
+            });
+    }
+
+    // NOTE: Numeric shortcuts follow the order in which Hack'n'Plan lists the levels
+    if let Ok(position) = urgency.parse::<usize>() {
+        return position
+            .checked_sub(1)
+            .and_then(|index| available_importance_levels.get(index))
+            .map(|level| level.0)
+            .ok_or_else(|| {
+                format!(
+                    "\"!{position}\" to be between !1 and !{}",
+                    available_importance_levels.len()
+                )
+            });
+    }
+
+    available_importance_levels
+        .iter()
+        .find(|(_, name, _)| name.to_lowercase().matches(&urgency).count() != 0)
+        .map(|level| level.0)
+        .ok_or_else(|| format!("to find importance level for \"{urgency}\""))
+}
+
+fn date(
+    matcher: &Regex,
+    text: &str,
+    what: &str,
+    options: &Options,
+) -> Result<Option<String>, String> {
+    let Some(captures) = matcher.captures(text) else {
+        return Ok(None);
+    };
+
+    let written = &captures["date"];
+    let date = chrono::NaiveDate::parse_from_str(written, "%Y-%m-%d")
+        .ok()
+        .or_else(|| relative_date(written, options.today?))
+        .ok_or_else(|| match options.today {
+            Some(_) => format!(
+                "{what} \"{written}\" to be written as YYYY-MM-DD, a weekday, `tomorrow`, \
+                 `next week`, `next month` or like `+3d`"
+            ),
+            None => format!("{what} \"{written}\" to be written as YYYY-MM-DD"),
+        })?;
+
+    Ok(Some(format!("{}T00:00:00", date.format("%Y-%m-%d"))))
+}
+
+/// `written` counted from `today`:
+///
+/// - `today` and `tomorrow`
+/// - a weekday like `friday` or `fri`, the first one after today
+/// - `next friday`, the friday of next week
+/// - `next week` and `next month`, their first day
+/// - `+3d`, `+2w` or `+1m`, that many days, weeks or months from today
+fn relative_date(written: &str, today: chrono::NaiveDate) -> Option<chrono::NaiveDate> {
+    use chrono::{Datelike, Days, Months, Weekday};
+
+    let written = written
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase();
+    let next_week = today.checked_add_days(Days::new(
+        7 - u64::from(today.weekday().num_days_from_monday()),
+    ))?;
+
+    if let Some(offset) = written.strip_prefix('+') {
+        let unit = offset.chars().last()?;
+        let amount = offset.strip_suffix(unit)?.parse::<u32>().ok()?;
+        return match unit {
+            'd' => today.checked_add_days(Days::new(amount.into())),
+            'w' => today.checked_add_days(Days::new(u64::from(amount) * 7)),
+            'm' => today.checked_add_months(Months::new(amount)),
+            _ => None,
+        };
+    }
+
+    match written.as_str() {
+        "today" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        "next week" => Some(next_week),
+        "next month" => today.with_day(1)?.checked_add_months(Months::new(1)),
+        _ => match written.strip_prefix("next ") {
+            Some(weekday) => {
+                let weekday = weekday.parse::<Weekday>().ok()?;
+                next_week.checked_add_days(Days::new(weekday.num_days_from_monday().into()))
+            }
+            None => {
+                let weekday = written.parse::<Weekday>().ok()?;
+                let days = (7 + weekday.num_days_from_monday()
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                // NOTE: Today's weekday means the one a week from now
+                today.checked_add_days(Days::new(if days == 0 { 7 } else { days.into() }))
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{
+        relative_date, EstimateMode, Markup, Options, Strictness, Tag, DEFAULT_WORD_CHARACTERS,
+    };
+
+    fn markup() -> Markup {
+        Markup::new(DEFAULT_WORD_CHARACTERS).expect("To compile the markup")
+    }
+
+    /// Names of the tags and categories in `title`, none of which exist yet
+    fn tags(title: &str) -> Vec<String> {
+        markup()
+            .match_tags_and_categories(title, &[], &[])
+            .into_iter()
+            .map(|tag| match tag {
+                Tag::UnaddedTag(name) | Tag::Tag(_, name) | Tag::Category(_, name) => name,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_tags_with_hyphens_dots_and_accents() {
+        assert_eq!(tags("Block out the cave #level-design"), ["level-design"]);
+        assert_eq!(tags("Update #v1.2 notes"), ["v1.2"]);
+        assert_eq!(
+            tags("Sprites voor #ontwerp_ná #日本語"),
+            ["ontwerp_ná", "日本語"]
+        );
+    }
+
+    #[test]
+    fn leaves_trailing_punctuation_out_of_tags() {
+        assert_eq!(tags("Crash on load #bug."), ["bug"]);
+        assert_eq!(
+            tags("Crash on load (#bug), again #crash-"),
+            ["bug", "crash"]
+        );
+    }
+
+    #[test]
+    fn leaves_languages_and_references_alone() {
+        assert!(tags("Port the tools to C# and F#").is_empty());
+        assert!(tags("Follow up on #1234").is_empty());
+    }
+
+    #[test]
+    fn reads_mentions_of_international_names() {
+        let users = [
+            (100, "József Nagy".to_owned(), "józsef".to_owned()),
+            (101, "Zoë Brandt".to_owned(), "zoe.b".to_owned()),
+            (102, "Åsa Lind".to_owned(), "asa-lind".to_owned()),
+        ];
+        let mentions = |title: &str| {
+            markup()
+                .match_mentions(title, &users, Strictness::Normal)
+                .expect("To find the mentioned users")
+                .0
+        };
+
+        assert_eq!(mentions("Rig the boss @józsef"), [100]);
+        assert_eq!(mentions("Rig the boss @JÓZSEF."), [100]);
+        assert_eq!(mentions("Review with @zoe.b and @asa-lind"), [101, 102]);
+    }
+
+    #[test]
+    fn leaves_email_addresses_alone() {
+        let users = [(100, "Bram Dingelstad".to_owned(), "bram".to_owned())];
+
+        assert_eq!(
+            markup().match_mentions(
+                "Mail bram@example.com the build",
+                &users,
+                Strictness::Normal
+            ),
+            Ok((vec![], vec![]))
+        );
+    }
+
+    fn day(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").expect("To be a date")
+    }
+
+    /// Friday
+    const TODAY: &str = "2026-10-16";
+
+    #[test]
+    fn reads_estimates_in_the_unit_of_the_project() {
+        let hours = Options::default();
+        let points = Options {
+            estimate_mode: EstimateMode::Points,
+            ..Options::default()
+        };
+
+        assert_eq!(markup().estimate("Fix the jump ~1d2h", &hours), Ok(10.0));
+        assert_eq!(markup().estimate("Fix the jump ~3p", &points), Ok(3.0));
+        assert!(markup().estimate("Fix the jump ~3p", &hours).is_err());
+        assert!(markup().estimate("Fix the jump ~2h", &points).is_err());
+    }
+
+    #[test]
+    fn splits_tickets_with_windows_line_endings() {
+        let contents = "Fix the jump\r\n===\r\nIt's floaty\r\n---\r\nAdd a dash\r\n";
+
+        assert_eq!(
+            markup()
+                .split_tickets(contents)
+                .into_iter()
+                .map(|(line, text)| (line, text.trim()))
+                .collect::<Vec<(usize, &str)>>(),
+            [(0, "Fix the jump\r\n===\r\nIt's floaty"), (4, "Add a dash")]
+        );
+    }
+
+    #[test]
+    fn counts_weekdays_from_tomorrow() {
+        assert_eq!(
+            relative_date("saturday", day(TODAY)),
+            Some(day("2026-10-17"))
+        );
+        assert_eq!(relative_date("Thu", day(TODAY)), Some(day("2026-10-22")));
+        // NOTE: Today's weekday is the one a week from now
+        assert_eq!(relative_date("friday", day(TODAY)), Some(day("2026-10-23")));
+    }
+
+    #[test]
+    fn counts_next_weekdays_in_next_week() {
+        assert_eq!(
+            relative_date("next tuesday", day(TODAY)),
+            Some(day("2026-10-20"))
+        );
+        assert_eq!(
+            relative_date("Next  Friday", day(TODAY)),
+            Some(day("2026-10-23"))
+        );
+        assert_eq!(
+            relative_date("next monday", day("2026-10-12")),
+            Some(day("2026-10-19"))
+        );
+        assert_eq!(
+            relative_date("next week", day(TODAY)),
+            Some(day("2026-10-19"))
+        );
+    }
+
+    #[test]
+    fn counts_offsets_from_today() {
+        assert_eq!(relative_date("today", day(TODAY)), Some(day(TODAY)));
+        assert_eq!(
+            relative_date("tomorrow", day(TODAY)),
+            Some(day("2026-10-17"))
+        );
+        assert_eq!(relative_date("+3d", day(TODAY)), Some(day("2026-10-19")));
+        assert_eq!(relative_date("+2w", day(TODAY)), Some(day("2026-10-30")));
+        assert_eq!(
+            relative_date("next month", day(TODAY)),
+            Some(day("2026-11-01"))
+        );
+    }
+
+    #[test]
+    fn keeps_months_within_the_month_at_its_end() {
+        assert_eq!(
+            relative_date("+1m", day("2026-01-31")),
+            Some(day("2026-02-28"))
+        );
+        assert_eq!(
+            relative_date("+1m", day("2028-01-31")),
+            Some(day("2028-02-29"))
+        );
+        assert_eq!(
+            relative_date("+1m", day("2026-12-31")),
+            Some(day("2027-01-31"))
+        );
+    }
+
+    #[test]
+    fn rejects_what_it_doesnt_know() {
+        assert_eq!(relative_date("+3é", day(TODAY)), None);
+        assert_eq!(relative_date("+é", day(TODAY)), None);
+        assert_eq!(relative_date("+", day(TODAY)), None);
+        assert_eq!(relative_date("+d", day(TODAY)), None);
+        assert_eq!(relative_date("someday", day(TODAY)), None);
+    }
+
+    #[test]
+    fn leaves_trailing_punctuation_out_of_dates() {
+        let options = Options {
+            today: Some(day(TODAY)),
+            ..Default::default()
+        };
+        let due_date = |title: &str| markup().due_date(title, &options);
+
+        assert_eq!(
+            due_date("Ship the demo due:friday."),
+            Ok(Some("2026-10-23T00:00:00".to_owned()))
+        );
+        assert_eq!(
+            due_date("Ship the demo (due:+3d), then rest"),
+            Ok(Some("2026-10-19T00:00:00".to_owned()))
+        );
+        assert_eq!(
+            due_date("Ship the demo due:2026-11-02, then rest"),
+            Ok(Some("2026-11-02T00:00:00".to_owned()))
+        );
+    }
+}
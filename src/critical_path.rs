@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate};
+use clap::Args;
+
+use crate::config::{Config, EstimateMode};
+use crate::graph::{cyclic_edges, Dependencies};
+use crate::i18n::Message;
+use crate::{client, get_models, models, output, Id};
+
+#[derive(Args, Debug)]
+pub struct CriticalPathArguments {
+    /// Milestone to find the longest chain of dependencies in
+    #[arg(long)]
+    milestone: String,
+}
+
+/// Prints the chain of dependencies in a milestone with the most estimated work left, the work
+/// items that decide whether its due date is met
+pub async fn run(critical_path_arguments: &CriticalPathArguments, config: &Config) {
+    let client = client();
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let dependencies = Dependencies::fetch(&client, &critical_path_arguments.milestone).await;
+    let milestone = get_models::<models::Milestone>(&client, "milestones")
+        .await
+        .into_iter()
+        .find(|milestone| {
+            milestone
+                .name
+                .eq_ignore_ascii_case(&critical_path_arguments.milestone)
+        })
+        .expect("milestone to be found by the dependencies already");
+    spinner.finish_and_clear();
+
+    let cyclic = cyclic_edges(&dependencies.edges);
+    if !cyclic.is_empty() {
+        output::error(format!(
+            "The dependencies have a cycle, so there's no longest chain: {}, see `hnp graph`",
+            cyclic
+                .iter()
+                .map(|(from, to)| format!("#{to} depends on #{from}"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+        output::exit_with_error();
+    }
+
+    // NOTE: Completed work items stay in the chain without adding to it
+    let remaining = |id: Id| {
+        let work_item = dependencies.work_item(id);
+        if work_item.is_completed() {
+            0.0
+        } else {
+            work_item.estimated_cost.unwrap_or_default()
+        }
+    };
+
+    let mut longest = HashMap::new();
+    let nodes = dependencies.nodes();
+    for id in &nodes {
+        chain(*id, &dependencies.edges, &remaining, &mut longest);
+    }
+
+    let Some(end) = nodes
+        .iter()
+        .filter(|id| longest[*id].0 > 0.0)
+        .max_by(|a, b| longest[*a].0.total_cmp(&longest[*b].0))
+    else {
+        output::success(format!(
+            "Nothing estimated is left in \"{}\"",
+            milestone.name
+        ));
+        return;
+    };
+
+    let mut path = vec![*end];
+    while let Some(previous) = longest[path.last().expect("path to start with its end")].1 {
+        path.push(previous);
+    }
+    path.reverse();
+
+    let unit = match config.estimates.mode {
+        EstimateMode::Hours => "h",
+        EstimateMode::Points => " points",
+    };
+    let total = longest[end].0;
+    output::info(format!(
+        "The critical path of \"{}\" has {total}{unit} of estimates left over {} work item(s)",
+        milestone.name,
+        path.len()
+    ));
+    for id in &path {
+        let work_item = dependencies.work_item(*id);
+        println!(
+            "    #{:<6} {:<40} {:>8}{}",
+            id,
+            work_item.title,
+            format!("{}{unit}", remaining(*id)),
+            if work_item.is_completed() {
+                " (completed)"
+            } else if !dependencies.in_milestone.contains(id) {
+                " (outside of the milestone)"
+            } else if work_item.estimated_cost.is_none() {
+                " (not estimated)"
+            } else {
+                ""
+            }
+        );
+    }
+
+    if config.estimates.mode == EstimateMode::Points {
+        return;
+    }
+
+    let Some(due) = date(&milestone.due_date) else {
+        return;
+    };
+    let today = config.calendar.today();
+    let available = today
+        .iter_days()
+        .take_while(|day| *day <= due)
+        .filter(|day| (day.weekday().num_days_from_monday() as f32) < config.calendar.days_per_week)
+        .count() as f32
+        * config.calendar.hours_per_day;
+
+    // NOTE: Work on a chain happens one item after another, so more people don't make it shorter
+    if total > available {
+        output::warn(format!(
+            "The critical path needs {total}h but only {available}h of working days are left \
+             until \"{}\" is due on {due}, these work items gate the release date",
+            milestone.name
+        ));
+    } else {
+        output::success(format!(
+            "The critical path fits in the {available}h of working days left until \"{}\" is due \
+             on {due}, with {}h to spare",
+            milestone.name,
+            available - total
+        ));
+    }
+}
+
+/// The most remaining work of any chain ending in `id`, with the dependency it continues from,
+/// remembered in `longest`. The dependencies can't have cycles.
+fn chain(
+    id: Id,
+    edges: &[(Id, Id)],
+    remaining: &impl Fn(Id) -> f32,
+    longest: &mut HashMap<Id, (f32, Option<Id>)>,
+) -> f32 {
+    if let Some((work, _)) = longest.get(&id) {
+        return *work;
+    }
+
+    let mut best = (0.0, None);
+    for (dependency_id, _) in edges.iter().filter(|(_, to)| *to == id) {
+        let work = chain(*dependency_id, edges, remaining, longest);
+        if best.1.is_none() || work > best.0 {
+            best = (work, Some(*dependency_id));
+        }
+    }
+
+    let work = best.0 + remaining(id);
+    longest.insert(id, (work, best.1));
+    work
+}
+
+fn date(date: &Option<String>) -> Option<NaiveDate> {
+    date.as_deref()
+        .and_then(|date| date.get(..10))
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+}
@@ -0,0 +1,169 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::{json, Value};
+
+use crate::cache;
+use crate::models::{CustomField, CustomFieldType};
+
+lazy_static! {
+    /// `field.<name>: value` on a line of its own in a ticket
+    static ref FIELD_MATCHER: Regex =
+        Regex::new(r"(?m)^[ \t]*field\.(?<name>[^:\n]+?):[ \t]*(?<value>[^\n]*?)[ \t]*(\r?\n|$)")
+            .expect("Custom field Regex to compile");
+}
+
+/// Takes the `field.<name>: value` lines out of `text`, returning what's left with the values by
+/// the name of their field
+pub fn take(text: &str) -> (String, Vec<(String, String)>) {
+    let fields = FIELD_MATCHER
+        .captures_iter(text)
+        .map(|captures| {
+            (
+                captures["name"].trim().to_owned(),
+                captures["value"].to_owned(),
+            )
+        })
+        .collect();
+
+    (FIELD_MATCHER.replace_all(text, "").into_owned(), fields)
+}
+
+/// Custom fields the project defines, only fetched when a ticket sets one
+pub async fn definitions(client: &reqwest::Client) -> Vec<CustomField> {
+    serde_json::from_value(cache::get_collection(client, "customfields").await)
+        .unwrap_or_else(|error| panic!("To deserialize customfields: {error}"))
+}
+
+/// `fields` of the ticket titled `title` as the `customFields` of its payload, with each value
+/// checked against the type of its field
+pub fn resolve(
+    fields: &[(String, String)],
+    definitions: &[CustomField],
+    title: &str,
+) -> Result<Value, String> {
+    fields
+        .iter()
+        .map(|(name, value)| {
+            let field = definitions
+                .iter()
+                .find(|field| field.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    format!(
+                        "field.{name} of \"{title}\" to be one of the custom fields {}",
+                        quoted(definitions.iter().map(|field| field.name.as_str()))
+                    )
+                })?;
+
+            let value = match field.kind {
+                CustomFieldType::Number => value
+                    .parse::<f64>()
+                    .map(|number| json!(number))
+                    .map_err(|_| {
+                        format!(
+                            "field.{} of \"{title}\" to be a number, not \"{value}\"",
+                            field.name
+                        )
+                    })?,
+                CustomFieldType::Text => json!(value),
+                CustomFieldType::Enum => field
+                    .options
+                    .iter()
+                    .find(|option| option.eq_ignore_ascii_case(value))
+                    .map(|option| json!(option))
+                    .ok_or_else(|| {
+                        format!(
+                            "field.{} of \"{title}\" to be one of {}, not \"{value}\"",
+                            field.name,
+                            quoted(field.options.iter().map(String::as_str))
+                        )
+                    })?,
+            };
+
+            Ok(json!({ "customFieldId": field.custom_field_id, "value": value }))
+        })
+        .collect::<Result<Vec<Value>, String>>()
+        .map(Value::Array)
+}
+
+/// `["a", "b"]` as `"a", "b"`
+fn quoted<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    names
+        .map(|name| format!("\"{name}\""))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{resolve, take};
+    use crate::models::{CustomField, CustomFieldType};
+
+    fn definitions() -> Vec<CustomField> {
+        vec![
+            CustomField {
+                custom_field_id: 1,
+                name: "Platform".to_owned(),
+                kind: CustomFieldType::Enum,
+                options: vec!["PC".to_owned(), "Switch".to_owned()],
+            },
+            CustomField {
+                custom_field_id: 2,
+                name: "Risk".to_owned(),
+                kind: CustomFieldType::Number,
+                options: vec![],
+            },
+            CustomField {
+                custom_field_id: 3,
+                name: "Team".to_owned(),
+                kind: CustomFieldType::Text,
+                options: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn takes_field_lines_out_of_the_description() {
+        let (text, fields) =
+            take("Port the menu\n===\nfield.Platform: switch\nKeep the layout\n  field.Risk: 3\n");
+
+        assert_eq!(text, "Port the menu\n===\nKeep the layout\n");
+        assert_eq!(
+            fields,
+            [
+                ("Platform".to_owned(), "switch".to_owned()),
+                ("Risk".to_owned(), "3".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_values_by_type() {
+        let fields = [
+            ("platform".to_owned(), "switch".to_owned()),
+            ("Risk".to_owned(), "2.5".to_owned()),
+            ("Team".to_owned(), "Audio".to_owned()),
+        ];
+
+        assert_eq!(
+            resolve(&fields, &definitions(), "Port the menu"),
+            Ok(json!([
+                { "customFieldId": 1, "value": "Switch" },
+                { "customFieldId": 2, "value": 2.5 },
+                { "customFieldId": 3, "value": "Audio" },
+            ]))
+        );
+    }
+
+    #[test]
+    fn rejects_values_of_the_wrong_type() {
+        let number = [("Risk".to_owned(), "high".to_owned())];
+        let option = [("Platform".to_owned(), "Xbox".to_owned())];
+        let unknown = [("Owner".to_owned(), "me".to_owned())];
+
+        assert!(resolve(&number, &definitions(), "A").is_err());
+        assert!(resolve(&option, &definitions(), "A").is_err());
+        assert!(resolve(&unknown, &definitions(), "A").is_err());
+    }
+}
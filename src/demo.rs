@@ -0,0 +1,279 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config::Config;
+use crate::i18n::{Action, Message};
+use crate::models::WorkItem;
+use crate::{
+    client, create_tickets, current_project_id, delete_json, get_work_items, output, paths,
+    post_json, Arguments, CreateArguments, Id, MARKUP,
+};
+
+const CATEGORIES: [&str; 2] = ["Demo Programming", "Demo Art"];
+const TAGS: [(&str, &str); 2] = [("demo-bug", "#e53935"), ("demo-polish", "#8e24aa")];
+const MILESTONE: &str = "Demo Milestone";
+const BOARD: &str = "Demo Board";
+/// Tickets of the demo in hnp's own markup, using the categories and tags above
+const TICKETS: &str = include_str!("demo.md");
+
+#[derive(Args, Debug)]
+pub struct DemoArguments {
+    /// Create sample categories, tags, a milestone with a board and a few tickets
+    #[arg(
+        long,
+        conflicts_with = "teardown",
+        required_unless_present = "teardown"
+    )]
+    create: bool,
+
+    /// Delete everything `--create` made
+    #[arg(long)]
+    teardown: bool,
+
+    /// Create or delete without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// What the demo created in a project, kept so it can be deleted again
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct Demo {
+    category_ids: Vec<Id>,
+    tag_ids: Vec<Id>,
+    milestone_ids: Vec<Id>,
+    board_ids: Vec<Id>,
+    work_item_ids: Vec<Id>,
+}
+
+impl Demo {
+    /// `~/.local/share/hnp/demo-<project>.json` on Linux
+    fn path() -> PathBuf {
+        paths::data_dir()
+            .expect("To find the user's data directory")
+            .join(format!("demo-{}.json", current_project_id()))
+    }
+
+    fn load() -> Option<Demo> {
+        let contents = fs::read_to_string(Self::path()).ok()?;
+
+        Some(serde_json::from_str(&contents).expect("To deserialize the demo"))
+    }
+
+    /// Saved after everything that's created, so a demo that fails halfway can still be deleted
+    fn save(&self) {
+        let path = Self::path();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)
+                .unwrap_or_else(|_| panic!("To create directory {}", directory.display()));
+        }
+
+        fs::write(
+            &path,
+            serde_json::to_string_pretty(self).expect("To serialize the demo"),
+        )
+        .unwrap_or_else(|_| panic!("To write the demo to {}", path.display()));
+    }
+}
+
+pub async fn run(arguments: &Arguments, demo_arguments: &DemoArguments, config: &Config) {
+    if demo_arguments.teardown {
+        teardown(arguments, demo_arguments).await
+    } else {
+        create(arguments, demo_arguments, config).await
+    }
+}
+
+/// Sets up a sandbox project with a bit of everything, so every command has something to work on
+async fn create(arguments: &Arguments, demo_arguments: &DemoArguments, config: &Config) {
+    if Demo::load().is_some() {
+        output::error(format!(
+            "Project {} already has the demo, remove it with `hnp demo --teardown` first",
+            current_project_id()
+        ));
+        output::exit_with_error();
+    }
+
+    let client = client();
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    if !work_items.is_empty() {
+        output::warn(format!(
+            "Project {} already has {} work item(s), the demo is meant for a sandbox project",
+            current_project_id(),
+            work_items.len()
+        ));
+    }
+
+    output::info(format!(
+        "Create categories {}, tags {}, milestone \"{MILESTONE}\" with board \"{BOARD}\" and {} \
+         tickets",
+        quoted(&CATEGORIES),
+        quoted(&TAGS.map(|(name, _)| name)),
+        MARKUP.split_tickets(TICKETS).len()
+    ));
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if demo_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Created));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmCreateDemo(current_project_id()))
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Created));
+        return;
+    }
+
+    let mut demo = Demo::default();
+    for name in CATEGORIES {
+        let body = json!({ "name": name });
+        demo.category_ids
+            .push(post_json(&client, "/categories", &body, "categoryId").await);
+        demo.save();
+    }
+    for (name, color) in TAGS {
+        let body = json!({ "name": name, "color": color });
+        demo.tag_ids
+            .push(post_json(&client, "/tags", &body, "tagId").await);
+        demo.save();
+    }
+
+    let today = config.calendar.today();
+    let body = json!({
+        "name": MILESTONE,
+        "description": "Created by `hnp demo`",
+        "startDate": today.format("%Y-%m-%dT00:00:00").to_string(),
+        "dueDate": (today + chrono::Duration::days(14))
+            .format("%Y-%m-%dT00:00:00")
+            .to_string(),
+    });
+    let milestone_id = post_json(&client, "/milestones", &body, "milestoneId").await;
+    demo.milestone_ids.push(milestone_id);
+    demo.save();
+
+    let body = json!({ "name": BOARD, "milestoneId": milestone_id });
+    let board_id = post_json(&client, "/boards", &body, "boardId").await;
+    demo.board_ids.push(board_id);
+    demo.save();
+
+    let create_arguments = CreateArguments {
+        default_category: None,
+        create_missing_tags: false,
+        fail_on_missing_tags: true,
+        milestone: None,
+        check_capacity: false,
+        auto_assign: None,
+        suggest_estimates: false,
+        apply_suggestions: false,
+        stage: None,
+        scan_description: false,
+        skip: 0,
+        limit: None,
+        batch_size: 1,
+        only: None,
+        pick: false,
+        yes: true,
+        annotate: None,
+        title_template: None,
+        input_format: None,
+        files: vec![],
+    };
+    let created = create_tickets(
+        arguments,
+        &create_arguments,
+        config,
+        TICKETS,
+        "hnp demo",
+        1,
+        Some(board_id),
+    )
+    .await;
+    demo.work_item_ids
+        .extend(created.into_iter().map(|(_, work_item_id)| work_item_id));
+    demo.save();
+
+    output::success(Message::DemoCreated {
+        milestone: MILESTONE,
+    });
+}
+
+/// Deletes what `create` made, leaving everything else in the project alone
+async fn teardown(arguments: &Arguments, demo_arguments: &DemoArguments) {
+    let Some(demo) = Demo::load() else {
+        output::info(Message::NoDemo(current_project_id()));
+        return;
+    };
+
+    output::info(format!(
+        "Delete {} work item(s), {} board(s), {} milestone(s), {} tag(s) and {} category(ies) of \
+         the demo",
+        demo.work_item_ids.len(),
+        demo.board_ids.len(),
+        demo.milestone_ids.len(),
+        demo.tag_ids.len(),
+        demo.category_ids.len()
+    ));
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if demo_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Deleted));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmRemoveDemo(current_project_id()))
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Deleted));
+        return;
+    }
+
+    // NOTE: Work items go first, so nothing refers to the board or categories anymore
+    let paths = [
+        ("workitems", &demo.work_item_ids),
+        ("boards", &demo.board_ids),
+        ("milestones", &demo.milestone_ids),
+        ("tags", &demo.tag_ids),
+        ("categories", &demo.category_ids),
+    ]
+    .into_iter()
+    .flat_map(|(collection, ids)| ids.iter().map(move |id| format!("/{collection}/{id}")))
+    .collect::<Vec<String>>();
+
+    let client = client();
+    let progress = output::progress(paths.len(), "Removing the demo");
+    for path in &paths {
+        delete_json(&client, path).await;
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    let _ = fs::remove_file(Demo::path());
+    output::success(Message::DemoRemoved(current_project_id()));
+}
+
+/// `["a", "b"]` as `"a", "b"`
+fn quoted(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!("\"{name}\""))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
@@ -0,0 +1,213 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+use hnp::client::API_VERSION;
+
+use crate::config::Config;
+use crate::{
+    access_problem, api_endpoint, api_version, cache, client, get_metadata, output, text,
+    Arguments, Id,
+};
+
+#[derive(Args, Debug)]
+pub struct DoctorArguments {
+    /// File with tickets to check the encoding and line endings of
+    #[arg(value_name = "FILE")]
+    file: Option<PathBuf>,
+}
+
+/// Checks everything hnp needs from the environment and Hack'n'Plan one by one, telling how to fix
+/// what's wrong
+pub async fn run(arguments: &Arguments, doctor_arguments: &DoctorArguments, config: &Config) {
+    let mut healthy = true;
+    let mut has_credentials = true;
+
+    match std::env::var("HACKNPLAN_API_KEY") {
+        Ok(api_key) if !api_key.trim().is_empty() => output::success("HACKNPLAN_API_KEY is set"),
+        _ => {
+            output::error(
+                "HACKNPLAN_API_KEY isn't set, create an API key in your Hack'n'Plan account \
+                 settings and set it in the environment",
+            );
+            has_credentials = false;
+        }
+    }
+
+    match std::env::var("HACKNPLAN_PROJECT_ID").map(|id| id.trim().parse::<Id>()) {
+        Ok(Ok(_)) => output::success("HACKNPLAN_PROJECT_ID is set"),
+        Ok(Err(_)) => {
+            output::error(
+                "HACKNPLAN_PROJECT_ID isn't a number, use the id from the URL of the project \
+                 in Hack'n'Plan",
+            );
+            has_credentials = false;
+        }
+        Err(_) => {
+            output::error(
+                "HACKNPLAN_PROJECT_ID isn't set, set it in the environment to the id from the \
+                 URL of the project in Hack'n'Plan",
+            );
+            has_credentials = false;
+        }
+    }
+
+    match Config::find(arguments.config.as_deref()) {
+        Some(path) => match Config::parse(&path) {
+            Ok(_) => output::success(format!("Config file {} is valid", path.display())),
+            Err(error) => {
+                output::error(format!(
+                    "Config file {} is invalid, compare it to example_config.toml: {error}",
+                    path.display()
+                ));
+                healthy = false;
+            }
+        },
+        None => output::success("No config file, using the defaults"),
+    }
+
+    if let Err(error) = hnp::core::Markup::new(&config.markup.word_characters) {
+        output::error(format!(
+            "`markup.word_characters` of the config isn't a valid character class: {error}"
+        ));
+        healthy = false;
+    }
+
+    if let Some(file) = &doctor_arguments.file {
+        healthy &= check_file(file);
+    }
+
+    // NOTE: Same order of precedence as the HTTP client, which reads the environment itself
+    let proxy = config
+        .api
+        .proxy
+        .as_ref()
+        .map(|proxy| (proxy.to_owned(), "`api.proxy` of the config".to_owned()))
+        .or_else(|| {
+            ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+                .iter()
+                .find_map(|variable| {
+                    std::env::var(variable)
+                        .ok()
+                        .filter(|value| !value.is_empty())
+                        .map(|value| (value, variable.to_string()))
+                })
+        });
+    match proxy {
+        Some((proxy, source)) => output::info(format!("Using proxy {proxy} from {source}")),
+        None => output::info("Not using a proxy"),
+    }
+
+    match api_version() {
+        Some(version) if version == API_VERSION => {
+            output::success(format!("Using {version} of the API at {}", api_endpoint()))
+        }
+        Some(version) => output::warn(format!(
+            "hnp is made for {API_VERSION} of the Hack'n'Plan API and may not work with \
+             {version}, check `api.version` of the config"
+        )),
+        None => output::warn(format!(
+            "Can't tell the API version from {}, hnp is made for {API_VERSION}",
+            api_endpoint()
+        )),
+    }
+
+    // NOTE: Without a key and project nothing else can be checked
+    if !has_credentials {
+        output::exit_with_error();
+    }
+
+    let client = client();
+    if let Some(problem) = access_problem(&client).await {
+        output::error(problem);
+        output::exit_with_error();
+    }
+    output::success("Reached Hack'n'Plan and the API key has access to the project");
+
+    let metadata = get_metadata(&client).await;
+    if metadata.categories.is_empty() {
+        output::error(
+            "The project has no categories, add one in the project settings of Hack'n'Plan as \
+             every work item needs one",
+        );
+        healthy = false;
+    } else {
+        output::success(format!(
+            "The project has {} categories",
+            metadata.categories.len()
+        ));
+    }
+
+    if metadata.importance_levels.is_empty() {
+        output::error(
+            "The project has no importance levels, add one in the project settings of \
+             Hack'n'Plan as every work item needs one",
+        );
+        healthy = false;
+    } else {
+        output::success(format!(
+            "The project has {} importance levels",
+            metadata.importance_levels.len()
+        ));
+    }
+
+    check_cache();
+
+    if !healthy {
+        output::exit_with_error();
+    }
+}
+
+/// Whether hnp can read `file`, reporting its encoding and line endings
+fn check_file(file: &std::path::Path) -> bool {
+    let bytes = match fs::read(file) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            output::error(format!("Could not read {}: {error}", file.display()));
+            return false;
+        }
+    };
+
+    let Some(encoding) = text::encoding(&bytes) else {
+        output::error(format!(
+            "{} isn't UTF-8 or UTF-16, save it as UTF-8 in your editor",
+            file.display()
+        ));
+        return false;
+    };
+
+    let contents = text::decode(&bytes).unwrap_or_default();
+    let line_endings = if contents.contains("\r\n") {
+        "Windows (CRLF)"
+    } else {
+        "Unix (LF)"
+    };
+    output::success(format!(
+        "{} is {encoding} with {line_endings} line endings",
+        file.display()
+    ));
+
+    true
+}
+
+/// Reports what's cached of the project. Entries are checked with Hack'n'Plan on every run, so
+/// they can't get stale.
+fn check_cache() {
+    let Some(directory) = cache::directory() else {
+        output::warn("There's no cache directory, everything is fetched in full on every run");
+        return;
+    };
+
+    let entries = cache::entries();
+    let Some(oldest) = entries.iter().filter_map(|(_, modified)| *modified).min() else {
+        output::success(format!("Nothing is cached in {} yet", directory.display()));
+        return;
+    };
+
+    output::success(format!(
+        "{} collection(s) are cached in {}, the oldest since {}",
+        entries.len(),
+        directory.display(),
+        chrono::DateTime::<chrono::Local>::from(oldest).format("%Y-%m-%d %H:%M")
+    ));
+}
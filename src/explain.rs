@@ -0,0 +1,293 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use console::style;
+
+use crate::config::Config;
+use crate::core::{self, TokenKind};
+use crate::tokens::strip_custom_tokens;
+use crate::{client, get_metadata, input, output, read_tickets, MARKUP};
+
+#[derive(Args, Debug)]
+pub struct ExplainArguments {
+    /// Also honor tags, estimates, importance and dates written in the description, like
+    /// `hnp create --scan-description`
+    #[arg(long)]
+    scan_description: bool,
+
+    /// Syntax of the files, by default `.org` files are read as org and `todo.txt` as todo.txt
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    input_format: Option<input::InputFormat>,
+
+    /// Files with tickets. `*` and `?` are expanded when the shell doesn't.
+    #[arg(required = true, value_name = "FILE")]
+    files: Vec<PathBuf>,
+}
+
+/// A recognized token with what it resolves to, `kind` is `None` for custom tokens
+struct Annotation {
+    kind: Option<TokenKind>,
+    name: String,
+    start: usize,
+    end: usize,
+    resolved: Result<String, String>,
+}
+
+/// Prints every ticket with its tokens highlighted and what each of them resolves to, exiting
+/// with 1 when any of them doesn't resolve
+pub async fn run(explain_arguments: &ExplainArguments, config: &Config) {
+    let files = input::expand(&explain_arguments.files).unwrap_or_else(|error| {
+        output::error(error);
+        output::exit_with_error();
+    });
+
+    let metadata = get_metadata(&client()).await;
+    let options = config.markup_options(explain_arguments.scan_description);
+    let mut failed = false;
+
+    for file in files {
+        output::set_context(Some(file.display().to_string()));
+        let contents = read_tickets(&file, explain_arguments.input_format).await;
+        let (front_matter, tickets) =
+            input::FrontMatter::parse(&contents).unwrap_or_else(|error| panic!("{error}"));
+        // NOTE: Lines are counted from the start of the file, front matter included
+        let offset = contents[..contents.len() - tickets.len()]
+            .matches('\n')
+            .count();
+        let tickets = front_matter.apply(tickets);
+
+        for (index, (line, text)) in MARKUP.split_tickets(&tickets).into_iter().enumerate() {
+            let text = text.trim();
+            let first_line = offset + line + 1;
+
+            let mut annotations = MARKUP
+                .tokens(text, explain_arguments.scan_description)
+                .into_iter()
+                .map(|token| {
+                    let written = &text[token.start..token.end];
+                    Annotation {
+                        kind: Some(token.kind),
+                        name: token.kind.name().to_owned(),
+                        start: token.start,
+                        end: token.end,
+                        resolved: core::resolve(&MARKUP, token.kind, written, &metadata, &options),
+                    }
+                })
+                .collect::<Vec<Annotation>>();
+
+            let title_end = MARKUP
+                .title_separator
+                .find(text)
+                .map_or(text.len(), |separator| separator.start());
+            for custom_token in &config.tokens {
+                for captures in custom_token.pattern.captures_iter(&text[..title_end]) {
+                    let token = captures.get(0).expect("To have a whole match");
+                    let mut value = String::new();
+                    captures.expand(&custom_token.value, &mut value);
+
+                    let field = format!("{:?}", custom_token.field).to_lowercase();
+                    annotations.push(Annotation {
+                        kind: None,
+                        name: format!("custom {field}"),
+                        start: token.start(),
+                        end: token.end(),
+                        resolved: Ok(format!("{field} \"{}\"", value.trim())),
+                    });
+                }
+            }
+            annotations.sort_by_key(|annotation| annotation.start);
+
+            let report = Report {
+                text,
+                first_line,
+                json: output::is_json_diagnostics(),
+            };
+            if !report.json {
+                println!(
+                    "{}",
+                    style(format!(
+                        "{}:{first_line}, ticket {}",
+                        file.display(),
+                        index + 1
+                    ))
+                    .bold()
+                );
+                for (number, line) in highlight(text, &annotations).lines().enumerate() {
+                    println!("{:>5} │ {line}", first_line + number);
+                }
+                println!();
+            }
+
+            for annotation in &annotations {
+                let (line, column) = report.position(annotation.start);
+                match &annotation.resolved {
+                    Ok(resolved) if !report.json => println!(
+                        "  {:<8} {:<14} {:<20} {resolved}",
+                        format!("{line}:{column}"),
+                        annotation.name,
+                        &text[annotation.start..annotation.end]
+                    ),
+                    Ok(_) => {}
+                    Err(expectation) => {
+                        failed = true;
+                        report.problem(
+                            "unresolved-token",
+                            core::expected(expectation),
+                            Some((annotation.start, annotation.end)),
+                            &annotation.name,
+                        );
+                    }
+                }
+            }
+
+            match core::parse_ticket(&MARKUP, text, &metadata, &options) {
+                Ok(parsed) => {
+                    let title = strip_custom_tokens(&parsed.title, &config.tokens);
+                    let title = title.split_whitespace().collect::<Vec<&str>>().join(" ");
+                    if !report.json {
+                        println!("  Title: {}", style(&title).bold());
+                    }
+
+                    if let Err(expectation) = core::check_limits(
+                        &title,
+                        &parsed.description,
+                        parsed.estimate,
+                        parsed.mentions.len(),
+                    ) {
+                        failed = true;
+                        report.problem("limit", core::expected(&expectation), None, "");
+                    }
+
+                    if parsed.category_id().is_none() {
+                        failed = true;
+                        report.problem(
+                            "missing-category",
+                            "Expected to have atleast one category available".to_owned(),
+                            None,
+                            "",
+                        );
+                    }
+                }
+                Err(expectation) => {
+                    failed = true;
+                    report.problem("parse", core::expected(&expectation), None, "");
+                }
+            }
+            if !report.json {
+                println!();
+            }
+        }
+        output::set_context(None);
+    }
+
+    if failed {
+        output::exit_with_error();
+    }
+}
+
+/// Where the problems of a ticket are printed, inline or as JSON diagnostics
+struct Report<'a> {
+    text: &'a str,
+    first_line: usize,
+    json: bool,
+}
+
+impl Report<'_> {
+    /// Line in the file and column (counting characters) of `index` in the ticket
+    fn position(&self, index: usize) -> (usize, usize) {
+        let before = &self.text[..index];
+        let line = self.first_line + before.matches('\n').count();
+        let column = before
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count()
+            + 1;
+
+        (line, column)
+    }
+
+    /// Reports an error, at `span` in the ticket when given or its first line otherwise. Only
+    /// diagnostics are printed with `--diagnostics json`.
+    fn problem(
+        &self,
+        code: &'static str,
+        message: String,
+        span: Option<(usize, usize)>,
+        name: &str,
+    ) {
+        if !self.json {
+            match span {
+                Some((start, end)) => {
+                    let (line, column) = self.position(start);
+                    println!(
+                        "  {:<8} {name:<14} {:<20} {}",
+                        format!("{line}:{column}"),
+                        &self.text[start..end],
+                        style(&message).red()
+                    );
+                }
+                None => println!("  {}", style(&message).red()),
+            }
+
+            // NOTE: GitHub Actions gets annotations next to the readable output
+            if !output::is_github() {
+                return;
+            }
+        }
+
+        let (line, span) = match span {
+            Some((start, end)) => {
+                let (line, column) = self.position(start);
+                let width = self.text[start..end].chars().count();
+                (line, Some((column, column + width)))
+            }
+            None => (self.first_line, None),
+        };
+        output::error_at(output::Diagnostic {
+            code,
+            message,
+            line: Some(line),
+            span,
+        });
+    }
+}
+
+/// `text` with the tokens colored by their kind, overlapping custom tokens are left plain
+fn highlight(text: &str, annotations: &[Annotation]) -> String {
+    let mut highlighted = String::new();
+    let mut position = 0;
+
+    for annotation in annotations {
+        if annotation.start < position {
+            continue;
+        }
+
+        let token = style(&text[annotation.start..annotation.end]);
+        let token = match annotation.kind {
+            Some(TokenKind::Tag) => token.cyan(),
+            Some(TokenKind::Mention) => token.magenta(),
+            Some(TokenKind::Estimate) => token.yellow(),
+            Some(TokenKind::Importance) => token.red(),
+            Some(TokenKind::DueDate | TokenKind::StartDate) => token.blue(),
+            Some(TokenKind::Stage) => token.green(),
+            Some(TokenKind::WorkItemType) => token.cyan(),
+            Some(TokenKind::Dependency | TokenKind::Reference) => token.underlined(),
+            Some(TokenKind::SubTask) => token.dim(),
+            None => token.bold(),
+        };
+        let token = if annotation.resolved.is_err() {
+            token.on_red()
+        } else {
+            token
+        };
+
+        highlighted.push_str(&text[position..annotation.start]);
+        highlighted.push_str(&token.to_string());
+        position = annotation.end;
+    }
+
+    highlighted.push_str(&text[position..]);
+    highlighted
+}
@@ -0,0 +1,19 @@
+use clap::Subcommand;
+
+use crate::config::Config;
+use crate::{github, ical, Arguments};
+
+#[derive(Subcommand, Debug)]
+pub enum ExportCommand {
+    /// Create and update GitHub issues for the work items matching a filter
+    Github(github::GithubArguments),
+    /// Write milestones and the due dates of open work items to an iCalendar file
+    Ical(ical::IcalArguments),
+}
+
+pub async fn run(arguments: &Arguments, command: &ExportCommand, config: &Config) {
+    match command {
+        ExportCommand::Github(github_arguments) => github::run(arguments, github_arguments).await,
+        ExportCommand::Ical(ical_arguments) => ical::run(ical_arguments, config).await,
+    }
+}
@@ -0,0 +1,254 @@
+use crate::models::WorkItem;
+use crate::normalize_name;
+
+/// Selection of work items written as space separated terms, all of which have to match:
+///
+/// - `tag:audio`, `category:art`, `stage:"In progress"`, `importance:high`, `board:"Sprint 3"`
+/// - `assignee:bram` or `@bram`
+/// - `id:1234`, `is:story`, `is:open`, `is:completed`
+/// - any other word has to appear in the title
+///
+/// Values separated by commas match any of them (`tag:audio,music`) and terms starting with a
+/// `-` exclude instead (`-tag:polish`).
+#[derive(Debug)]
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug)]
+struct Term {
+    negated: bool,
+    field: Field,
+    values: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Field {
+    Text,
+    Id,
+    Tag,
+    Category,
+    Stage,
+    Importance,
+    Board,
+    Assignee,
+    Is,
+}
+
+impl Filter {
+    pub fn parse(query: &str) -> Result<Filter, String> {
+        let terms = split_terms(query)?
+            .into_iter()
+            .map(|term| parse_term(&term))
+            .collect::<Result<Vec<Term>, String>>()?;
+
+        Ok(Filter { terms })
+    }
+
+    pub fn matches(&self, work_item: &WorkItem) -> bool {
+        self.terms.iter().all(|term| {
+            let matches = term
+                .values
+                .iter()
+                .any(|value| matches_value(work_item, term.field, value));
+
+            matches != term.negated
+        })
+    }
+}
+
+/// Splits on whitespace outside of double quotes, removing the quotes
+fn split_terms(query: &str) -> Result<Vec<String>, String> {
+    let mut terms = vec![];
+    let mut term = String::new();
+    let mut quoted = false;
+
+    for character in query.chars() {
+        match character {
+            '"' => quoted = !quoted,
+            character if character.is_whitespace() && !quoted => {
+                if !term.is_empty() {
+                    terms.push(std::mem::take(&mut term));
+                }
+            }
+            character => term.push(character),
+        }
+    }
+
+    if quoted {
+        return Err(format!("quote in filter \"{query}\" is never closed"));
+    }
+
+    if !term.is_empty() {
+        terms.push(term);
+    }
+
+    Ok(terms)
+}
+
+fn parse_term(term: &str) -> Result<Term, String> {
+    let (negated, term) = match term.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, term),
+    };
+
+    let (field, value) = if let Some(user) = term.strip_prefix('@') {
+        (Field::Assignee, user)
+    } else if let Some((field, value)) = term.split_once(':') {
+        let field = match field.to_lowercase().as_str() {
+            "id" => Field::Id,
+            "tag" => Field::Tag,
+            "category" => Field::Category,
+            "stage" => Field::Stage,
+            "importance" => Field::Importance,
+            "board" => Field::Board,
+            "assignee" => Field::Assignee,
+            "is" => Field::Is,
+            _ => return Err(format!("unknown filter field \"{field}\" in \"{term}\"")),
+        };
+
+        (field, value.trim_start_matches('@'))
+    } else {
+        (Field::Text, term)
+    };
+
+    let values = value
+        .split(',')
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<String>>();
+
+    if values.is_empty() {
+        return Err(format!("filter term \"{term}\" needs a value"));
+    }
+
+    if field == Field::Is {
+        if let Some(value) = values
+            .iter()
+            .find(|value| !["story", "open", "completed"].contains(&value.to_lowercase().as_str()))
+        {
+            return Err(format!(
+                "\"is:{value}\" should be one of is:story, is:open or is:completed"
+            ));
+        }
+    }
+
+    if field == Field::Id {
+        if let Some(value) = values.iter().find(|value| value.parse::<i64>().is_err()) {
+            return Err(format!("\"id:{value}\" should be a number"));
+        }
+    }
+
+    Ok(Term {
+        negated,
+        field,
+        values,
+    })
+}
+
+fn matches_value(work_item: &WorkItem, field: Field, value: &str) -> bool {
+    let name_matches = |name: Option<&String>| {
+        name.is_some_and(|name| normalize_name(name) == normalize_name(value))
+    };
+
+    match field {
+        Field::Text => work_item
+            .title
+            .to_lowercase()
+            .contains(&value.to_lowercase()),
+        Field::Id => work_item.work_item_id.to_string() == value,
+        Field::Tag => work_item
+            .tags
+            .iter()
+            .any(|tag| name_matches(Some(&tag.name))),
+        Field::Category => name_matches(work_item.category.as_ref().map(|category| &category.name)),
+        Field::Stage => name_matches(work_item.stage.as_ref().map(|stage| &stage.name)),
+        Field::Importance => name_matches(
+            work_item
+                .importance_level
+                .as_ref()
+                .map(|importance_level| &importance_level.name),
+        ),
+        Field::Board => name_matches(work_item.board.as_ref().map(|board| &board.name)),
+        Field::Assignee => work_item.assigned_users.iter().any(|assigned_user| {
+            assigned_user.user.username.eq_ignore_ascii_case(value)
+                || assigned_user
+                    .user
+                    .name
+                    .to_lowercase()
+                    .contains(&value.to_lowercase())
+        }),
+        Field::Is => match value.to_lowercase().as_str() {
+            "story" => work_item.is_story,
+            "completed" => work_item.is_completed(),
+            _ => !work_item.is_completed(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{split_terms, Filter};
+    use crate::models::WorkItem;
+
+    fn work_item() -> WorkItem {
+        serde_json::from_value(json!({
+            "workItemId": 1234,
+            "title": "Footstep sounds on grass",
+            "isStory": false,
+            "category": { "categoryId": 1, "name": "Programming" },
+            "stage": { "stageId": 2, "name": "In progress", "status": "started" },
+            "board": { "boardId": 20, "name": "Sprint 12", "milestoneId": 7 },
+            "assignedUsers": [{ "user": { "id": 101, "name": "Alice Jansen", "username": "alice" } }],
+            "tags": [{ "tagId": 5, "name": "audio" }],
+        }))
+        .expect("To read the work item")
+    }
+
+    fn matches(query: &str) -> bool {
+        Filter::parse(query)
+            .expect("To parse the filter")
+            .matches(&work_item())
+    }
+
+    #[test]
+    fn splits_terms_outside_of_quotes() {
+        assert_eq!(
+            split_terms(r#"stage:"In progress"  tag:audio"#),
+            Ok(vec!["stage:In progress".to_owned(), "tag:audio".to_owned()])
+        );
+        assert!(split_terms(r#"stage:"In progress"#).is_err());
+    }
+
+    #[test]
+    fn matches_every_term() {
+        assert!(matches(r#"tag:audio stage:"In progress" @alice id:1234"#));
+        assert!(matches("board:sprint-12 category:programming footstep"));
+        assert!(!matches("tag:audio is:completed"));
+    }
+
+    #[test]
+    fn excludes_negated_terms() {
+        assert!(matches("-tag:polish"));
+        assert!(!matches("-tag:audio"));
+        // NOTE: A lone `-` is a word in the title, not a negation
+        assert!(!matches("-"));
+    }
+
+    #[test]
+    fn matches_any_of_the_values_separated_by_commas() {
+        assert!(matches("tag:music,audio"));
+        assert!(!matches("tag:music,art"));
+        assert!(matches("-assignee:bob,carol"));
+    }
+
+    #[test]
+    fn rejects_what_it_doesnt_know() {
+        assert!(Filter::parse("colour:red").is_err());
+        assert!(Filter::parse("is:blocked").is_err());
+        assert!(Filter::parse("id:abc").is_err());
+        assert!(Filter::parse("tag:").is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::{BTreeSet, HashMap};
+
+use clap::Args;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::filter::Filter;
+use crate::i18n::{Action, Message};
+use crate::models::WorkItem;
+use crate::recording::SendRecorded;
+use crate::{client, get_work_items, output, Arguments, Id};
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
+lazy_static! {
+    /// Hidden line in the issue body pointing back to the work item
+    static ref MARKER_MATCHER: Regex =
+        Regex::new(r"<!--\s*hnp:\s*(?<id>\d+)\s*-->").expect("Marker Regex to compile");
+    static ref NEXT_PAGE_MATCHER: Regex =
+        Regex::new(r#"<(?<url>[^>]+)>;\s*rel="next""#).expect("Next page Regex to compile");
+}
+
+#[derive(Args, Debug)]
+pub struct GithubArguments {
+    /// Repository to put the issues in, like `owner/name`
+    #[arg(long)]
+    repo: String,
+
+    /// Work items to export, e.g. `tag:public -is:story`
+    #[arg(short, long)]
+    filter: String,
+
+    /// Export without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct Issue {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    state: String,
+    labels: Vec<Label>,
+    /// Set for pull requests, which GitHub lists as issues as well
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Label {
+    name: String,
+}
+
+/// The issue a work item should have
+#[derive(Debug, PartialEq)]
+struct Contents {
+    title: String,
+    body: String,
+    labels: BTreeSet<String>,
+    state: &'static str,
+}
+
+impl Contents {
+    fn of(work_item: &WorkItem) -> Contents {
+        let description = work_item.description.as_deref().unwrap_or_default().trim();
+        let marker = format!("<!-- hnp: {} -->", work_item.work_item_id);
+
+        Contents {
+            title: work_item.title.clone(),
+            body: if description.is_empty() {
+                marker
+            } else {
+                format!("{description}\n\n{marker}")
+            },
+            labels: work_item.tags.iter().map(|tag| tag.name.clone()).collect(),
+            state: if work_item.is_completed() {
+                "closed"
+            } else {
+                "open"
+            },
+        }
+    }
+
+    fn from_issue(issue: &Issue) -> Contents {
+        Contents {
+            title: issue.title.clone(),
+            body: issue.body.clone().unwrap_or_default().trim().to_owned(),
+            labels: issue
+                .labels
+                .iter()
+                .map(|label| label.name.clone())
+                .collect(),
+            state: if issue.state == "closed" {
+                "closed"
+            } else {
+                "open"
+            },
+        }
+    }
+}
+
+/// Requests to the GitHub API of one repository, authenticated with GITHUB_TOKEN
+struct Github {
+    client: reqwest::Client,
+    repo_url: String,
+    token: String,
+}
+
+impl Github {
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .header(reqwest::header::USER_AGENT, "hnp")
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .bearer_auth(&self.token)
+    }
+
+    /// Every issue of the repository, open or closed
+    async fn issues(&self) -> Vec<Issue> {
+        let mut issues = vec![];
+        let mut url = Some(format!("{}/issues?state=all&per_page=100", self.repo_url));
+
+        // NOTE: GitHub pages its lists, the `Link` header has the next page until the last one
+        while let Some(page_url) = url {
+            let response = self
+                .request(reqwest::Method::GET, &page_url)
+                .send_recorded()
+                .await
+                .and_then(|response| response.error_for_status())
+                .unwrap_or_else(|error| panic!("To get the issues from GitHub: {error}"));
+
+            url = response
+                .headers()
+                .get(reqwest::header::LINK)
+                .and_then(|link| link.to_str().ok())
+                .and_then(|link| NEXT_PAGE_MATCHER.captures(link))
+                .map(|captures| captures["url"].to_owned());
+
+            issues.extend(
+                response
+                    .json::<Vec<Issue>>()
+                    .await
+                    .expect("To parse the issues from GitHub")
+                    .into_iter()
+                    .filter(|issue| issue.pull_request.is_none()),
+            );
+        }
+
+        issues
+    }
+
+    async fn create(&self, contents: &Contents) -> u64 {
+        let issue = self
+            .request(reqwest::Method::POST, &format!("{}/issues", self.repo_url))
+            .json(&json!({
+                "title": contents.title,
+                "body": contents.body,
+                "labels": contents.labels,
+            }))
+            .send_recorded()
+            .await
+            .and_then(|response| response.error_for_status())
+            .unwrap_or_else(|error| {
+                panic!("To create an issue for \"{}\": {error}", contents.title)
+            })
+            .json::<Issue>()
+            .await
+            .expect("To parse the created issue from GitHub");
+
+        issue.number
+    }
+
+    async fn update(&self, number: u64, contents: &Contents) {
+        self.request(
+            reqwest::Method::PATCH,
+            &format!("{}/issues/{number}", self.repo_url),
+        )
+        .json(&json!({
+            "title": contents.title,
+            "body": contents.body,
+            "labels": contents.labels,
+            "state": contents.state,
+        }))
+        .send_recorded()
+        .await
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|error| panic!("To update issue #{number}: {error}"));
+    }
+}
+
+pub async fn run(arguments: &Arguments, github_arguments: &GithubArguments) {
+    let filter = Filter::parse(&github_arguments.filter).unwrap_or_else(|error| {
+        output::error(format!("Invalid filter: {error}"));
+        output::exit_with_error();
+    });
+
+    let Ok(token) = std::env::var("GITHUB_TOKEN") else {
+        output::error("Set GITHUB_TOKEN to a token that can write issues in the repository");
+        output::exit_with_error();
+    };
+
+    let api_url = std::env::var("GITHUB_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_owned());
+    let github = Github {
+        client: client(),
+        repo_url: format!(
+            "{}/repos/{}",
+            api_url.trim_end_matches('/'),
+            github_arguments.repo.trim_matches('/')
+        ),
+        token,
+    };
+
+    let client = client();
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    let spinner = output::spinner("Fetching issues from GitHub");
+    let issues = github.issues().await;
+    spinner.finish_and_clear();
+
+    // NOTE: The marker in the body is the cross-reference, so any checkout can update the issues
+    let issue_by_work_item = issues
+        .iter()
+        .filter_map(|issue| {
+            let captures = MARKER_MATCHER.captures(issue.body.as_deref()?)?;
+            Some((captures["id"].parse::<Id>().ok()?, issue))
+        })
+        .collect::<HashMap<Id, &Issue>>();
+
+    let mut creates = vec![];
+    let mut updates = vec![];
+    let mut unchanged = 0;
+    for work_item in work_items
+        .iter()
+        .filter(|work_item| filter.matches(work_item))
+    {
+        let contents = Contents::of(work_item);
+
+        match issue_by_work_item.get(&work_item.work_item_id) {
+            Some(issue) if Contents::from_issue(issue) == contents => unchanged += 1,
+            Some(issue) => updates.push((issue.number, work_item, contents)),
+            // NOTE: Completed work items only close the issues they already have
+            None if work_item.is_completed() => unchanged += 1,
+            None => creates.push((work_item, contents)),
+        }
+    }
+
+    if creates.is_empty() && updates.is_empty() {
+        output::info(format!(
+            "Nothing to export, {unchanged} work item(s) matched \"{}\" and are already up to date",
+            github_arguments.filter
+        ));
+        return;
+    }
+
+    for (work_item, _) in &creates {
+        output::info(format!(
+            "Create an issue for #{} {}",
+            work_item.work_item_id, work_item.title
+        ));
+    }
+    for (number, work_item, contents) in &updates {
+        let action = if contents.state == "closed" {
+            "Close and update"
+        } else {
+            "Update"
+        };
+        output::info(format!(
+            "{action} issue {}#{number} for #{} {}",
+            github_arguments.repo, work_item.work_item_id, work_item.title
+        ));
+    }
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if github_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Exported));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmGithubExport {
+            create: creates.len(),
+            update: updates.len(),
+            repo: &github_arguments.repo,
+        })
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Exported));
+        return;
+    }
+
+    let progress = output::progress(creates.len() + updates.len(), "Exporting to GitHub");
+    for (work_item, contents) in &creates {
+        let number = github.create(contents).await;
+        progress.suspend(|| {
+            output::success(format!(
+                "Created issue {}#{number} for #{}",
+                github_arguments.repo, work_item.work_item_id
+            ))
+        });
+        progress.inc(1);
+    }
+    for (number, _, contents) in &updates {
+        github.update(*number, contents).await;
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    output::success(format!(
+        "Created {} and updated {} issue(s) in {}",
+        creates.len(),
+        updates.len(),
+        github_arguments.repo
+    ));
+}
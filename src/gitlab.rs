@@ -0,0 +1,358 @@
+use clap::Args;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::config::{Config, EstimateMode};
+use crate::import::upload_tickets;
+use crate::recording::SendRecorded;
+use crate::{
+    client, find_board, get_available_boards, get_available_milestones, get_available_users, input,
+    output, parse_tickets, Arguments, CreateArguments, Id,
+};
+
+const DEFAULT_URL: &str = "https://gitlab.com";
+
+lazy_static! {
+    /// `#12` in an issue refers to an issue of the same project, not to a work item
+    static ref ISSUE_REFERENCE_MATCHER: Regex =
+        Regex::new(r"\B#(?<iid>\d+)\b").expect("Issue reference Regex to compile");
+}
+
+#[derive(Args, Debug)]
+pub struct GitlabArguments {
+    /// Path of the GitLab project, like `group/repo`
+    #[arg(long, value_name = "PATH")]
+    gitlab_project: String,
+
+    /// Import the open issues with this label
+    #[arg(long)]
+    label: String,
+
+    /// Address of a self-hosted GitLab, defaults to GITLAB_URL or https://gitlab.com
+    #[arg(long)]
+    url: Option<String>,
+
+    /// Hours per point of issue weight, in projects that estimate in hours
+    #[arg(long, default_value_t = 1.0)]
+    hours_per_weight: f32,
+
+    /// Category for the work items that don't get one from their labels
+    #[arg(long)]
+    category: Option<String>,
+
+    /// Put the work items on this board, unless their milestone has a board
+    #[arg(long, value_name = "BOARD")]
+    board: Option<String>,
+
+    /// Leave the label on the issues, by default it's taken off after importing so they're
+    /// only imported once
+    #[arg(long)]
+    keep_label: bool,
+
+    /// Create tags that don't exist on Hack'n'Plan yet without asking
+    #[arg(long)]
+    create_missing_tags: bool,
+
+    /// Import without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct Issue {
+    iid: u64,
+    title: String,
+    description: Option<String>,
+    labels: Vec<String>,
+    milestone: Option<Milestone>,
+    weight: Option<f32>,
+    assignees: Vec<Assignee>,
+    due_date: Option<String>,
+    web_url: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Milestone {
+    title: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Assignee {
+    username: String,
+}
+
+/// Requests to the GitLab API of one project, authenticated with GITLAB_TOKEN when it's set
+struct Gitlab {
+    client: reqwest::Client,
+    project_url: String,
+    token: Option<String>,
+}
+
+impl Gitlab {
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}{path}", self.project_url));
+
+        match &self.token {
+            Some(token) => request.header("PRIVATE-TOKEN", token),
+            None => request,
+        }
+    }
+
+    async fn open_issues(&self, label: &str) -> Vec<Issue> {
+        let mut issues = vec![];
+        let mut page = "1".to_owned();
+
+        // NOTE: GitLab pages its lists, the last page has no `x-next-page`
+        while !page.is_empty() {
+            let response = self
+                .request(reqwest::Method::GET, "/issues")
+                .query(&[
+                    ("labels", label),
+                    ("state", "opened"),
+                    ("per_page", "100"),
+                    ("page", &page),
+                ])
+                .send_recorded()
+                .await
+                .and_then(|response| response.error_for_status())
+                .unwrap_or_else(|error| panic!("To get the issues from GitLab: {error}"));
+
+            page = response
+                .headers()
+                .get("x-next-page")
+                .and_then(|next_page| next_page.to_str().ok())
+                .unwrap_or_default()
+                .to_owned();
+
+            issues.extend(
+                response
+                    .json::<Vec<Issue>>()
+                    .await
+                    .expect("To parse the issues from GitLab"),
+            );
+        }
+
+        issues
+    }
+
+    /// Takes `label` off the issue and leaves a note pointing to the work item
+    async fn mark_imported(&self, iid: u64, label: &str, work_item_id: Id) -> reqwest::Result<()> {
+        self.request(reqwest::Method::POST, &format!("/issues/{iid}/notes"))
+            .json(&serde_json::json!({
+                "body": format!("Imported into Hack'n'Plan as work item #{work_item_id}")
+            }))
+            .send_recorded()
+            .await
+            .and_then(|response| response.error_for_status())?;
+
+        self.request(reqwest::Method::PUT, &format!("/issues/{iid}"))
+            .json(&serde_json::json!({ "remove_labels": label }))
+            .send_recorded()
+            .await
+            .and_then(|response| response.error_for_status())?;
+
+        Ok(())
+    }
+}
+
+pub async fn run(arguments: &Arguments, gitlab_arguments: &GitlabArguments, config: &Config) {
+    let url = gitlab_arguments
+        .url
+        .clone()
+        .or_else(|| std::env::var("GITLAB_URL").ok())
+        .unwrap_or_else(|| DEFAULT_URL.to_owned());
+    let project = &gitlab_arguments.gitlab_project;
+
+    let gitlab = Gitlab {
+        client: client(),
+        project_url: format!(
+            "{}/api/v4/projects/{}",
+            url.trim_end_matches('/'),
+            project.trim_matches('/').replace('/', "%2F")
+        ),
+        token: std::env::var("GITLAB_TOKEN").ok(),
+    };
+
+    let issues = gitlab.open_issues(&gitlab_arguments.label).await;
+    if issues.is_empty() {
+        output::info(format!(
+            "Nothing to import, no open issues in {project} have the label \"{}\"",
+            gitlab_arguments.label
+        ));
+        return;
+    }
+
+    let client = client();
+    let default_board_id = match &gitlab_arguments.board {
+        Some(board) => Some(find_board(&client, board).await),
+        None => None,
+    };
+    let available_users = get_available_users(&client).await;
+    let available_milestones = get_available_milestones(&client).await;
+    let available_boards = get_available_boards(&client).await;
+
+    let mut texts = vec![];
+    let mut boards = vec![];
+    for issue in &issues {
+        let mut tokens = issue
+            .labels
+            .iter()
+            .filter(|label| **label != gitlab_arguments.label)
+            .map(|label| label_token(label))
+            .collect::<Vec<String>>();
+
+        for assignee in &issue.assignees {
+            let is_known = available_users
+                .iter()
+                .any(|(_, _, username)| username.eq_ignore_ascii_case(&assignee.username));
+            if is_known {
+                tokens.push(format!("@{}", assignee.username.to_lowercase()));
+            } else {
+                output::warn(format!(
+                    "No Hack'n'Plan user is called {}, {project}#{} is imported without them",
+                    assignee.username, issue.iid
+                ));
+            }
+        }
+
+        if let Some(weight) = issue.weight.filter(|weight| *weight > 0.0) {
+            tokens.push(match config.estimates.mode {
+                EstimateMode::Points => format!("~{weight}p"),
+                EstimateMode::Hours => format!("~{}h", weight * gitlab_arguments.hours_per_weight),
+            });
+        }
+        tokens.extend(issue.due_date.iter().map(|date| format!("due:{date}")));
+        // NOTE: The first category in a title wins, so the default goes at the end
+        tokens.extend(gitlab_arguments.category.as_deref().map(input::hash_tag));
+
+        // NOTE: A milestone goes to the first board of the Hack'n'Plan milestone of the same
+        // name, or else to a board of that name
+        let board = issue.milestone.as_ref().and_then(|milestone| {
+            let board = available_milestones
+                .iter()
+                .find(|(_, name)| name.eq_ignore_ascii_case(&milestone.title))
+                .and_then(|(milestone_id, _)| {
+                    available_boards.iter().find(|(_, _, board_milestone_id)| {
+                        *board_milestone_id == Some(*milestone_id)
+                    })
+                })
+                .or_else(|| {
+                    available_boards
+                        .iter()
+                        .find(|(_, name, _)| name.eq_ignore_ascii_case(&milestone.title))
+                })
+                .map(|(board_id, _, _)| *board_id);
+
+            if board.is_none() {
+                output::warn(format!(
+                    "No board for milestone \"{}\" of {project}#{}",
+                    milestone.title, issue.iid
+                ));
+            }
+
+            board
+        });
+        boards.push(board);
+
+        let description = ISSUE_REFERENCE_MATCHER.replace_all(
+            issue.description.as_deref().unwrap_or_default(),
+            format!("{project}#$iid"),
+        );
+        texts.push(format!(
+            "{} {}\n===\n{}\n\nImported from {}",
+            issue.title,
+            tokens.join(" "),
+            description.trim(),
+            issue.web_url
+        ));
+    }
+
+    let create_arguments = CreateArguments {
+        default_category: None,
+        create_missing_tags: gitlab_arguments.create_missing_tags,
+        fail_on_missing_tags: false,
+        milestone: None,
+        check_capacity: false,
+        auto_assign: None,
+        suggest_estimates: false,
+        apply_suggestions: false,
+        stage: None,
+        scan_description: false,
+        skip: 0,
+        limit: None,
+        batch_size: 1,
+        only: None,
+        pick: false,
+        yes: gitlab_arguments.yes,
+        annotate: None,
+        title_template: None,
+        input_format: None,
+        files: vec![],
+    };
+
+    let texts = texts.iter().map(String::as_str).collect::<Vec<&str>>();
+    let (mut tickets, _) = parse_tickets(
+        arguments,
+        &create_arguments,
+        config,
+        &client,
+        &texts,
+        &[],
+        default_board_id,
+    )
+    .await;
+
+    for (ticket, board) in tickets.iter_mut().zip(&boards) {
+        if let Some(board_id) = board {
+            ticket.board_id = *board_id;
+        }
+    }
+
+    let origins = issues
+        .iter()
+        .map(|issue| format!("{project}#{}", issue.iid))
+        .collect::<Vec<String>>();
+
+    let mut imported = vec![];
+    upload_tickets(
+        arguments,
+        gitlab_arguments.yes,
+        &format!("gitlab {project}"),
+        &tickets,
+        &origins,
+        |index, work_item_id| imported.push((issues[index].iid, work_item_id)),
+    )
+    .await;
+
+    if gitlab_arguments.keep_label {
+        return;
+    }
+
+    for (iid, work_item_id) in imported {
+        if let Err(error) = gitlab
+            .mark_imported(iid, &gitlab_arguments.label, work_item_id)
+            .await
+        {
+            output::warn(format!(
+                "Couldn't take \"{}\" off {project}#{iid}, remove it by hand so it isn't imported again: {error}",
+                gitlab_arguments.label
+            ));
+        }
+    }
+}
+
+/// Tag token for a label, quoted when it has characters hnp doesn't read as part of a tag, like
+/// the `::` of scoped labels
+fn label_token(label: &str) -> String {
+    if label
+        .chars()
+        .all(|character| character.is_alphanumeric() || "-_.".contains(character))
+    {
+        input::hash_tag(label)
+    } else {
+        format!("#\"{label}\"")
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::{HashMap, HashSet};
+
+use clap::Args;
+
+use crate::clone::find_milestone;
+use crate::i18n::Message;
+use crate::models::WorkItem;
+use crate::{client, get_available_boards, get_available_milestones, get_work_items, output, Id};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    /// Graphviz, render with e.g. `dot -Tsvg`
+    Dot,
+    /// Mermaid flowchart, rendered by GitHub and GitLab in Markdown
+    Mermaid,
+}
+
+#[derive(Args, Debug)]
+pub struct GraphArguments {
+    /// Milestone whose work items are drawn, dependencies outside of it are drawn dashed
+    #[arg(long)]
+    milestone: String,
+
+    #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+    format: GraphFormat,
+}
+
+/// How a work item is drawn
+#[derive(PartialEq)]
+enum State {
+    Completed,
+    /// Open with a dependency that isn't completed
+    Blocked,
+    Open,
+    /// Not in the milestone, only drawn because something in it depends on it
+    Outside,
+}
+
+/// Work items of a milestone with the dependencies between them
+pub struct Dependencies {
+    pub work_items: Vec<WorkItem>,
+    /// Ids of the work items on the boards of the milestone
+    pub in_milestone: Vec<Id>,
+    /// From each dependency to the work item in the milestone that depends on it, dependencies
+    /// that no longer exist are left out
+    pub edges: Vec<(Id, Id)>,
+}
+
+impl Dependencies {
+    pub async fn fetch(client: &reqwest::Client, milestone: &str) -> Self {
+        let milestone_id = find_milestone(milestone, &get_available_milestones(client).await);
+        let board_ids = get_available_boards(client)
+            .await
+            .into_iter()
+            .filter(|(_, _, board_milestone_id)| *board_milestone_id == Some(milestone_id))
+            .map(|(id, _, _)| id)
+            .collect::<Vec<Id>>();
+        let work_items = get_work_items::<WorkItem>(client).await;
+
+        let in_milestone = work_items
+            .iter()
+            .filter(|work_item| {
+                work_item
+                    .board
+                    .as_ref()
+                    .is_some_and(|board| board_ids.contains(&board.board_id))
+            })
+            .map(|work_item| work_item.work_item_id)
+            .collect::<Vec<Id>>();
+        let edges = work_items
+            .iter()
+            .filter(|work_item| in_milestone.contains(&work_item.work_item_id))
+            .flat_map(|work_item| {
+                work_item
+                    .dependency_ids
+                    .iter()
+                    .filter(|dependency_id| {
+                        work_items
+                            .iter()
+                            .any(|candidate| candidate.work_item_id == **dependency_id)
+                    })
+                    .map(|dependency_id| (*dependency_id, work_item.work_item_id))
+            })
+            .collect::<Vec<(Id, Id)>>();
+
+        Dependencies {
+            work_items,
+            in_milestone,
+            edges,
+        }
+    }
+
+    /// The work items in the milestone, followed by the dependencies outside of it
+    pub fn nodes(&self) -> Vec<Id> {
+        let mut nodes = self.in_milestone.clone();
+        for (dependency_id, _) in &self.edges {
+            if !nodes.contains(dependency_id) {
+                nodes.push(*dependency_id);
+            }
+        }
+
+        nodes
+    }
+
+    pub fn work_item(&self, id: Id) -> &WorkItem {
+        self.work_items
+            .iter()
+            .find(|work_item| work_item.work_item_id == id)
+            .expect("work item of a node to be fetched")
+    }
+}
+
+/// Prints the dependencies between the work items of a milestone as a graph, an arrow pointing
+/// from each dependency to what depends on it. Cycles are drawn in red and warned about.
+pub async fn run(graph_arguments: &GraphArguments) {
+    let client = client();
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let dependencies = Dependencies::fetch(&client, &graph_arguments.milestone).await;
+    spinner.finish_and_clear();
+
+    let by_id = dependencies
+        .work_items
+        .iter()
+        .map(|work_item| (work_item.work_item_id, work_item))
+        .collect::<HashMap<Id, &WorkItem>>();
+    let edges = &dependencies.edges;
+    let nodes = dependencies.nodes();
+
+    let state = |id: &Id| {
+        let work_item = by_id[id];
+        if !dependencies.in_milestone.contains(id) {
+            State::Outside
+        } else if work_item.is_completed() {
+            State::Completed
+        } else if work_item
+            .dependency_ids
+            .iter()
+            .filter_map(|dependency_id| by_id.get(dependency_id))
+            .any(|dependency| !dependency.is_completed())
+        {
+            State::Blocked
+        } else {
+            State::Open
+        }
+    };
+
+    let cyclic = cyclic_edges(edges);
+    if !cyclic.is_empty() {
+        output::warn(format!(
+            "The dependencies have a cycle, nothing in it can be started: {}",
+            cyclic
+                .iter()
+                .map(|(from, to)| format!("#{to} depends on #{from}"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+    }
+
+    // NOTE: Nothing else is printed to stdout, so the graph can be piped into `dot`
+    let label = |id: &Id| format!("#{id} {}", by_id[id].title);
+    let mut lines = vec![];
+    match graph_arguments.format {
+        GraphFormat::Dot => {
+            lines.push("digraph dependencies {".to_owned());
+            lines.push("    rankdir=LR;".to_owned());
+            lines.push("    node [shape=box, style=rounded];".to_owned());
+            for id in &nodes {
+                let style = match state(id) {
+                    State::Completed => ", style=\"rounded,filled\", fillcolor=palegreen",
+                    State::Blocked => ", color=orange, penwidth=2",
+                    State::Open => "",
+                    State::Outside => ", style=\"rounded,dashed\"",
+                };
+                lines.push(format!(
+                    "    {id} [label=\"{}\"{style}];",
+                    label(id).replace('\\', "\\\\").replace('"', "\\\"")
+                ));
+            }
+            for edge @ (from, to) in edges {
+                let style = if cyclic.contains(edge) {
+                    " [color=red]"
+                } else {
+                    ""
+                };
+                lines.push(format!("    {from} -> {to}{style};"));
+            }
+            lines.push("}".to_owned());
+        }
+        GraphFormat::Mermaid => {
+            // NOTE: `#` starts an entity in Mermaid labels, so it's escaped as one
+            lines.push("flowchart LR".to_owned());
+            for id in &nodes {
+                lines.push(format!(
+                    "    w{id}[\"{}\"]",
+                    label(id).replace('#', "#35;").replace('"', "#quot;")
+                ));
+            }
+            for (from, to) in edges {
+                lines.push(format!("    w{from} --> w{to}"));
+            }
+
+            lines.push("    classDef completed fill:#cfc".to_owned());
+            lines.push("    classDef blocked stroke:#f90,stroke-width:2px".to_owned());
+            lines.push("    classDef outside stroke-dasharray:4".to_owned());
+            for id in &nodes {
+                match state(id) {
+                    State::Completed => lines.push(format!("    class w{id} completed")),
+                    State::Blocked => lines.push(format!("    class w{id} blocked")),
+                    State::Outside => lines.push(format!("    class w{id} outside")),
+                    State::Open => {}
+                }
+            }
+            // NOTE: Mermaid numbers links in the order they're declared
+            for (index, edge) in edges.iter().enumerate() {
+                if cyclic.contains(edge) {
+                    lines.push(format!("    linkStyle {index} stroke:red"));
+                }
+            }
+        }
+    }
+
+    println!("{}", lines.join("\n"));
+}
+
+/// The edges that are part of a cycle, those whose end can reach their start again
+pub fn cyclic_edges(edges: &[(Id, Id)]) -> Vec<(Id, Id)> {
+    let reachable = |start: Id| {
+        let mut seen = HashSet::new();
+        let mut stack = vec![start];
+        while let Some(id) = stack.pop() {
+            for (_, to) in edges.iter().filter(|(from, _)| *from == id) {
+                if seen.insert(*to) {
+                    stack.push(*to);
+                }
+            }
+        }
+        seen
+    };
+
+    edges
+        .iter()
+        .filter(|(from, to)| *from == *to || reachable(*to).contains(from))
+        .copied()
+        .collect()
+}
+
+/// Path from one of `starts` into a cycle of `dependencies`, which map each work item to what it
+/// depends on. The path ends with the first work item it visits twice.
+pub fn path_into_cycle(starts: &[Id], dependencies: &HashMap<Id, Vec<Id>>) -> Option<Vec<Id>> {
+    // NOTE: Work items whose dependencies were all walked without finding a cycle
+    let mut done = HashSet::new();
+
+    for start in starts {
+        let mut path = vec![*start];
+        let mut next = vec![dependencies.get(start).map_or(&[][..], Vec::as_slice)];
+        while let Some(remaining) = next.last_mut() {
+            let Some((id, rest)) = remaining.split_first() else {
+                done.insert(path.pop().expect("To have a work item for each level"));
+                next.pop();
+                continue;
+            };
+            *remaining = rest;
+
+            if path.contains(id) {
+                path.push(*id);
+                return Some(path);
+            }
+            if done.contains(id) {
+                continue;
+            }
+
+            path.push(*id);
+            next.push(dependencies.get(id).map_or(&[][..], Vec::as_slice));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::path_into_cycle;
+
+    #[test]
+    fn finds_the_path_into_a_cycle() {
+        let dependencies = HashMap::from([(1, vec![2]), (2, vec![3]), (3, vec![4]), (4, vec![2])]);
+
+        assert_eq!(
+            path_into_cycle(&[1], &dependencies),
+            Some(vec![1, 2, 3, 4, 2])
+        );
+    }
+
+    #[test]
+    fn finds_work_items_that_depend_on_themselves() {
+        let dependencies = HashMap::from([(5, vec![5])]);
+
+        assert_eq!(path_into_cycle(&[5], &dependencies), Some(vec![5, 5]));
+    }
+
+    #[test]
+    fn allows_shared_dependencies_without_a_cycle() {
+        let dependencies =
+            HashMap::from([(1, vec![2, 3]), (2, vec![4]), (3, vec![4]), (4, vec![])]);
+
+        assert_eq!(path_into_cycle(&[1, 3, 9], &dependencies), None);
+    }
+}
@@ -0,0 +1,270 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::i18n::{Action, Message};
+use crate::{client, current_project_id, delete_json, output, paths, stats, Arguments, Id};
+
+/// Work items created by a single run of `hnp`, kept so the run can be undone
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Run {
+    pub id: String,
+    pub project_id: Id,
+    /// What the work items were created from, e.g. the task file
+    pub source: String,
+    pub created_at: String,
+    pub status: RunStatus,
+    /// In the order they were created in, so parents come before their children
+    pub work_item_ids: Vec<Id>,
+    /// Exactly what was sent to create each work item in `work_item_ids`
+    #[serde(default)]
+    pub payloads: Vec<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    /// Still running, or it failed or got interrupted halfway
+    Incomplete,
+    Completed,
+    Undone,
+}
+
+#[derive(Args, Debug)]
+pub struct HistoryArguments {
+    #[command(subcommand)]
+    command: Option<HistoryCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+enum HistoryCommand {
+    /// Print the payloads a run sent to Hack'n'Plan
+    Show {
+        /// Id of the run, as listed by `hnp history`
+        run: String,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct UndoArguments {
+    /// Run to undo, defaults to the last run in this project
+    run: Option<String>,
+
+    /// Delete the work items without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+impl Run {
+    pub fn new(source: &str) -> Run {
+        let now = chrono::Local::now();
+
+        // NOTE: Runs started within the same second (e.g. recurring tickets) get a suffix
+        let timestamp = now.format("%Y%m%d-%H%M%S").to_string();
+        let id = std::iter::once(timestamp.clone())
+            .chain((2..).map(|suffix| format!("{timestamp}-{suffix}")))
+            .find(|id| !directory().join(format!("{id}.json")).exists())
+            .expect("To find an unused run id");
+
+        Run {
+            id,
+            project_id: current_project_id(),
+            source: source.to_owned(),
+            created_at: now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            status: RunStatus::Incomplete,
+            work_item_ids: vec![],
+            payloads: vec![],
+        }
+    }
+
+    /// Saves the run before anything gets created, so failed runs show up in the history
+    pub fn start(&self) {
+        self.save();
+    }
+
+    /// Adds a created work item, saving right away so runs that fail halfway can still be undone
+    pub fn record(&mut self, work_item_id: Id, payload: Value) {
+        self.work_item_ids.push(work_item_id);
+        self.payloads.push(payload);
+        self.save();
+    }
+
+    pub fn finish(&mut self) {
+        self.status = RunStatus::Completed;
+        self.save();
+        stats::record(self);
+    }
+
+    fn save(&self) {
+        let directory = directory();
+        fs::create_dir_all(&directory)
+            .unwrap_or_else(|_| panic!("To create directory {}", directory.display()));
+
+        let path = directory.join(format!("{}.json", self.id));
+        fs::write(
+            &path,
+            serde_json::to_string_pretty(self).expect("To serialize run history"),
+        )
+        .unwrap_or_else(|_| panic!("To write run history to {}", path.display()));
+    }
+
+    /// Runs in all projects, oldest first
+    fn all() -> Vec<Run> {
+        let Ok(entries) = fs::read_dir(directory()) else {
+            return vec![];
+        };
+
+        let mut runs = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let contents = fs::read_to_string(&path).ok()?;
+
+                serde_json::from_str::<Run>(&contents).ok()
+            })
+            .collect::<Vec<Run>>();
+
+        runs.sort_by(|a, b| a.id.cmp(&b.id));
+        runs
+    }
+
+    fn status_label(&self) -> &'static str {
+        match self.status {
+            RunStatus::Incomplete => "failed or interrupted",
+            RunStatus::Completed => "completed",
+            RunStatus::Undone => "undone",
+        }
+    }
+}
+
+/// Directory holding a file per run, `~/.local/share/hnp/history` on Linux
+fn directory() -> PathBuf {
+    paths::data_dir()
+        .expect("To find the user's data directory")
+        .join("history")
+}
+
+pub fn history(history_arguments: &HistoryArguments) {
+    let runs = Run::all();
+
+    match &history_arguments.command {
+        None => {
+            if runs.is_empty() {
+                output::info("No runs were recorded yet");
+            }
+
+            for run in &runs {
+                output::info(format!(
+                    "{} at {}: {} work item(s) from {} in project {}, {}",
+                    run.id,
+                    run.created_at,
+                    run.work_item_ids.len(),
+                    run.source,
+                    run.project_id,
+                    run.status_label()
+                ));
+            }
+        }
+        Some(HistoryCommand::Show { run: id }) => {
+            let Some(run) = runs.iter().find(|run| &run.id == id) else {
+                output::error(format!("Could not find run {id}, see `hnp history`"));
+                output::exit_with_error();
+            };
+
+            output::info(format!(
+                "Run {} at {} from {} in project {}, {}",
+                run.id,
+                run.created_at,
+                run.source,
+                run.project_id,
+                run.status_label()
+            ));
+
+            for (id, payload) in run.work_item_ids.iter().zip(&run.payloads) {
+                println!(
+                    "#{id}\n{}",
+                    serde_json::to_string_pretty(payload).expect("To serialize payload")
+                );
+            }
+        }
+    }
+}
+
+pub async fn undo(arguments: &Arguments, undo_arguments: &UndoArguments) {
+    let runs = Run::all()
+        .into_iter()
+        .filter(|run| run.project_id == current_project_id() && run.status != RunStatus::Undone)
+        .filter(|run| !run.work_item_ids.is_empty())
+        .collect::<Vec<Run>>();
+
+    let run = match &undo_arguments.run {
+        Some(id) => runs.into_iter().find(|run| &run.id == id),
+        None => runs.into_iter().last(),
+    };
+
+    let Some(mut run) = run else {
+        output::error(match &undo_arguments.run {
+            Some(id) => {
+                format!("Could not find run {id} to undo in this project, see `hnp history`")
+            }
+            None => "There are no runs to undo in this project".to_owned(),
+        });
+        output::exit_with_error();
+    };
+
+    output::info(format!(
+        "Run {} created {} work item(s) from {}: {}",
+        run.id,
+        run.work_item_ids.len(),
+        run.source,
+        run.work_item_ids
+            .iter()
+            .map(|id| format!("#{id}"))
+            .collect::<Vec<String>>()
+            .join(", ")
+    ));
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if undo_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Deleted));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmUndo(run.work_item_ids.len()))
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Deleted));
+        return;
+    }
+
+    let client = client();
+
+    let progress = output::progress(run.work_item_ids.len(), "Deleting work items");
+    // NOTE: Children were created after their parents, so they get deleted first
+    for id in run.work_item_ids.iter().rev() {
+        delete_work_item(&client, *id).await;
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    run.status = RunStatus::Undone;
+    run.save();
+
+    output::success(Message::Undone {
+        work_items: run.work_item_ids.len(),
+        run: &run.id,
+    });
+}
+
+async fn delete_work_item(client: &reqwest::Client, id: Id) {
+    // NOTE: Work items that were already deleted by hand are fine
+    delete_json(client, &format!("/workitems/{id}")).await;
+}
@@ -0,0 +1,116 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use serde_json::Value;
+
+use crate::config::Hooks;
+use crate::output;
+
+/// Commands from the config, set before any ticket is read
+static HOOKS: OnceLock<Hooks> = OnceLock::new();
+
+/// Points in creating a ticket where a command from `[hooks]` gets to change it
+#[derive(Debug, Clone, Copy)]
+pub enum Hook {
+    PreParse,
+    PostResolve,
+    PreUpload,
+    PostUpload,
+}
+
+impl Hook {
+    fn name(self) -> &'static str {
+        match self {
+            Hook::PreParse => "pre_parse",
+            Hook::PostResolve => "post_resolve",
+            Hook::PreUpload => "pre_upload",
+            Hook::PostUpload => "post_upload",
+        }
+    }
+}
+
+pub fn set(hooks: Hooks) {
+    HOOKS.set(hooks).expect("To only set the hooks once");
+}
+
+fn command(hook: Hook) -> Option<&'static str> {
+    let hooks = HOOKS.get()?;
+    match hook {
+        Hook::PreParse => hooks.pre_parse.as_deref(),
+        Hook::PostResolve => hooks.post_resolve.as_deref(),
+        Hook::PreUpload => hooks.pre_upload.as_deref(),
+        Hook::PostUpload => hooks.post_upload.as_deref(),
+    }
+}
+
+/// Pipes `value` through the command for `hook`, returning the JSON it prints. `None` when no
+/// command is configured for `hook`.
+pub fn run(hook: Hook, value: &Value) -> Option<Value> {
+    let command = command(hook)?;
+
+    let output = pipe(command, value)
+        .unwrap_or_else(|error| panic!("To run the {} hook `{command}`: {error}", hook.name()));
+    Some(serde_json::from_slice(&output).unwrap_or_else(|error| {
+        panic!(
+            "To get JSON back from the {} hook `{command}`: {error}",
+            hook.name()
+        )
+    }))
+}
+
+/// Pipes `value` through the command for `hook` without waiting for anything back, a failing
+/// command only gets a warning as there's nothing left to stop
+pub fn notify(hook: Hook, value: &Value) {
+    let Some(command) = command(hook) else {
+        return;
+    };
+
+    if let Err(error) = pipe(command, value) {
+        output::warn(format!(
+            "The {} hook `{command}` failed: {error}",
+            hook.name()
+        ));
+    }
+}
+
+/// Runs `command` in the shell with `value` on stdin, returning its stdout. Its stderr is shown
+/// as is, so hooks can log.
+fn pipe(command: &str, value: &Value) -> Result<Vec<u8>, String> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|error| error.to_string())?;
+
+    // NOTE: Dropping stdin closes it, so the command knows the JSON is complete
+    child
+        .stdin
+        .take()
+        .expect("To have the stdin of the hook")
+        .write_all(value.to_string().as_bytes())
+        .map_err(|error| error.to_string())?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|error| error.to_string())?;
+    if !output.status.success() {
+        return Err(format!("it exited with {}", output.status));
+    }
+
+    Ok(output.stdout)
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
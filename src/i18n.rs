@@ -0,0 +1,718 @@
+use std::fmt::{self, Display};
+use std::sync::OnceLock;
+
+use hnp::Id;
+
+use crate::output;
+
+/// Language of the messages hnp prints
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Language {
+    #[default]
+    #[value(name = "en")]
+    English,
+    #[value(name = "nl")]
+    Dutch,
+}
+
+/// Messages that are printed in the chosen language. What's filled in, like titles and names,
+/// is printed as it is.
+// NOTE: Other messages are printed in English
+#[derive(Clone, Copy)]
+pub enum Message<'a> {
+    WorkingInProject {
+        name: &'a str,
+        id: Id,
+    },
+    NothingWas(Action),
+    NonInteractiveWithoutYes(Action),
+    NoTicketsPicked,
+    StrictWithDuplicateTitles,
+    OverLimits,
+    TicketsDontParse,
+    TagsNotFound(&'a str),
+    FetchingWorkItems,
+    FetchingMetadata,
+    FetchingMilestonesAndWorkItems,
+    TimedOut,
+    Unreachable(&'a dyn Display),
+    InvalidApiKey,
+    NoAccessToProject(Id),
+    NoApiAt(&'a str),
+    UnexpectedStatus(&'a dyn Display),
+    ProjectDoesntExist(Id),
+    UploadedTicket(&'a str),
+    Summary {
+        tickets: usize,
+        estimate: &'a str,
+    },
+    Interrupted {
+        uploaded: usize,
+        tickets: usize,
+    },
+    StoppingAfterCurrentTicket,
+    MissingReference {
+        title: &'a str,
+        id: Id,
+    },
+    TicketOnLine {
+        line: usize,
+        message: &'a dyn Display,
+    },
+    NotValidatingReferences,
+    UndoRun(&'a str),
+    UndoCreated(&'a str),
+    Undone {
+        work_items: usize,
+        run: &'a str,
+    },
+    ChangedWorkItems(usize),
+    NothingToSync(usize),
+    CreatedWorkItem {
+        id: Id,
+        title: &'a str,
+    },
+    UpdatedWorkItem {
+        id: Id,
+        title: &'a str,
+    },
+    ArchivedWorkItem {
+        id: Id,
+        title: &'a str,
+        stage: &'a str,
+    },
+    DemoCreated {
+        milestone: &'a str,
+    },
+    NoDemo(Id),
+    DemoRemoved(Id),
+    // NOTE: Questions asked before changing anything
+    ConfirmUpload {
+        tickets: usize,
+        project: &'a str,
+    },
+    ConfirmAddTags(&'a str),
+    TypeProjectName(&'a str),
+    PickTickets,
+    ConfirmCreateDemo(Id),
+    ConfirmRemoveDemo(Id),
+    #[cfg(feature = "self-update")]
+    ConfirmSelfUpdate(&'a dyn Display),
+    ConfirmClone(usize),
+    ConfirmCloneBoard {
+        board: &'a str,
+        work_items: usize,
+    },
+    ConfirmCreateEntries(usize),
+    ConfirmUndo(usize),
+    ConfirmRollover {
+        work_items: usize,
+        milestone: &'a str,
+    },
+    ConfirmRestore,
+    ConfirmSync {
+        create: usize,
+        update: usize,
+    },
+    ConfirmArchive(usize),
+    ConfirmGithubExport {
+        create: usize,
+        update: usize,
+        repo: &'a str,
+    },
+    ConfirmStale {
+        work_items: usize,
+        tag: bool,
+        remind: bool,
+    },
+    ConfirmPlan {
+        work_items: usize,
+        milestone: &'a str,
+    },
+    ConfirmBulk(usize),
+    ConfirmQa(usize),
+    ConfirmImport(usize),
+    ConfirmApply(usize),
+}
+
+/// What "nothing was" done when a command stops early
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Changed,
+    Created,
+    Uploaded,
+    Deleted,
+    Imported,
+    Exported,
+    Restored,
+    RolledOver,
+    Updated,
+    Cloned,
+}
+
+/// English and Dutch of each action
+const ACTIONS: &[(Action, &str, &str)] = &[
+    (Action::Changed, "changed", "veranderd"),
+    (Action::Created, "created", "aangemaakt"),
+    (Action::Uploaded, "uploaded", "geüpload"),
+    (Action::Deleted, "deleted", "verwijderd"),
+    (Action::Imported, "imported", "geïmporteerd"),
+    (Action::Exported, "exported", "geëxporteerd"),
+    (Action::Restored, "restored", "hersteld"),
+    (Action::RolledOver, "rolled over", "doorgeschoven"),
+    (Action::Updated, "updated", "bijgewerkt"),
+    (Action::Cloned, "cloned", "gekloond"),
+];
+
+/// Picks the language from `--lang`, or else from `LC_ALL`, `LC_MESSAGES` or `LANG` like other
+/// command line tools
+pub fn init(language: Option<Language>) {
+    let language = language.unwrap_or_else(|| {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|variable| std::env::var(variable).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+
+        if locale.to_lowercase().starts_with("nl") {
+            Language::Dutch
+        } else {
+            Language::English
+        }
+    });
+
+    LANGUAGE
+        .set(language)
+        .expect("To only set the language once");
+}
+
+/// The chosen language, English for JSON diagnostics and GitHub annotations as tools read those
+fn language() -> Language {
+    if output::is_json_diagnostics() || output::is_github() {
+        return Language::English;
+    }
+
+    LANGUAGE.get().copied().unwrap_or_default()
+}
+
+impl Action {
+    fn text(self, language: Language) -> &'static str {
+        let (_, english, dutch) = ACTIONS
+            .iter()
+            .find(|(action, _, _)| *action == self)
+            .expect("To have a translation for every action");
+
+        match language {
+            Language::English => english,
+            Language::Dutch => dutch,
+        }
+    }
+}
+
+impl Display for Message<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(formatter, language())
+    }
+}
+
+impl Message<'_> {
+    fn write(&self, formatter: &mut fmt::Formatter<'_>, language: Language) -> fmt::Result {
+        match language {
+            Language::English => self.english(formatter),
+            Language::Dutch => self.dutch(formatter),
+        }
+    }
+
+    fn english(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::WorkingInProject { name, id } => {
+                write!(formatter, "Working in project \"{name}\" (#{id})")
+            }
+            Message::NothingWas(action) => {
+                write!(formatter, "Nothing was {}", action.text(Language::English))
+            }
+            Message::NonInteractiveWithoutYes(action) => write!(
+                formatter,
+                "Running --non-interactive without --yes, nothing was {}",
+                action.text(Language::English)
+            ),
+            Message::NoTicketsPicked => {
+                formatter.write_str("No tickets picked, nothing was uploaded")
+            }
+            Message::StrictWithDuplicateTitles => {
+                formatter.write_str("Running --strict with duplicate titles, nothing was uploaded")
+            }
+            Message::OverLimits => {
+                formatter.write_str("Tickets don't fit Hack'n'Plan's limits, nothing was uploaded")
+            }
+            Message::TicketsDontParse => {
+                formatter.write_str("Tickets have mistakes in them, nothing was uploaded")
+            }
+            Message::TagsNotFound(tags) => write!(
+                formatter,
+                "Could not find tags on Hack'n'Plan for {tags}, nothing was uploaded"
+            ),
+            Message::FetchingWorkItems => {
+                formatter.write_str("Fetching work items from Hack'n'Plan")
+            }
+            Message::FetchingMetadata => {
+                formatter.write_str("Fetching project metadata from Hack'n'Plan")
+            }
+            Message::FetchingMilestonesAndWorkItems => {
+                formatter.write_str("Fetching milestones and work items from Hack'n'Plan")
+            }
+            Message::TimedOut => formatter.write_str(
+                "Hack'n'Plan didn't respond in time, try again or raise the limit with --timeout",
+            ),
+            Message::Unreachable(error) => {
+                write!(formatter, "Could not reach Hack'n'Plan: {error}")
+            }
+            Message::InvalidApiKey => formatter.write_str(
+                "The API key in HACKNPLAN_API_KEY is invalid, create a new one in your \
+                 Hack'n'Plan account settings",
+            ),
+            Message::NoAccessToProject(id) => write!(
+                formatter,
+                "The API key in HACKNPLAN_API_KEY lacks access to project {id}, check that the \
+                 key's owner is a member of it and the key has the needed scopes"
+            ),
+            Message::NoApiAt(url) => write!(
+                formatter,
+                "There's no Hack'n'Plan API at {url}, check `api.url` and `api.version` of the \
+                 config"
+            ),
+            Message::UnexpectedStatus(status) => write!(
+                formatter,
+                "Hack'n'Plan responded with {status} when looking up the project"
+            ),
+            Message::ProjectDoesntExist(id) => write!(
+                formatter,
+                "Project {id} doesn't exist, check HACKNPLAN_PROJECT_ID or --project-id"
+            ),
+            Message::UploadedTicket(title) => write!(formatter, "Uploaded ticket \"{title}\""),
+            Message::Summary { tickets, estimate } => write!(
+                formatter,
+                "Summary of {tickets} ticket(s), {estimate} estimated in total:"
+            ),
+            Message::Interrupted { uploaded, tickets } => write!(
+                formatter,
+                "Interrupted after uploading {uploaded} of {tickets} ticket(s), the rest weren't \
+                 uploaded"
+            ),
+            Message::StoppingAfterCurrentTicket => formatter.write_str(
+                "Stopping after the current ticket, press Ctrl-C again to stop right away",
+            ),
+            Message::MissingReference { title, id } => write!(
+                formatter,
+                "Description of \"{title}\" refers to #{id}, which doesn't exist"
+            ),
+            Message::TicketOnLine { line, message } => {
+                write!(formatter, "Ticket on line {line}: {message}")
+            }
+            Message::NotValidatingReferences => {
+                formatter.write_str("Not validating references to work items while --offline")
+            }
+            Message::UndoRun(run) => write!(formatter, "Undo this run with `hnp undo {run}`"),
+            Message::UndoCreated(run) => write!(
+                formatter,
+                "Undo the created work items with `hnp undo {run}`"
+            ),
+            Message::Undone { work_items, run } => write!(
+                formatter,
+                "Deleted {work_items} work item(s) created by run {run}"
+            ),
+            Message::ChangedWorkItems(work_items) => {
+                write!(formatter, "Changed {work_items} work item(s)")
+            }
+            Message::NothingToSync(files) => {
+                write!(formatter, "Nothing to sync, {files} file(s) are up to date")
+            }
+            Message::CreatedWorkItem { id, title } => write!(formatter, "Created #{id} {title}"),
+            Message::UpdatedWorkItem { id, title } => write!(formatter, "Updated #{id} {title}"),
+            Message::ArchivedWorkItem { id, title, stage } => {
+                write!(formatter, "Archived #{id} {title} into {stage}")
+            }
+            Message::DemoCreated { milestone } => write!(
+                formatter,
+                "Created the demo, try `hnp graph --milestone \"{milestone}\"` on it and remove it \
+                 with `hnp demo --teardown`"
+            ),
+            Message::NoDemo(id) => write!(formatter, "Project {id} has no demo to remove"),
+            Message::DemoRemoved(id) => write!(formatter, "Removed the demo from project {id}"),
+            Message::ConfirmUpload { tickets, project } => {
+                write!(formatter, "Upload {tickets} ticket(s) to \"{project}\"?")
+            }
+            Message::ConfirmAddTags(tags) => write!(
+                formatter,
+                "Could not find tags on Hack'n'Plan for the following list, would you like to add \
+                 these in bulk?\n{tags}"
+            ),
+            Message::TypeProjectName(project) => write!(
+                formatter,
+                "Project \"{project}\" is protected, type its name to continue:"
+            ),
+            Message::PickTickets => formatter.write_str("Which tickets should be uploaded?"),
+            Message::ConfirmCreateDemo(id) => write!(formatter, "Create the demo in project {id}?"),
+            Message::ConfirmRemoveDemo(id) => {
+                write!(formatter, "Remove the demo from project {id}?")
+            }
+            #[cfg(feature = "self-update")]
+            Message::ConfirmSelfUpdate(version) => write!(formatter, "Update hnp to {version}?"),
+            Message::ConfirmClone(work_items) => {
+                write!(formatter, "Clone {work_items} work item(s)?")
+            }
+            Message::ConfirmCloneBoard { board, work_items } => write!(
+                formatter,
+                "Clone board \"{board}\" with {work_items} work item(s)?"
+            ),
+            Message::ConfirmCreateEntries(entries) => {
+                write!(formatter, "Create these {entries} entries?")
+            }
+            Message::ConfirmUndo(work_items) => {
+                write!(formatter, "Delete these {work_items} work item(s)?")
+            }
+            Message::ConfirmRollover {
+                work_items,
+                milestone,
+            } => write!(
+                formatter,
+                "Roll over {work_items} work item(s) to \"{milestone}\"?"
+            ),
+            Message::ConfirmRestore => formatter.write_str(
+                "Restoring creates all work items again, even if they already exist. Continue?",
+            ),
+            Message::ConfirmSync { create, update } => write!(
+                formatter,
+                "Create {create} and update {update} work item(s)?"
+            ),
+            Message::ConfirmArchive(work_items) => write!(
+                formatter,
+                "Archive the {work_items} work item(s) whose file was deleted?"
+            ),
+            Message::ConfirmGithubExport {
+                create,
+                update,
+                repo,
+            } => write!(
+                formatter,
+                "Create {create} and update {update} issue(s) in {repo}?"
+            ),
+            Message::ConfirmStale {
+                work_items,
+                tag,
+                remind,
+            } => write!(
+                formatter,
+                "{} {work_items} stale work item(s)?",
+                match (tag, remind) {
+                    (true, true) => "Tag and comment on",
+                    (true, false) => "Tag",
+                    _ => "Comment on",
+                }
+            ),
+            Message::ConfirmPlan {
+                work_items,
+                milestone,
+            } => write!(
+                formatter,
+                "Assign {work_items} work item(s) and put them on the board of \"{milestone}\"?"
+            ),
+            Message::ConfirmBulk(work_items) => write!(
+                formatter,
+                "Apply these changes to {work_items} work item(s)?"
+            ),
+            Message::ConfirmQa(tasks) => write!(formatter, "Create {tasks} QA task(s)?"),
+            Message::ConfirmImport(tickets) => write!(formatter, "Import {tickets} ticket(s)?"),
+            Message::ConfirmApply(changes) => {
+                write!(formatter, "Apply {changes} change(s) to Hack'n'Plan?")
+            }
+        }
+    }
+
+    fn dutch(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::WorkingInProject { name, id } => {
+                write!(formatter, "Aan het werk in project \"{name}\" (#{id})")
+            }
+            Message::NothingWas(action) => {
+                write!(formatter, "Er is niets {}", action.text(Language::Dutch))
+            }
+            Message::NonInteractiveWithoutYes(action) => write!(
+                formatter,
+                "--non-interactive zonder --yes, er is niets {}",
+                action.text(Language::Dutch)
+            ),
+            Message::NoTicketsPicked => {
+                formatter.write_str("Geen tickets gekozen, er is niets geüpload")
+            }
+            Message::StrictWithDuplicateTitles => {
+                formatter.write_str("--strict met dubbele titels, er is niets geüpload")
+            }
+            Message::OverLimits => formatter.write_str(
+                "Tickets passen niet binnen de limieten van Hack'n'Plan, er is niets geüpload",
+            ),
+            Message::TicketsDontParse => {
+                formatter.write_str("Tickets bevatten fouten, er is niets geüpload")
+            }
+            Message::TagsNotFound(tags) => write!(
+                formatter,
+                "Geen tags op Hack'n'Plan gevonden voor {tags}, er is niets geüpload"
+            ),
+            Message::FetchingWorkItems => formatter.write_str("Werkitems ophalen van Hack'n'Plan"),
+            Message::FetchingMetadata => {
+                formatter.write_str("Projectgegevens ophalen van Hack'n'Plan")
+            }
+            Message::FetchingMilestonesAndWorkItems => {
+                formatter.write_str("Mijlpalen en werkitems ophalen van Hack'n'Plan")
+            }
+            Message::TimedOut => formatter.write_str(
+                "Hack'n'Plan antwoordde niet op tijd, probeer het opnieuw of verhoog de limiet \
+                 met --timeout",
+            ),
+            Message::Unreachable(error) => {
+                write!(formatter, "Kon Hack'n'Plan niet bereiken: {error}")
+            }
+            Message::InvalidApiKey => formatter.write_str(
+                "De API-sleutel in HACKNPLAN_API_KEY is ongeldig, maak een nieuwe aan in je \
+                 accountinstellingen op Hack'n'Plan",
+            ),
+            Message::NoAccessToProject(id) => write!(
+                formatter,
+                "De API-sleutel in HACKNPLAN_API_KEY heeft geen toegang tot project {id}, \
+                 controleer of de eigenaar van de sleutel er lid van is en de sleutel de nodige \
+                 rechten heeft"
+            ),
+            Message::NoApiAt(url) => write!(
+                formatter,
+                "Er is geen Hack'n'Plan-API op {url}, controleer `api.url` en `api.version` in de \
+                 config"
+            ),
+            Message::UnexpectedStatus(status) => write!(
+                formatter,
+                "Hack'n'Plan antwoordde met {status} bij het opzoeken van het project"
+            ),
+            Message::ProjectDoesntExist(id) => write!(
+                formatter,
+                "Project {id} bestaat niet, controleer HACKNPLAN_PROJECT_ID of --project-id"
+            ),
+            Message::UploadedTicket(title) => write!(formatter, "Ticket \"{title}\" geüpload"),
+            Message::Summary { tickets, estimate } => write!(
+                formatter,
+                "Overzicht van {tickets} ticket(s), in totaal {estimate} geschat:"
+            ),
+            Message::Interrupted { uploaded, tickets } => write!(
+                formatter,
+                "Onderbroken na het uploaden van {uploaded} van {tickets} ticket(s), de rest is \
+                 niet geüpload"
+            ),
+            Message::StoppingAfterCurrentTicket => formatter.write_str(
+                "Stoppen na het huidige ticket, druk nogmaals op Ctrl-C om direct te stoppen",
+            ),
+            Message::MissingReference { title, id } => write!(
+                formatter,
+                "Beschrijving van \"{title}\" verwijst naar #{id}, dat niet bestaat"
+            ),
+            Message::TicketOnLine { line, message } => {
+                write!(formatter, "Ticket op regel {line}: {message}")
+            }
+            Message::NotValidatingReferences => formatter
+                .write_str("Verwijzingen naar werkitems worden niet gecontroleerd met --offline"),
+            Message::UndoRun(run) => {
+                write!(formatter, "Maak deze run ongedaan met `hnp undo {run}`")
+            }
+            Message::UndoCreated(run) => write!(
+                formatter,
+                "Maak de aangemaakte werkitems ongedaan met `hnp undo {run}`"
+            ),
+            Message::Undone { work_items, run } => write!(
+                formatter,
+                "{work_items} werkitem(s) van run {run} verwijderd"
+            ),
+            Message::ChangedWorkItems(work_items) => {
+                write!(formatter, "{work_items} werkitem(s) veranderd")
+            }
+            Message::NothingToSync(files) => write!(
+                formatter,
+                "Niets te synchroniseren, {files} bestand(en) zijn up-to-date"
+            ),
+            Message::CreatedWorkItem { id, title } => {
+                write!(formatter, "#{id} {title} aangemaakt")
+            }
+            Message::UpdatedWorkItem { id, title } => {
+                write!(formatter, "#{id} {title} bijgewerkt")
+            }
+            Message::ArchivedWorkItem { id, title, stage } => {
+                write!(formatter, "#{id} {title} gearchiveerd in {stage}")
+            }
+            Message::DemoCreated { milestone } => write!(
+                formatter,
+                "Demo aangemaakt, probeer er `hnp graph --milestone \"{milestone}\"` op en \
+                 verwijder hem met `hnp demo --teardown`"
+            ),
+            Message::NoDemo(id) => {
+                write!(formatter, "Project {id} heeft geen demo om te verwijderen")
+            }
+            Message::DemoRemoved(id) => write!(formatter, "Demo verwijderd uit project {id}"),
+            Message::ConfirmUpload { tickets, project } => {
+                write!(
+                    formatter,
+                    "{tickets} ticket(s) uploaden naar \"{project}\"?"
+                )
+            }
+            Message::ConfirmAddTags(tags) => write!(
+                formatter,
+                "Geen tags op Hack'n'Plan gevonden voor de volgende lijst, wil je ze allemaal \
+                 toevoegen?\n{tags}"
+            ),
+            Message::TypeProjectName(project) => write!(
+                formatter,
+                "Project \"{project}\" is beschermd, typ de naam om door te gaan:"
+            ),
+            Message::PickTickets => formatter.write_str("Welke tickets moeten worden geüpload?"),
+            Message::ConfirmCreateDemo(id) => {
+                write!(formatter, "De demo aanmaken in project {id}?")
+            }
+            Message::ConfirmRemoveDemo(id) => {
+                write!(formatter, "De demo verwijderen uit project {id}?")
+            }
+            #[cfg(feature = "self-update")]
+            Message::ConfirmSelfUpdate(version) => {
+                write!(formatter, "hnp bijwerken naar {version}?")
+            }
+            Message::ConfirmClone(work_items) => {
+                write!(formatter, "{work_items} werkitem(s) klonen?")
+            }
+            Message::ConfirmCloneBoard { board, work_items } => write!(
+                formatter,
+                "Bord \"{board}\" met {work_items} werkitem(s) klonen?"
+            ),
+            Message::ConfirmCreateEntries(entries) => {
+                write!(formatter, "Deze {entries} items aanmaken?")
+            }
+            Message::ConfirmUndo(work_items) => {
+                write!(formatter, "Deze {work_items} werkitem(s) verwijderen?")
+            }
+            Message::ConfirmRollover {
+                work_items,
+                milestone,
+            } => write!(
+                formatter,
+                "{work_items} werkitem(s) doorschuiven naar \"{milestone}\"?"
+            ),
+            Message::ConfirmRestore => formatter.write_str(
+                "Herstellen maakt alle werkitems opnieuw aan, ook als ze al bestaan. Doorgaan?",
+            ),
+            Message::ConfirmSync { create, update } => write!(
+                formatter,
+                "{create} werkitem(s) aanmaken en {update} bijwerken?"
+            ),
+            Message::ConfirmArchive(work_items) => write!(
+                formatter,
+                "De {work_items} werkitem(s) waarvan het bestand is verwijderd archiveren?"
+            ),
+            Message::ConfirmGithubExport {
+                create,
+                update,
+                repo,
+            } => write!(
+                formatter,
+                "{create} issue(s) aanmaken en {update} bijwerken in {repo}?"
+            ),
+            Message::ConfirmStale {
+                work_items,
+                tag,
+                remind,
+            } => match (tag, remind) {
+                (true, true) => write!(
+                    formatter,
+                    "{work_items} verouderde werkitem(s) taggen en er commentaar bij plaatsen?"
+                ),
+                (true, false) => write!(formatter, "{work_items} verouderde werkitem(s) taggen?"),
+                _ => write!(
+                    formatter,
+                    "Commentaar plaatsen bij {work_items} verouderde werkitem(s)?"
+                ),
+            },
+            Message::ConfirmPlan {
+                work_items,
+                milestone,
+            } => write!(
+                formatter,
+                "{work_items} werkitem(s) toewijzen en op het bord van \"{milestone}\" zetten?"
+            ),
+            Message::ConfirmBulk(work_items) => write!(
+                formatter,
+                "Deze wijzigingen toepassen op {work_items} werkitem(s)?"
+            ),
+            Message::ConfirmQa(tasks) => write!(formatter, "{tasks} QA-taak(en) aanmaken?"),
+            Message::ConfirmImport(tickets) => {
+                write!(formatter, "{tickets} ticket(s) importeren?")
+            }
+            Message::ConfirmApply(changes) => {
+                write!(
+                    formatter,
+                    "{changes} wijziging(en) toepassen op Hack'n'Plan?"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{self, Display};
+
+    use super::{Action, Language, Message};
+
+    /// `message` in `language`, whatever language was chosen
+    struct In<'a>(Message<'a>, Language);
+
+    impl Display for In<'_> {
+        fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.write(formatter, self.1)
+        }
+    }
+
+    #[test]
+    fn translates_messages_by_key() {
+        assert_eq!(
+            In(Message::NothingWas(Action::RolledOver), Language::Dutch).to_string(),
+            "Er is niets doorgeschoven"
+        );
+        assert_eq!(
+            In(Message::NothingWas(Action::RolledOver), Language::English).to_string(),
+            "Nothing was rolled over"
+        );
+    }
+
+    #[test]
+    fn leaves_what_is_filled_in_as_it_is() {
+        // NOTE: A title that reads like a message of its own
+        let message = Message::UploadedTicket("Nothing was changed");
+
+        assert_eq!(
+            In(message, Language::Dutch).to_string(),
+            "Ticket \"Nothing was changed\" geüpload"
+        );
+    }
+
+    #[test]
+    fn translates_questions() {
+        let question = Message::ConfirmStale {
+            work_items: 3,
+            tag: true,
+            remind: false,
+        };
+
+        assert_eq!(
+            In(question, Language::English).to_string(),
+            "Tag 3 stale work item(s)?"
+        );
+        assert_eq!(
+            In(question, Language::Dutch).to_string(),
+            "3 verouderde werkitem(s) taggen?"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{Days, NaiveDate};
+use clap::Args;
+
+use crate::config::Config;
+use crate::i18n::Message;
+use crate::models::{self, WorkItem};
+use crate::{
+    client, current_project_id, find_user, get_available_users, get_models, get_work_items, output,
+};
+
+#[derive(Args, Debug)]
+pub struct IcalArguments {
+    /// File to write the calendar to
+    #[arg(long)]
+    out: PathBuf,
+
+    /// Only the work items assigned to these users, by username or (part of) their name
+    #[arg(long, value_name = "USER")]
+    assignee: Vec<String>,
+}
+
+/// An all-day event on a single day
+struct Event {
+    uid: String,
+    date: NaiveDate,
+    summary: String,
+    description: String,
+    url: Option<String>,
+}
+
+pub async fn run(ical_arguments: &IcalArguments, config: &Config) {
+    let client = client();
+
+    let spinner = output::spinner(Message::FetchingMilestonesAndWorkItems);
+    let milestones = get_models::<models::Milestone>(&client, "milestones").await;
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    let available_users = get_available_users(&client).await;
+    spinner.finish_and_clear();
+
+    let assignee_ids = ical_arguments
+        .assignee
+        .iter()
+        .map(|user| find_user(user.trim_start_matches('@'), &available_users).0)
+        .collect::<Vec<_>>();
+
+    let mut events = vec![];
+    for milestone in &milestones {
+        let Some(date) = parse_date(&milestone.due_date) else {
+            continue;
+        };
+
+        let description = match parse_date(&milestone.start_date) {
+            Some(start) => format!("Milestone from {start} to {date}"),
+            None => format!("Milestone due {date}"),
+        };
+        events.push(Event {
+            uid: format!("milestone-{}", milestone.milestone_id),
+            date,
+            summary: format!("{} ends", milestone.name),
+            description,
+            url: None,
+        });
+    }
+
+    // NOTE: Completed work items have nothing left to be late for
+    for work_item in work_items
+        .iter()
+        .filter(|work_item| !work_item.is_completed())
+    {
+        let Some(date) = parse_date(&work_item.due_date) else {
+            continue;
+        };
+
+        if !assignee_ids.is_empty()
+            && !work_item
+                .assigned_user_ids()
+                .iter()
+                .any(|id| assignee_ids.contains(id))
+        {
+            continue;
+        }
+
+        events.push(Event {
+            uid: format!("workitem-{}", work_item.work_item_id),
+            date,
+            summary: format!("#{} {}", work_item.work_item_id, work_item.title),
+            description: work_item.description.clone().unwrap_or_default(),
+            url: Some(
+                config
+                    .references
+                    .url(current_project_id(), work_item.work_item_id),
+            ),
+        });
+    }
+
+    events.sort_by_key(|event| event.date);
+    fs::write(&ical_arguments.out, calendar(&events))
+        .unwrap_or_else(|_| panic!("To write the calendar to {}", ical_arguments.out.display()));
+
+    output::success(format!(
+        "Wrote {} event(s) to {}",
+        events.len(),
+        ical_arguments.out.display()
+    ));
+}
+
+/// Day of a date from the API, which leaves dates out as `null` or an empty string
+fn parse_date(date: &Option<String>) -> Option<NaiveDate> {
+    date.as_deref()
+        .and_then(|date| date.get(..10))
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+}
+
+fn calendar(events: &[Event]) -> String {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let project_id = current_project_id();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//hnp//hnp-cli//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+        format!(
+            "X-WR-CALNAME:{}",
+            text(&format!("Hack'n'Plan project {project_id}"))
+        ),
+    ];
+
+    for event in events {
+        // NOTE: The end of an all-day event is the day after, it's exclusive
+        let end = event
+            .date
+            .checked_add_days(Days::new(1))
+            .unwrap_or(event.date);
+
+        lines.push("BEGIN:VEVENT".to_owned());
+        lines.push(format!("UID:{}-{project_id}@hnp", event.uid));
+        lines.push(format!("DTSTAMP:{stamp}"));
+        lines.push(format!(
+            "DTSTART;VALUE=DATE:{}",
+            event.date.format("%Y%m%d")
+        ));
+        lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+        lines.push(format!("SUMMARY:{}", text(&event.summary)));
+        if !event.description.trim().is_empty() {
+            lines.push(format!("DESCRIPTION:{}", text(event.description.trim())));
+        }
+        if let Some(url) = &event.url {
+            lines.push(format!("URL:{url}"));
+        }
+        lines.push("END:VEVENT".to_owned());
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines
+        .iter()
+        .map(|line| fold(line))
+        .map(|line| format!("{line}\r\n"))
+        .collect()
+}
+
+/// Escapes a TEXT value, where `\`, `;`, `,` and line breaks have a special meaning
+fn text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds lines longer than 75 bytes, continuation lines start with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+
+    for character in line.chars() {
+        if length + character.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+
+        folded.push(character);
+        length += character.len_utf8();
+    }
+
+    folded
+}
@@ -0,0 +1,382 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use clap::{Args, Subcommand};
+use regex::Regex;
+
+use crate::config::Config;
+use crate::i18n::{Action, Message};
+use crate::{
+    client, find_board, history, input, output, parse_tickets, text, upload_with_children,
+    Arguments, CreateArguments, Id, Ticket, SUBTASK_MATCHER,
+};
+use crate::{gitlab, notion};
+
+#[derive(Subcommand, Debug)]
+pub enum ImportCommand {
+    /// Create work items from notes and tasks in an Obsidian vault that carry a tag
+    Obsidian(ObsidianArguments),
+    /// Create work items from the pages of a Notion export
+    Notion(notion::NotionArguments),
+    /// Create work items from the open issues of a GitLab project that carry a label
+    Gitlab(gitlab::GitlabArguments),
+}
+
+#[derive(Args, Debug)]
+pub struct ObsidianArguments {
+    /// Directory of the vault, notes in hidden directories like `.obsidian` are left out
+    #[arg(long)]
+    vault: PathBuf,
+
+    /// Tag marking what to import. On a task line only that task is imported, anywhere else in a
+    /// note (or in its `tags`) the whole note is.
+    #[arg(long, default_value = "#hnp/todo")]
+    tag: String,
+
+    /// Category for the work items that don't name one themselves
+    #[arg(long)]
+    category: Option<String>,
+
+    /// Put the work items on this board
+    #[arg(long, value_name = "BOARD")]
+    board: Option<String>,
+
+    /// Create tags that don't exist on Hack'n'Plan yet without asking
+    #[arg(long)]
+    create_missing_tags: bool,
+
+    /// Import without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// A note or task line to import
+struct Candidate {
+    path: PathBuf,
+    /// Index of the task line, `None` when it's the whole note
+    line: Option<usize>,
+    title: String,
+    description: String,
+}
+
+pub async fn run(arguments: &Arguments, command: &ImportCommand, config: &Config) {
+    match command {
+        ImportCommand::Obsidian(obsidian_arguments) => {
+            obsidian(arguments, obsidian_arguments, config).await
+        }
+        ImportCommand::Notion(notion_arguments) => {
+            notion::run(arguments, notion_arguments, config).await
+        }
+        ImportCommand::Gitlab(gitlab_arguments) => {
+            gitlab::run(arguments, gitlab_arguments, config).await
+        }
+    }
+}
+
+async fn obsidian(arguments: &Arguments, obsidian_arguments: &ObsidianArguments, config: &Config) {
+    let tag = obsidian_arguments.tag.trim_start_matches('#');
+    // NOTE: Nested tags like `#hnp/todo/art` count as well
+    let tag_matcher = Regex::new(&format!(r"#{}(?:/[\w/-]+)?\b", regex::escape(tag)))
+        .expect("Tag Regex to compile");
+    // NOTE: Tags in the front matter are written without `#`, like `tags: [hnp/todo]`
+    let front_matter_tag_matcher = Regex::new(&format!(
+        r"(?m)(?:^|[\s\[,#])(?:{})(?:$|[\s\],])",
+        regex::escape(tag)
+    ))
+    .expect("Front matter tag Regex to compile");
+    let marker_matcher = Regex::new(r"%%\s*hnp:\s*\d+\s*%%").expect("Marker Regex to compile");
+    let link_matcher = Regex::new(r"!?\[\[(?<target>[^\]|]+)(?:\|(?<alias>[^\]]+))?\]\]")
+        .expect("Wiki-link Regex to compile");
+    let comment_matcher = Regex::new(r"(?s)%%.*?%%").expect("Comment Regex to compile");
+
+    // NOTE: Wiki-links become their alias or the name of what they link to, headings and blocks
+    // follow the note's name as `#` would otherwise be read as a tag
+    let plain_text = |text: &str| {
+        let text = link_matcher.replace_all(text, |captures: &regex::Captures| {
+            match captures.name("alias") {
+                Some(alias) => alias.as_str().to_owned(),
+                None => captures["target"]
+                    .split('#')
+                    .filter(|part| !part.is_empty())
+                    .map(|part| part.trim_start_matches('^'))
+                    .collect::<Vec<&str>>()
+                    .join(" > "),
+            }
+        });
+
+        comment_matcher.replace_all(&text, "").into_owned()
+    };
+
+    let mut candidates = vec![];
+    for path in notes(&obsidian_arguments.vault) {
+        let contents = text::read(&path);
+        let (front_matter, body) = split_front_matter(&contents);
+
+        let mut in_code_block = false;
+        let mut note_tagged = front_matter
+            .is_some_and(|front_matter| front_matter_tag_matcher.is_match(front_matter));
+        let mut note_imported = false;
+        let mut note_lines = vec![];
+
+        let offset = contents[..contents.len() - body.len()].lines().count();
+        for (index, line) in body.lines().enumerate() {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+
+            let task = SUBTASK_MATCHER
+                .captures(line)
+                .filter(|_| !in_code_block && line.trim_start().starts_with(['-', '*', '+']));
+
+            match task {
+                Some(task) if tag_matcher.is_match(line) => {
+                    // NOTE: Completed and already imported tasks are left alone
+                    if task["checked"].trim().is_empty() && !marker_matcher.is_match(line) {
+                        candidates.push(Candidate {
+                            path: path.clone(),
+                            line: Some(offset + index),
+                            title: plain_text(&tag_matcher.replace_all(&task["title"], "")),
+                            description: String::new(),
+                        });
+                    }
+                }
+                _ => {
+                    if !in_code_block {
+                        note_tagged |= tag_matcher.is_match(line);
+                        note_imported |= marker_matcher.is_match(line);
+                    }
+                    note_lines.push(tag_matcher.replace_all(line, "").into_owned());
+                }
+            }
+        }
+
+        if note_tagged && !note_imported {
+            candidates.push(Candidate {
+                title: path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                description: plain_text(&note_lines.join("\n")).trim().to_owned(),
+                path,
+                line: None,
+            });
+        }
+    }
+
+    if candidates.is_empty() {
+        output::info(format!(
+            "Nothing to import, no notes or open tasks in {} are tagged #{tag} that weren't imported before",
+            obsidian_arguments.vault.display()
+        ));
+        return;
+    }
+
+    let create_arguments = CreateArguments {
+        default_category: None,
+        create_missing_tags: obsidian_arguments.create_missing_tags,
+        fail_on_missing_tags: false,
+        milestone: None,
+        check_capacity: false,
+        auto_assign: None,
+        suggest_estimates: false,
+        apply_suggestions: false,
+        stage: None,
+        scan_description: false,
+        skip: 0,
+        limit: None,
+        batch_size: 1,
+        only: None,
+        pick: false,
+        yes: obsidian_arguments.yes,
+        annotate: None,
+        title_template: None,
+        input_format: None,
+        files: vec![],
+    };
+
+    let client = client();
+    let default_board_id = match &obsidian_arguments.board {
+        Some(board) => Some(find_board(&client, board).await),
+        None => None,
+    };
+
+    let texts = candidates
+        .iter()
+        .map(|candidate| {
+            // NOTE: The first category in a title wins, so the default goes at the end
+            let category = obsidian_arguments
+                .category
+                .as_deref()
+                .map(|category| format!(" {}", input::hash_tag(category)))
+                .unwrap_or_default();
+
+            format!(
+                "{}{category}\n===\n{}",
+                candidate.title, candidate.description
+            )
+        })
+        .collect::<Vec<String>>();
+    let texts = texts.iter().map(String::as_str).collect::<Vec<&str>>();
+    let (tickets, _) = parse_tickets(
+        arguments,
+        &create_arguments,
+        config,
+        &client,
+        &texts,
+        &[],
+        default_board_id,
+    )
+    .await;
+
+    let origins = candidates
+        .iter()
+        .map(|candidate| match candidate.line {
+            Some(line) => format!("line {} of {}", line + 1, candidate.path.display()),
+            None => candidate.path.display().to_string(),
+        })
+        .collect::<Vec<String>>();
+
+    upload_tickets(
+        arguments,
+        obsidian_arguments.yes,
+        &format!("obsidian {}", obsidian_arguments.vault.display()),
+        &tickets,
+        &origins,
+        |index, work_item_id| write_marker(&candidates[index], work_item_id),
+    )
+    .await;
+}
+
+/// Previews `tickets` and uploads them once confirmed, `origins` says where each ticket came
+/// from. `on_uploaded` is called with the index and id of every uploaded ticket right away, so
+/// nothing is lost when a later upload fails.
+pub async fn upload_tickets(
+    arguments: &Arguments,
+    yes: bool,
+    source: &str,
+    tickets: &[Ticket],
+    origins: &[String],
+    mut on_uploaded: impl FnMut(usize, Id),
+) {
+    for (ticket, origin) in tickets.iter().zip(origins) {
+        output::info(format!("Import \"{}\" from {origin}", ticket.title));
+    }
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Imported));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmImport(tickets.len()))
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Imported));
+        return;
+    }
+
+    let client = client();
+    let run = history::Run::new(source);
+    run.start();
+    let run = Mutex::new(run);
+
+    let progress = output::progress(tickets.len(), "Uploading tickets");
+    for (index, ticket) in tickets.iter().enumerate() {
+        let work_item_id = upload_with_children(&client, ticket, &run).await;
+        on_uploaded(index, work_item_id);
+
+        progress.suspend(|| output::success(Message::UploadedTicket(&ticket.title)));
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    let mut run = run
+        .into_inner()
+        .expect("To have recorded every upload in the run history");
+    run.finish();
+    output::info(Message::UndoRun(&run.id));
+}
+
+/// Markdown files in `vault` and its subdirectories, except hidden ones
+fn notes(vault: &Path) -> Vec<PathBuf> {
+    let mut notes = vec![];
+    let mut directories = vec![vault.to_owned()];
+
+    while let Some(directory) = directories.pop() {
+        let entries = fs::read_dir(&directory)
+            .unwrap_or_else(|_| panic!("To read directory {}", directory.display()));
+
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            if path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+            {
+                continue;
+            }
+
+            if path.is_dir() {
+                directories.push(path);
+            } else if path.extension().is_some_and(|extension| extension == "md") {
+                notes.push(path);
+            }
+        }
+    }
+
+    notes.sort();
+    notes
+}
+
+/// YAML front matter between `---` lines, if any, and the rest of the note
+fn split_front_matter(contents: &str) -> (Option<&str>, &str) {
+    let Some(rest) = contents
+        .strip_prefix("---")
+        .filter(|rest| rest.starts_with(['\n', '\r']))
+    else {
+        return (None, contents);
+    };
+
+    match rest.find("\n---") {
+        Some(end) => {
+            let body = &rest[end + 4..];
+            let body = body.find('\n').map_or("", |start| &body[start + 1..]);
+
+            (Some(&rest[..end]), body)
+        }
+        None => (None, contents),
+    }
+}
+
+/// Writes `%%hnp: <id>%%` behind the task or at the end of the note, an Obsidian comment that
+/// stops it from being imported again
+fn write_marker(candidate: &Candidate, work_item_id: Id) {
+    let path = &candidate.path;
+    let (contents, encoding) = text::read_as_is(path);
+    let marker = format!("%%hnp: {work_item_id}%%");
+
+    let contents = match candidate.line {
+        Some(index) => contents
+            .split_inclusive('\n')
+            .enumerate()
+            .map(|(line_index, line)| {
+                if line_index != index {
+                    return line.to_owned();
+                }
+
+                let content = line.trim_end_matches(['\n', '\r']);
+                format!("{content} {marker}{}", &line[content.len()..])
+            })
+            .collect::<String>(),
+        None => {
+            let separator = if contents.ends_with('\n') { "" } else { "\n" };
+            format!("{contents}{separator}\n{marker}\n")
+        }
+    };
+
+    text::write(path, &contents, encoding);
+}
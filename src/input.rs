@@ -0,0 +1,243 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{MENTION_MATCHER, TICKET_SEPARATOR, TITLE_SEPARATOR};
+
+const FRONT_MATTER_DELIMITER: &str = "+++";
+
+/// Syntax of a file with tickets
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    /// hnp's own syntax, tickets separated by `---`
+    Markdown,
+    /// Emacs org-mode
+    Org,
+    /// todo.txt, a task per line
+    Todotxt,
+}
+
+impl InputFormat {
+    /// Format of `path` judging by its name, `.org` files and `todo.txt` or `done.txt` are
+    /// recognized
+    pub fn detect(path: &Path) -> InputFormat {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if name.ends_with(".org") {
+            InputFormat::Org
+        } else if name.ends_with("todo.txt") || name.ends_with("done.txt") {
+            InputFormat::Todotxt
+        } else {
+            InputFormat::Markdown
+        }
+    }
+}
+
+/// Defaults for every ticket in a file, written as TOML between `+++` lines at its very top:
+///
+/// ```text
+/// +++
+/// board = "Sprint 3"
+/// category = "Audio"
+/// tags = ["sfx"]
+/// +++
+/// ```
+///
+/// Tickets that specify their own category, importance, estimate or assignee keep those, tags
+/// are added to the ticket's own.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FrontMatter {
+    /// Profile from the config whose project the tickets go to, like `%%project:` in a ticket
+    pub project: Option<String>,
+    pub board: Option<String>,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub assignees: Vec<String>,
+    /// Importance level or alias, like in `!high`
+    pub importance: Option<String>,
+    /// Estimate like in `~2h`
+    pub estimate: Option<String>,
+}
+
+impl FrontMatter {
+    /// Splits the front matter, if any, off `contents`
+    pub fn parse(contents: &str) -> Result<(FrontMatter, &str), String> {
+        let Some(rest) = contents
+            .trim_start()
+            .strip_prefix(FRONT_MATTER_DELIMITER)
+            .filter(|rest| rest.starts_with(['\n', '\r']))
+        else {
+            return Ok((FrontMatter::default(), contents));
+        };
+
+        let (front_matter, rest) = rest
+            .split_once(&format!("\n{FRONT_MATTER_DELIMITER}"))
+            .ok_or("front matter to be closed by a line with +++")?;
+
+        let front_matter = toml::from_str(front_matter)
+            .map_err(|error| format!("front matter to be valid: {error}"))?;
+
+        Ok((front_matter, rest))
+    }
+
+    /// `contents` with the defaults added to the title of every ticket
+    pub fn apply(&self, contents: &str) -> String {
+        let mut tokens = vec![];
+        tokens.extend(self.category.iter().map(|category| hash_tag(category)));
+        tokens.extend(self.tags.iter().map(|tag| hash_tag(tag)));
+        tokens.extend(
+            self.importance
+                .iter()
+                .map(|importance| format!("!{importance}")),
+        );
+        tokens.extend(self.estimate.iter().map(|estimate| format!("~{estimate}")));
+
+        if tokens.is_empty() && self.assignees.is_empty() {
+            return contents.to_owned();
+        }
+
+        TICKET_SEPARATOR
+            .split(contents)
+            .map(|text| {
+                if text.trim().is_empty() {
+                    return text.to_owned();
+                }
+
+                let mut chunks = TITLE_SEPARATOR.splitn(text, 2);
+                let title = chunks.next().unwrap_or_default();
+                let rest = chunks.next();
+
+                // NOTE: The first category, importance and estimate in a title win, so defaults
+                // go at the end. Assignees add up, so they're only added when there are none.
+                let mut defaults = tokens.clone();
+                if !MENTION_MATCHER.is_match(title) {
+                    defaults.extend(self.assignees.iter().map(|assignee| format!("@{assignee}")));
+                }
+
+                let (leading, title) = title.split_at(title.len() - title.trim_start().len());
+                let title = format!("{leading}{} {}\n", title.trim_end(), defaults.join(" "));
+
+                match rest {
+                    Some(rest) => format!("{title}==={rest}"),
+                    None => title,
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("---")
+    }
+}
+
+/// `#name`, quoted when the name contains spaces
+pub fn hash_tag(name: &str) -> String {
+    if name.contains(char::is_whitespace) {
+        format!("#\"{name}\"")
+    } else {
+        format!("#{name}")
+    }
+}
+
+/// Files matching `patterns` in the order they're given, patterns can use `*` and `?` in any
+/// part of the path for shells that don't expand them
+pub fn expand(patterns: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    let mut files = vec![];
+
+    for pattern in patterns {
+        let is_pattern = pattern
+            .to_str()
+            .is_some_and(|pattern| pattern.contains(['*', '?']));
+
+        if !is_pattern {
+            files.push(pattern.to_owned());
+            continue;
+        }
+
+        let mut matches = vec![PathBuf::new()];
+        for component in pattern.components() {
+            let Component::Normal(part) = component else {
+                for path in &mut matches {
+                    path.push(component);
+                }
+                continue;
+            };
+
+            let part = part.to_string_lossy();
+            if !part.contains(['*', '?']) {
+                for path in &mut matches {
+                    path.push(&*part);
+                }
+                continue;
+            }
+
+            let matcher = wildcard(&part);
+            matches = matches
+                .iter()
+                .flat_map(|directory| entries(directory))
+                .filter(|path| {
+                    path.file_name().is_some_and(|name| {
+                        let name = name.to_string_lossy();
+
+                        // NOTE: Like shells, wildcards don't match hidden files
+                        matcher.is_match(&name) && (!name.starts_with('.') || part.starts_with('.'))
+                    })
+                })
+                .collect();
+        }
+
+        matches.retain(|path| path.is_file());
+        matches.sort();
+
+        if matches.is_empty() {
+            return Err(format!("No files match {}", pattern.display()));
+        }
+
+        files.extend(matches);
+    }
+
+    Ok(files)
+}
+
+/// Regex for a file name with `*` and `?` wildcards
+fn wildcard(pattern: &str) -> Regex {
+    let expression = pattern
+        .split('*')
+        .map(|part| {
+            part.split('?')
+                .map(regex::escape)
+                .collect::<Vec<String>>()
+                .join("[^/]")
+        })
+        .collect::<Vec<String>>()
+        .join("[^/]*");
+
+    Regex::new(&format!("^{expression}$")).expect("Wildcard Regex to compile")
+}
+
+fn entries(directory: &Path) -> Vec<PathBuf> {
+    let directory = if directory.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        directory
+    };
+
+    let Ok(entries) = fs::read_dir(directory) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            // NOTE: Keeps relative patterns relative instead of prefixing `./`
+            if directory == Path::new(".") {
+                PathBuf::from(entry.file_name())
+            } else {
+                entry.path()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,21 @@
+//! Hack'n'Plan models and a small API client, the parts of hnp that make sense without the CLI.
+//!
+//! The `client` feature adds [`client::Client`] on top of the models and `blocking` adds a
+//! synchronous `blocking::Client`. The `core` feature adds the parsing of hnp's markup without any
+//! I/O, which also builds for wasm32. The `tui` feature pulls in the prompts and progress bars, and
+//! the `cli` feature (on by default, with `tui`) is only needed for the `hnp` binary. Depend on hnp
+//! with `default-features = false` and `features = ["client"]` to leave clap, inquire, indicatif
+//! and the rest out.
+
+pub type Id = i64;
+
+pub mod models;
+
+#[cfg(feature = "core")]
+pub mod core;
+
+#[cfg(feature = "client")]
+pub mod client;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::{client, core, current_project_id, get_metadata, input, output, Id, MARKUP};
+
+lazy_static! {
+    /// `#`, `@` or `!` right before the cursor, with what's typed of the name so far
+    static ref TRIGGER_MATCHER: Regex = Regex::new(r#"(?:^|\W)(?<trigger>[#@!])(?<name>"?[^\s#@!]*)$"#)
+        .expect("Completion trigger Regex to compile");
+    static ref STAGE_TRIGGER_MATCHER: Regex =
+        Regex::new(r#"\bstage:(?<name>"?[^\s"]*)$"#).expect("Stage trigger Regex to compile");
+}
+
+// NOTE: Numbers the LSP specification gives to severities and completion kinds
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+const KIND_CATEGORY: u8 = 13;
+const KIND_TAG: u8 = 14;
+const KIND_USER: u8 = 18;
+const KIND_LEVEL: u8 = 20;
+
+/// Open documents by URI and what their tokens resolve against
+struct Server {
+    metadata: core::Metadata,
+    options: core::Options,
+    project_id: Id,
+    config: Config,
+    documents: HashMap<String, String>,
+}
+
+/// Serves the Language Server Protocol over stdin and stdout until the editor sends `exit`. The
+/// metadata is fetched once on start, so restart the server after changing the project.
+pub async fn run(config: Config) {
+    let metadata = get_metadata(&client()).await;
+
+    let mut server = Server {
+        metadata,
+        options: config.markup_options(false),
+        project_id: current_project_id(),
+        config,
+        documents: HashMap::new(),
+    };
+
+    let mut stdin = io::stdin().lock();
+    while let Some(message) = read_message(&mut stdin) {
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+
+        match message["method"].as_str().unwrap_or_default() {
+            "initialize" => respond(
+                id,
+                json!({
+                    "capabilities": {
+                        // NOTE: Documents are short enough to send them whole on every change
+                        "textDocumentSync": 1,
+                        "completionProvider": { "triggerCharacters": ["#", "@", "!", ":"] },
+                        "hoverProvider": true,
+                    },
+                    "serverInfo": { "name": "hnp", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            ),
+            "shutdown" => respond(id, Value::Null),
+            "exit" => return,
+            "textDocument/didOpen" => {
+                let uri = string(&params["textDocument"]["uri"]);
+                server
+                    .documents
+                    .insert(uri.clone(), string(&params["textDocument"]["text"]));
+                server.publish_diagnostics(&uri);
+            }
+            "textDocument/didChange" => {
+                let uri = string(&params["textDocument"]["uri"]);
+                if let Some(change) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                {
+                    server
+                        .documents
+                        .insert(uri.clone(), string(&change["text"]));
+                }
+                server.publish_diagnostics(&uri);
+            }
+            "textDocument/didClose" => {
+                let uri = string(&params["textDocument"]["uri"]);
+                server.documents.remove(&uri);
+                notify(
+                    "textDocument/publishDiagnostics",
+                    json!({ "uri": uri, "diagnostics": [] }),
+                );
+            }
+            "textDocument/completion" => {
+                let completions = server
+                    .line(params)
+                    .map(|(line, position)| server.completions(line, position))
+                    .unwrap_or_default();
+                respond(id, Value::Array(completions));
+            }
+            "textDocument/hover" => {
+                let hover = server
+                    .line(params)
+                    .and_then(|(line, position)| server.hover(line, position));
+                respond(id, hover.unwrap_or(Value::Null));
+            }
+            method => {
+                // NOTE: Notifications we don't handle are ignored, requests get an error
+                if id.is_some() {
+                    write_message(&json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32601, "message": format!("hnp doesn't handle {method}") },
+                    }));
+                }
+            }
+        }
+    }
+}
+
+impl Server {
+    fn publish_diagnostics(&self, uri: &str) {
+        let text = self.documents.get(uri).map_or("", String::as_str);
+
+        let diagnostics = match input::FrontMatter::parse(text) {
+            Ok((front_matter, contents)) => {
+                // NOTE: Lines are counted from the start of the file, front matter included
+                let offset = text[..text.len() - contents.len()].matches('\n').count();
+
+                core::lint(
+                    &MARKUP,
+                    &front_matter.apply(contents),
+                    &self.metadata,
+                    &self.options,
+                )
+                .into_iter()
+                .map(|diagnostic| {
+                    let severity = if diagnostic.is_error {
+                        SEVERITY_ERROR
+                    } else {
+                        SEVERITY_WARNING
+                    };
+                    self::diagnostic(offset + diagnostic.line, severity, &diagnostic.message)
+                })
+                .collect::<Vec<Value>>()
+            }
+            Err(error) => vec![diagnostic(0, SEVERITY_ERROR, &format!("Expected {error}"))],
+        };
+
+        notify(
+            "textDocument/publishDiagnostics",
+            json!({ "uri": uri, "diagnostics": diagnostics }),
+        );
+    }
+
+    /// The line of the document that the request's position is on, with the position
+    fn line<'a>(&'a self, params: &Value) -> Option<(&'a str, Position)> {
+        let text = self
+            .documents
+            .get(params["textDocument"]["uri"].as_str()?)?;
+        let position = Position {
+            line: params["position"]["line"].as_u64()? as usize,
+            character: params["position"]["character"].as_u64()? as usize,
+        };
+
+        Some((text.lines().nth(position.line).unwrap_or(""), position))
+    }
+
+    fn completions(&self, line: &str, position: Position) -> Vec<Value> {
+        let before = &line[..byte_index(line, position.character)];
+
+        // NOTE: Completions replace what's typed of the name, so quotes don't get doubled
+        let item = |label: &str, detail: String, kind: u8, new_text: String, start: usize| {
+            json!({
+                "label": label,
+                "detail": detail,
+                "kind": kind,
+                "filterText": new_text,
+                "textEdit": {
+                    "range": {
+                        "start": { "line": position.line, "character": utf16_length(&before[..start]) },
+                        "end": { "line": position.line, "character": position.character },
+                    },
+                    "newText": new_text,
+                },
+            })
+        };
+
+        if let Some(captures) = STAGE_TRIGGER_MATCHER.captures(before) {
+            let start = captures
+                .name("name")
+                .map_or(before.len(), |name| name.start());
+
+            return self
+                .metadata
+                .stages
+                .iter()
+                .map(|stage| {
+                    let name = quoted(&stage.name);
+                    item(&stage.name, "Stage".to_owned(), KIND_LEVEL, name, start)
+                })
+                .collect();
+        }
+
+        let Some(captures) = TRIGGER_MATCHER.captures(before) else {
+            return vec![];
+        };
+        let start = captures
+            .name("name")
+            .map_or(before.len(), |name| name.start());
+
+        match &captures["trigger"] {
+            "#" => self
+                .metadata
+                .categories
+                .iter()
+                .map(|(_, name)| (name, "Category", KIND_CATEGORY))
+                .chain(
+                    self.metadata
+                        .tags
+                        .iter()
+                        .map(|(_, name)| (name, "Tag", KIND_TAG)),
+                )
+                .map(|(name, detail, kind)| {
+                    item(name, detail.to_owned(), kind, quoted(name), start)
+                })
+                .collect(),
+            "@" => self
+                .metadata
+                .users
+                .iter()
+                .map(|(_, name, username)| {
+                    item(
+                        username,
+                        name.to_owned(),
+                        KIND_USER,
+                        username.to_lowercase(),
+                        start,
+                    )
+                })
+                .collect(),
+            _ => {
+                // NOTE: `!` only takes a single word, so levels with spaces go by their position
+                let levels = self.metadata.importance_levels.iter().enumerate().map(
+                    |(index, (_, name, _))| {
+                        let new_text = if name.contains(char::is_whitespace) {
+                            (index + 1).to_string()
+                        } else {
+                            name.to_lowercase()
+                        };
+                        item(
+                            name,
+                            "Importance level".to_owned(),
+                            KIND_LEVEL,
+                            new_text,
+                            start,
+                        )
+                    },
+                );
+                let aliases = self
+                    .options
+                    .importance_aliases
+                    .iter()
+                    .map(|(alias, level)| {
+                        item(
+                            alias,
+                            format!("Alias of {level}"),
+                            KIND_LEVEL,
+                            alias.to_owned(),
+                            start,
+                        )
+                    });
+
+                levels.chain(aliases).collect()
+            }
+        }
+    }
+
+    fn hover(&self, line: &str, position: Position) -> Option<Value> {
+        let index = byte_index(line, position.character);
+        // NOTE: A line on its own reads as a title, so every kind of token is found
+        let token = MARKUP
+            .tokens(line, true)
+            .into_iter()
+            .find(|token| token.start <= index && index <= token.end)?;
+        let written = &line[token.start..token.end];
+
+        let contents = match token.kind {
+            core::TokenKind::Dependency | core::TokenKind::Reference => {
+                let id = written.rsplit('#').next()?;
+                let url = self
+                    .config
+                    .references
+                    .url(self.project_id, id.parse().ok()?);
+                format!("Work item [#{id}]({url})")
+            }
+            kind => match core::resolve(&MARKUP, kind, written, &self.metadata, &self.options) {
+                Ok(resolved) => resolved,
+                Err(expectation) => core::expected(&expectation),
+            },
+        };
+
+        Some(json!({
+            "contents": { "kind": "markdown", "value": contents },
+            "range": {
+                "start": { "line": position.line, "character": utf16_length(&line[..token.start]) },
+                "end": { "line": position.line, "character": utf16_length(&line[..token.end]) },
+            },
+        }))
+    }
+}
+
+/// Position in a document as LSP counts it, `character` is in UTF-16 code units
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    line: usize,
+    character: usize,
+}
+
+/// Index in `line` of the UTF-16 based `character`
+fn byte_index(line: &str, character: usize) -> usize {
+    let mut units = 0;
+    for (index, character_in_line) in line.char_indices() {
+        if units >= character {
+            return index;
+        }
+        units += character_in_line.len_utf16();
+    }
+
+    line.len()
+}
+
+fn utf16_length(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// `name` as it's written after `#` or `stage:`, quoted when it contains spaces
+fn quoted(name: &str) -> String {
+    input::hash_tag(name).trim_start_matches('#').to_owned()
+}
+
+fn string(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_owned()
+}
+
+fn diagnostic(line: usize, severity: u8, message: &str) -> Value {
+    json!({
+        "range": {
+            "start": { "line": line, "character": 0 },
+            "end": { "line": line + 1, "character": 0 },
+        },
+        "severity": severity,
+        "source": "hnp",
+        "message": message,
+    })
+}
+
+/// Next message from the editor, `None` once stdin is closed
+fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+    loop {
+        let mut length = None;
+
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).ok()? == 0 {
+                return None;
+            }
+
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("Content-Length") {
+                    length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+
+        let Some(length) = length else {
+            output::warn("Skipping a message without Content-Length from the editor");
+            continue;
+        };
+
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).ok()?;
+
+        match serde_json::from_slice(&body) {
+            Ok(message) => return Some(message),
+            Err(error) => output::warn(format!("Skipping a message from the editor: {error}")),
+        }
+    }
+}
+
+fn write_message(message: &Value) {
+    let body = message.to_string();
+    let mut stdout = io::stdout().lock();
+
+    write!(stdout, "Content-Length: {}\r\n\r\n{body}", body.len())
+        .and_then(|_| stdout.flush())
+        .expect("To write to the editor");
+}
+
+fn respond(id: Option<Value>, result: Value) {
+    write_message(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+}
+
+fn notify(method: &str, params: Value) {
+    write_message(&json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+}
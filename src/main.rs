@@ -1,14 +1,84 @@
+use std::collections::HashMap;
 use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
 
+use futures_util::{stream, StreamExt};
+use hnp::client::{http_client_builder, API_VERSION, DEFAULT_API_ENDPOINT};
+use hnp::core::{self, match_tag_or_category, normalize_name, SubTask, Tag};
+use hnp::{models, Id};
 use lazy_static::lazy_static;
-use regex::{Regex, RegexBuilder};
-use serde::Serialize;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-type Id = i64;
+mod accuracy;
+mod annotate;
+mod apply;
+mod assign;
+mod backup;
+mod board;
+mod bulk;
+mod cache;
+mod capacity;
+mod changelog;
+mod clone;
+mod config;
+mod critical_path;
+mod custom_fields;
+mod demo;
+mod doctor;
+mod explain;
+mod export;
+mod filter;
+mod github;
+mod gitlab;
+mod graph;
+mod history;
+mod hooks;
+mod i18n;
+mod ical;
+mod import;
+mod input;
+mod lsp;
+mod markdown;
+mod metadata;
+mod notion;
+mod org;
+mod output;
+mod paths;
+mod plan;
+mod qa;
+mod recording;
+mod recurring;
+mod report;
+mod roadmap;
+mod rollover;
+mod schedule;
+mod schema;
+#[cfg(feature = "scripting")]
+mod script;
+mod search;
+#[cfg(feature = "self-update")]
+mod self_update;
+mod stale;
+mod standup;
+mod stats;
+mod suggest;
+mod sync;
+mod text;
+mod todotxt;
+mod tokens;
 
-#[derive(Serialize, Debug, Default)]
-#[serde(rename_all = "camelCase")]
+use config::{Config, EstimateMode, TokenField};
+use hooks::Hook;
+use i18n::{Action, Message};
+use recording::SendRecorded;
+use tokens::{match_custom_tokens, strip_custom_tokens, CustomTokenMatch};
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
 struct Ticket {
     title: String,
     description: String,
@@ -22,405 +92,2279 @@ struct Ticket {
     due_date: String,   // TODO: Convert to chrono / iso8601
     assigned_user_ids: Vec<Id>,
     tag_ids: Vec<Id>,
-    sub_tasks: Vec<String>,
+    #[serde(serialize_with = "serialize_sub_task_titles", skip_deserializing)]
+    sub_tasks: Vec<SubTask>,
     dependency_ids: Vec<Id>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    design_element_id: Option<Id>,
+    /// Stage to move the work item to after creating it, new work items start in the first stage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stage_id: Option<Id>,
+    /// Stage for completed sub tasks that become work items of their own
+    #[serde(skip)]
+    completed_stage_id: Option<Id>,
+    /// Fields from a ```json block in the ticket, replacing the ones above in the payload
+    #[serde(skip)]
+    overrides: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug)]
-enum Tag {
-    Category(Id, String),
-    Tag(Id, String),
-    UnaddedTag(String),
+/// Sub tasks are created by title, completed ones get marked as such after creation
+fn serialize_sub_task_titles<S: serde::Serializer>(
+    sub_tasks: &[SubTask],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        sub_tasks
+            .iter()
+            .filter(|sub_task| !sub_task.is_work_item())
+            .map(|sub_task| &sub_task.title),
+    )
 }
 
-const API_ENDPOINT: &str = "https://api.hacknplan.com/v0";
+impl Ticket {
+    /// What gets sent to Hack'n'Plan, with the overrides applied
+    fn payload(&self) -> serde_json::Value {
+        let mut payload = serde_json::to_value(self).expect("To serialize ticket");
+        if let Some(fields) = payload.as_object_mut() {
+            fields.extend(self.overrides.clone());
+        }
+
+        payload
+    }
+
+    /// What gets sent to Hack'n'Plan after the `pre_upload` hook
+    fn outgoing_payload(&self) -> serde_json::Value {
+        let payload = self.payload();
+        hooks::run(Hook::PreUpload, &payload).unwrap_or(payload)
+    }
+
+    /// Takes over the fields of `payload`, which replaces the payload as a whole. Sub tasks keep
+    /// their completion and assignees, only their titles can change.
+    fn apply(&mut self, payload: serde_json::Value) {
+        let mut ticket = serde_json::from_value::<Ticket>(payload.clone())
+            .unwrap_or_else(|error| panic!("To read back ticket \"{}\": {error}", self.title));
+        ticket.sub_tasks = std::mem::take(&mut self.sub_tasks);
+        ticket.completed_stage_id = self.completed_stage_id;
+        ticket.overrides = payload.as_object().cloned().unwrap_or_default();
+
+        *self = ticket;
+    }
+
+    /// Work items for the sub tasks that have their own assignee or estimate, parented to
+    /// `parent_id`. Completed sub tasks go to the completed stage.
+    fn child_work_items(&self, parent_id: Id) -> Vec<Ticket> {
+        self.sub_tasks
+            .iter()
+            .filter(|sub_task| sub_task.is_work_item())
+            .map(|sub_task| Ticket {
+                title: sub_task.title.to_owned(),
+                parent_id,
+                category_id: self.category_id,
+                importance_level_id: self.importance_level_id,
+                board_id: self.board_id,
+                estimated_cost: sub_task.estimated_cost,
+                assigned_user_ids: sub_task.assigned_user_ids.clone(),
+                stage_id: self.completed_stage_id.filter(|_| sub_task.is_completed),
+                ..Default::default()
+            })
+            .collect::<Vec<Ticket>>()
+    }
+}
 
 lazy_static! {
-    static ref API_KEY: String = std::env::var("HACKNPLAN_API_KEY")
-        .expect("you to have set HACKNPLAN_API_KEY to a valid value");
-    static ref PROJECT_ID: Id = std::env::var("HACKNPLAN_PROJECT_ID")
-        .expect("you to set HACKNPLAN_PROJECT_ID to a valid value")
-        .parse::<Id>()
-        .expect("you to set HACKNPLAN_PROJECT_ID to a valid value");
-    static ref HASH_TAG_MATCHER: Regex = Regex::new(r"#\w+").expect("Hash tag Regex to compile");
-    static ref MENTION_MATCHER: Regex = Regex::new(r"@\w+").expect("Mention Regex to compile");
-    static ref SUBTASK_MATCHER: Regex = RegexBuilder::new(r"^\[\].*$")
-        .multi_line(true)
-        .build()
-        .expect("Subtask Regex to compile");
-    static ref ESTIMATE_MATCHER: Regex =
-        Regex::new(r"~((?<days>\d+)d)?((?<hours>\d+)h)?((?<minutes>\d+)m)?((?<seconds>\d+)s)?")
-            .expect("Estimate Regex to compile");
-    static ref URGENCY_MATCHER: Regex = Regex::new(r"!\w+").expect("Urgency Regex to compile");
+    static ref API_KEY: String = match std::env::var("HACKNPLAN_API_KEY") {
+        Ok(api_key) => api_key,
+        // NOTE: Replays never send anything, so they don't need a key
+        Err(_) if recording::is_replaying() => String::new(),
+        Err(_) => panic!("you to have set HACKNPLAN_API_KEY to a valid value"),
+    };
+    /// Project from HACKNPLAN_PROJECT_ID, used until another one is picked
+    static ref DEFAULT_PROJECT_ID: Id = match std::env::var("HACKNPLAN_PROJECT_ID") {
+        Ok(id) => id
+            .parse::<Id>()
+            .expect("you to set HACKNPLAN_PROJECT_ID to a valid value"),
+        // NOTE: Runs using a metadata snapshot can take the project from there
+        Err(_) => metadata::get()
+            .and_then(metadata::Metadata::project_id)
+            .or_else(recording::project_id)
+            .expect("you to set HACKNPLAN_PROJECT_ID to a valid value"),
+    };
+    static ref MARKUP: core::Markup = core::Markup::new(
+        WORD_CHARACTERS
+            .get()
+            .map(String::as_str)
+            .unwrap_or(core::DEFAULT_WORD_CHARACTERS)
+    )
+    .expect("Markup Regexes to compile, check markup.word_characters in your config");
+    static ref TICKET_SEPARATOR: Regex = MARKUP.ticket_separator.clone();
+    static ref TITLE_SEPARATOR: Regex = MARKUP.title_separator.clone();
+    static ref MENTION_MATCHER: Regex = MARKUP.mention.clone();
+    static ref SUBTASK_MATCHER: Regex = MARKUP.sub_task.clone();
+    static ref REFERENCE_MATCHER: Regex = MARKUP.reference.clone();
+    static ref DEPENDENCY_MATCHER: Regex = MARKUP.dependency.clone();
+    /// Fenced ```json block in a ticket with fields to override in the payload
+    static ref OVERRIDES_MATCHER: Regex = Regex::new(r"(?ms)^```json[ \t]*\n(?<json>.*?)^```[ \t]*$\n?")
+        .expect("Overrides Regex to compile");
+    /// `%%project:<profile>` in a ticket, sending it to the project of that profile
+    static ref PROJECT_MATCHER: Regex = Regex::new(r"%%project:(?<profile>[^\s%]+)(%%)?")
+        .expect("Project Regex to compile");
+    /// Version the base URL of the API ends in, like `/v0`
+    static ref API_VERSION_MATCHER: Regex = Regex::new(r"/(?<version>v\d+)$")
+        .expect("API version Regex to compile");
+}
+
+/// Characters allowed in tags, categories and mentions, set from the config before any matching
+static WORD_CHARACTERS: OnceLock<String> = OnceLock::new();
+
+/// Project picked with `--project-id` or `--profile`, set once before any request
+static RUN_PROJECT_ID: OnceLock<Id> = OnceLock::new();
+
+tokio::task_local! {
+    /// Project of the tickets being uploaded, when a file or ticket names its own
+    static SCOPED_PROJECT_ID: Id;
+}
+
+/// The project requests go to
+fn current_project_id() -> Id {
+    SCOPED_PROJECT_ID
+        .try_with(|id| *id)
+        .unwrap_or_else(|_| *RUN_PROJECT_ID.get().unwrap_or(&DEFAULT_PROJECT_ID))
+}
+
+/// Runs `future` with its requests going to project `id`
+async fn in_project<F: std::future::Future>(id: Id, future: F) -> F::Output {
+    SCOPED_PROJECT_ID.scope(id, future).await
+}
+
+/// Base URL of the API and the client requests go through, set from the arguments and config
+/// before any request
+static API_ENDPOINT: OnceLock<String> = OnceLock::new();
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+fn api_endpoint() -> &'static str {
+    API_ENDPOINT
+        .get()
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_API_ENDPOINT)
+}
+
+/// Version of the API requests go to, when the base URL ends in one
+fn api_version() -> Option<&'static str> {
+    API_VERSION_MATCHER
+        .captures(api_endpoint())
+        .and_then(|captures| captures.name("version"))
+        .map(|version| version.as_str())
+}
+
+/// The one HTTP client all requests go through, so they share a pool of connections
+fn client() -> reqwest::Client {
+    CLIENT
+        .get_or_init(|| {
+            http_client_builder(None)
+                .build()
+                .expect("To build the HTTP client")
+        })
+        .clone()
+}
+
+/// Points requests at `--api-url` or the configured endpoint and applies the timeout, proxy and
+/// user agent suffix.
+/// Without a configured proxy, HTTPS_PROXY, HTTP_PROXY and NO_PROXY from the environment are used.
+fn configure_api(arguments: &Arguments, config: &Config) {
+    let endpoint = arguments
+        .api_url
+        .as_deref()
+        .or(config.api.url.as_deref())
+        .unwrap_or(DEFAULT_API_ENDPOINT)
+        .trim_end_matches('/');
+    // NOTE: `api.version` replaces the version the URL ends in, so it also applies to `api.url`
+    let endpoint = match &config.api.version {
+        Some(version) => format!(
+            "{}/{}",
+            API_VERSION_MATCHER.replace(endpoint, ""),
+            version.trim_matches('/')
+        ),
+        None => endpoint.to_owned(),
+    };
+    API_ENDPOINT
+        .set(endpoint)
+        .expect("API endpoint to only be set once");
+
+    let user_agent_suffix = arguments
+        .user_agent_suffix
+        .as_deref()
+        .or(config.api.user_agent_suffix.as_deref());
+    let mut builder = http_client_builder(user_agent_suffix);
+
+    if let Some(timeout) = arguments.timeout.or(config.api.timeout) {
+        builder = builder.timeout(std::time::Duration::from_secs(timeout));
+    }
+
+    if let Some(proxy) = &config.api.proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .unwrap_or_else(|error| panic!("To use proxy \"{proxy}\" from the config: {error}"))
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    CLIENT
+        .set(builder.build().expect("To build the HTTP client"))
+        .expect("HTTP client to only be set once");
 }
 
 async fn get_available_categories(client: &reqwest::Client) -> Vec<(Id, String)> {
-    client
+    get_models::<models::Category>(client, "categories")
+        .await
+        .into_iter()
+        .map(|category| (category.category_id, category.name))
+        .collect::<Vec<(Id, String)>>()
+}
+
+async fn get_available_users(client: &reqwest::Client) -> Vec<(Id, String, String)> {
+    get_models::<models::ProjectUser>(client, "users")
+        .await
+        .into_iter()
+        .map(|models::ProjectUser { user }| (user.id, user.name, user.username))
+        .collect::<Vec<(Id, String, String)>>()
+}
+
+/// Makes sure the API key works and has access to the project before doing any real work,
+/// exiting with an actionable error when it doesn't
+async fn check_access(client: &reqwest::Client) {
+    if let Some(version) = api_version().filter(|version| *version != API_VERSION) {
+        output::warn(format!(
+            "hnp is made for {API_VERSION} of the Hack'n'Plan API and may not work with {version}, \
+             check `api.version` of the config"
+        ));
+    }
+
+    if let Some(problem) = access_problem(client).await {
+        output::error(problem);
+        output::exit_with_error();
+    }
+}
+
+/// What keeps hnp from reaching the project, as an error telling how to fix it
+async fn access_problem(client: &reqwest::Client) -> Option<String> {
+    let response = client
         .get(format!(
-            "{API_ENDPOINT}/projects/{PROJECT_ID}/categories",
-            PROJECT_ID = *PROJECT_ID
+            "{API_ENDPOINT}/projects/{PROJECT_ID}",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
         ))
         .header(
             "Authorization",
             format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
         )
-        .send()
-        .await
-        .expect("To get categories from Hack'n'Plan")
-        .json::<serde_json::Value>()
-        .await
-        .expect("To deserialize categories into JSON")
-        .as_array()
-        .expect("Categories results to be an array")
-        .iter()
-        .map(|value| {
-            (
-                value
-                    .get("categoryId")
-                    .expect("categoryId to be available")
-                    .as_i64()
-                    .expect("categoryId to be i64"),
-                value
-                    .get("name")
-                    .expect("name to be available")
-                    .as_str()
-                    .expect("name to be String")
-                    .to_owned(),
-            )
-        })
-        .collect::<Vec<(Id, String)>>()
+        .send_recorded()
+        .await;
+
+    let response = match response {
+        Ok(response) => response,
+        Err(error) if error.is_timeout() => {
+            return Some(Message::TimedOut.to_string());
+        }
+        Err(error) => return Some(Message::Unreachable(&error).to_string()),
+    };
+
+    let message = match response.status() {
+        reqwest::StatusCode::UNAUTHORIZED => Message::InvalidApiKey.to_string(),
+        reqwest::StatusCode::FORBIDDEN => {
+            Message::NoAccessToProject(current_project_id()).to_string()
+        }
+        // NOTE: When the current user can't be found either, it's the API that's missing rather
+        // than the project
+        reqwest::StatusCode::NOT_FOUND if !api_exists(client).await => {
+            Message::NoApiAt(api_endpoint()).to_string()
+        }
+        reqwest::StatusCode::NOT_FOUND => {
+            Message::ProjectDoesntExist(current_project_id()).to_string()
+        }
+        status if !status.is_success() => Message::UnexpectedStatus(&status).to_string(),
+        _ => return None,
+    };
+
+    Some(message)
 }
 
-async fn get_available_users(client: &reqwest::Client) -> Vec<(Id, String, String)> {
+/// Whether the API answers at its base URL, by looking up the user of the API key
+async fn api_exists(client: &reqwest::Client) -> bool {
     client
         .get(format!(
-            "{API_ENDPOINT}/projects/{PROJECT_ID}/users",
-            PROJECT_ID = *PROJECT_ID
+            "{API_ENDPOINT}/users/me",
+            API_ENDPOINT = api_endpoint()
         ))
         .header(
             "Authorization",
             format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
         )
-        .send()
+        .send_recorded()
         .await
-        .expect("To get tags from Hack'n'Plan")
-        .json::<serde_json::Value>()
+        .is_ok_and(|response| response.status() != reqwest::StatusCode::NOT_FOUND)
+}
+
+/// Whether a destructive command may go ahead, which on a project of a protected profile takes
+/// that `--profile` and the name of the project typed out
+async fn confirm_protected(arguments: &Arguments, config: &Config) -> bool {
+    let Some((name, _)) = config
+        .profiles
+        .iter()
+        .find(|(_, profile)| profile.protected && profile.project_id == current_project_id())
+    else {
+        return true;
+    };
+
+    if arguments.dry_run {
+        return true;
+    }
+
+    if arguments.profile.as_deref() != Some(name) {
+        output::error(format!(
+            "Project {} is protected, run with --profile {name} to change it",
+            current_project_id()
+        ));
+        output::exit_with_error();
+    }
+
+    let project_name = project_name(&client()).await;
+    if arguments.non_interactive {
+        output::error(format!(
+            "Project \"{project_name}\" is protected and can't be changed with \
+             --non-interactive, nothing was changed"
+        ));
+        output::exit_with_error();
+    }
+
+    output::ask(Message::TypeProjectName(&project_name))
+        .is_some_and(|typed| typed.trim() == project_name)
+}
+
+/// Project of the profile called `name` in the config, exiting when there's none
+fn find_profile(config: &Config, name: &str) -> Id {
+    config
+        .profiles
+        .iter()
+        .find(|(profile, _)| profile.eq_ignore_ascii_case(name))
+        .map(|(_, profile)| profile.project_id)
+        .unwrap_or_else(|| {
+            output::error(format!(
+                "There's no profile \"{name}\" in `profiles` of the config"
+            ));
+            output::exit_with_error();
+        })
+}
+
+/// Name of the project as it shows in Hack'n'Plan
+async fn project_name(client: &reqwest::Client) -> String {
+    get_json(client, "")
+        .await
+        .get("name")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// `collection` of the project, taken from the metadata snapshot when one was given
+async fn get_collection(client: &reqwest::Client, collection: &str) -> serde_json::Value {
+    if let Some(metadata) = metadata::get() {
+        return metadata.collection(collection);
+    }
+
+    cache::get_collection(client, collection).await
+}
+
+/// `collection` of the project deserialized into its model, naming the field that didn't match
+async fn get_models<T: DeserializeOwned>(client: &reqwest::Client, collection: &str) -> Vec<T> {
+    serde_json::from_value(get_collection(client, collection).await)
+        .unwrap_or_else(|error| panic!("To deserialize {collection}: {error}"))
+}
+
+/// Boards with the id of the milestone they belong to, if any
+async fn get_available_boards(client: &reqwest::Client) -> Vec<(Id, String, Option<Id>)> {
+    get_models::<models::Board>(client, "boards")
+        .await
+        .into_iter()
+        .map(|board| (board.board_id, board.name, board.milestone_id))
+        .collect::<Vec<(Id, String, Option<Id>)>>()
+}
+
+async fn get_available_milestones(client: &reqwest::Client) -> Vec<(Id, String)> {
+    get_models::<models::Milestone>(client, "milestones")
         .await
-        .expect("To deserialize tags into JSON")
-        .as_array()
-        .expect("Tags results to be an array")
+        .into_iter()
+        .map(|milestone| (milestone.milestone_id, milestone.name))
+        .collect::<Vec<(Id, String)>>()
+}
+
+/// Start of the milestone of board `board_id`, which relative dates of tickets on it count from
+fn milestone_start(
+    board_id: Id,
+    available_boards: &[(Id, String, Option<Id>)],
+    milestones: &[models::Milestone],
+) -> Option<chrono::NaiveDate> {
+    let (_, _, milestone_id) = available_boards.iter().find(|(id, _, _)| *id == board_id)?;
+    let milestone = milestones
         .iter()
-        .map(|value| {
-            let user = value
-                .get("user")
-                .expect("user to be available")
-                .as_object()
-                .expect("user to be an Object");
+        .find(|milestone| Some(milestone.milestone_id) == *milestone_id)?;
 
+    chrono::NaiveDate::parse_from_str(milestone.start_date.as_deref()?.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// The milestone that today falls within, if any
+async fn get_current_milestone(client: &reqwest::Client, config: &Config) -> Option<(Id, String)> {
+    let today = config.calendar.today();
+    let date = |date: &Option<String>| {
+        date.as_deref()
+            .and_then(|date| date.get(..10))
+            .and_then(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    };
+
+    get_models::<models::Milestone>(client, "milestones")
+        .await
+        .into_iter()
+        .find(|milestone| {
+            date(&milestone.start_date).is_some_and(|start| start <= today)
+                && date(&milestone.due_date).is_some_and(|due| today <= due)
+        })
+        .map(|milestone| (milestone.milestone_id, milestone.name))
+}
+
+async fn get_available_importance_levels(client: &reqwest::Client) -> Vec<(Id, String, bool)> {
+    get_models::<models::ImportanceLevel>(client, "importancelevels")
+        .await
+        .into_iter()
+        .map(|importance_level| {
             (
-                user.get("id")
-                    .expect("user id to be available")
-                    .as_i64()
-                    .expect("tagId to be i64"),
-                user.get("name")
-                    .expect("name to be available")
-                    .as_str()
-                    .expect("name to be String")
-                    .to_owned(),
-                user.get("username")
-                    .expect("username to be available")
-                    .as_str()
-                    .expect("username to be String")
-                    .to_owned(),
+                importance_level.importance_level_id,
+                importance_level.name,
+                importance_level.is_default,
             )
         })
-        .collect::<Vec<(Id, String, String)>>()
+        .collect::<Vec<(Id, String, bool)>>()
+}
+
+async fn get_available_tags(client: &reqwest::Client) -> Vec<(Id, String)> {
+    get_models::<models::TagDef>(client, "tags")
+        .await
+        .into_iter()
+        .map(|tag| (tag.tag_id, tag.name))
+        .collect::<Vec<(Id, String)>>()
+}
+
+/// All work items in the project, fetched page by page. Backups ask for `serde_json::Value`s to
+/// keep every field the API returns.
+async fn get_work_items<T: DeserializeOwned>(client: &reqwest::Client) -> Vec<T> {
+    const PAGE_SIZE: usize = 100;
+
+    let mut work_items = vec![];
+    loop {
+        let page = client
+            .get(format!(
+                "{API_ENDPOINT}/projects/{PROJECT_ID}/workitems",
+                API_ENDPOINT = api_endpoint(),
+                PROJECT_ID = current_project_id()
+            ))
+            .query(&[("offset", work_items.len()), ("limit", PAGE_SIZE)])
+            .header(
+                "Authorization",
+                format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+            )
+            .send_recorded()
+            .await
+            .expect("To get work items from Hack'n'Plan")
+            .json::<models::Page<T>>()
+            .await
+            .unwrap_or_else(|error| panic!("To deserialize work items: {error}"));
+
+        let count = page.items.len();
+        work_items.extend(page.items);
+
+        if count < PAGE_SIZE {
+            return work_items;
+        }
+    }
 }
 
-// TODO: Perhaps implement board assignment?
-#[allow(unused)]
-async fn get_available_boards(client: &reqwest::Client) -> Vec<(Id, String)> {
+/// Response of a GET to `path` within the project
+async fn get_json(client: &reqwest::Client, path: &str) -> serde_json::Value {
     client
         .get(format!(
-            "{API_ENDPOINT}/projects/{PROJECT_ID}/boards",
-            PROJECT_ID = *PROJECT_ID
+            "{API_ENDPOINT}/projects/{PROJECT_ID}{path}",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
         ))
         .header(
             "Authorization",
             format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
         )
-        .send()
+        .send_recorded()
         .await
-        .expect("To get boards from Hack'n'Plan")
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|error| panic!("To get {path} from Hack'n'Plan: {error}"))
         .json::<serde_json::Value>()
         .await
-        .expect("To deserialize boards into JSON")
-        .as_array()
-        .expect("boards results to be an array")
-        .iter()
-        .map(|value| {
-            (
-                value
-                    .get("boardId")
-                    .expect("boardId to be available")
-                    .as_i64()
-                    .expect("boardId to be i64"),
-                value
-                    .get("name")
-                    .expect("name to be available")
-                    .as_str()
-                    .expect("name to be String")
-                    .to_owned(),
-            )
-        })
-        .collect::<Vec<(Id, String)>>()
+        .unwrap_or_else(|_| panic!("To deserialize {path} into JSON"))
 }
 
-async fn get_available_importance_levels(client: &reqwest::Client) -> Vec<(Id, String, bool)> {
+/// Creates `body` at `path`, returning the id in `id_field` of the response
+async fn post_json(
+    client: &reqwest::Client,
+    path: &str,
+    body: &serde_json::Value,
+    id_field: &str,
+) -> Id {
     client
-        .get(format!(
-            "{API_ENDPOINT}/projects/{PROJECT_ID}/importancelevels",
-            PROJECT_ID = *PROJECT_ID
+        .post(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}{path}",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
         ))
         .header(
             "Authorization",
             format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
         )
-        .send()
+        .json(body)
+        .send_recorded()
         .await
-        .expect("To get importanceLevels from Hack'n'Plan")
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|error| panic!("To create {path} on Hack'n'Plan: {error}"))
         .json::<serde_json::Value>()
         .await
-        .expect("To deserialize importanceLevels into JSON")
-        .as_array()
-        .expect("importanceLevels results to be an array")
-        .iter()
-        .map(|value| {
-            (
-                value
-                    .get("importanceLevelId")
-                    .expect("importanceLevelId to be available")
-                    .as_i64()
-                    .expect("importanceLevelId to be i64"),
-                value
-                    .get("name")
-                    .expect("name to be available")
-                    .as_str()
-                    .expect("name to be String")
-                    .to_owned(),
-                value
-                    .get("isDefault")
-                    .expect("isDefault to be available")
-                    .as_bool()
-                    .expect("isDefault to be a bool"),
-            )
-        })
-        .collect::<Vec<(Id, String, bool)>>()
+        .unwrap_or_else(|_| panic!("To deserialize created {path} into JSON"))
+        .get(id_field)
+        .and_then(|id| id.as_i64())
+        .unwrap_or_default()
 }
 
-async fn get_available_tags(client: &reqwest::Client) -> Vec<(Id, String)> {
-    client
-        .get(format!(
-            "{API_ENDPOINT}/projects/{PROJECT_ID}/tags",
-            PROJECT_ID = *PROJECT_ID
+/// Deletes whatever lives at `path`, which is fine when it's already gone
+async fn delete_json(client: &reqwest::Client, path: &str) {
+    let response = client
+        .delete(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}{path}",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
         ))
         .header(
             "Authorization",
             format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
         )
-        .send()
+        .send_recorded()
         .await
-        .expect("To get tags from Hack'n'Plan")
-        .json::<serde_json::Value>()
+        .unwrap_or_else(|error| panic!("To delete {path} on Hack'n'Plan: {error}"));
+
+    if response.status() != reqwest::StatusCode::NOT_FOUND {
+        response
+            .error_for_status()
+            .unwrap_or_else(|error| panic!("To delete {path} on Hack'n'Plan: {error}"));
+    }
+}
+
+/// Changes the fields in `body` of whatever lives at `path`
+async fn patch_json(client: &reqwest::Client, path: &str, body: &serde_json::Value) {
+    client
+        .patch(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}{path}",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
+        ))
+        .header(
+            "Authorization",
+            format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+        )
+        .json(body)
+        .send_recorded()
         .await
-        .expect("To deserialize tags into JSON")
-        .as_array()
-        .expect("Tags results to be an array")
-        .iter()
-        .map(|value| {
-            (
-                value
-                    .get("tagId")
-                    .expect("tagId to be available")
-                    .as_i64()
-                    .expect("tagId to be i64"),
-                value
-                    .get("name")
-                    .expect("name to be available")
-                    .as_str()
-                    .expect("name to be String")
-                    .to_owned(),
-            )
-        })
-        .collect::<Vec<(Id, String)>>()
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|error| panic!("To update {path} on Hack'n'Plan: {error}"));
 }
 
 async fn add_unmatched_tags(
     client: &reqwest::Client,
     unmatched_tags: Vec<String>,
+    available_categories: &[(Id, String)],
     arguments: &Arguments,
-) {
-    for tag in unmatched_tags {
-        let datum = json!({ "name": tag });
+    config: &Config,
+) -> Vec<(Id, String)> {
+    let colliding_tags = unmatched_tags
+        .iter()
+        .filter(|tag| {
+            available_categories
+                .iter()
+                .any(|(_, category)| normalize_name(category) == normalize_name(tag))
+        })
+        .cloned()
+        .collect::<Vec<String>>();
+
+    if !colliding_tags.is_empty() {
+        output::error(format!(
+            "Refusing to create tags with the same name as a category: {}",
+            colliding_tags.join(", ")
+        ));
+        output::exit_with_error();
+    }
+
+    let mut created_tags = vec![];
+    for (index, tag) in unmatched_tags.into_iter().enumerate() {
+        let datum = match config.tags.color(&tag, index) {
+            Some(color) => json!({ "name": tag, "color": color }),
+            None => json!({ "name": tag }),
+        };
+
         if arguments.dry_run {
-            println!("datum: {datum:#?}");
+            output::info(format!("Would create tag: {datum}"));
         } else {
-            client
-                .get(format!(
-                    "{API_ENDPOINT}/projects/{PROJECT_ID}/categories",
-                    PROJECT_ID = *PROJECT_ID
+            let created_tag = client
+                .post(format!(
+                    "{API_ENDPOINT}/projects/{PROJECT_ID}/tags",
+                    API_ENDPOINT = api_endpoint(),
+                    PROJECT_ID = current_project_id()
                 ))
                 .header(
                     "Authorization",
                     format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
                 )
                 .json(&datum)
-                .send()
+                .send_recorded()
+                .await
+                .expect("Creation of tag would go successfully")
+                .error_for_status()
+                .unwrap_or_else(|error| panic!("Creation of tag \"{tag}\" to succeed: {error}"))
+                .json::<models::TagDef>()
                 .await
-                .expect("Creation of tag would go successfully");
+                .unwrap_or_else(|error| panic!("To deserialize created tag \"{tag}\": {error}"));
+
+            created_tags.push((created_tag.tag_id, tag));
         }
     }
+
+    created_tags
+}
+
+fn find_user<'a>(
+    user_name: &str,
+    available_users: &'a [(Id, String, String)],
+) -> &'a (Id, String, String) {
+    core::find_user(user_name, available_users)
+        .unwrap_or_else(|| panic!("{}", core::user_not_found(user_name, available_users)))
 }
 
-fn match_tags_and_categories(
+/// Registers `depends on #1234` as dependencies and, when configured, validates references to
+/// existing work items and turns them into links. Stops when a dependency leads into a cycle in
+/// `dependency_graph`.
+async fn resolve_references(
+    client: &reqwest::Client,
+    description: &str,
     title: &str,
-    available_categories: &Vec<(Id, String)>,
-    available_tags: &Vec<(Id, String)>,
-) -> Vec<Tag> {
-    HASH_TAG_MATCHER
-        .find_iter(&title)
-        .map(|hash_tag| {
-            let hash_tag = hash_tag.as_str().replace("#", "").trim().to_owned();
-
-            match available_categories
-                .iter()
-                .find(|(_, category)| category.to_lowercase() == hash_tag)
-            {
-                Some((id, category)) => Tag::Category(*id, category.to_owned()),
-                None => match available_tags
-                    .iter()
-                    .find(|(_, tag)| tag.to_lowercase() == hash_tag)
-                {
-                    Some((id, tag)) => Tag::Tag(*id, tag.to_owned()),
-                    None => Tag::UnaddedTag(hash_tag),
-                },
+    config: &Config,
+    dependency_graph: &HashMap<Id, Vec<Id>>,
+) -> (String, Vec<Id>) {
+    let mut references = REFERENCE_MATCHER
+        .captures_iter(description)
+        .map(|captures| captures["id"].parse::<Id>().expect("reference to be an id"))
+        .collect::<Vec<Id>>();
+    references.sort();
+    references.dedup();
+
+    let mut missing_references = vec![];
+    if config.references.validate {
+        for id in &references {
+            if !work_item_exists(client, *id).await {
+                output::warn(Message::MissingReference { title, id: *id });
+                missing_references.push(*id);
             }
+        }
+    }
+
+    let dependencies = DEPENDENCY_MATCHER
+        .captures_iter(description)
+        .map(|captures| {
+            captures["id"]
+                .parse::<Id>()
+                .expect("dependency to be an id")
         })
-        .collect::<Vec<Tag>>()
-}
+        .filter(|id| !missing_references.contains(id))
+        .collect::<Vec<Id>>();
 
-fn match_mentions<'a>(
-    string: &'a str,
-    available_users: &'a Vec<(Id, String, String)>,
-) -> Vec<&'a (Id, String, String)> {
-    MENTION_MATCHER
-        .find_iter(&string)
-        .map(|mention| {
-            let user_name = mention.as_str().replace("@", "").trim().to_owned();
+    // NOTE: Nothing depends on a ticket before it's created, but the work items it depends on
+    // can already be stuck in a cycle, which would block the ticket forever
+    if let Some(path) = graph::path_into_cycle(&dependencies, dependency_graph) {
+        panic!(
+            "To not make \"{title}\" depend on a cycle: it depends on {}",
+            path.iter()
+                .map(|id| format!("#{id}"))
+                .collect::<Vec<String>>()
+                .join(", which depends on ")
+        );
+    }
 
-            available_users
-                .iter()
-                .find(|(_, name, _)| name.to_lowercase().matches(&user_name).count() != 0)
-                .expect(&format!("To find a user for user_name: {user_name}"))
-        })
-        .collect::<Vec<&(Id, String, String)>>()
-}
+    if !config.references.link {
+        return (description.to_owned(), dependencies);
+    }
 
-fn get_estimate(title: &str) -> f32 {
-    let captures = ESTIMATE_MATCHER.captures(&title);
-    if let Some(captures) = captures {
-        let mut hours = 0.0;
+    let description = REFERENCE_MATCHER
+        .replace_all(description, |captures: &regex::Captures| {
+            let reference = captures.get(0).unwrap().as_str();
+            let id = captures["id"].parse::<Id>().expect("reference to be an id");
 
-        if let Some(days) = captures.name("days") {
-            hours += days.as_str().parse::<f32>().expect("to parse days") * 8.0;
-        }
+            if missing_references.contains(&id) {
+                reference.to_owned()
+            } else {
+                format!(
+                    "[{reference}]({})",
+                    config.references.url(current_project_id(), id)
+                )
+            }
+        })
+        .into_owned();
 
-        if let Some(hours_string) = captures.name("hours") {
-            hours += hours_string
-                .as_str()
-                .parse::<f32>()
-                .expect("to parse hours");
-        }
+    (description, dependencies)
+}
 
-        if let Some(minutes) = captures.name("minutes") {
-            hours += minutes.as_str().parse::<f32>().expect("to parse minutes") / 60.0;
-        }
+async fn work_item_exists(client: &reqwest::Client, id: Id) -> bool {
+    let response = client
+        .get(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}/workitems/{id}",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
+        ))
+        .header(
+            "Authorization",
+            format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+        )
+        .send_recorded()
+        .await
+        .expect("To get work item from Hack'n'Plan");
 
-        if let Some(seconds) = captures.name("seconds") {
-            hours += seconds.as_str().parse::<f32>().expect("to parse seconds") / 3600.0;
+    match response.status() {
+        reqwest::StatusCode::NOT_FOUND => false,
+        _ => {
+            response
+                .error_for_status()
+                .unwrap_or_else(|error| panic!("to look up work item #{id}: {error}"));
+            true
         }
-
-        hours
-    } else {
-        0.0
     }
 }
 
-fn get_importance_level(title: &str, available_importance_levels: &Vec<(Id, String, bool)>) -> Id {
-    if let Some(urgency) = URGENCY_MATCHER.find(&title) {
-        let urgency = urgency.as_str().replace('!', "");
-
-        available_importance_levels
-            .iter()
-            .find(|(_, name, _)| name.to_lowercase().matches(&urgency).count() != 0)
-            .expect(&format!("to find importance level for \"{urgency}\""))
-            .0 // Access first element that represents the id
-    } else {
-        available_importance_levels
-            .iter()
-            .find(
-                |level| level.2, // NOTE: This is where the isDefault bool lives, will auto-filter
-            )
-            .expect("atleast one importance level to be default")
-            .0 // Access first element that represents the id
-    }
+fn get_estimate(title: &str, config: &Config) -> Result<f32, String> {
+    MARKUP.estimate(title, &config.markup_options(false))
 }
 
-use clap::Parser;
+fn find_importance_level(
+    urgency: &str,
+    available_importance_levels: &[(Id, String, bool)],
+    config: &Config,
+) -> Id {
+    core::find_importance_level(
+        urgency,
+        available_importance_levels,
+        &config.markup_options(false),
+    )
+    .unwrap_or_else(|expectation| panic!("{expectation}"))
+}
 
-#[derive(Parser, Debug)]
-#[command(author = "Bram Dingelstad <bram@dingelstad.works>", version = "1.0")]
-struct Arguments {
-    #[arg(short, long)]
-    dry_run: bool,
+async fn upload_ticket(client: &reqwest::Client, ticket: &Ticket) -> Id {
+    upload_payload(client, &ticket.title, &ticket.payload()).await
+}
 
-    #[arg(long)]
-    default_category: Option<String>,
+async fn upload_payload(client: &reqwest::Client, title: &str, payload: &serde_json::Value) -> Id {
+    client
+        .post(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}/workitems",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
+        ))
+        .header(
+            "Authorization",
+            format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+        )
+        .json(payload)
+        .send_recorded()
+        .await
+        .unwrap_or_else(|_| panic!(r#"to send ticket "{title}" successfully"#))
+        .error_for_status()
+        .unwrap_or_else(|_| panic!(r#"to send ticket "{title}" successfully"#))
+        .json::<models::WorkItem>()
+        .await
+        .unwrap_or_else(|error| panic!("To deserialize created work item: {error}"))
+        .work_item_id
+}
 
-    file: std::path::PathBuf,
+async fn get_sub_tasks(client: &reqwest::Client, work_item_id: Id) -> Vec<models::SubTask> {
+    client
+        .get(format!(
+            "{API_ENDPOINT}/projects/{PROJECT_ID}/workitems/{work_item_id}/subtasks",
+            API_ENDPOINT = api_endpoint(),
+            PROJECT_ID = current_project_id()
+        ))
+        .header(
+            "Authorization",
+            format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+        )
+        .send_recorded()
+        .await
+        .expect("To get sub tasks from Hack'n'Plan")
+        .json::<Vec<models::SubTask>>()
+        .await
+        .unwrap_or_else(|error| panic!("To deserialize sub tasks: {error}"))
 }
 
-#[tokio::main]
-async fn main() {
-    let arguments = Arguments::parse();
+/// Marks the sub tasks that were checked (`[x]`) as completed on the created work item
+async fn complete_sub_tasks(client: &reqwest::Client, work_item_id: Id, ticket: &Ticket) {
+    let sub_tasks = ticket
+        .sub_tasks
+        .iter()
+        .filter(|sub_task| !sub_task.is_work_item())
+        .collect::<Vec<&SubTask>>();
 
-    let contents = fs::read_to_string(&arguments.file).expect("To read file");
-    let default_category: Option<&str> = None; //Some("programming");
+    if !sub_tasks.iter().any(|sub_task| sub_task.is_completed) {
+        return;
+    }
 
-    let texts = contents
-        .split("---")
-        .filter(|text| text.trim().len() > 0) // Remove empty texts (usually trailing)
-        .collect::<Vec<&str>>();
+    let created_sub_tasks = get_sub_tasks(client, work_item_id).await;
 
-    let client = reqwest::Client::new();
+    // NOTE: Sub tasks are returned in the order they were created in
+    for (sub_task, created_sub_task) in sub_tasks.into_iter().zip(&created_sub_tasks) {
+        if !sub_task.is_completed {
+            continue;
+        }
 
-    let available_categories = get_available_categories(&client).await;
-    let available_tags = get_available_tags(&client).await;
-    let available_users = get_available_users(&client).await;
-    let available_importance_levels = get_available_importance_levels(&client).await;
+        let sub_task_id = created_sub_task.id;
 
-    // Pre-pass for checking tags and verifying data
-    let mut unmatched_tags: Vec<String> = vec![];
-    for text in &texts {
-        // FIXME: Verify that there is only one '===' in the string (double tickets)
-        let mut chunks = text.split("===");
-        let title = chunks.next().unwrap().trim().to_owned();
+        client
+            .patch(format!(
+                "{API_ENDPOINT}/projects/{PROJECT_ID}/workitems/{work_item_id}/subtasks/{sub_task_id}",
+                API_ENDPOINT = api_endpoint(),
+                PROJECT_ID = current_project_id()
+            ))
+            .header(
+                "Authorization",
+                format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+            )
+            .json(&json!({ "isCompleted": true }))
+            .send_recorded()
+            .await
+            .and_then(|response| response.error_for_status())
+            .unwrap_or_else(|error| {
+                panic!("to complete sub task \"{}\": {error}", sub_task.title)
+            });
+    }
+}
+
+/// Shows which project, boards and milestones the tickets go to and asks before uploading them,
+/// as the project only shows up in an environment variable otherwise
+async fn confirm_upload(
+    arguments: &Arguments,
+    create_arguments: &CreateArguments,
+    config: &Config,
+    client: &reqwest::Client,
+    tickets: &[Ticket],
+    metadata: &core::Metadata,
+    source: &str,
+) -> bool {
+    let project_name = project_name(client).await;
+    let milestones = get_available_milestones(client).await;
+
+    let count = |names: Vec<String>| {
+        let mut counts: Vec<(String, usize)> = vec![];
+        for name in names {
+            match counts.iter_mut().find(|(counted, _)| *counted == name) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((name, 1)),
+            }
+        }
+
+        counts
+            .iter()
+            .map(|(name, count)| format!("{name} ({count})"))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    let categories = count(
+        tickets
+            .iter()
+            .map(|ticket| {
+                metadata
+                    .categories
+                    .iter()
+                    .find(|(id, _)| *id == ticket.category_id)
+                    .map(|(_, name)| name.to_owned())
+                    .unwrap_or_else(|| "none".to_owned())
+            })
+            .collect(),
+    );
+    let boards = count(
+        tickets
+            .iter()
+            .map(|ticket| {
+                let Some((_, board, milestone_id)) = metadata
+                    .boards
+                    .iter()
+                    .find(|(id, _, _)| *id == ticket.board_id)
+                else {
+                    return "backlog".to_owned();
+                };
+
+                match milestone_id
+                    .and_then(|milestone_id| milestones.iter().find(|(id, _)| *id == milestone_id))
+                {
+                    Some((_, milestone)) => format!("{board} in {milestone}"),
+                    None => board.to_owned(),
+                }
+            })
+            .collect(),
+    );
+
+    let estimate = tickets.iter().map(estimate_with_sub_tasks).sum::<f32>();
+    output::info(format!("Uploading from {source}:"));
+    println!("  Project:    {project_name} (#{})", current_project_id());
+    println!("  Tickets:    {}", tickets.len());
+    println!("  Categories: {categories}");
+    println!(
+        "  Estimate:   {}",
+        match config.estimates.mode {
+            EstimateMode::Hours => format!("{estimate}h"),
+            EstimateMode::Points => format!("{estimate} points"),
+        }
+    );
+    println!("  Boards:     {boards}");
+
+    if create_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Uploaded));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmUpload {
+            tickets: tickets.len(),
+            project: &project_name,
+        })
+    }
+}
+
+/// Totals of a dry run, so planners can tell whether the tickets fit before uploading them
+fn print_summary(tickets: &[Ticket], metadata: &core::Metadata, config: &Config) {
+    let amount = |estimate: f32| match config.estimates.mode {
+        EstimateMode::Hours => format!("{estimate}h"),
+        EstimateMode::Points => format!("{estimate} points"),
+    };
+
+    output::info(Message::Summary {
+        tickets: tickets.len(),
+        estimate: &amount(tickets.iter().map(estimate_with_sub_tasks).sum::<f32>()),
+    });
+
+    println!("  Per category:");
+    for (id, category) in &metadata.categories {
+        let tickets = tickets
+            .iter()
+            .filter(|ticket| ticket.category_id == *id)
+            .collect::<Vec<&Ticket>>();
+        if tickets.is_empty() {
+            continue;
+        }
+
+        let estimate = tickets
+            .iter()
+            .map(|ticket| estimate_with_sub_tasks(ticket))
+            .sum::<f32>();
+        println!(
+            "    {category}: {} ticket(s), {}",
+            tickets.len(),
+            amount(estimate)
+        );
+    }
+
+    println!("  Per assignee:");
+    for (id, _, user_name) in &metadata.users {
+        let estimate = assigned_estimate(tickets, *id);
+
+        if estimate == 0.0 {
+            continue;
+        }
+
+        match config.estimates.mode {
+            EstimateMode::Hours => {
+                let days = estimate / config.calendar.daily_capacity(user_name);
+                println!("    @{user_name}: {estimate}h ({days:.1} working days)");
+            }
+            EstimateMode::Points => println!("    @{user_name}: {estimate} points"),
+        }
+    }
+
+    let unassigned = tickets
+        .iter()
+        .filter(|ticket| ticket.assigned_user_ids.is_empty())
+        .map(|ticket| ticket.estimated_cost)
+        .chain(
+            tickets
+                .iter()
+                .flat_map(|ticket| &ticket.sub_tasks)
+                .filter(|sub_task| sub_task.assigned_user_ids.is_empty())
+                .map(|sub_task| sub_task.estimated_cost),
+        )
+        .sum::<f32>();
+    if unassigned > 0.0 {
+        println!("    Unassigned: {}", amount(unassigned));
+    }
+
+    println!("  Per importance level:");
+    for (id, level, _) in &metadata.importance_levels {
+        let count = tickets
+            .iter()
+            .filter(|ticket| ticket.importance_level_id == *id)
+            .count();
+        if count > 0 {
+            println!("    {level}: {count} ticket(s)");
+        }
+    }
+}
+
+/// Estimate of `ticket` including its sub tasks, which become child work items when estimated
+fn estimate_with_sub_tasks(ticket: &Ticket) -> f32 {
+    ticket.estimated_cost
+        + ticket
+            .sub_tasks
+            .iter()
+            .map(|sub_task| sub_task.estimated_cost)
+            .sum::<f32>()
+}
+
+/// Estimate of the tickets and sub tasks assigned to the user with `id`
+fn assigned_estimate(tickets: &[Ticket], id: Id) -> f32 {
+    tickets
+        .iter()
+        .filter(|ticket| ticket.assigned_user_ids.contains(&id))
+        .map(|ticket| ticket.estimated_cost)
+        .chain(
+            tickets
+                .iter()
+                .flat_map(|ticket| &ticket.sub_tasks)
+                .filter(|sub_task| sub_task.assigned_user_ids.contains(&id))
+                .map(|sub_task| sub_task.estimated_cost),
+        )
+        .sum::<f32>()
+}
+
+use clap::{Args, Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(author = "Bram Dingelstad <bram@dingelstad.works>", version = "1.0")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+#[command(after_help = "Exit codes:
+  0    Success
+  1    An error stopped the run, tickets after the error weren't uploaded
+  2    Invalid arguments
+  3    Finished with warnings, only with --fail-on warn
+  130  Interrupted with Ctrl-C")]
+struct Arguments {
+    #[arg(short, long, global = true)]
+    dry_run: bool,
+
+    /// Treat anything hnp can't resolve as an error: unknown tags, mentions matching several
+    /// users, duplicate titles and too much work for --check-capacity
+    #[arg(long, global = true, conflicts_with = "lenient")]
+    strict: bool,
+
+    /// Keep unknown mentions, tags and importance levels in the title as text with a warning,
+    /// instead of failing or creating the tags. --create-missing-tags still creates them.
+    #[arg(long, global = true)]
+    lenient: bool,
+
+    /// Never prompt for input, use together with --create-missing-tags or --fail-on-missing-tags
+    #[arg(long, global = true)]
+    non_interactive: bool,
+
+    /// Disable colors and emoji, also done when NO_COLOR is set or output isn't a terminal
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Language of the messages, taken from LC_ALL, LC_MESSAGES or LANG when not given
+    #[arg(long, global = true, value_enum, value_name = "LANGUAGE")]
+    lang: Option<i18n::Language>,
+
+    /// How warnings and errors are printed, `json` is meant for editors and CI annotations
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FORMAT",
+        default_value = "text"
+    )]
+    diagnostics: output::DiagnosticsFormat,
+
+    /// Where the output is read, `github` prints annotations and a job summary for GitHub Actions
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FORMAT",
+        default_value = "text",
+        conflicts_with = "diagnostics"
+    )]
+    output_format: output::OutputFormat,
+
+    /// What makes hnp exit with a failing code, for deciding what breaks a CI build
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "LEVEL",
+        default_value = "error"
+    )]
+    fail_on: output::FailOn,
+
+    /// Path to the config file, defaults to ./hnp.toml or the user's config directory
+    #[arg(short, long, global = true)]
+    config: Option<std::path::PathBuf>,
+
+    /// Read categories, tags, users, boards and importance levels from a file made by
+    /// `hnp metadata export` instead of fetching them from Hack'n'Plan
+    #[arg(long, global = true, value_name = "FILE")]
+    metadata: Option<std::path::PathBuf>,
+
+    /// Never use the network, requires --metadata and implies --dry-run
+    #[arg(long, global = true, requires = "metadata")]
+    offline: bool,
+
+    /// Hack'n'Plan project to work in instead of the one in HACKNPLAN_PROJECT_ID
+    #[arg(long, visible_alias = "project", global = true, value_name = "ID")]
+    project_id: Option<Id>,
+
+    /// Work in the project of a profile from `profiles` in the config, required for destructive
+    /// commands on protected ones
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        conflicts_with = "project_id"
+    )]
+    profile: Option<String>,
+
+    /// Base URL of the Hack'n'Plan API, for proxies, mocks or other API versions
+    #[arg(long, global = true, value_name = "URL")]
+    api_url: Option<String>,
+
+    /// Write every request and response to a JSONL file to attach to bug reports, the API key is
+    /// left out
+    #[arg(long, global = true, value_name = "FILE", conflicts_with = "replay")]
+    record: Option<std::path::PathBuf>,
+
+    /// Answer requests from a file made with --record instead of sending them to Hack'n'Plan
+    #[arg(long, global = true, value_name = "FILE")]
+    replay: Option<std::path::PathBuf>,
+
+    /// Seconds a request to Hack'n'Plan may take before giving up, defaults to 30
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+
+    /// Text added to the user agent of every request, e.g. to recognize the traffic of a team in
+    /// a proxy
+    #[arg(long, global = true, value_name = "TEXT")]
+    user_agent_suffix: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Running `hnp <FILE>` is the same as `hnp create <FILE>`
+    #[command(flatten)]
+    create: CreateArguments,
+}
+
+impl Arguments {
+    fn strictness(&self) -> core::Strictness {
+        if self.strict {
+            core::Strictness::Strict
+        } else if self.lenient {
+            core::Strictness::Lenient
+        } else {
+            core::Strictness::Normal
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Create work items from a file with tasks (the default command)
+    Create(CreateArguments),
+    /// Show how tickets are read, token by token, without uploading anything
+    Explain(explain::ExplainArguments),
+    /// Change all work items matching a filter at once
+    Bulk(bulk::BulkArguments),
+    /// Copy a work item, including the work items below it when it's a story
+    #[command(alias = "copy")]
+    Clone(clone::CloneArguments),
+    /// Create tickets on a schedule from recurring.toml
+    Recurring {
+        #[command(subcommand)]
+        command: recurring::RecurringCommand,
+    },
+    /// Create and rename categories, tags, importance levels, milestones and boards to match a spec
+    Apply(apply::ApplyArguments),
+    /// List the previous runs that created work items
+    History(history::HistoryArguments),
+    /// Delete the work items created by a previous run
+    Undo(history::UndoArguments),
+    /// Export or import the project's categories, tags, users, boards, stages and milestones
+    Metadata {
+        #[command(subcommand)]
+        command: metadata::MetadataCommand,
+    },
+    /// Save the whole project to JSON files
+    Backup(backup::BackupArguments),
+    /// Re-create a project from a snapshot made by `hnp backup`
+    Restore(backup::RestoreArguments),
+    /// Manage boards
+    Board {
+        #[command(subcommand)]
+        command: board::BoardCommand,
+    },
+    /// Create and update work items from a directory with a file per ticket
+    Sync(sync::SyncArguments),
+    /// Create work items from notes kept in other tools
+    Import {
+        #[command(subcommand)]
+        command: import::ImportCommand,
+    },
+    /// Mirror work items to other tools
+    Export {
+        #[command(subcommand)]
+        command: export::ExportCommand,
+    },
+    /// Render reports to share with people outside of Hack'n'Plan
+    Report {
+        #[command(subcommand)]
+        command: report::ReportCommand,
+    },
+    /// Create QA tasks for completed work items
+    Qa {
+        #[command(subcommand)]
+        command: qa::QaCommand,
+    },
+    /// Move the unfinished work items of a milestone to the next one at the end of a sprint
+    Rollover(rollover::RolloverArguments),
+    /// Propose who works on which open work items in a milestone, filling everyone's capacity by
+    /// importance, and optionally assign them
+    Plan(plan::PlanArguments),
+    /// Print how the work items of a milestone depend on each other, for Graphviz or Mermaid
+    Graph(graph::GraphArguments),
+    /// Find work items by the words in their title or description
+    Search(search::SearchArguments),
+    /// Summarize what you completed yesterday, are working on today and are blocked on, to paste
+    /// into chat
+    Standup(standup::StandupArguments),
+    /// Show how many work items you created per week and their average estimate, from the
+    /// statistics kept on this machine when `stats.enabled` is on
+    Stats(stats::StatsArguments),
+    /// List the work item types that `type:bug` or `[bug]` at the start of a title can name
+    Types,
+    /// Print the JSON Schema of the tickets `--dry-run` prints, for validators and editors
+    Schema,
+    /// Fill a sandbox project with sample categories, tags, a board and tickets to try hnp on, or
+    /// remove them again
+    Demo(demo::DemoArguments),
+    /// Check the API key, project and connection to Hack'n'Plan, telling how to fix what's wrong
+    Doctor(doctor::DoctorArguments),
+    /// Serve diagnostics, completion and hover for the markup to editors over stdin and stdout
+    Lsp,
+    /// Update hnp to the latest release on GitHub
+    #[cfg(feature = "self-update")]
+    SelfUpdate(self_update::SelfUpdateArguments),
+}
+
+#[derive(Args, Debug)]
+struct CreateArguments {
+    #[arg(long)]
+    default_category: Option<String>,
+
+    /// Create tags that don't exist on Hack'n'Plan yet without asking
+    #[arg(long, conflicts_with = "fail_on_missing_tags")]
+    create_missing_tags: bool,
+
+    /// Abort when tags are used that don't exist on Hack'n'Plan yet, also during a dry run
+    #[arg(long)]
+    fail_on_missing_tags: bool,
+
+    /// Milestone the tickets are planned for, to check the capacity against
+    #[arg(long, value_name = "MILESTONE")]
+    milestone: Option<String>,
+
+    /// Warn when the estimates add up to more hours than the team has left in --milestone, going
+    /// by the capacity in the config
+    #[arg(long, requires = "milestone")]
+    check_capacity: bool,
+
+    /// Divide the tickets without mentions among the users in `assignment.pool` of the config
+    #[arg(long, value_enum, value_name = "STRATEGY")]
+    auto_assign: Option<assign::Strategy>,
+
+    /// Suggest an estimate for tickets without one, from the work items in the same category with
+    /// the most similar titles
+    #[arg(long)]
+    suggest_estimates: bool,
+
+    /// Give tickets without an estimate the one --suggest-estimates suggests
+    #[arg(long)]
+    apply_suggestions: bool,
+
+    /// Stage to put the work items in when they don't name one with `stage:`, instead of the
+    /// first stage
+    #[arg(long, value_name = "STAGE")]
+    stage: Option<String>,
+
+    /// Also honor tags, estimates, importance and due dates written in the description
+    #[arg(long)]
+    scan_description: bool,
+
+    /// Leave out the first N tickets of each file
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    skip: usize,
+
+    /// Process at most N tickets
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+
+    /// Upload up to N tickets at the same time, Hack'n'Plan has no endpoint for creating
+    /// several work items in one request
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    batch_size: u16,
+
+    /// Only process tickets whose title contains this text, before --skip and --limit apply
+    #[arg(long, value_name = "TEXT")]
+    only: Option<String>,
+
+    /// Choose which of the tickets get uploaded from a list before anything is created
+    #[arg(long)]
+    pick: bool,
+
+    /// Upload without asking for confirmation after the summary of what's uploaded where
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Mark the tickets in Markdown files with the work items created for them, so they can be
+    /// found again. After the run, not during a dry run.
+    #[arg(long, value_enum, value_name = "STYLE", num_args = 0..=1, require_equals = true, default_missing_value = "comment")]
+    annotate: Option<annotate::Annotation>,
+
+    /// Title of the created work items, like "{milestone}: {title}". Besides `{title}` there's
+    /// `{category}`, `{board}` and `{milestone}`, the milestone of the ticket's board or else
+    /// --milestone. Overrides `markup.title_template` of the config.
+    #[arg(long, value_name = "TEMPLATE")]
+    title_template: Option<String>,
+
+    /// Syntax of the files, by default `.org` files are read as org and `todo.txt` as todo.txt
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    input_format: Option<input::InputFormat>,
+
+    /// Files with tickets, processed in order. `*` and `?` are expanded when the shell doesn't.
+    #[arg(required = true, value_name = "FILE")]
+    files: Vec<std::path::PathBuf>,
+}
+
+#[tokio::main]
+async fn main() {
+    let mut arguments = Arguments::parse();
+    i18n::init(arguments.lang);
+    output::init(
+        arguments.no_color,
+        arguments.diagnostics,
+        arguments.output_format,
+        arguments.fail_on,
+    );
+    output::install_panic_hook();
+    // NOTE: Doctor reports a broken config itself instead of panicking on it
+    let mut config = match arguments.command {
+        Some(Command::Doctor(_)) => Config::find(arguments.config.as_deref())
+            .and_then(|path| Config::parse(&path).ok())
+            .unwrap_or_default(),
+        _ => Config::load(arguments.config.as_deref()),
+    };
+    WORD_CHARACTERS
+        .set(config.markup.word_characters.clone())
+        .expect("word characters to only be set once");
+    configure_api(&arguments, &config);
+
+    let run_project_id = match &arguments.profile {
+        Some(name) => Some(find_profile(&config, name)),
+        None => arguments.project_id,
+    };
+    if let Some(project_id) = run_project_id {
+        RUN_PROJECT_ID
+            .set(project_id)
+            .expect("project to only be set once");
+    }
+    hooks::set(config.hooks.clone());
+    stats::set(config.stats.enabled);
+
+    if let Some(path) = &arguments.metadata {
+        metadata::set(metadata::Metadata::load(path));
+    }
+
+    if let Some(path) = &arguments.record {
+        recording::record(path);
+    } else if let Some(path) = &arguments.replay {
+        recording::replay(path);
+    }
+
+    if arguments.offline {
+        if !matches!(
+            arguments.command,
+            None | Some(Command::Create(_) | Command::Explain(_))
+        ) {
+            output::error("--offline only works when creating or explaining tickets");
+            output::exit_with_error();
+        }
+
+        arguments.dry_run = true;
+
+        if config.references.validate {
+            output::warn(Message::NotValidatingReferences);
+            config.references.validate = false;
+        }
+    }
+
+    let uses_network = !arguments.offline
+        && match &arguments.command {
+            // NOTE: Doctor checks access itself to report every problem instead of the first
+            Some(
+                Command::History(_) | Command::Schema | Command::Doctor(_) | Command::Stats(_),
+            ) => false,
+            // NOTE: Updating only talks to GitHub, so it works without a Hack'n'Plan API key
+            #[cfg(feature = "self-update")]
+            Some(Command::SelfUpdate(_)) => false,
+            _ => true,
+        };
+    if uses_network {
+        check_access(&client()).await;
+
+        // NOTE: Only commands that change the project name it, the others print what they fetch
+        // and the language server talks over stdout
+        if matches!(
+            arguments.command,
+            None | Some(
+                Command::Create(_)
+                    | Command::Bulk(_)
+                    | Command::Clone(_)
+                    | Command::Recurring { .. }
+                    | Command::Apply(_)
+                    | Command::Undo(_)
+                    | Command::Restore(_)
+                    | Command::Board { .. }
+                    | Command::Sync(_)
+                    | Command::Import { .. }
+                    | Command::Qa { .. }
+                    | Command::Rollover(_)
+                    | Command::Plan(_)
+                    | Command::Demo(_)
+            )
+        ) {
+            output::info(Message::WorkingInProject {
+                name: &project_name(&client()).await,
+                id: current_project_id(),
+            });
+        }
+
+        if matches!(
+            arguments.command,
+            Some(Command::Bulk(_) | Command::Rollover(_) | Command::Restore(_) | Command::Undo(_))
+        ) && !confirm_protected(&arguments, &config).await
+        {
+            output::info(Message::NothingWas(Action::Changed));
+            return;
+        }
+    }
+
+    match &arguments.command {
+        Some(Command::Create(create_arguments)) => {
+            create(&arguments, create_arguments, &config).await
+        }
+        Some(Command::Explain(explain_arguments)) => explain::run(explain_arguments, &config).await,
+        Some(Command::Bulk(bulk_arguments)) => bulk::run(&arguments, bulk_arguments, &config).await,
+        Some(Command::Clone(clone_arguments)) => {
+            clone::run(&arguments, clone_arguments, &config).await
+        }
+        Some(Command::Board { command }) => board::run(&arguments, command).await,
+        Some(Command::Apply(apply_arguments)) => apply::run(&arguments, apply_arguments).await,
+        Some(Command::History(history_arguments)) => history::history(history_arguments),
+        Some(Command::Undo(undo_arguments)) => history::undo(&arguments, undo_arguments).await,
+        Some(Command::Metadata { command }) => metadata::run(&arguments, command).await,
+        Some(Command::Backup(backup_arguments)) => backup::backup(backup_arguments).await,
+        Some(Command::Restore(restore_arguments)) => {
+            backup::restore(&arguments, restore_arguments).await
+        }
+        Some(Command::Recurring { command }) => recurring::run(&arguments, command, &config).await,
+        Some(Command::Sync(sync_arguments)) => sync::run(&arguments, sync_arguments, &config).await,
+        Some(Command::Import { command }) => import::run(&arguments, command, &config).await,
+        Some(Command::Export { command }) => export::run(&arguments, command, &config).await,
+        Some(Command::Report { command }) => report::run(&arguments, command, &config).await,
+        Some(Command::Qa { command }) => qa::run(&arguments, command, &config).await,
+        Some(Command::Rollover(rollover_arguments)) => {
+            rollover::run(&arguments, rollover_arguments).await
+        }
+        Some(Command::Plan(plan_arguments)) => plan::run(&arguments, plan_arguments, &config).await,
+        Some(Command::Graph(graph_arguments)) => graph::run(graph_arguments).await,
+        Some(Command::Search(search_arguments)) => search::run(search_arguments).await,
+        Some(Command::Standup(standup_arguments)) => standup::run(standup_arguments, &config).await,
+        Some(Command::Stats(stats_arguments)) => stats::run(stats_arguments),
+        Some(Command::Types) => list_types(&client()).await,
+        Some(Command::Schema) => println!(
+            "{}",
+            serde_json::to_string_pretty(&schema::ticket()).expect("To serialize the schema")
+        ),
+        Some(Command::Demo(demo_arguments)) => demo::run(&arguments, demo_arguments, &config).await,
+        Some(Command::Doctor(doctor_arguments)) => {
+            doctor::run(&arguments, doctor_arguments, &config).await
+        }
+        Some(Command::Lsp) => lsp::run(config).await,
+        #[cfg(feature = "self-update")]
+        Some(Command::SelfUpdate(self_update_arguments)) => {
+            self_update::run(&arguments, self_update_arguments).await
+        }
+        None => create(&arguments, &arguments.create, &config).await,
+    }
+
+    output::finish();
+}
+
+async fn create(arguments: &Arguments, create_arguments: &CreateArguments, config: &Config) {
+    let files = input::expand(&create_arguments.files).unwrap_or_else(|error| {
+        output::error(error);
+        output::exit_with_error();
+    });
+
+    for file in files {
+        let source = file.display().to_string();
+        output::set_context(Some(source.clone()));
+
+        let full_contents = read_tickets(&file, create_arguments.input_format).await;
+        let (front_matter, contents) =
+            input::FrontMatter::parse(&full_contents).unwrap_or_else(|error| panic!("{error}"));
+
+        let project_id = match &front_matter.project {
+            Some(profile) => {
+                if arguments.metadata.is_some() {
+                    output::error("Tickets for other projects can't be used with --metadata");
+                    output::exit_with_error();
+                }
+
+                find_profile(config, profile)
+            }
+            None => current_project_id(),
+        };
+
+        let created = in_project(project_id, async {
+            if front_matter.project.is_some() {
+                check_access(&client()).await;
+                output::info(Message::WorkingInProject {
+                    name: &project_name(&client()).await,
+                    id: project_id,
+                });
+            }
+
+            let default_board_id = match &front_matter.board {
+                Some(board) => Some(find_board(&client(), board).await),
+                None => None,
+            };
+
+            // NOTE: Lines are counted from the start of the file, front matter included
+            let first_line = full_contents[..full_contents.len() - contents.len()]
+                .matches('\n')
+                .count()
+                + 1;
+
+            create_tickets(
+                arguments,
+                create_arguments,
+                config,
+                &front_matter.apply(contents),
+                &source,
+                first_line,
+                default_board_id,
+            )
+            .await
+        })
+        .await;
+
+        if let Some(annotation) = create_arguments.annotate {
+            annotate_file(&file, create_arguments.input_format, annotation, &created);
+        }
+    }
+
+    output::set_context(None);
+}
+
+/// Writes the ids of the `created` work items next to their tickets in `file`, only Markdown
+/// files as org and todo.txt files are converted first
+fn annotate_file(
+    file: &std::path::Path,
+    input_format: Option<input::InputFormat>,
+    annotation: annotate::Annotation,
+    created: &[(usize, Id)],
+) {
+    if created.is_empty() {
+        return;
+    }
+
+    if input_format.unwrap_or_else(|| input::InputFormat::detect(file))
+        != input::InputFormat::Markdown
+    {
+        output::warn(format!(
+            "Not annotating {}, --annotate only works for Markdown files",
+            file.display()
+        ));
+        return;
+    }
+
+    annotate::write(file, annotation, created);
+    output::info(format!(
+        "Annotated {} ticket(s) in {}",
+        created.len(),
+        file.display()
+    ));
+}
+
+/// Contents of `file` in hnp's own syntax, converting org and todo.txt files
+async fn read_tickets(file: &std::path::Path, input_format: Option<input::InputFormat>) -> String {
+    let contents = text::read(file);
+
+    match input_format.unwrap_or_else(|| input::InputFormat::detect(file)) {
+        input::InputFormat::Markdown => contents,
+        input::InputFormat::Org => {
+            let stages = get_models::<models::Stage>(&client(), "stages").await;
+            org::to_tickets(&contents, &stages)
+        }
+        input::InputFormat::Todotxt => {
+            let stages = get_models::<models::Stage>(&client(), "stages").await;
+            let importance_levels = get_available_importance_levels(&client()).await;
+            todotxt::to_tickets(&contents, &stages, importance_levels.len())
+        }
+    }
+}
+
+/// Prints the types `type:` takes, Hack'n'Plan only has stories and tasks so the categories
+/// stand in for the rest
+async fn list_types(client: &reqwest::Client) {
+    println!("story  Work item whose sub tasks can be work items of their own");
+    println!("task   Work item, the default");
+    for (id, category) in get_available_categories(client).await {
+        println!(
+            "{:<6} Category {id}",
+            normalize_name(category.as_str()).replace(' ', "-")
+        );
+    }
+}
+
+async fn find_board(client: &reqwest::Client, board: &str) -> Id {
+    get_available_boards(client)
+        .await
+        .iter()
+        .find(|(_, name, _)| name.eq_ignore_ascii_case(board))
+        .unwrap_or_else(|| panic!("To find a board for \"{board}\""))
+        .0
+}
+
+/// Creates work items for the tickets in `contents`, placing them on `default_board_id` unless
+/// a ticket names its own board. The run is recorded in the history as coming from `source`.
+/// Returns the line each uploaded ticket starts on with the id of its work item.
+async fn create_tickets(
+    arguments: &Arguments,
+    create_arguments: &CreateArguments,
+    config: &Config,
+    contents: &str,
+    source: &str,
+    first_line: usize,
+    default_board_id: Option<Id>,
+) -> Vec<(usize, Id)> {
+    let texts = MARKUP
+        .split_tickets(contents)
+        .into_iter()
+        .filter(|(_, text)| {
+            create_arguments.only.as_ref().is_none_or(|only| {
+                let title = TITLE_SEPARATOR.splitn(text, 2).next().unwrap_or_default();
+                title.to_lowercase().contains(&only.to_lowercase())
+            })
+        })
+        .skip(create_arguments.skip)
+        .take(create_arguments.limit.unwrap_or(usize::MAX))
+        .collect::<Vec<(usize, &str)>>();
+
+    if texts.is_empty() {
+        output::warn(format!("No tickets in {source} to process"));
+        return vec![];
+    }
+
+    let texts = if create_arguments.pick {
+        pick_tickets(texts, arguments)
+    } else {
+        texts
+    };
+
+    // NOTE: Tickets with `%%project:` go to the project of that profile, the others to the
+    // current one, a project at a time. The position of each in the file is kept to resume from.
+    let positions = texts
+        .iter()
+        .enumerate()
+        .map(|(position, (line, _))| (first_line + line, position))
+        .collect::<HashMap<usize, usize>>();
+    let project_id = current_project_id();
+    let mut projects: Vec<(Id, Vec<(usize, String)>)> = vec![];
+    for (line, text) in texts {
+        let ticket_project_id = PROJECT_MATCHER
+            .captures(text)
+            .map_or(project_id, |captures| {
+                find_profile(config, &captures["profile"])
+            });
+        let text = PROJECT_MATCHER.replace_all(text, "").into_owned();
+
+        match projects.iter_mut().find(|(id, _)| *id == ticket_project_id) {
+            Some((_, texts)) => texts.push((line, text)),
+            None => projects.push((ticket_project_id, vec![(line, text)])),
+        }
+    }
+
+    let switches_project = projects.iter().any(|(id, _)| *id != project_id);
+    if switches_project {
+        if arguments.metadata.is_some() {
+            output::error("Tickets for other projects can't be used with --metadata");
+            output::exit_with_error();
+        }
+
+        // NOTE: Checked up front, so no project gets half of the tickets
+        for (id, _) in &projects {
+            in_project(*id, check_access(&client())).await;
+        }
+    }
+
+    let mut created = vec![];
+    for (index, (id, texts)) in projects.into_iter().enumerate() {
+        let texts = texts
+            .iter()
+            .map(|(line, text)| (*line, text.as_str()))
+            .collect();
+        // NOTE: The board of the front matter was found in the project of the file
+        let default_board_id = default_board_id.filter(|_| id == project_id);
+
+        created.extend(
+            in_project(id, async {
+                if index > 0 || id != project_id {
+                    output::info(Message::WorkingInProject {
+                        name: &project_name(&client()).await,
+                        id,
+                    });
+                }
+
+                upload_tickets(
+                    arguments,
+                    create_arguments,
+                    config,
+                    texts,
+                    source,
+                    first_line,
+                    default_board_id,
+                )
+                .await
+            })
+            .await,
+        );
+
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            let uploaded = created
+                .iter()
+                .map(|(line, _)| positions[line])
+                .collect::<Vec<usize>>();
+            resume_hint(create_arguments, source, &uploaded, positions.len());
+            std::process::exit(output::EXIT_INTERRUPTED);
+        }
+    }
+
+    created
+}
+
+/// Tells how to resume an interrupted upload of `total` tickets, of which the ones at
+/// `uploaded` in the file (after `--only`, `--skip` and `--limit`) made it
+fn resume_hint(create_arguments: &CreateArguments, source: &str, uploaded: &[usize], total: usize) {
+    if create_arguments.pick {
+        output::info("Resume with --pick and leave out the uploaded tickets");
+        return;
+    }
+
+    // NOTE: Tickets for other projects are uploaded a project at a time, so the uploaded ones
+    // aren't always the first ones in the file
+    let resume = (0..total)
+        .find(|position| !uploaded.contains(position))
+        .unwrap_or(total);
+    output::info(format!(
+        "Resume {source} with --skip {}",
+        create_arguments.skip + resume
+    ));
+
+    let later = uploaded
+        .iter()
+        .filter(|position| **position > resume)
+        .count();
+    if later > 0 {
+        output::warn(format!(
+            "{later} ticket(s) after that were already uploaded, leave them out or they're \
+            uploaded twice"
+        ));
+    }
+}
+
+/// Parses the tickets and uploads them to the current project, returning the line and id of the
+/// work item of each
+async fn upload_tickets(
+    arguments: &Arguments,
+    create_arguments: &CreateArguments,
+    config: &Config,
+    texts: Vec<(usize, &str)>,
+    source: &str,
+    first_line: usize,
+    default_board_id: Option<Id>,
+) -> Vec<(usize, Id)> {
+    let (lines, texts): (Vec<usize>, Vec<&str>) = texts.into_iter().unzip();
+
+    let client = client();
+    let (mut tickets, metadata) = parse_tickets(
+        arguments,
+        create_arguments,
+        config,
+        &client,
+        &texts,
+        &lines
+            .iter()
+            .map(|line| first_line + line)
+            .collect::<Vec<usize>>(),
+        default_board_id,
+    )
+    .await;
+
+    // NOTE: Before assigning, so the least loaded users are found with the suggested estimates
+    if create_arguments.suggest_estimates || create_arguments.apply_suggestions {
+        if arguments.offline {
+            output::warn("Not suggesting estimates while --offline");
+        } else {
+            suggest::estimates(
+                &client,
+                &mut tickets,
+                config,
+                create_arguments.apply_suggestions,
+            )
+            .await;
+        }
+    }
+
+    if let Some(strategy) = create_arguments.auto_assign {
+        assign::run(&client, &mut tickets, &metadata, config, strategy).await;
+    }
+
+    check_duplicate_titles(&tickets, source, arguments.strict);
+    check_limits(&tickets, &lines, first_line);
+
+    if let Some(milestone) = create_arguments
+        .milestone
+        .as_deref()
+        .filter(|_| create_arguments.check_capacity)
+    {
+        capacity::check(
+            &client,
+            &tickets,
+            &metadata,
+            config,
+            milestone,
+            arguments.strict,
+        )
+        .await;
+    }
+
+    if !arguments.dry_run
+        && !confirm_upload(
+            arguments,
+            create_arguments,
+            config,
+            &client,
+            &tickets,
+            &metadata,
+            source,
+        )
+        .await
+    {
+        output::info(Message::NothingWas(Action::Uploaded));
+        return vec![];
+    }
+
+    let run = history::Run::new(source);
+    if !arguments.dry_run {
+        run.start();
+    }
+
+    if !arguments.dry_run {
+        handle_interrupts();
+    }
+
+    let mut uploaded = 0;
+    let mut work_item_ids = vec![];
+    if arguments.dry_run {
+        for ticket in &tickets {
+            output::info(format!(
+                "\"Pretend\" uploading ticket:\n{}",
+                serde_json::to_string_pretty(&ticket.outgoing_payload()).unwrap()
+            ));
+
+            for sub_task in ticket
+                .sub_tasks
+                .iter()
+                .filter(|sub_task| sub_task.is_completed && !sub_task.is_work_item())
+            {
+                output::info(format!(
+                    "Would mark sub task \"{}\" as completed",
+                    sub_task.title
+                ));
+            }
+
+            // NOTE: The parent doesn't exist during a dry run, so its id is left at 0
+            for child in ticket.child_work_items(0) {
+                output::info(format!(
+                    "\"Pretend\" uploading sub task \"{}\" as a work item below \"{}\":\n{}",
+                    child.title,
+                    ticket.title,
+                    serde_json::to_string_pretty(&child.outgoing_payload()).unwrap()
+                ));
+            }
+        }
+    }
+
+    // NOTE: Up to --batch-size tickets are uploaded at once, but they finish in order. After
+    // Ctrl-C no new uploads are started, the ones underway are allowed to finish. Work items are
+    // recorded as they're created, the order only matters for the output and --skip.
+    let shared_run = Mutex::new(run);
+    let progress = output::progress(tickets.len(), "Uploading tickets");
+    let mut uploads = stream::iter(
+        tickets
+            .iter()
+            .filter(|_| !arguments.dry_run)
+            .take_while(|_| !INTERRUPTED.load(Ordering::SeqCst))
+            .map(|ticket| {
+                let client = &client;
+                let run = &shared_run;
+                async move { (ticket, upload_with_children(client, ticket, run).await) }
+            }),
+    )
+    .buffered(create_arguments.batch_size.into());
+    while let Some((ticket, work_item_id)) = uploads.next().await {
+        work_item_ids.push(work_item_id);
+
+        progress.suspend(|| output::success(Message::UploadedTicket(&ticket.title)));
+        progress.inc(1);
+        uploaded += 1;
+    }
+    progress.finish_and_clear();
+    drop(uploads);
+    let mut run = shared_run
+        .into_inner()
+        .expect("To have recorded every upload in the run history");
+
+    // NOTE: The caller tells how to resume, as it knows where these tickets are in the file
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        output::warn(Message::Interrupted {
+            uploaded,
+            tickets: tickets.len(),
+        });
+        output::info(Message::UndoRun(&run.id));
+
+        return lines
+            .iter()
+            .map(|line| first_line + line)
+            .zip(work_item_ids)
+            .collect();
+    }
+
+    if output::is_github() {
+        write_job_summary(&tickets, &work_item_ids, &metadata, config, source);
+    }
+
+    if arguments.dry_run {
+        print_summary(&tickets, &metadata, config);
+    } else {
+        run.finish();
+
+        if !run.work_item_ids.is_empty() {
+            output::info(Message::UndoRun(&run.id));
+        }
+    }
+
+    lines
+        .iter()
+        .map(|line| first_line + line)
+        .zip(work_item_ids)
+        .collect()
+}
+
+/// Adds a table of the tickets to the GitHub Actions job summary, `work_item_ids` is empty for a
+/// dry run
+fn write_job_summary(
+    tickets: &[Ticket],
+    work_item_ids: &[Id],
+    metadata: &core::Metadata,
+    config: &Config,
+    source: &str,
+) {
+    let name = |names: &[(Id, String)], id: Id| {
+        names
+            .iter()
+            .find(|(available_id, _)| *available_id == id)
+            .map_or("", |(_, name)| name.as_str())
+            .to_owned()
+    };
+    // NOTE: Pipes would end the cell early
+    let cell = |text: &str| text.replace('|', "\\|");
+
+    let heading = if work_item_ids.is_empty() {
+        format!("Tickets from {source} that would be created")
+    } else {
+        format!("Tickets created from {source}")
+    };
+    let mut markdown = format!(
+        "### {heading}\n\n| Work item | Title | Category | Board | Assignees | Estimate |\n| --- | --- | --- | --- | --- | --- |\n"
+    );
+
+    for (index, ticket) in tickets.iter().enumerate() {
+        let work_item = work_item_ids.get(index).map_or("—".to_owned(), |id| {
+            format!(
+                "[#{id}]({})",
+                config.references.url(current_project_id(), *id)
+            )
+        });
+        let board = metadata
+            .boards
+            .iter()
+            .find(|(id, _, _)| *id == ticket.board_id)
+            .map_or("Backlog", |(_, name, _)| name.as_str());
+        let assignees = metadata
+            .users
+            .iter()
+            .filter(|(id, _, _)| ticket.assigned_user_ids.contains(id))
+            .map(|(_, _, username)| format!("@{username}"))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        markdown.push_str(&format!(
+            "| {work_item} | {} | {} | {} | {} | {} |\n",
+            cell(&ticket.title),
+            cell(&name(&metadata.categories, ticket.category_id)),
+            cell(board),
+            assignees,
+            estimate_with_sub_tasks(ticket)
+        ));
+    }
+
+    output::github_summary(&format!("{markdown}\n"));
+}
+
+/// Checks the tickets against the limits of Hack'n'Plan's API, pointing at the line each of the
+/// failing tickets starts on. `lines` count from 0 from `first_line` on.
+fn check_limits(tickets: &[Ticket], lines: &[usize], first_line: usize) {
+    let mut failed = false;
+
+    for (ticket, line) in tickets.iter().zip(lines) {
+        let sub_tasks = ticket.sub_tasks.iter().map(|sub_task| {
+            core::check_limits(
+                &sub_task.title,
+                "",
+                sub_task.estimated_cost,
+                sub_task.assigned_user_ids.len(),
+            )
+        });
+
+        let problems = [core::check_limits(
+            &ticket.title,
+            &ticket.description,
+            ticket.estimated_cost,
+            ticket.assigned_user_ids.len(),
+        )]
+        .into_iter()
+        .chain(sub_tasks)
+        .filter_map(Result::err);
+
+        for expectation in problems {
+            failed = true;
+            output::error_at(output::Diagnostic {
+                code: "limit",
+                message: core::expected(&expectation),
+                line: Some(first_line + line),
+                ..Default::default()
+            });
+        }
+    }
+
+    if failed {
+        output::error(Message::OverLimits);
+        output::exit_with_error();
+    }
+}
+
+/// Warns about tickets with the same title, almost always a copy-paste mistake, and aborts with
+/// --strict before anything is uploaded
+fn check_duplicate_titles(tickets: &[Ticket], source: &str, strict: bool) {
+    let mut duplicates: Vec<(&str, usize)> = vec![];
+    for (index, ticket) in tickets.iter().enumerate() {
+        let is_duplicate = tickets[..index]
+            .iter()
+            .any(|other| other.title.eq_ignore_ascii_case(&ticket.title));
+        if !is_duplicate {
+            continue;
+        }
+
+        match duplicates
+            .iter_mut()
+            .find(|(title, _)| title.eq_ignore_ascii_case(&ticket.title))
+        {
+            Some((_, count)) => *count += 1,
+            None => duplicates.push((&ticket.title, 2)),
+        }
+    }
+
+    // NOTE: Errors already mention the file through the output context
+    for (title, count) in &duplicates {
+        if strict {
+            output::error_at(output::Diagnostic {
+                code: "duplicate-title",
+                message: format!("{count} tickets are titled \"{title}\""),
+                ..Default::default()
+            });
+        } else {
+            output::warn_at(output::Diagnostic {
+                code: "duplicate-title",
+                message: format!("{count} tickets in {source} are titled \"{title}\""),
+                ..Default::default()
+            });
+        }
+    }
+
+    if strict && !duplicates.is_empty() {
+        output::error(Message::StrictWithDuplicateTitles);
+        output::exit_with_error();
+    }
+}
+
+/// Everything the markup is resolved against, from Hack'n'Plan or the --metadata snapshot
+async fn get_metadata(client: &reqwest::Client) -> core::Metadata {
+    core::Metadata {
+        categories: get_available_categories(client).await,
+        users: get_available_users(client).await,
+        tags: get_available_tags(client).await,
+        importance_levels: get_available_importance_levels(client).await,
+        boards: get_available_boards(client).await,
+        stages: get_models::<models::Stage>(client, "stages").await,
+    }
+}
+
+/// Turns `texts` into tickets, creating the tags they use that don't exist yet (after asking).
+/// Returns the project's metadata as well for printing the summary.
+async fn parse_tickets(
+    arguments: &Arguments,
+    create_arguments: &CreateArguments,
+    config: &Config,
+    client: &reqwest::Client,
+    texts: &[&str],
+    lines: &[usize],
+    default_board_id: Option<Id>,
+) -> (Vec<Ticket>, core::Metadata) {
+    let spinner = output::spinner(Message::FetchingMetadata);
+    let mut metadata = get_metadata(client).await;
+    spinner.finish_and_clear();
+
+    let (texts, overrides): (Vec<String>, Vec<_>) = texts
+        .iter()
+        .map(
+            |text| match hooks::run(Hook::PreParse, &json!({ "text": text })) {
+                Some(changed) => changed["text"]
+                    .as_str()
+                    .map(str::to_owned)
+                    .expect("To get {\"text\": ...} back from the pre_parse hook"),
+                None => text.to_string(),
+            },
+        )
+        .map(|text| take_overrides(&annotate::strip(&text)))
+        .map(|(text, overrides)| {
+            let (text, fields) = custom_fields::take(&text);
+            let text = resolve_aliases(&text, config, &metadata);
+            (
+                expand_tags(&text, config, arguments.dry_run),
+                (overrides, fields),
+            )
+        })
+        .unzip();
+
+    // NOTE: Custom fields aren't part of the metadata, they're only fetched when a ticket sets one
+    let custom_field_definitions = if overrides.iter().any(|(_, fields)| !fields.is_empty()) {
+        if arguments.offline {
+            output::error(
+                "Tickets set custom fields with `field.<name>:`, which --offline can't check",
+            );
+            output::exit_with_error();
+        }
+
+        custom_fields::definitions(client).await
+    } else {
+        vec![]
+    };
 
-        match_tags_and_categories(&title, &available_categories, &available_tags)
+    // NOTE: Milestones aren't part of the metadata either, they're only fetched when a custom
+    // token puts a ticket in one or when dates can count from the start of a board's milestone
+    let milestone_token = config.tokens.iter().any(|token| {
+        token.field == TokenField::Milestone
+            && texts.iter().any(|text| token.pattern.is_match(text))
+    });
+    if milestone_token && arguments.offline {
+        output::error("Tickets use a custom token for milestones, which --offline can't find");
+        output::exit_with_error();
+    }
+    let milestone_dates = !arguments.offline
+        && metadata
+            .boards
             .iter()
+            .any(|(_, _, milestone_id)| milestone_id.is_some())
+        && texts
+            .iter()
+            .any(|text| MARKUP.due_date.is_match(text) || MARKUP.start_date.is_match(text));
+    let milestones = if milestone_token || milestone_dates {
+        get_models::<models::Milestone>(client, "milestones").await
+    } else {
+        vec![]
+    };
+
+    // Pre-pass for checking tags and verifying data
+    let mut unmatched_tags: Vec<String> = vec![];
+    for text in &texts {
+        // FIXME: Verify that there is only one '===' in the string (double tickets)
+        let mut chunks = TITLE_SEPARATOR.splitn(text, 2);
+        let title = chunks.next().unwrap().trim().to_owned();
+        let description = chunks.next().unwrap_or("");
+
+        let description_tags = if create_arguments.scan_description {
+            MARKUP.match_tags_and_categories(
+                &SUBTASK_MATCHER.replace_all(description, ""),
+                &metadata.categories,
+                &metadata.tags,
+            )
+        } else {
+            vec![]
+        };
+
+        let custom_tags = match_custom_tokens(&MARKUP.strip_builtin_tokens(&title), &config.tokens)
+            .into_iter()
+            .filter(|token| matches!(token.field, TokenField::Tag))
+            .map(|token| match_tag_or_category(&token.value, &metadata.categories, &metadata.tags));
+
+        MARKUP
+            .match_tags_and_categories(&title, &metadata.categories, &metadata.tags)
+            .into_iter()
+            .chain(description_tags)
+            .chain(custom_tags)
             .filter_map(|tag_or_category| {
                 if let Tag::UnaddedTag(tag) = tag_or_category {
-                    Some(tag.to_owned())
+                    Some(tag)
                 } else {
                     None
                 }
@@ -428,85 +2372,302 @@ async fn main() {
             .for_each(|tag| unmatched_tags.push(tag));
     }
 
-    unmatched_tags.sort();
-    unmatched_tags.dedup();
+    unmatched_tags.sort_by_key(|tag| normalize_name(tag));
+    unmatched_tags.dedup_by_key(|tag| normalize_name(tag));
 
-    if !arguments.dry_run && unmatched_tags.len() > 0 {
-        match inquire::Confirm::new(&format!("Could not find tags on Hack'n'Plan for the following list, would you like to add these in bulk?\n{unmatched_tags:#?}"))
-                .with_default(false)
-                .prompt() {
-            Ok(true) => {},
-            _ => return
+    // NOTE: A tag that's close to an existing one is more likely a typo than a new tag
+    for tag in &unmatched_tags {
+        let suggestion = core::did_you_mean('#', tag, core::known_tags(&metadata));
+        if !suggestion.is_empty() {
+            output::warn(format!("\"{tag}\" isn't a tag or category yet{suggestion}"));
         }
     }
 
-    add_unmatched_tags(&client, unmatched_tags, &arguments).await;
+    // NOTE: Lenient runs keep unknown tags as text, unless asked to create them
+    if arguments.lenient && !create_arguments.create_missing_tags {
+        unmatched_tags.clear();
+    }
 
-    let available_tags = get_available_tags(&client).await;
+    if !unmatched_tags.is_empty() {
+        let create_missing_tags = if create_arguments.fail_on_missing_tags || arguments.strict {
+            false
+        } else if create_arguments.create_missing_tags || arguments.dry_run {
+            true
+        } else if arguments.non_interactive {
+            output::warn("Running --non-interactive without --create-missing-tags, treating missing tags as an error");
+            false
+        } else {
+            output::confirm(Message::ConfirmAddTags(&format!("{unmatched_tags:#?}")))
+        };
 
-    let mut tickets: Vec<Ticket> = vec![];
-    for text in &texts {
-        let mut chunks = text.split("===");
-        let title = chunks.next().unwrap().trim().to_owned();
+        if !create_missing_tags {
+            output::error(Message::TagsNotFound(&unmatched_tags.join(", ")));
+            output::exit_with_error();
+        }
+    }
+
+    metadata.tags.extend(
+        add_unmatched_tags(
+            client,
+            unmatched_tags,
+            &metadata.categories,
+            arguments,
+            config,
+        )
+        .await,
+    );
 
-        let title = if let Some(category) = default_category {
-            format!("{title} #{category}")
+    let mut options = config.markup_options(create_arguments.scan_description);
+    options.strictness = arguments.strictness();
+    let default_stage_id = create_arguments.stage.as_deref().map(|stage| {
+        core::find_stage(stage, &metadata.stages).unwrap_or_else(|expectation| {
+            output::error(core::expected(&expectation));
+            output::exit_with_error();
+        })
+    });
+
+    // NOTE: What the existing work items depend on, to check that tickets don't depend on a
+    // cycle. It's only fetched when a ticket has dependencies.
+    let dependency_graph =
+        if !arguments.offline && texts.iter().any(|text| DEPENDENCY_MATCHER.is_match(text)) {
+            get_work_items::<models::WorkItem>(client)
+                .await
+                .into_iter()
+                .map(|work_item| (work_item.work_item_id, work_item.dependency_ids))
+                .collect::<HashMap<Id, Vec<Id>>>()
         } else {
-            title
+            HashMap::new()
+        };
+
+    let completed_stage_id = metadata
+        .stages
+        .iter()
+        .find(|stage| stage.status == "completed")
+        .map(|stage| stage.stage_id);
+
+    let mut tickets: Vec<Ticket> = vec![];
+    let mut failed = false;
+    for (index, (text, (mut overrides, fields))) in texts.iter().zip(overrides).enumerate() {
+        // NOTE: Reported like the limits, so every ticket with a mistake shows up at once
+        let report = |expectation: &str| {
+            output::error_at(output::Diagnostic {
+                code: "parse",
+                message: core::expected(expectation),
+                line: lines.get(index).copied(),
+                ..Default::default()
+            });
         };
 
-        let categories_or_tags =
-            match_tags_and_categories(&title, &available_categories, &available_tags);
-        let mentions = match_mentions(&title, &available_users);
-        let estimate = get_estimate(&title);
-        let importance_level = get_importance_level(&title, &available_importance_levels);
-        // TODO: Implement dependencies
-        // let dependencies =
+        let parsed = core::parse_ticket(&MARKUP, text, &metadata, &options);
+        let Ok(core::Parsed {
+            title,
+            mut categories_or_tags,
+            mut mentions,
+            mut estimate,
+            mut importance_level,
+            mut due_date,
+            mut start_date,
+            stage_id,
+            is_story,
+            description,
+            mut sub_tasks,
+            unresolved,
+            skip_definition_of_done,
+        }) = parsed.inspect_err(|expectation| report(expectation))
+        else {
+            failed = true;
+            continue;
+        };
+
+        for token in unresolved {
+            output::warn_at(output::Diagnostic {
+                code: "unresolved-token",
+                message: format!(
+                    "Keeping {token} as text in \"{}\", it doesn't resolve",
+                    title.split_whitespace().collect::<Vec<&str>>().join(" ")
+                ),
+                line: lines.get(index).copied(),
+                ..Default::default()
+            });
+        }
+        let mut board_id = default_board_id.unwrap_or_default();
+        let mut board_from_token = false;
+
+        // Custom tokens from the config are evaluated after the built-in ones and take precedence
+        for CustomTokenMatch { field, value } in match_custom_tokens(&title, &config.tokens) {
+            let name = normalize_name(&value);
+            let resolved = match field {
+                TokenField::Tag => {
+                    categories_or_tags.push(match_tag_or_category(
+                        &value,
+                        &metadata.categories,
+                        &metadata.tags,
+                    ));
+                    Ok(())
+                }
+                TokenField::Category => metadata
+                    .categories
+                    .iter()
+                    .find(|(_, category)| normalize_name(category) == name)
+                    .map(|(id, category)| {
+                        categories_or_tags.insert(0, Tag::Category(*id, category.to_owned()))
+                    })
+                    .ok_or_else(|| format!("To find a category for \"{value}\"")),
+                TokenField::Importance => core::find_importance_level(
+                    &value,
+                    &metadata.importance_levels,
+                    &config.markup_options(false),
+                )
+                .map(|id| importance_level = id),
+                TokenField::Assignee => core::find_user(&value, &metadata.users)
+                    .map(|(id, _, _)| mentions.push(*id))
+                    .ok_or_else(|| core::user_not_found(&value, &metadata.users)),
+                TokenField::Board => metadata
+                    .boards
+                    .iter()
+                    .find(|(_, board, _)| normalize_name(board) == name)
+                    .map(|(id, _, _)| {
+                        board_id = *id;
+                        board_from_token = true;
+                    })
+                    .ok_or_else(|| format!("To find a board for \"{value}\"")),
+                TokenField::Milestone => milestones
+                    .iter()
+                    .find(|milestone| {
+                        normalize_name(&milestone.name) == name
+                            || milestone.milestone_id.to_string() == value
+                    })
+                    .ok_or_else(|| format!("To find a milestone for \"{value}\""))
+                    .and_then(|milestone| {
+                        metadata
+                            .boards
+                            .iter()
+                            .find(|(_, _, milestone_id)| {
+                                *milestone_id == Some(milestone.milestone_id)
+                            })
+                            .ok_or_else(|| {
+                                format!("To find a board in milestone \"{}\"", milestone.name)
+                            })
+                    })
+                    .map(|(id, _, _)| {
+                        board_id = *id;
+                        board_from_token = true;
+                    }),
+                // NOTE: The value uses the same syntax as the built-in `~` estimate
+                TokenField::Estimate => {
+                    get_estimate(&format!("~{value}"), config).map(|amount| estimate = amount)
+                }
+            };
+
+            if let Err(expectation) = resolved {
+                report(&expectation);
+                failed = true;
+            }
+        }
 
-        // Remove all entries of tags, mentions
-        let title = HASH_TAG_MATCHER.replace_all(&title, "");
-        let title = MENTION_MATCHER.replace_all(&title, "");
-        let title = ESTIMATE_MATCHER.replace_all(&title, "");
-        let title = URGENCY_MATCHER.replace_all(&title, "");
+        let title = strip_custom_tokens(&title, &config.tokens);
 
         // Remove all double spaces
-        let title = title
-            .trim()
-            .split_whitespace()
-            .collect::<Vec<&str>>()
-            .join(" ");
+        let title = title.split_whitespace().collect::<Vec<&str>>().join(" ");
 
-        let description = chunks.next().unwrap_or("").trim().to_owned();
+        // NOTE: A board written on the ticket wins over the routing, which wins over the default board
+        let category = categories_or_tags.iter().find_map(|entry| match entry {
+            Tag::Category(_, category) => Some(category.as_str()),
+            _ => None,
+        });
+        if let Some((category, board)) = category
+            .filter(|_| !board_from_token)
+            .and_then(|category| Some((category, config.routing.board(category)?)))
+        {
+            match metadata
+                .boards
+                .iter()
+                .find(|(_, name, _)| name.eq_ignore_ascii_case(board))
+            {
+                Some((id, _, _)) if arguments.dry_run => {
+                    board_id = *id;
+                    output::info(format!(
+                        "Routing \"{title}\" to board \"{board}\" for category {category}"
+                    ));
+                }
+                Some((id, _, _)) => board_id = *id,
+                None => {
+                    report(&format!(
+                        "To find a board for \"{board}\" to route {category} to"
+                    ));
+                    failed = true;
+                }
+            }
+        }
 
-        let description = MENTION_MATCHER
-            .replace_all(&description, |capture: &regex::Captures| {
-                let mention = capture.get(0).unwrap().as_str().replace('@', "");
+        // NOTE: The board is only known now, so the dates are read again counting from the start
+        // of its milestone
+        if let Some(start) = milestone_start(board_id, &metadata.boards, &milestones)
+            .filter(|_| due_date.is_some() || start_date.is_some())
+        {
+            let options = core::Options {
+                today: Some(start),
+                ..options.clone()
+            };
+            match core::parse_ticket(&MARKUP, text, &metadata, &options) {
+                Ok(parsed) => (due_date, start_date) = (parsed.due_date, parsed.start_date),
+                Err(expectation) => {
+                    report(&expectation);
+                    failed = true;
+                }
+            }
+        }
 
-                let user_name = available_users
-                    .iter()
-                    .find(|(_, name, _)| name.to_lowercase().matches(&mention).count() != 0)
-                    .expect(&format!("To find a user for user_name: {mention}"))
-                    .2 // NOTE: This is the third entry in the tuple: the `user_name`
-                    .to_owned();
+        if !skip_definition_of_done {
+            add_definition_of_done(&mut sub_tasks, category, &title, config, arguments.dry_run);
+        }
 
-                format!("@{user_name}")
-            })
-            .to_string();
+        let description = match category.and_then(|category| config.templates.description(category))
+        {
+            Some(path) => append_template(&description, path),
+            None => description,
+        };
 
-        let subtasks = SUBTASK_MATCHER
-            .find_iter(&description)
-            .map(|subtask| subtask.as_str().replace("[]", "").trim().to_owned())
-            .collect::<Vec<String>>();
+        let (description, dependencies) =
+            resolve_references(client, &description, &title, config, &dependency_graph).await;
 
-        let description = SUBTASK_MATCHER
-            .replace_all(&description, "")
-            .trim()
-            .to_string();
+        let description = if config.markup.convert_markdown {
+            let (description, warnings) = markdown::to_hacknplan(&description);
+            for warning in warnings {
+                output::warn(format!("Description of \"{title}\": {warning}"));
+            }
+
+            description
+        } else {
+            description
+        };
+
+        if !fields.is_empty() {
+            match custom_fields::resolve(&fields, &custom_field_definitions, &title) {
+                Ok(custom_fields) => {
+                    overrides.insert("customFields".to_owned(), custom_fields);
+                }
+                Err(expectation) => {
+                    report(&expectation);
+                    failed = true;
+                }
+            }
+        }
+
+        if completed_stage_id.is_none()
+            && sub_tasks
+                .iter()
+                .any(|sub_task| sub_task.is_completed && sub_task.is_work_item())
+        {
+            output::warn(format!(
+                "No completed stage for the completed sub tasks of \"{title}\", they stay open"
+            ));
+        }
 
         tickets.push(Ticket {
             title: title.to_owned(),
             description,
-            assigned_user_ids: mentions.iter().map(|(id, _, _)| *id).collect::<Vec<Id>>(),
+            assigned_user_ids: mentions,
             tag_ids: categories_or_tags
                 .iter()
                 .filter_map(|entry| match entry {
@@ -523,49 +2684,397 @@ async fn main() {
                         None
                     }
                 })
-                .expect(&format!(
-                    "To have atleast one category available for ticket: {title}"
-                )),
+                .unwrap_or_else(|| {
+                    panic!("To have atleast one category available for ticket: {title}")
+                }),
             estimated_cost: estimate,
-            sub_tasks: subtasks,
+            // NOTE: Sub tasks that are work items need a story to belong to, whatever `type:` says
+            is_story: is_story.unwrap_or_default() || sub_tasks.iter().any(SubTask::is_work_item),
+            sub_tasks,
             importance_level_id: importance_level,
+            board_id,
+            start_date: start_date.unwrap_or_default(),
+            due_date: due_date.unwrap_or_default(),
+            dependency_ids: dependencies,
+            stage_id: stage_id.or(default_stage_id),
+            completed_stage_id,
+            overrides,
             ..Default::default()
         });
     }
 
+    if failed {
+        output::error(Message::TicketsDontParse);
+        output::exit_with_error();
+    }
+
+    if let Some(template) = create_arguments
+        .title_template
+        .as_deref()
+        .or(config.markup.title_template.as_deref())
+    {
+        let milestones = if template.contains("{milestone}") {
+            get_available_milestones(client).await
+        } else {
+            vec![]
+        };
+        apply_title_template(
+            template,
+            &mut tickets,
+            &metadata,
+            &milestones,
+            create_arguments.milestone.as_deref(),
+        );
+    }
+
+    for ticket in &mut tickets {
+        if let Some(payload) = hooks::run(Hook::PostResolve, &ticket.payload()) {
+            ticket.apply(payload);
+        }
+    }
+
+    if let Some(path) = config.hooks.transform_script() {
+        #[cfg(feature = "scripting")]
+        script::transform(&path, &mut tickets, &metadata);
+        #[cfg(not(feature = "scripting"))]
+        output::warn(format!(
+            "Not running {}, hnp was built without the `scripting` feature",
+            path.display()
+        ));
+    }
+
+    (tickets, metadata)
+}
+
+/// Replaces the title of `text`, the part before `===`, with what `change` makes of it
+fn change_title(text: &str, change: impl FnOnce(&str) -> String) -> String {
+    let end = TITLE_SEPARATOR
+        .find(text)
+        .map_or(text.len(), |separator| separator.start());
+    let (title, rest) = text.split_at(end);
+
+    format!("{}{rest}", change(title))
+}
+
+/// Writes the full names from `aliases` of the config in place of the shorthands in the title
+/// of `text`
+fn resolve_aliases(text: &str, config: &Config, metadata: &core::Metadata) -> String {
+    let aliases = &config.aliases;
+    if aliases.users.is_empty() && aliases.tags.is_empty() {
+        return text.to_owned();
+    }
+
+    change_title(text, |title| {
+        let title = MARKUP
+            .hash_tag
+            .replace_all(title, |captures: &regex::Captures| {
+                let tag = captures[0].trim_start_matches('#').trim_matches('"');
+                aliases
+                    .tag(tag)
+                    .map_or(captures[0].to_owned(), input::hash_tag)
+            });
+
+        // NOTE: Full names can contain spaces, so the mention becomes the username
+        MENTION_MATCHER
+            .replace_all(&title, |captures: &regex::Captures| {
+                match aliases.user(captures[0].trim_start_matches('@')) {
+                    Some(user_name) => format!("@{}", find_user(user_name, &metadata.users).2),
+                    None => captures[0].to_owned(),
+                }
+            })
+            .into_owned()
+    })
+}
+
+/// Writes the expansions from `tags.expansions` of the config in place of the tags in the
+/// title of `text`
+fn expand_tags(text: &str, config: &Config, dry_run: bool) -> String {
+    if config.tags.expansions.is_empty() {
+        return text.to_owned();
+    }
+
+    change_title(text, |title| {
+        MARKUP
+            .hash_tag
+            .replace_all(title, |captures: &regex::Captures| {
+                let hash_tag = &captures[0];
+                let tag = hash_tag.trim_start_matches('#').trim_matches('"');
+                match config.tags.expansion(tag) {
+                    Some(expansion) => {
+                        if dry_run {
+                            output::info(format!(
+                                "Expanding {hash_tag} to \"{expansion}\" in \"{}\"",
+                                title.trim()
+                            ));
+                        }
+                        expansion.to_owned()
+                    }
+                    None => hash_tag.to_owned(),
+                }
+            })
+            .into_owned()
+    })
+}
+
+/// Fills in `template` for the title of every ticket. A placeholder without a value, like
+/// `{milestone}` for a ticket in the backlog, is left empty with a warning.
+fn apply_title_template(
+    template: &str,
+    tickets: &mut [Ticket],
+    metadata: &core::Metadata,
+    milestones: &[(Id, String)],
+    default_milestone: Option<&str>,
+) {
     for ticket in tickets {
-        if !arguments.dry_run {
-            println!(
-                "☁️ Uploading ticket:\n{}",
-                serde_json::to_string_pretty(&ticket).unwrap()
-            );
+        let board = metadata
+            .boards
+            .iter()
+            .find(|(id, _, _)| *id == ticket.board_id);
+        let milestone = board
+            .and_then(|(_, _, milestone_id)| *milestone_id)
+            .and_then(|milestone_id| {
+                milestones
+                    .iter()
+                    .find(|(id, _)| *id == milestone_id)
+                    .map(|(_, name)| name.as_str())
+            })
+            .or(default_milestone);
+        let category = metadata
+            .categories
+            .iter()
+            .find(|(id, _)| *id == ticket.category_id)
+            .map(|(_, name)| name.as_str());
 
-            client
-                .get(format!(
-                    "{API_ENDPOINT}/projects/{PROJECT_ID}/categories",
-                    PROJECT_ID = *PROJECT_ID
-                ))
-                .header(
-                    "Authorization",
-                    format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
-                )
-                .json(&ticket)
-                .send()
-                .await
-                .expect(&format!(
-                    r#"to send ticket "{}" successfully"#,
-                    ticket.title
-                ))
-                .error_for_status()
-                .expect(&format!(
-                    r#"to send ticket "{}" successfully"#,
+        let mut title = template.to_owned();
+        for (placeholder, value) in [
+            ("{category}", category),
+            ("{board}", board.map(|(_, name, _)| name.as_str())),
+            ("{milestone}", milestone),
+        ] {
+            if !template.contains(placeholder) {
+                continue;
+            }
+
+            if value.is_none() {
+                output::warn(format!(
+                    "No value for {placeholder} in the title template of \"{}\"",
                     ticket.title
                 ));
-        } else {
-            println!(
-                "💨 \"Pretend\" Uploading ticket:\n{}",
-                serde_json::to_string_pretty(&ticket).unwrap()
-            );
+            }
+            title = title.replace(placeholder, value.unwrap_or_default());
+        }
+        let title = title.replace("{title}", &ticket.title);
+
+        // NOTE: Empty placeholders would otherwise leave double spaces behind
+        ticket.title = title.split_whitespace().collect::<Vec<&str>>().join(" ");
+    }
+}
+
+/// Adds the sub tasks of the definition of done for `category` that the ticket doesn't have yet
+fn add_definition_of_done(
+    sub_tasks: &mut Vec<SubTask>,
+    category: Option<&str>,
+    title: &str,
+    config: &Config,
+    dry_run: bool,
+) {
+    let missing = config
+        .definition_of_done
+        .checklist(category)
+        .into_iter()
+        .filter(|item| {
+            !sub_tasks
+                .iter()
+                .any(|sub_task| sub_task.title.eq_ignore_ascii_case(item))
+        })
+        .collect::<Vec<&str>>();
+    if missing.is_empty() {
+        return;
+    }
+
+    if dry_run {
+        output::info(format!(
+            "Adding the definition of done to \"{title}\": {}",
+            missing.join(", ")
+        ));
+    }
+
+    sub_tasks.extend(missing.into_iter().map(|item| SubTask {
+        title: item.to_owned(),
+        is_completed: false,
+        assigned_user_ids: vec![],
+        estimated_cost: 0.0,
+    }));
+}
+
+/// `description` followed by the template at `path`, unless the description already has it
+fn append_template(description: &str, path: &std::path::Path) -> String {
+    let template = fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("To read the description template {}", path.display()));
+    let template = template.trim();
+
+    // NOTE: Tickets written from existing work items, like with `hnp sync`, already have it
+    if description.contains(template) {
+        description.to_owned()
+    } else if description.is_empty() {
+        template.to_owned()
+    } else {
+        format!("{description}\n\n{template}")
+    }
+}
+
+/// Takes the ```json blocks out of `text`, returning what's left with the fields they set. This
+/// allows setting fields of the API that the markup doesn't cover, like `"isStory": true`.
+fn take_overrides(text: &str) -> (String, serde_json::Map<String, serde_json::Value>) {
+    let mut overrides = serde_json::Map::new();
+
+    for captures in OVERRIDES_MATCHER.captures_iter(text) {
+        let title = text.trim_start().lines().next().unwrap_or_default().trim();
+        match serde_json::from_str::<serde_json::Value>(&captures["json"]) {
+            Ok(serde_json::Value::Object(fields)) => overrides.extend(fields),
+            Ok(_) => panic!("To have an object in the ```json block of \"{title}\""),
+            Err(error) => panic!("To parse the ```json block of \"{title}\": {error}"),
+        }
+    }
+
+    (
+        OVERRIDES_MATCHER.replace_all(text, "").into_owned(),
+        overrides,
+    )
+}
+
+/// Uploads `ticket` together with the work items for its sub tasks, recording each in `run` as
+/// soon as it's created. Returns the id of the work item for `ticket` itself.
+// NOTE: `run` is shared, as several tickets are uploaded at once with --batch-size
+async fn upload_with_children(
+    client: &reqwest::Client,
+    ticket: &Ticket,
+    run: &Mutex<history::Run>,
+) -> Id {
+    let payload = ticket.outgoing_payload();
+    let work_item_id = upload_payload(client, &ticket.title, &payload).await;
+    uploaded(run, work_item_id, payload);
+    complete_sub_tasks(client, work_item_id, ticket).await;
+
+    if let Some(stage_id) = ticket.stage_id {
+        clone::set_stage(client, work_item_id, stage_id).await;
+    }
+
+    for child in ticket.child_work_items(work_item_id) {
+        let payload = child.outgoing_payload();
+        let child_id = upload_payload(client, &child.title, &payload).await;
+        uploaded(run, child_id, payload);
+
+        if let Some(stage_id) = child.stage_id {
+            clone::set_stage(client, child_id, stage_id).await;
         }
     }
+
+    work_item_id
+}
+
+/// Records the work item in `run` and passes it on to the `post_upload` hook
+fn uploaded(run: &Mutex<history::Run>, work_item_id: Id, payload: serde_json::Value) {
+    let mut notification = payload.clone();
+    notification["workItemId"] = work_item_id.into();
+    hooks::notify(Hook::PostUpload, &notification);
+
+    run.lock()
+        .expect("To lock the run history")
+        .record(work_item_id, payload);
+}
+
+/// Lets the user choose which of `texts` to upload, all of them are selected at first
+fn pick_tickets<'a>(texts: Vec<(usize, &'a str)>, arguments: &Arguments) -> Vec<(usize, &'a str)> {
+    if arguments.non_interactive {
+        output::error(
+            "--pick needs to ask which tickets to upload, which --non-interactive prevents",
+        );
+        output::exit_with_error();
+    }
+
+    let titles = texts
+        .iter()
+        .map(|(_, text)| {
+            let title = TITLE_SEPARATOR.splitn(text, 2).next().unwrap_or_default();
+            MARKUP
+                .strip_builtin_tokens(title)
+                .split_whitespace()
+                .collect::<Vec<&str>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>();
+
+    let picked = output::pick(Message::PickTickets, titles).unwrap_or_else(|| {
+        output::info(Message::NothingWas(Action::Uploaded));
+        output::exit_with_error();
+    });
+
+    if picked.is_empty() {
+        output::info(Message::NoTicketsPicked);
+        std::process::exit(0);
+    }
+
+    picked.into_iter().map(|index| texts[index]).collect()
+}
+
+/// Set by the first Ctrl-C while uploading, so the ticket being uploaded is finished first
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Makes the first Ctrl-C stop uploading after the current ticket, a second one stops right away.
+/// Only listens once, however many projects the tickets are uploaded to.
+fn handle_interrupts() {
+    static LISTENING: std::sync::Once = std::sync::Once::new();
+
+    LISTENING.call_once(|| {
+        tokio::spawn(async {
+            tokio::signal::ctrl_c().await.expect("To listen for Ctrl-C");
+            INTERRUPTED.store(true, Ordering::SeqCst);
+            output::warn(Message::StoppingAfterCurrentTicket);
+
+            tokio::signal::ctrl_c().await.expect("To listen for Ctrl-C");
+            std::process::exit(output::EXIT_INTERRUPTED);
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::{Arguments, SubTask, Ticket};
+
+    #[test]
+    fn arguments_have_no_clashing_flags() {
+        Arguments::command().debug_assert();
+    }
+
+    #[test]
+    fn completes_checked_sub_tasks_that_become_work_items() {
+        let ticket = Ticket {
+            title: "Jumping".to_owned(),
+            completed_stage_id: Some(4),
+            sub_tasks: vec![
+                SubTask {
+                    title: "Polish jump".to_owned(),
+                    is_completed: true,
+                    assigned_user_ids: vec![7],
+                    ..Default::default()
+                },
+                SubTask {
+                    title: "Tune gravity".to_owned(),
+                    is_completed: false,
+                    estimated_cost: 2.0,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let children = ticket.child_work_items(12);
+
+        assert_eq!(children[0].stage_id, Some(4));
+        assert_eq!(children[1].stage_id, None);
+        assert!(children.iter().all(|child| child.parent_id == 12));
+    }
 }
@@ -0,0 +1,161 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref HEADING_MATCHER: Regex =
+        Regex::new(r"^#{1,6}\s+(?<text>.+?)\s*#*\s*$").expect("Heading Regex to compile");
+    static ref TABLE_DIVIDER_MATCHER: Regex =
+        Regex::new(r"^\|?\s*:?-{3,}:?\s*(\|\s*:?-{3,}:?\s*)*\|?\s*$")
+            .expect("Table divider Regex to compile");
+    static ref TASK_MATCHER: Regex =
+        Regex::new(r"^(?<indent>\s*)(?<bullet>(?:[-*+]|\d+\.)\s+)?\[(?<checked>[ xX]?)\]\s*")
+            .expect("Task Regex to compile");
+    static ref IMAGE_MATCHER: Regex =
+        Regex::new(r"!\[[^\]]*\]\([^)]*\)").expect("Image Regex to compile");
+    static ref HTML_MATCHER: Regex =
+        Regex::new(r"</?[a-zA-Z][a-zA-Z0-9]*(\s[^>]*)?/?>").expect("HTML Regex to compile");
+    static ref FOOTNOTE_MATCHER: Regex =
+        Regex::new(r"\[\^[^\]]+\]").expect("Footnote Regex to compile");
+}
+
+/// Converts a Markdown description into the subset Hack'n'Plan renders, returning the converted
+/// text and warnings for constructs that will still render badly.
+///
+/// - Headings become bold lines
+/// - Fenced code blocks become indented code blocks
+/// - Tables become a list with one item per row
+/// - Task list items that weren't turned into sub tasks become ☐ and ☑ characters
+pub fn to_hacknplan(description: &str) -> (String, Vec<String>) {
+    let mut lines: Vec<String> = vec![];
+    let mut warnings: Vec<String> = vec![];
+    let mut in_code_block = false;
+    let mut table_header: Option<Vec<String>> = None;
+
+    let source_lines = description.lines().collect::<Vec<&str>>();
+    for (index, line) in source_lines.iter().enumerate() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+
+        if in_code_block {
+            lines.push(format!("    {line}"));
+            continue;
+        }
+
+        if is_table_row(trimmed) {
+            let next_is_divider = source_lines
+                .get(index + 1)
+                .is_some_and(|next| TABLE_DIVIDER_MATCHER.is_match(next.trim()));
+
+            if next_is_divider {
+                table_header = Some(table_cells(trimmed));
+                continue;
+            }
+
+            if TABLE_DIVIDER_MATCHER.is_match(trimmed) && table_header.is_some() {
+                continue;
+            }
+
+            if let Some(header) = &table_header {
+                let row = header
+                    .iter()
+                    .zip(table_cells(trimmed))
+                    .filter(|(_, cell)| !cell.is_empty())
+                    .map(|(column, cell)| format!("{column}: {cell}"))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                lines.push(format!("- {row}"));
+                continue;
+            }
+        } else {
+            table_header = None;
+        }
+
+        if let Some(captures) = HEADING_MATCHER.captures(trimmed) {
+            lines.push(format!("**{}**", &captures["text"]));
+            continue;
+        }
+
+        let line = TASK_MATCHER.replace(line, |captures: &regex::Captures| {
+            let checkbox = if captures["checked"].trim().is_empty() {
+                "☐"
+            } else {
+                "☑"
+            };
+
+            format!("{}{checkbox} ", &captures["indent"])
+        });
+
+        if IMAGE_MATCHER.is_match(&line) {
+            warnings.push(format!("images won't be shown: {}", line.trim()));
+        }
+
+        if HTML_MATCHER.is_match(&line) {
+            warnings.push(format!("HTML will be shown as text: {}", line.trim()));
+        }
+
+        if FOOTNOTE_MATCHER.is_match(&line) {
+            warnings.push(format!("footnotes aren't supported: {}", line.trim()));
+        }
+
+        lines.push(line.into_owned());
+    }
+
+    if in_code_block {
+        warnings.push("code block is never closed".to_owned());
+    }
+
+    (lines.join("\n"), warnings)
+}
+
+fn is_table_row(line: &str) -> bool {
+    line.starts_with('|') && line.ends_with('|') && line.len() > 1
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    line.trim_matches('|')
+        .split('|')
+        .map(|cell| cell.trim().to_owned())
+        .collect::<Vec<String>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_hacknplan;
+
+    #[test]
+    fn indents_fenced_code_blocks() {
+        let (text, warnings) = to_hacknplan("Run:\n```sh\ncargo build\n```\nDone");
+
+        assert_eq!(text, "Run:\n    cargo build\nDone");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn turns_tables_into_lists() {
+        let (text, warnings) =
+            to_hacknplan("| Key | Action |\n| --- | :---: |\n| A | Jump |\n| B | |\nAfter");
+
+        assert_eq!(text, "- Key: A, Action: Jump\n- Key: B\nAfter");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn keeps_the_indent_of_task_list_items() {
+        let (text, _) = to_hacknplan("- [ ] Open\n  - [x] Done\n  * [X] Also done");
+
+        assert_eq!(text, "☐ Open\n  ☑ Done\n  ☑ Also done");
+    }
+
+    #[test]
+    fn warns_about_unclosed_code_blocks() {
+        let (text, warnings) = to_hacknplan("~~~\nlet jump = 1;");
+
+        assert_eq!(text, "    let jump = 1;");
+        assert_eq!(warnings, vec!["code block is never closed".to_owned()]);
+    }
+}
@@ -0,0 +1,195 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use clap::{Args, Subcommand};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backup::{restore_collections, COLLECTIONS};
+use crate::i18n::{Action, Message};
+use crate::{client, get_collection, get_json, output, Arguments};
+
+/// Snapshot used instead of fetching metadata from Hack'n'Plan, set by `--metadata`
+static METADATA: OnceLock<Metadata> = OnceLock::new();
+
+#[derive(Subcommand, Debug)]
+pub enum MetadataCommand {
+    /// Print the project's metadata as one JSON document, for --metadata or comparing projects
+    Export,
+    /// Create the categories, tags, importance levels, stages, milestones and boards of an
+    /// exported document that don't exist in this project yet
+    Import(ImportArguments),
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArguments {
+    /// Document made by `hnp metadata export`
+    file: PathBuf,
+
+    /// Create without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// Project metadata in one document, every collection exactly as the API returns it
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct Metadata {
+    pub project: Value,
+    pub categories: Value,
+    pub tags: Value,
+    pub users: Value,
+    pub boards: Value,
+    pub stages: Value,
+    pub milestones: Value,
+    pub importance_levels: Value,
+}
+
+impl Metadata {
+    /// Fetches the metadata of the configured project
+    pub async fn fetch(client: &reqwest::Client) -> Metadata {
+        Metadata {
+            project: get_json(client, "").await,
+            categories: get_collection(client, "categories").await,
+            tags: get_collection(client, "tags").await,
+            users: get_collection(client, "users").await,
+            boards: get_collection(client, "boards").await,
+            stages: get_collection(client, "stages").await,
+            milestones: get_collection(client, "milestones").await,
+            importance_levels: get_collection(client, "importancelevels").await,
+        }
+    }
+
+    pub fn load(path: &Path) -> Metadata {
+        let contents = fs::read_to_string(path)
+            .unwrap_or_else(|_| panic!("To read metadata from {}", path.display()));
+
+        serde_json::from_str(&contents)
+            .unwrap_or_else(|error| panic!("To parse metadata in {}: {error}", path.display()))
+    }
+
+    /// The collection that the API serves at `/projects/{id}/{collection}`
+    pub fn collection(&self, collection: &str) -> Value {
+        let value = match collection {
+            "categories" => &self.categories,
+            "tags" => &self.tags,
+            "users" => &self.users,
+            "boards" => &self.boards,
+            "stages" => &self.stages,
+            "milestones" => &self.milestones,
+            "importancelevels" => &self.importance_levels,
+            _ => panic!("To only look up project metadata, not {collection}"),
+        };
+
+        match value {
+            Value::Null => Value::Array(vec![]),
+            value => value.clone(),
+        }
+    }
+
+    /// Id of the project the snapshot was taken from
+    pub fn project_id(&self) -> Option<i64> {
+        self.project.get("id").and_then(Value::as_i64)
+    }
+}
+
+pub fn set(metadata: Metadata) {
+    METADATA
+        .set(metadata)
+        .expect("metadata to only be set once");
+}
+
+pub fn get() -> Option<&'static Metadata> {
+    METADATA.get()
+}
+
+pub async fn run(arguments: &Arguments, command: &MetadataCommand) {
+    match command {
+        MetadataCommand::Export => export().await,
+        MetadataCommand::Import(import_arguments) => import(arguments, import_arguments).await,
+    }
+}
+
+async fn export() {
+    let client = client();
+
+    let spinner = output::spinner(Message::FetchingMetadata);
+    let metadata = Metadata::fetch(&client).await;
+    spinner.finish_and_clear();
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&metadata).expect("To serialize metadata")
+    );
+}
+
+async fn import(arguments: &Arguments, import_arguments: &ImportArguments) {
+    let metadata = Metadata::load(&import_arguments.file);
+    let client = client();
+
+    // NOTE: Design elements aren't part of the metadata
+    let collections = COLLECTIONS
+        .into_iter()
+        .filter(|(collection, _, _)| !collection.starts_with("design"))
+        .collect::<Vec<_>>();
+
+    let mut missing = vec![];
+    for (collection, _, _) in &collections {
+        let existing = get_json(&client, &format!("/{collection}")).await;
+        let names = |values: &Value| {
+            values
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|value| value.get("name")?.as_str().map(str::to_owned))
+                .collect::<Vec<String>>()
+        };
+
+        let existing = names(&existing);
+        missing.extend(
+            names(&metadata.collection(collection))
+                .into_iter()
+                .filter(|name| {
+                    !existing
+                        .iter()
+                        .any(|existing| existing.eq_ignore_ascii_case(name))
+                })
+                .map(|name| format!("{collection}: {name}")),
+        );
+    }
+
+    if missing.is_empty() {
+        output::success("This project already has everything in the metadata");
+        return;
+    }
+
+    for entry in &missing {
+        output::info(format!("Create {entry}"));
+    }
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if import_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Created));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmCreateEntries(missing.len()))
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Created));
+        return;
+    }
+
+    restore_collections(&client, &collections, |collection| {
+        metadata.collection(collection)
+    })
+    .await;
+
+    output::success(format!("Created {} entries", missing.len()));
+}
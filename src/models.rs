@@ -0,0 +1,185 @@
+use serde::Deserialize;
+
+use crate::Id;
+
+// NOTE: Only the fields hnp uses are modelled, anything else the API returns is ignored
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Category {
+    pub category_id: Id,
+    pub name: String,
+}
+
+/// Member of the project, the API wraps the user itself in a `user` field
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProjectUser {
+    pub user: User,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct User {
+    pub id: Id,
+    pub name: String,
+    pub username: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TagDef {
+    pub tag_id: Id,
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportanceLevel {
+    pub importance_level_id: Id,
+    pub name: String,
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Board {
+    pub board_id: Id,
+    pub name: String,
+    pub milestone_id: Option<Id>,
+}
+
+/// Field a project defines on top of the built-in ones, set with `field.<name>:` in a ticket
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomField {
+    pub custom_field_id: Id,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: CustomFieldType,
+    /// Values an `enum` field can be set to
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomFieldType {
+    Number,
+    Text,
+    Enum,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Milestone {
+    pub milestone_id: Id,
+    pub name: String,
+    pub start_date: Option<String>,
+    pub due_date: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Stage {
+    pub stage_id: Id,
+    pub name: String,
+    /// One of `created`, `started` or `completed`
+    #[serde(default)]
+    pub status: String,
+    /// Whether moving a work item here releases the work items that depend on it
+    #[serde(default)]
+    pub is_unblocker: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DesignElement {
+    pub design_element_id: Id,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkItem {
+    pub work_item_id: Id,
+    pub title: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub is_story: bool,
+    pub parent_story_id: Option<Id>,
+    pub category: Option<Category>,
+    pub importance_level: Option<ImportanceLevel>,
+    pub board: Option<Board>,
+    pub stage: Option<Stage>,
+    pub design_element: Option<DesignElement>,
+    pub estimated_cost: Option<f32>,
+    pub due_date: Option<String>,
+    pub update_date: Option<String>,
+    #[serde(default)]
+    pub dependency_ids: Vec<Id>,
+    #[serde(default)]
+    pub assigned_users: Vec<ProjectUser>,
+    #[serde(default)]
+    pub tags: Vec<TagDef>,
+}
+
+impl WorkItem {
+    /// The story this work item is below, the API uses 0 for none
+    pub fn parent_story_id(&self) -> Option<Id> {
+        self.parent_story_id.filter(|id| *id != 0)
+    }
+
+    pub fn tag_ids(&self) -> Vec<Id> {
+        self.tags.iter().map(|tag| tag.tag_id).collect()
+    }
+
+    pub fn assigned_user_ids(&self) -> Vec<Id> {
+        self.assigned_users
+            .iter()
+            .map(|assigned_user| assigned_user.user.id)
+            .collect()
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.stage
+            .as_ref()
+            .is_some_and(|stage| stage.status == "completed")
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubTask {
+    pub id: Id,
+    pub title: String,
+    #[serde(default)]
+    pub is_completed: bool,
+}
+
+/// File attached to a work item
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub name: String,
+}
+
+/// Paginated response, like the one for work items
+#[derive(Deserialize, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+}
+
+/// Time someone logged on a work item, in hours
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkLog {
+    pub value: f32,
+    pub user: Option<User>,
+    pub creation_date: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    pub text: String,
+    pub creation_date: Option<String>,
+}
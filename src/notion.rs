@@ -0,0 +1,451 @@
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::config::{Config, PropertyField};
+use crate::import::upload_tickets;
+use crate::{client, find_board, input, output, parse_tickets, Arguments, CreateArguments};
+
+lazy_static! {
+    /// Notion puts the page's id behind every file and directory name
+    static ref PAGE_ID_MATCHER: Regex =
+        Regex::new(r"[ \t]+[0-9a-f]{32}$").expect("Page id Regex to compile");
+    static ref HTML_TITLE_MATCHER: Regex =
+        Regex::new(r#"(?s)<h1 class="page-title">(?<title>.*?)</h1>"#)
+            .expect("HTML title Regex to compile");
+    static ref HTML_PROPERTY_MATCHER: Regex =
+        Regex::new(r#"(?s)<tr class="property-row[^"]*"><th>(?<name>.*?)</th><td>(?<value>.*?)</td></tr>"#)
+            .expect("HTML property Regex to compile");
+    static ref HTML_BODY_MATCHER: Regex =
+        Regex::new(r#"(?s)<div class="page-body">(?<body>.*)</div>\s*</article>"#)
+            .expect("HTML body Regex to compile");
+    static ref HTML_TAG_MATCHER: Regex = Regex::new(r"(?s)<[^>]*>").expect("HTML tag Regex to compile");
+    static ref HTML_BLOCK_END_MATCHER: Regex =
+        Regex::new(r"</(?:p|li|h\d|div|pre|blockquote)>").expect("HTML block Regex to compile");
+    static ref MARKDOWN_PROPERTY_MATCHER: Regex =
+        Regex::new(r"^(?<name>[^:\n]{1,60}):[ \t]+(?<value>.+)$")
+            .expect("Markdown property Regex to compile");
+}
+
+#[derive(Args, Debug)]
+pub struct NotionArguments {
+    /// Zip file made by Notion's "Export" (Markdown or HTML), or the directory it was unpacked to
+    export: PathBuf,
+
+    /// Only import the pages below the page or database with this name
+    #[arg(long, value_name = "PAGE")]
+    parent: Option<String>,
+
+    /// Category for the work items that don't get one from their properties
+    #[arg(long)]
+    category: Option<String>,
+
+    /// Put the work items on this board, unless a property names one
+    #[arg(long, value_name = "BOARD")]
+    board: Option<String>,
+
+    /// Create tags that don't exist on Hack'n'Plan yet without asking
+    #[arg(long)]
+    create_missing_tags: bool,
+
+    /// Import without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// A page of the export, as parsed from its Markdown or HTML file
+struct Page {
+    /// Path within the export
+    path: String,
+    title: String,
+    properties: Vec<(String, String)>,
+    body: String,
+}
+
+pub async fn run(arguments: &Arguments, notion_arguments: &NotionArguments, config: &Config) {
+    let files = if notion_arguments.export.is_dir() {
+        read_directory(&notion_arguments.export)
+    } else {
+        read_zip(&notion_arguments.export)
+    };
+
+    let pages = files
+        .into_iter()
+        .filter_map(|(path, contents)| {
+            if path.ends_with(".md") {
+                Some(parse_markdown(path, &contents))
+            } else if path.ends_with(".html") {
+                Some(parse_html(path, &contents))
+            } else {
+                None
+            }
+        })
+        .filter(|page| {
+            notion_arguments.parent.as_ref().is_none_or(|parent| {
+                let mut components = page.path.split('/').collect::<Vec<&str>>();
+                components.pop();
+
+                components
+                    .iter()
+                    .any(|component| strip_page_id(component).eq_ignore_ascii_case(parent))
+            })
+        })
+        .collect::<Vec<Page>>();
+
+    if pages.is_empty() {
+        output::info(match &notion_arguments.parent {
+            Some(parent) => format!("Nothing to import, no pages below \"{parent}\""),
+            None => "Nothing to import, the export has no pages".to_owned(),
+        });
+        return;
+    }
+
+    let unmapped = pages
+        .iter()
+        .flat_map(|page| &page.properties)
+        .map(|(name, _)| name.as_str())
+        .filter(|name| !config.notion.properties.contains_key(*name))
+        .collect::<BTreeSet<&str>>();
+    if !unmapped.is_empty() {
+        output::warn(format!(
+            "Properties {} go in the description, map them to fields in [notion.properties] of the config",
+            unmapped.into_iter().collect::<Vec<&str>>().join(", ")
+        ));
+    }
+
+    let client = client();
+    let default_board_id = match &notion_arguments.board {
+        Some(board) => Some(find_board(&client, board).await),
+        None => None,
+    };
+
+    let mut texts = vec![];
+    let mut boards = vec![];
+    for page in &pages {
+        let mut tokens = vec![];
+        let mut board = None;
+        let mut description = vec![];
+
+        for (name, value) in &page.properties {
+            let Some(field) = config.notion.properties.get(name) else {
+                description.push(format!("{name}: {value}"));
+                continue;
+            };
+
+            let values = value
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty());
+
+            match field {
+                PropertyField::Tag | PropertyField::Category => {
+                    tokens.extend(values.map(input::hash_tag))
+                }
+                // NOTE: Tokens end at a space, the first word is enough to find the level or user
+                PropertyField::Importance => tokens.extend(
+                    values
+                        .filter_map(|value| value.split_whitespace().next())
+                        .map(|value| format!("!{}", value.to_lowercase())),
+                ),
+                PropertyField::Assignee => tokens.extend(
+                    values
+                        .filter_map(|value| value.split_whitespace().next())
+                        .map(|value| format!("@{}", value.to_lowercase())),
+                ),
+                PropertyField::Board => board = values.map(str::to_owned).next(),
+                PropertyField::Estimate => tokens.extend(values.map(|value| {
+                    if value.parse::<f32>().is_ok() {
+                        format!("~{value}h")
+                    } else {
+                        format!("~{}", value.replace(' ', ""))
+                    }
+                })),
+                PropertyField::Stage => {
+                    tokens.extend(values.map(|value| format!("stage:\"{value}\"")))
+                }
+                // NOTE: Date ranges are written as `start → end`
+                PropertyField::Start => tokens.extend(
+                    value
+                        .split('→')
+                        .next()
+                        .and_then(date)
+                        .map(|date| format!("start:{date}")),
+                ),
+                PropertyField::Due => tokens.extend(
+                    value
+                        .split('→')
+                        .next_back()
+                        .and_then(date)
+                        .map(|date| format!("due:{date}")),
+                ),
+            }
+        }
+
+        // NOTE: The first category in a title wins, so the default goes at the end
+        tokens.extend(notion_arguments.category.as_deref().map(input::hash_tag));
+
+        if !description.is_empty() {
+            description.push(String::new());
+        }
+        description.push(page.body.clone());
+
+        texts.push(format!(
+            "{} {}\n===\n{}",
+            page.title,
+            tokens.join(" "),
+            description.join("\n")
+        ));
+        boards.push(board);
+    }
+
+    let create_arguments = CreateArguments {
+        default_category: None,
+        create_missing_tags: notion_arguments.create_missing_tags,
+        fail_on_missing_tags: false,
+        milestone: None,
+        check_capacity: false,
+        auto_assign: None,
+        suggest_estimates: false,
+        apply_suggestions: false,
+        stage: None,
+        scan_description: false,
+        skip: 0,
+        limit: None,
+        batch_size: 1,
+        only: None,
+        pick: false,
+        yes: notion_arguments.yes,
+        annotate: None,
+        title_template: None,
+        input_format: None,
+        files: vec![],
+    };
+
+    let texts = texts.iter().map(String::as_str).collect::<Vec<&str>>();
+    let (mut tickets, _) = parse_tickets(
+        arguments,
+        &create_arguments,
+        config,
+        &client,
+        &texts,
+        &[],
+        default_board_id,
+    )
+    .await;
+
+    for (ticket, board) in tickets.iter_mut().zip(&boards) {
+        if let Some(board) = board {
+            ticket.board_id = find_board(&client, board).await;
+        }
+    }
+
+    let origins = pages
+        .iter()
+        .map(|page| {
+            page.path
+                .split('/')
+                .map(strip_page_id)
+                .collect::<Vec<&str>>()
+                .join(" / ")
+        })
+        .collect::<Vec<String>>();
+
+    upload_tickets(
+        arguments,
+        notion_arguments.yes,
+        &format!("notion {}", notion_arguments.export.display()),
+        &tickets,
+        &origins,
+        |_, _| {},
+    )
+    .await;
+}
+
+/// Files of the export by their path within it
+fn read_zip(path: &Path) -> Vec<(String, String)> {
+    let file = File::open(path).unwrap_or_else(|_| panic!("To open {}", path.display()));
+    let mut archive = zip::ZipArchive::new(file)
+        .unwrap_or_else(|error| panic!("{} to be a zip file: {error}", path.display()));
+
+    let mut files = vec![];
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .unwrap_or_else(|error| panic!("To read {}: {error}", path.display()));
+
+        if entry.is_dir() {
+            continue;
+        }
+
+        let name = entry.name().to_owned();
+        let mut contents = String::new();
+        if entry.read_to_string(&mut contents).is_ok() {
+            files.push((name, contents));
+        }
+    }
+
+    files.sort();
+    files
+}
+
+fn read_directory(directory: &Path) -> Vec<(String, String)> {
+    let mut files = vec![];
+    let mut directories = vec![directory.to_owned()];
+
+    while let Some(current) = directories.pop() {
+        let entries = fs::read_dir(&current)
+            .unwrap_or_else(|_| panic!("To read directory {}", current.display()));
+
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            if path.is_dir() {
+                directories.push(path);
+            } else if let Ok(contents) = fs::read_to_string(&path) {
+                let relative = path.strip_prefix(directory).unwrap_or(&path);
+                let relative = relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+
+                files.push((relative, contents));
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+fn strip_page_id(name: &str) -> &str {
+    let name = name
+        .strip_suffix(".md")
+        .or_else(|| name.strip_suffix(".html"))
+        .unwrap_or(name);
+
+    PAGE_ID_MATCHER
+        .find(name)
+        .map_or(name, |page_id| &name[..page_id.start()])
+}
+
+/// Title from the `# ` heading, properties from the `Name: value` lines right below it
+fn parse_markdown(path: String, contents: &str) -> Page {
+    let mut lines = contents.lines().peekable();
+
+    let title = match lines.peek() {
+        Some(line) if line.starts_with("# ") => {
+            let title = line[2..].trim().to_owned();
+            lines.next();
+            title
+        }
+        _ => strip_page_id(path.rsplit('/').next().unwrap_or_default()).to_owned(),
+    };
+
+    while lines.peek().is_some_and(|line| line.trim().is_empty()) {
+        lines.next();
+    }
+
+    let mut properties = vec![];
+    while let Some(captures) = lines
+        .peek()
+        .and_then(|line| MARKDOWN_PROPERTY_MATCHER.captures(line))
+    {
+        properties.push((
+            captures["name"].trim().to_owned(),
+            captures["value"].trim().to_owned(),
+        ));
+        lines.next();
+    }
+
+    Page {
+        path,
+        title,
+        properties,
+        body: lines.collect::<Vec<&str>>().join("\n").trim().to_owned(),
+    }
+}
+
+fn parse_html(path: String, contents: &str) -> Page {
+    let title = HTML_TITLE_MATCHER
+        .captures(contents)
+        .map(|captures| text(&captures["title"]))
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| strip_page_id(path.rsplit('/').next().unwrap_or_default()).to_owned());
+
+    let properties = HTML_PROPERTY_MATCHER
+        .captures_iter(contents)
+        .map(|captures| {
+            // NOTE: Every value of a multi-select or person property is a span of its own
+            let value = captures["value"].replace("</span><span", "</span>, <span");
+            (text(&captures["name"]), text(&value))
+        })
+        .collect();
+
+    let body = HTML_BODY_MATCHER
+        .captures(contents)
+        .map(|captures| {
+            let body = captures["body"]
+                .replace("<br>", "\n")
+                .replace("<br/>", "\n")
+                .replace("<li>", "<li>- ");
+            let body = HTML_BLOCK_END_MATCHER.replace_all(&body, "$0\n");
+
+            decode_entities(&HTML_TAG_MATCHER.replace_all(&body, ""))
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<&str>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+
+    Page {
+        path,
+        title,
+        properties,
+        body: body.trim().to_owned(),
+    }
+}
+
+/// Text of an HTML fragment, dates are written as `@October 20, 2026`
+fn text(html: &str) -> String {
+    decode_entities(&HTML_TAG_MATCHER.replace_all(html, ""))
+        .trim()
+        .trim_start_matches('@')
+        .to_owned()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Date as YYYY-MM-DD, Notion writes them like `October 20, 2026` or `2026/10/20`
+fn date(value: &str) -> Option<String> {
+    let value = value.trim().trim_start_matches('@');
+    let words = value.split_whitespace().collect::<Vec<&str>>();
+
+    // NOTE: A time can follow the date, like `October 20, 2026 3:00 PM`
+    let date = [3, 1].iter().find_map(|length| {
+        let date = words.get(..*length)?.join(" ");
+        ["%B %d, %Y", "%b %d, %Y", "%Y/%m/%d", "%Y-%m-%d", "%d/%m/%Y"]
+            .iter()
+            .find_map(|format| chrono::NaiveDate::parse_from_str(&date, format).ok())
+    });
+
+    if date.is_none() {
+        output::warn(format!(
+            "\"{value}\" isn't a date hnp understands, skipping it"
+        ));
+    }
+
+    date.map(|date| date.format("%Y-%m-%d").to_string())
+}
@@ -0,0 +1,479 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::models::Stage;
+use crate::{input, normalize_name, output};
+
+lazy_static! {
+    static ref HEADLINE_MATCHER: Regex =
+        Regex::new(r"^(?<stars>\*+)[ \t]+(?<title>.*?)(?:[ \t]+(?<tags>:(?:[\w@#%]+:)+))?[ \t]*$")
+            .expect("Headline Regex to compile");
+    static ref PRIORITY_MATCHER: Regex =
+        Regex::new(r"^\[#[A-Za-z0-9]\][ \t]*").expect("Priority Regex to compile");
+    static ref PLANNING_MATCHER: Regex = Regex::new(
+        r"(?<keyword>SCHEDULED|DEADLINE|CLOSED):[ \t]*[<\[](?<date>\d{4}-\d{2}-\d{2})[^>\]]*[>\]]"
+    )
+    .expect("Planning Regex to compile");
+    static ref PROPERTY_MATCHER: Regex =
+        Regex::new(r"^[ \t]*:(?<name>[^:\s]+):[ \t]*(?<value>.*?)[ \t]*$")
+            .expect("Property Regex to compile");
+    static ref DRAWER_MATCHER: Regex =
+        Regex::new(r"^[ \t]*:[\w-]+:[ \t]*$").expect("Drawer Regex to compile");
+    static ref LINK_MATCHER: Regex =
+        Regex::new(r"\[\[(?<target>[^\]]+)\](?:\[(?<description>[^\]]+)\])?\]")
+            .expect("Link Regex to compile");
+    static ref DURATION_MATCHER: Regex = Regex::new(
+        r"^(?:(?<hours>\d+):(?<minutes>\d{2})|(?<amount>\d+(?:\.\d+)?)[ \t]*(?<unit>min|h|d|w))$"
+    )
+    .expect("Duration Regex to compile");
+}
+
+/// TODO keywords of a file, set with `#+TODO: TODO NEXT | DONE CANCELLED`
+struct Keywords {
+    active: Vec<String>,
+    done: Vec<String>,
+}
+
+/// A headline while its body is being collected
+struct Entry {
+    title: String,
+    keyword: Option<String>,
+    tags: Vec<String>,
+    /// `true` for headlines below a ticket, which become its sub tasks
+    is_sub_task: bool,
+    scheduled: Option<String>,
+    deadline: Option<String>,
+    effort: Option<String>,
+    body: Vec<String>,
+}
+
+/// Converts an org file to tickets in hnp's own syntax.
+///
+/// Headlines with a TODO keyword become tickets, or every top level headline when no headline has
+/// one. Headlines below a ticket become its sub tasks. Tags (including inherited ones) become
+/// tags or categories, SCHEDULED and DEADLINE become start and due dates, the Effort property
+/// becomes the estimate, and keywords move the work item to the stage of the same name. Without
+/// such a stage, the first keyword keeps the first stage, other open keywords go to the first
+/// started stage and done keywords to the first completed one.
+pub fn to_tickets(contents: &str, stages: &[Stage]) -> String {
+    let keywords = keywords(contents);
+    let is_keyword = |word: &str| {
+        keywords.active.iter().any(|keyword| keyword == word)
+            || keywords.done.iter().any(|keyword| keyword == word)
+    };
+
+    let uses_keywords = contents.lines().any(|line| {
+        HEADLINE_MATCHER.captures(line).is_some_and(|captures| {
+            captures["title"]
+                .split_whitespace()
+                .next()
+                .is_some_and(is_keyword)
+        })
+    });
+
+    let mut entries: Vec<Entry> = vec![];
+    // NOTE: Tags of every headline above the current one, org tags are inherited
+    let mut inherited: Vec<(usize, Vec<String>)> = vec![];
+    let mut ticket_level: Option<usize> = None;
+    let mut in_drawer = false;
+
+    for line in contents.lines() {
+        if let Some(captures) = HEADLINE_MATCHER.captures(line) {
+            let level = captures["stars"].len();
+            let mut title = captures["title"].to_owned();
+            let keyword = title
+                .split_whitespace()
+                .next()
+                .filter(|word| is_keyword(word))
+                .map(str::to_owned);
+            if let Some(keyword) = &keyword {
+                title = title[keyword.len()..].trim_start().to_owned();
+            }
+            let title = PRIORITY_MATCHER.replace(&title, "").into_owned();
+            let tags = captures
+                .name("tags")
+                .map(|tags| {
+                    tags.as_str()
+                        .split(':')
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_owned)
+                        .collect::<Vec<String>>()
+                })
+                .unwrap_or_default();
+
+            inherited.retain(|(inherited_level, _)| *inherited_level < level);
+            if ticket_level.is_some_and(|ticket_level| level <= ticket_level) {
+                ticket_level = None;
+            }
+
+            let is_ticket = if uses_keywords {
+                keyword.is_some()
+            } else {
+                level == 1
+            };
+
+            in_drawer = false;
+            if ticket_level.is_some() {
+                entries.push(Entry {
+                    title,
+                    keyword,
+                    tags: tags.clone(),
+                    is_sub_task: true,
+                    scheduled: None,
+                    deadline: None,
+                    effort: None,
+                    body: vec![],
+                });
+            } else if is_ticket {
+                let mut all_tags = inherited
+                    .iter()
+                    .flat_map(|(_, tags)| tags.clone())
+                    .collect::<Vec<String>>();
+                all_tags.extend(tags.iter().cloned());
+
+                ticket_level = Some(level);
+                entries.push(Entry {
+                    title,
+                    keyword,
+                    tags: all_tags,
+                    is_sub_task: false,
+                    scheduled: None,
+                    deadline: None,
+                    effort: None,
+                    body: vec![],
+                });
+            }
+
+            inherited.push((level, tags));
+            continue;
+        }
+
+        // NOTE: Text outside of tickets, like the file's own settings, is left out
+        if ticket_level.is_none() {
+            continue;
+        }
+        let Some(entry) = entries.last_mut() else {
+            continue;
+        };
+
+        if PLANNING_MATCHER.is_match(line) && entry.body.is_empty() {
+            for captures in PLANNING_MATCHER.captures_iter(line) {
+                match &captures["keyword"] {
+                    "SCHEDULED" => entry.scheduled = Some(captures["date"].to_owned()),
+                    "DEADLINE" => entry.deadline = Some(captures["date"].to_owned()),
+                    _ => {}
+                }
+            }
+            continue;
+        }
+
+        if in_drawer {
+            if line.trim().eq_ignore_ascii_case(":END:") {
+                in_drawer = false;
+            } else if let Some(captures) = PROPERTY_MATCHER.captures(line) {
+                if captures["name"].eq_ignore_ascii_case("Effort") {
+                    entry.effort = Some(captures["value"].to_owned());
+                }
+            }
+            continue;
+        }
+
+        if DRAWER_MATCHER.is_match(line) {
+            in_drawer = true;
+            continue;
+        }
+
+        // NOTE: Sub tasks only have a title, so their text goes to the ticket they're below
+        if entry.is_sub_task && line.trim().is_empty() {
+            continue;
+        }
+        if let Some(ticket) = entries.iter_mut().rev().find(|entry| !entry.is_sub_task) {
+            ticket.body.push(line.to_owned());
+        }
+    }
+
+    let mut tickets: Vec<String> = vec![];
+    for entry in &entries {
+        if entry.is_sub_task {
+            let Some(ticket) = tickets.last_mut() else {
+                continue;
+            };
+
+            let checked = entry
+                .keyword
+                .as_ref()
+                .is_some_and(|keyword| keywords.done.contains(keyword));
+            let mut tokens = entry
+                .tags
+                .iter()
+                .map(|tag| tag_token(tag))
+                .collect::<Vec<_>>();
+            tokens.extend(entry.effort.as_deref().and_then(estimate));
+
+            ticket.push_str(&format!(
+                "\n- [{}] {} {}",
+                if checked { "x" } else { " " },
+                title_text(&entry.title),
+                tokens.join(" ")
+            ));
+            continue;
+        }
+
+        let mut tokens = entry
+            .tags
+            .iter()
+            .map(|tag| tag_token(tag))
+            .collect::<Vec<_>>();
+        tokens.extend(entry.effort.as_deref().and_then(estimate));
+        tokens.extend(entry.scheduled.iter().map(|date| format!("start:{date}")));
+        tokens.extend(entry.deadline.iter().map(|date| format!("due:{date}")));
+        tokens.extend(
+            entry
+                .keyword
+                .as_deref()
+                .and_then(|keyword| stage(keyword, &keywords, stages))
+                .map(|stage| format!("stage:\"{stage}\"")),
+        );
+
+        let description = plain_text(&entry.body.join("\n")).trim().to_owned();
+        tickets.push(format!(
+            "{} {}\n===\n{description}",
+            title_text(&entry.title),
+            tokens.join(" ")
+        ));
+    }
+
+    tickets.join("\n---\n")
+}
+
+fn keywords(contents: &str) -> Keywords {
+    let definitions = contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            ["#+TODO:", "#+SEQ_TODO:", "#+TYP_TODO:"]
+                .iter()
+                .find_map(|prefix| {
+                    line.get(..prefix.len())
+                        .filter(|start| start.eq_ignore_ascii_case(prefix))
+                        .map(|_| &line[prefix.len()..])
+                })
+        })
+        .collect::<Vec<&str>>();
+
+    if definitions.is_empty() {
+        return Keywords {
+            active: vec!["TODO".to_owned()],
+            done: vec!["DONE".to_owned()],
+        };
+    }
+
+    let mut keywords = Keywords {
+        active: vec![],
+        done: vec![],
+    };
+    for definition in definitions {
+        // NOTE: Fast access keys like `TODO(t)` aren't part of the keyword
+        let words = definition
+            .split_whitespace()
+            .map(|word| word.split('(').next().unwrap_or_default().to_owned())
+            .collect::<Vec<String>>();
+
+        match words.iter().position(|word| word == "|") {
+            Some(separator) => {
+                keywords.active.extend(words[..separator].iter().cloned());
+                keywords.done.extend(words[separator + 1..].iter().cloned());
+            }
+            // NOTE: Without a `|`, the last keyword is the done one
+            None => {
+                if let Some((done, active)) = words.split_last() {
+                    keywords.active.extend(active.iter().cloned());
+                    keywords.done.push(done.clone());
+                }
+            }
+        }
+    }
+
+    keywords
+}
+
+/// Name of the stage for `keyword`, `None` to leave the work item in the first stage
+fn stage(keyword: &str, keywords: &Keywords, stages: &[Stage]) -> Option<String> {
+    if let Some(stage) = stages
+        .iter()
+        .find(|stage| normalize_name(&stage.name) == normalize_name(keyword))
+    {
+        return Some(stage.name.clone());
+    }
+
+    if keywords
+        .active
+        .first()
+        .is_some_and(|first| first == keyword)
+    {
+        return None;
+    }
+
+    let status = if keywords.done.iter().any(|done| done == keyword) {
+        "completed"
+    } else {
+        "started"
+    };
+
+    let stage = stages.iter().find(|stage| stage.status == status);
+    if stage.is_none() {
+        output::warn(format!(
+            "No {status} stage for the org keyword {keyword}, leaving it in the first stage"
+        ));
+    }
+
+    stage.map(|stage| stage.name.clone())
+}
+
+fn tag_token(tag: &str) -> String {
+    // NOTE: Org tags use `_` where hnp's tags would use a space
+    input::hash_tag(&tag.replace('_', " "))
+}
+
+/// Estimate token for an org duration like `1:30`, `45min` or `2d`
+fn estimate(effort: &str) -> Option<String> {
+    let Some(captures) = DURATION_MATCHER.captures(effort.trim()) else {
+        output::warn(format!(
+            "Effort \"{effort}\" isn't a duration hnp understands, skipping it"
+        ));
+        return None;
+    };
+
+    if let Some(hours) = captures.name("hours") {
+        let minutes = captures["minutes"].parse::<u32>().unwrap_or_default();
+        return Some(match minutes {
+            0 => format!("~{}h", hours.as_str()),
+            minutes => format!("~{}h{minutes}m", hours.as_str()),
+        });
+    }
+
+    let unit = match &captures["unit"] {
+        "min" => "m",
+        unit => unit,
+    };
+
+    Some(format!("~{}{unit}", &captures["amount"]))
+}
+
+/// Org links as Markdown, `[[url][description]]` becomes `[description](url)`
+fn plain_text(text: &str) -> String {
+    LINK_MATCHER
+        .replace_all(text, |captures: &regex::Captures| {
+            match captures.name("description") {
+                Some(description) => format!("[{}]({})", description.as_str(), &captures["target"]),
+                None => captures["target"].to_owned(),
+            }
+        })
+        .into_owned()
+}
+
+/// Titles can't have links, so only their description (or target) is kept
+fn title_text(text: &str) -> String {
+    LINK_MATCHER
+        .replace_all(text, |captures: &regex::Captures| {
+            captures
+                .name("description")
+                .map_or(&captures["target"], |description| description.as_str())
+                .to_owned()
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{estimate, to_tickets};
+    use crate::models::Stage;
+
+    fn stages() -> Vec<Stage> {
+        [
+            (1, "Planned", "created"),
+            (2, "In Progress", "started"),
+            (3, "Testing", "started"),
+            (4, "Completed", "completed"),
+        ]
+        .into_iter()
+        .map(|(stage_id, name, status)| Stage {
+            stage_id,
+            name: name.to_owned(),
+            status: status.to_owned(),
+            is_unblocker: false,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn turns_headlines_into_tickets_and_sub_tasks() {
+        let contents =
+            "#+TITLE: Space Game\n* Fix jump\nThe player falls\n** Tune gravity\n* Ship it";
+
+        assert_eq!(
+            to_tickets(contents, &stages()),
+            "Fix jump \n===\nThe player falls\n- [ ] Tune gravity \n---\nShip it \n===\n"
+        );
+    }
+
+    #[test]
+    fn moves_todo_keywords_to_stages() {
+        let contents = concat!(
+            "#+TODO: TODO(t) NEXT TESTING | DONE\n",
+            "* TODO [#A] Fix jump\n",
+            "* NEXT Tune gravity\n",
+            "* TESTING Add levels\n",
+            "* DONE Ship it\n",
+            "* Notes\n",
+        );
+
+        assert_eq!(
+            to_tickets(contents, &stages()),
+            concat!(
+                "Fix jump \n===\n\n---\n",
+                "Tune gravity stage:\"In Progress\"\n===\n\n---\n",
+                "Add levels stage:\"Testing\"\n===\n\n---\n",
+                "Ship it stage:\"Completed\"\n===\n",
+            )
+        );
+    }
+
+    #[test]
+    fn inherits_tags() {
+        let contents = "* Game :programming:\n** TODO Fix jump :bug:\n** TODO Tune :game_feel:";
+
+        assert_eq!(
+            to_tickets(contents, &stages()),
+            "Fix jump #programming #bug\n===\n\n---\nTune #programming #\"game feel\"\n===\n"
+        );
+    }
+
+    #[test]
+    fn turns_scheduled_and_deadline_into_dates() {
+        let contents = concat!(
+            "* TODO Fix jump\n",
+            "SCHEDULED: <2024-02-01 Thu> DEADLINE: <2024-03-01 Fri>\n",
+            "The player falls",
+        );
+
+        assert_eq!(
+            to_tickets(contents, &stages()),
+            "Fix jump start:2024-02-01 due:2024-03-01\n===\nThe player falls"
+        );
+    }
+
+    #[test]
+    fn turns_effort_into_estimates() {
+        let contents = "* TODO Fix jump\n:PROPERTIES:\n:Effort: 1:30\n:END:\nThe player falls";
+
+        assert_eq!(
+            to_tickets(contents, &stages()),
+            "Fix jump ~1h30m\n===\nThe player falls"
+        );
+        assert_eq!(estimate("2:00").as_deref(), Some("~2h"));
+        assert_eq!(estimate("45min").as_deref(), Some("~45m"));
+        assert_eq!(estimate("1.5 d").as_deref(), Some("~1.5d"));
+        assert_eq!(estimate("soon"), None);
+    }
+}
@@ -0,0 +1,417 @@
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+#[cfg(feature = "tui")]
+use std::time::Duration;
+
+use console::style;
+#[cfg(feature = "tui")]
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::json;
+
+use crate::i18n::Message;
+
+/// Whether we're allowed to use colors, emoji and animated progress
+static FANCY: AtomicBool = AtomicBool::new(true);
+
+/// What is being worked on, like the file tickets come from, mentioned in every error
+static CONTEXT: Mutex<Option<String>> = Mutex::new(None);
+
+/// Whether warnings and errors are printed as JSON for editors and CI
+static JSON_DIAGNOSTICS: AtomicBool = AtomicBool::new(false);
+
+/// When the run counts as failed, set from `--fail-on`
+static FAIL_ON: OnceLock<FailOn> = OnceLock::new();
+
+/// Warnings printed so far, for `--fail-on warn`
+static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+/// Something went wrong and the run stopped, nothing more was uploaded
+pub const EXIT_ERROR: i32 = 1;
+/// The run finished but printed warnings, only with `--fail-on warn`
+pub const EXIT_WARNINGS: i32 = 3;
+/// The user pressed Ctrl-C
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// What makes hnp exit with a failing code
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum FailOn {
+    /// Warnings and errors
+    Warn,
+    /// Only errors
+    #[default]
+    Error,
+    /// Nothing, errors still stop the run but exit with 0
+    Never,
+}
+
+/// Whether we're running for GitHub Actions, see [`OutputFormat::Github`]
+static GITHUB: AtomicBool = AtomicBool::new(false);
+
+/// Where the output is meant to be read
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    /// Warnings and errors as GitHub Actions annotations and the created tickets in the job
+    /// summary
+    Github,
+}
+
+/// How warnings and errors are printed
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum DiagnosticsFormat {
+    #[default]
+    Text,
+    /// One object per line on stderr with the severity, code, message, file, line and span
+    Json,
+}
+
+/// A warning or error about a place in the file that's being worked on
+#[derive(Debug, Default)]
+pub struct Diagnostic {
+    /// What kind of problem it is, like `limit` or `unresolved-token`
+    pub code: &'static str,
+    pub message: String,
+    /// Line in the file, counting from 1
+    pub line: Option<usize>,
+    /// Columns on `line` the problem covers, counting from 1 and without the end
+    pub span: Option<(usize, usize)>,
+}
+
+/// Decides on colored or plain output, plain output is used when `--no-color` is passed,
+/// `NO_COLOR` is set or stdout isn't a terminal (e.g. in CI logs)
+pub fn init(
+    no_color: bool,
+    diagnostics: DiagnosticsFormat,
+    output_format: OutputFormat,
+    fail_on: FailOn,
+) {
+    FAIL_ON.set(fail_on).expect("To only set --fail-on once");
+    JSON_DIAGNOSTICS.store(diagnostics == DiagnosticsFormat::Json, Ordering::Relaxed);
+    GITHUB.store(output_format == OutputFormat::Github, Ordering::Relaxed);
+
+    let fancy = !no_color
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::io::stdout().is_terminal();
+
+    FANCY.store(fancy, Ordering::Relaxed);
+    console::set_colors_enabled(fancy);
+    console::set_colors_enabled_stderr(fancy);
+}
+
+/// Reports panics (our `expect`s) as error lines, unless a backtrace was asked for
+pub fn install_panic_hook() {
+    if std::env::var_os("RUST_BACKTRACE").is_some() {
+        return;
+    }
+
+    std::panic::set_hook(Box::new(|info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown error");
+
+        // NOTE: Our expectations read as "Expected to ...", so lowercase the leading "To"
+        let mut characters = message.chars();
+        let message = match characters.next() {
+            Some(first) => first.to_lowercase().chain(characters).collect::<String>(),
+            None => String::new(),
+        };
+
+        error(format!("Expected {message}"));
+        exit_with_error();
+    }));
+}
+
+fn fail_on() -> FailOn {
+    FAIL_ON.get().copied().unwrap_or_default()
+}
+
+/// Stops the run after an error, with [`EXIT_ERROR`] unless running `--fail-on never`
+pub fn exit_with_error() -> ! {
+    if fail_on() == FailOn::Never {
+        std::process::exit(0);
+    }
+
+    std::process::exit(EXIT_ERROR);
+}
+
+/// Ends a run that got to the end, with [`EXIT_WARNINGS`] when there were warnings and running
+/// `--fail-on warn`
+pub fn finish() {
+    if fail_on() == FailOn::Warn && WARNINGS.load(Ordering::Relaxed) > 0 {
+        std::process::exit(EXIT_WARNINGS);
+    }
+}
+
+pub fn is_fancy() -> bool {
+    FANCY.load(Ordering::Relaxed)
+}
+
+fn symbol(emoji: &'static str, plain: &'static str) -> &'static str {
+    if is_fancy() {
+        emoji
+    } else {
+        plain
+    }
+}
+
+pub fn info(message: impl Display) {
+    let message = message.to_string();
+    println!("{} {}", style(symbol("ℹ️ ", "[info]")).cyan(), message);
+}
+
+pub fn success(message: impl Display) {
+    let message = message.to_string();
+    println!("{} {}", style(symbol("✅", "[ok]")).green(), message);
+}
+
+pub fn warn(message: impl Display) {
+    WARNINGS.fetch_add(1, Ordering::Relaxed);
+
+    if is_json_diagnostics() {
+        print_json("warning", None, &message.to_string(), None, None);
+        return;
+    } else if is_github() {
+        print_annotation("warning", None, &message.to_string(), None, None);
+        return;
+    }
+
+    let message = message.to_string();
+    eprintln!(
+        "{} {}",
+        style(symbol("⚠️ ", "[warn]")).yellow(),
+        style(message).yellow()
+    );
+}
+
+pub fn set_context(context: Option<String>) {
+    *CONTEXT.lock().expect("To lock the output context") = context;
+}
+
+pub fn error(message: impl Display) {
+    if is_json_diagnostics() {
+        print_json("error", None, &message.to_string(), None, None);
+        return;
+    } else if is_github() {
+        print_annotation("error", None, &message.to_string(), None, None);
+        return;
+    }
+
+    let message = message.to_string();
+    let message = match &*CONTEXT.lock().expect("To lock the output context") {
+        Some(context) => format!("{context}: {message}"),
+        None => message,
+    };
+
+    eprintln!(
+        "{} {}",
+        style(symbol("❌", "[error]")).red().bold(),
+        style(message).red()
+    );
+}
+
+pub fn is_json_diagnostics() -> bool {
+    JSON_DIAGNOSTICS.load(Ordering::Relaxed)
+}
+
+pub fn is_github() -> bool {
+    GITHUB.load(Ordering::Relaxed)
+}
+
+pub fn warn_at(diagnostic: Diagnostic) {
+    if is_json_diagnostics() {
+        WARNINGS.fetch_add(1, Ordering::Relaxed);
+        print_diagnostic("warning", diagnostic);
+    } else if is_github() {
+        WARNINGS.fetch_add(1, Ordering::Relaxed);
+        print_annotation(
+            "warning",
+            Some(diagnostic.code),
+            &diagnostic.message,
+            diagnostic.line,
+            diagnostic.span,
+        );
+    } else {
+        warn(diagnostic);
+    }
+}
+
+pub fn error_at(diagnostic: Diagnostic) {
+    if is_json_diagnostics() {
+        print_diagnostic("error", diagnostic);
+    } else if is_github() {
+        print_annotation(
+            "error",
+            Some(diagnostic.code),
+            &diagnostic.message,
+            diagnostic.line,
+            diagnostic.span,
+        );
+    } else {
+        error(diagnostic);
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(
+                formatter,
+                "{}",
+                Message::TicketOnLine {
+                    line,
+                    message: &self.message
+                }
+            ),
+            None => write!(formatter, "{}", self.message),
+        }
+    }
+}
+
+fn print_diagnostic(severity: &str, diagnostic: Diagnostic) {
+    print_json(
+        severity,
+        Some(diagnostic.code),
+        &diagnostic.message,
+        diagnostic.line,
+        diagnostic.span,
+    );
+}
+
+fn print_json(
+    severity: &str,
+    code: Option<&str>,
+    message: &str,
+    line: Option<usize>,
+    span: Option<(usize, usize)>,
+) {
+    let file = CONTEXT.lock().expect("To lock the output context").clone();
+
+    eprintln!(
+        "{}",
+        json!({
+            "severity": severity,
+            "code": code,
+            "message": message,
+            "file": file,
+            "line": line,
+            "span": span.map(|(start, end)| json!({ "start": start, "end": end })),
+        })
+    );
+}
+
+/// Prints a workflow command that GitHub Actions turns into an annotation on the file
+fn print_annotation(
+    severity: &str,
+    code: Option<&str>,
+    message: &str,
+    line: Option<usize>,
+    span: Option<(usize, usize)>,
+) {
+    // NOTE: GitHub reads `%`, newlines and, in properties, `:` and `,` as part of the command
+    let escape = |text: &str| {
+        text.replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A")
+    };
+    let property = |text: &str| escape(text).replace(':', "%3A").replace(',', "%2C");
+
+    let mut properties = vec![];
+    if let Some(file) = &*CONTEXT.lock().expect("To lock the output context") {
+        properties.push(format!("file={}", property(file)));
+    }
+    if let Some(line) = line {
+        properties.push(format!("line={line}"));
+    }
+    // NOTE: GitHub's end column is the last one of the span
+    if let Some((start, end)) = span {
+        properties.push(format!(
+            "col={start},endColumn={}",
+            end.saturating_sub(1).max(start)
+        ));
+    }
+    if let Some(code) = code {
+        properties.push(format!("title={}", property(code)));
+    }
+
+    println!("::{severity} {}::{}", properties.join(","), escape(message));
+}
+
+/// Adds `markdown` to the summary of the GitHub Actions job, or prints it when not running in
+/// GitHub Actions
+pub fn github_summary(markdown: &str) {
+    let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY") else {
+        println!("{markdown}");
+        return;
+    };
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| std::io::Write::write_all(&mut file, markdown.as_bytes()))
+        .unwrap_or_else(|error| panic!("To write the job summary: {error}"));
+}
+
+/// Asks a yes or no question, answering no when the prompt is cancelled
+#[cfg(feature = "tui")]
+pub fn confirm(question: impl Display) -> bool {
+    inquire::Confirm::new(&question.to_string())
+        .with_default(false)
+        .prompt()
+        .unwrap_or(false)
+}
+
+/// Asks for a line of text, `None` when the prompt is cancelled
+#[cfg(feature = "tui")]
+pub fn ask(question: impl Display) -> Option<String> {
+    inquire::Text::new(&question.to_string()).prompt().ok()
+}
+
+/// Lets the user pick any of `options`, all of them are picked at first. Returns the positions of
+/// the picked options, `None` when the prompt is cancelled.
+#[cfg(feature = "tui")]
+pub fn pick(question: impl Display, options: Vec<String>) -> Option<Vec<usize>> {
+    let all = (0..options.len()).collect::<Vec<usize>>();
+
+    inquire::MultiSelect::new(&question.to_string(), options)
+        .with_default(&all)
+        .with_page_size(15)
+        .raw_prompt()
+        .ok()
+        .map(|picked| picked.into_iter().map(|option| option.index).collect())
+}
+
+/// Spinner for work of unknown length, hidden when output is plain
+#[cfg(feature = "tui")]
+pub fn spinner(message: impl Display) -> ProgressBar {
+    if !is_fancy() {
+        return ProgressBar::hidden();
+    }
+
+    let spinner = ProgressBar::new_spinner().with_message(message.to_string());
+    spinner.enable_steady_tick(Duration::from_millis(100));
+
+    spinner
+}
+
+/// Progress bar over `length` items, hidden when output is plain
+#[cfg(feature = "tui")]
+pub fn progress(length: usize, message: impl Display) -> ProgressBar {
+    if !is_fancy() {
+        return ProgressBar::hidden();
+    }
+
+    ProgressBar::new(length as u64)
+        .with_style(
+            ProgressStyle::with_template("{msg} [{bar:30.cyan/blue}] {pos}/{len}")
+                .expect("Progress bar template to be valid")
+                .progress_chars("=> "),
+        )
+        .with_message(message.to_string())
+}
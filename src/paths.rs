@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use directories::{BaseDirs, ProjectDirs};
+
+/// Directories of hnp by the conventions of each platform, e.g. `~/.config/hnp` on Linux,
+/// `~/Library/Application Support/hnp` on macOS and `%APPDATA%\hnp\config` on Windows
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "hnp")
+}
+
+/// `hnp` in the user's directory from `base` as used before following the conventions of each
+/// platform. It's only different on Windows, where everything lived in `%APPDATA%\hnp`.
+fn legacy_dir(base: fn(&BaseDirs) -> &std::path::Path) -> Option<PathBuf> {
+    BaseDirs::new().map(|base_dirs| base(&base_dirs).join("hnp"))
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    project_dirs().map(|project_dirs| project_dirs.config_dir().to_path_buf())
+}
+
+/// The directory the config was looked for in before, when it's somewhere else than
+/// [`config_dir`]
+pub fn legacy_config_dir() -> Option<PathBuf> {
+    legacy_dir(BaseDirs::config_dir).filter(|legacy| Some(legacy) != config_dir().as_ref())
+}
+
+/// Where runs and recurring state are kept. Windows keeps using `%APPDATA%\hnp` when that's
+/// where they already are, so nothing is lost.
+pub fn data_dir() -> Option<PathBuf> {
+    let data_dir = project_dirs()?.data_dir().to_path_buf();
+
+    match legacy_dir(BaseDirs::data_dir) {
+        Some(legacy) if legacy != data_dir && legacy.exists() && !data_dir.exists() => Some(legacy),
+        _ => Some(data_dir),
+    }
+}
+
+pub fn cache_dir() -> Option<PathBuf> {
+    project_dirs().map(|project_dirs| project_dirs.cache_dir().to_path_buf())
+}
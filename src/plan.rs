@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use clap::Args;
+use serde_json::json;
+
+use crate::bulk::{self, Changes};
+use crate::capacity::working_days_left;
+use crate::config::{Config, EstimateMode};
+use crate::filter::Filter;
+use crate::i18n::{Action, Message};
+use crate::models::{self, WorkItem};
+use crate::{
+    client, get_available_boards, get_metadata, get_models, get_work_items, output, patch_json,
+    Arguments, Id,
+};
+
+#[derive(Args, Debug)]
+pub struct PlanArguments {
+    /// Milestone to plan, its working days left and the capacity in the config decide how much
+    /// fits
+    #[arg(long)]
+    milestone: String,
+
+    /// Work items to plan from, e.g. `stage:Backlog -tag:polish`. Only open work items without
+    /// assignees are planned.
+    #[arg(short, long)]
+    filter: Option<String>,
+
+    /// Board of the milestone to put the planned work items on, its first board by default
+    #[arg(long, value_name = "BOARD")]
+    board: Option<String>,
+
+    /// Assign the work items and put them on the board of the milestone as proposed
+    #[arg(long)]
+    apply: bool,
+
+    /// Apply the plan without asking for confirmation
+    #[arg(short, long, requires = "apply")]
+    yes: bool,
+}
+
+/// Work item proposed for a user, with the estimate it was planned with
+struct Proposal<'a> {
+    work_item: &'a WorkItem,
+    estimate: f32,
+    /// Category whose average estimate was used, for work items without an estimate
+    guessed_from: Option<String>,
+    user: Option<(Id, String)>,
+}
+
+/// Proposes who works on what in a milestone, handing out the open work items without assignees
+/// by importance until everyone's capacity is used up
+pub async fn run(arguments: &Arguments, plan_arguments: &PlanArguments, config: &Config) {
+    if config.estimates.mode == EstimateMode::Points {
+        output::error("hnp plan fills the capacity in hours, the project estimates in points");
+        output::exit_with_error();
+    }
+
+    let filter = Filter::parse(plan_arguments.filter.as_deref().unwrap_or_default())
+        .unwrap_or_else(|error| {
+            output::error(format!("Invalid filter: {error}"));
+            output::exit_with_error();
+        });
+
+    let client = client();
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let metadata = get_metadata(&client).await;
+    let available_boards = get_available_boards(&client).await;
+    let milestones = get_models::<models::Milestone>(&client, "milestones").await;
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    let milestone = milestones
+        .iter()
+        .find(|milestone| {
+            milestone
+                .name
+                .eq_ignore_ascii_case(&plan_arguments.milestone)
+        })
+        .unwrap_or_else(|| panic!("To find a milestone for \"{}\"", plan_arguments.milestone));
+    let boards = available_boards
+        .iter()
+        .filter(|(_, _, milestone_id)| *milestone_id == Some(milestone.milestone_id))
+        .collect::<Vec<&(Id, String, Option<Id>)>>();
+    let board_id = match &plan_arguments.board {
+        Some(board) => {
+            boards
+                .iter()
+                .find(|(_, name, _)| name.eq_ignore_ascii_case(board))
+                .unwrap_or_else(|| panic!("To find board \"{board}\" in \"{}\"", milestone.name))
+                .0
+        }
+        None => {
+            boards
+                .first()
+                .unwrap_or_else(|| panic!("To find a board in milestone \"{}\"", milestone.name))
+                .0
+        }
+    };
+
+    // NOTE: Users of `assignment.pool` make up the team when it's set, otherwise everyone in the
+    // project does
+    let team = metadata
+        .users
+        .iter()
+        .filter(|(_, _, username)| {
+            config.assignment.pool.is_empty()
+                || config.assignment.pool.iter().any(|member| {
+                    member
+                        .trim_start_matches('@')
+                        .eq_ignore_ascii_case(username)
+                })
+        })
+        .collect::<Vec<&(Id, String, String)>>();
+
+    let working_days = working_days_left(milestone, config);
+    let on_milestone = |work_item: &WorkItem| {
+        work_item
+            .board
+            .as_ref()
+            .is_some_and(|board| boards.iter().any(|(id, _, _)| *id == board.board_id))
+    };
+    let mut left = team
+        .iter()
+        .map(|(id, _, username)| {
+            let assigned = work_items
+                .iter()
+                .filter(|work_item| !work_item.is_story && !work_item.is_completed())
+                .filter(|work_item| on_milestone(work_item))
+                .filter(|work_item| work_item.assigned_user_ids().contains(id))
+                .map(|work_item| work_item.estimated_cost.unwrap_or_default())
+                .sum::<f32>();
+
+            (
+                *id,
+                working_days * config.calendar.daily_capacity(username) - assigned,
+            )
+        })
+        .collect::<HashMap<Id, f32>>();
+    let capacity = left.clone();
+
+    // NOTE: Work items without an estimate are planned with the average estimate of their
+    // category, taken from every work item that has one
+    let mut estimates: HashMap<Id, (f32, usize)> = HashMap::new();
+    for work_item in &work_items {
+        if let (Some(category), Some(estimate)) = (
+            &work_item.category,
+            work_item.estimated_cost.filter(|estimate| *estimate > 0.0),
+        ) {
+            let (total, count) = estimates.entry(category.category_id).or_default();
+            *total += estimate;
+            *count += 1;
+        }
+    }
+
+    let importance = |work_item: &WorkItem| {
+        work_item
+            .importance_level
+            .as_ref()
+            .and_then(|importance_level| {
+                metadata
+                    .importance_levels
+                    .iter()
+                    .position(|(id, _, _)| *id == importance_level.importance_level_id)
+            })
+            .unwrap_or(usize::MAX)
+    };
+    let mut candidates = work_items
+        .iter()
+        .filter(|work_item| !work_item.is_story && !work_item.is_completed())
+        .filter(|work_item| work_item.assigned_users.is_empty())
+        .filter(|work_item| filter.matches(work_item))
+        .collect::<Vec<&WorkItem>>();
+    // NOTE: Importance levels are listed from most to least important by the API, work items
+    // that are due first go first within a level
+    candidates.sort_by_key(|work_item| {
+        let due_date = work_item
+            .due_date
+            .clone()
+            .filter(|due_date| !due_date.is_empty());
+        (
+            importance(work_item),
+            due_date.is_none(),
+            due_date,
+            work_item.work_item_id,
+        )
+    });
+
+    let mut proposals = vec![];
+    let mut unestimated = vec![];
+    for work_item in candidates {
+        let (estimate, guessed_from) = match work_item.estimated_cost.filter(|cost| *cost > 0.0) {
+            Some(estimate) => (estimate, None),
+            None => {
+                let average = work_item.category.as_ref().and_then(|category| {
+                    let (total, count) = estimates.get(&category.category_id)?;
+                    Some((total / *count as f32, category.name.clone()))
+                });
+                let Some((estimate, category)) = average else {
+                    unestimated.push(work_item);
+                    continue;
+                };
+
+                (estimate, Some(category))
+            }
+        };
+
+        // NOTE: Whoever has the most room left gets the work item, so work spreads evenly
+        let user = team
+            .iter()
+            .filter(|(id, _, _)| left[id] >= estimate)
+            .max_by(|(a, _, _), (b, _, _)| left[a].total_cmp(&left[b]))
+            .map(|(id, _, username)| (*id, username.to_owned()));
+        if let Some((id, _)) = &user {
+            *left.get_mut(id).expect("To have the capacity of the user") -= estimate;
+        }
+
+        proposals.push(Proposal {
+            work_item,
+            estimate,
+            guessed_from,
+            user,
+        });
+    }
+
+    for proposal in &proposals {
+        let estimate = match &proposal.guessed_from {
+            Some(category) => format!("~{:.1}h, guessed from {category}", proposal.estimate),
+            None => format!("~{}h", proposal.estimate),
+        };
+        let user = match &proposal.user {
+            Some((_, username)) => format!("@{username}"),
+            None => "doesn't fit".to_owned(),
+        };
+        println!(
+            "#{} {} ({estimate}) → {user}",
+            proposal.work_item.work_item_id, proposal.work_item.title
+        );
+    }
+    println!();
+
+    for (id, _, username) in &team {
+        output::info(format!(
+            "@{username}: {:.1}h planned, {:.1}h of {:.1}h left",
+            capacity[id] - left[id],
+            left[id],
+            working_days * config.calendar.daily_capacity(username)
+        ));
+    }
+
+    let planned = proposals
+        .iter()
+        .filter(|proposal| proposal.user.is_some())
+        .collect::<Vec<&Proposal>>();
+    if planned.len() < proposals.len() {
+        output::warn(format!(
+            "{} work item(s) don't fit in \"{}\"",
+            proposals.len() - planned.len(),
+            milestone.name
+        ));
+    }
+    if !unestimated.is_empty() {
+        output::warn(format!(
+            "Left out {} work item(s) without an estimate or a category to guess one from: {}",
+            unestimated.len(),
+            unestimated
+                .iter()
+                .map(|work_item| format!("#{}", work_item.work_item_id))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+    }
+
+    if !plan_arguments.apply || arguments.dry_run || planned.is_empty() {
+        return;
+    }
+
+    let confirmed = if plan_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Changed));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmPlan {
+            work_items: planned.len(),
+            milestone: &milestone.name,
+        })
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Changed));
+        return;
+    }
+
+    let progress = output::progress(planned.len(), "Updating work items");
+    for proposal in &planned {
+        let id = proposal.work_item.work_item_id;
+        let changes = Changes {
+            assign: proposal.user.iter().cloned().collect(),
+            ..Default::default()
+        };
+        bulk::apply(&client, id, &changes).await;
+        patch_json(
+            &client,
+            &format!("/workitems/{id}"),
+            &json!({ "boardId": board_id }),
+        )
+        .await;
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    output::success(format!(
+        "Planned {} work item(s) in \"{}\"",
+        planned.len(),
+        milestone.name
+    ));
+}
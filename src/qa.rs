@@ -0,0 +1,187 @@
+use clap::{Args, Subcommand};
+
+use crate::assign::{self, Strategy};
+use crate::config::{Config, Qa};
+use crate::graph::Dependencies;
+use crate::i18n::{Action, Message};
+use crate::models::WorkItem;
+use crate::{
+    client, get_metadata, get_work_items, normalize_name, output, upload_ticket, Arguments,
+    SubTask, Ticket,
+};
+
+#[derive(Subcommand, Debug)]
+pub enum QaCommand {
+    /// Create a QA task for every completed work item with a tag, depending on the work item and
+    /// assigned to the testers in `qa.pool` of the config
+    Generate(GenerateArguments),
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateArguments {
+    /// Tag of the completed work items that need testing
+    #[arg(long, default_value = "needs-qa")]
+    tag: String,
+
+    /// Only the work items in this milestone
+    #[arg(long, value_name = "MILESTONE")]
+    milestone: Option<String>,
+
+    /// How the QA tasks are divided among the testers
+    #[arg(long, value_enum, default_value_t = Strategy::RoundRobin)]
+    strategy: Strategy,
+
+    /// Create the QA tasks without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub async fn run(arguments: &Arguments, command: &QaCommand, config: &Config) {
+    match command {
+        QaCommand::Generate(generate_arguments) => {
+            generate(arguments, generate_arguments, config).await
+        }
+    }
+}
+
+async fn generate(arguments: &Arguments, generate_arguments: &GenerateArguments, config: &Config) {
+    if config.qa.pool.is_empty() {
+        output::error("Generating QA tasks needs usernames in `qa.pool` of the config");
+        output::exit_with_error();
+    }
+
+    let client = client();
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let metadata = get_metadata(&client).await;
+    let (work_items, in_milestone) = match &generate_arguments.milestone {
+        Some(milestone) => {
+            let dependencies = Dependencies::fetch(&client, milestone).await;
+            (dependencies.work_items, Some(dependencies.in_milestone))
+        }
+        None => (get_work_items::<WorkItem>(&client).await, None),
+    };
+    spinner.finish_and_clear();
+
+    let category_id = config.qa.category.as_ref().map(|category| {
+        metadata
+            .categories
+            .iter()
+            .find(|(_, name)| normalize_name(name) == normalize_name(category))
+            .unwrap_or_else(|| panic!("To find category \"{category}\" from `qa.category`"))
+            .0
+    });
+
+    let importance_level_id = metadata
+        .importance_levels
+        .iter()
+        .find(|(_, _, is_default)| *is_default)
+        .map(|(id, _, _)| *id)
+        .unwrap_or_default();
+
+    let tag = normalize_name(generate_arguments.tag.trim_start_matches('#'));
+    let mut tickets = vec![];
+    let mut already_tested = 0;
+    for work_item in work_items
+        .iter()
+        .filter(|work_item| work_item.is_completed() && !work_item.is_story)
+        .filter(|work_item| {
+            work_item
+                .tags
+                .iter()
+                .any(|work_item_tag| normalize_name(&work_item_tag.name) == tag)
+        })
+        .filter(|work_item| {
+            in_milestone
+                .as_ref()
+                .is_none_or(|in_milestone| in_milestone.contains(&work_item.work_item_id))
+        })
+    {
+        let id = work_item.work_item_id;
+        let title = Qa::fill(&config.qa.title, id, &work_item.title);
+
+        // NOTE: Running it again skips the work items that already have their QA task
+        if work_items
+            .iter()
+            .any(|candidate| candidate.dependency_ids.contains(&id) && candidate.title == title)
+        {
+            already_tested += 1;
+            continue;
+        }
+
+        tickets.push(Ticket {
+            title,
+            description: Qa::fill(&config.qa.description, id, &work_item.title),
+            category_id: category_id
+                .or(work_item
+                    .category
+                    .as_ref()
+                    .map(|category| category.category_id))
+                .unwrap_or_default(),
+            importance_level_id,
+            board_id: work_item
+                .board
+                .as_ref()
+                .map(|board| board.board_id)
+                .unwrap_or_default(),
+            sub_tasks: config
+                .qa
+                .checklist
+                .iter()
+                .map(|title| SubTask {
+                    title: title.to_owned(),
+                    ..Default::default()
+                })
+                .collect(),
+            dependency_ids: vec![id],
+            ..Default::default()
+        });
+    }
+
+    if tickets.is_empty() {
+        output::success(format!(
+            "No completed work items tagged #{} need a QA task{}",
+            generate_arguments.tag.trim_start_matches('#'),
+            match already_tested {
+                0 => String::new(),
+                already_tested => format!(", {already_tested} already have one"),
+            }
+        ));
+        return;
+    }
+
+    assign::divide(
+        &client,
+        &mut tickets,
+        &metadata,
+        &config.qa.pool,
+        generate_arguments.strategy,
+    )
+    .await;
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if generate_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Created));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmQa(tickets.len()))
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Created));
+        return;
+    }
+
+    let progress = output::progress(tickets.len(), "Creating QA tasks");
+    for ticket in &tickets {
+        upload_ticket(&client, ticket).await;
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    output::success(format!("Created {} QA task(s)", tickets.len()));
+}
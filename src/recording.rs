@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::Id;
+
+/// Headers that carry credentials, their values are left out of recordings
+const SECRET_HEADERS: [&str; 6] = [
+    "authorization",
+    "proxy-authorization",
+    "private-token",
+    "x-api-key",
+    "cookie",
+    "set-cookie",
+];
+
+/// Recording or replay of the traffic with Hack'n'Plan, set by `--record` or `--replay`
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+enum Mode {
+    Record(Mutex<File>),
+    /// Exchanges that weren't replayed yet, in the order they were recorded in
+    Replay(Mutex<Vec<Exchange>>),
+}
+
+/// One request and its response, a line in the JSONL recording
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Exchange {
+    method: String,
+    url: String,
+    /// Request headers, with the API key and other credentials redacted
+    request_headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<Value>,
+    status: u16,
+    response_headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    response_body: Option<Value>,
+}
+
+/// Writes every request and response to `path` from now on
+pub fn record(path: &Path) {
+    let file =
+        File::create(path).unwrap_or_else(|_| panic!("To create recording {}", path.display()));
+
+    set(Mode::Record(Mutex::new(file)));
+}
+
+/// Answers requests from the recording at `path` instead of sending them
+pub fn replay(path: &Path) {
+    let contents =
+        fs::read_to_string(path).unwrap_or_else(|_| panic!("To read recording {}", path.display()));
+
+    let exchanges = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .unwrap_or_else(|error| panic!("To parse recording {}: {error}", path.display()))
+        })
+        .collect::<Vec<Exchange>>();
+
+    set(Mode::Replay(Mutex::new(exchanges)));
+}
+
+fn set(mode: Mode) {
+    if MODE.set(mode).is_err() {
+        panic!("recording to only be set up once");
+    }
+}
+
+pub fn is_replaying() -> bool {
+    matches!(MODE.get(), Some(Mode::Replay(_)))
+}
+
+/// Project the recording was made for, so replays don't need HACKNPLAN_PROJECT_ID
+pub fn project_id() -> Option<Id> {
+    let Some(Mode::Replay(exchanges)) = MODE.get() else {
+        return None;
+    };
+
+    exchanges
+        .lock()
+        .expect("To lock the replay")
+        .iter()
+        .find_map(|exchange| {
+            exchange
+                .url
+                .split_once("/projects/")?
+                .1
+                .split(['/', '?'])
+                .next()?
+                .parse()
+                .ok()
+        })
+}
+
+pub trait SendRecorded {
+    /// Like `send`, but recorded or answered from the replay when either is active
+    async fn send_recorded(self) -> reqwest::Result<reqwest::Response>;
+}
+
+impl SendRecorded for reqwest::RequestBuilder {
+    async fn send_recorded(self) -> reqwest::Result<reqwest::Response> {
+        let Some(mode) = MODE.get() else {
+            return self.send().await;
+        };
+
+        let (client, request) = self.build_split();
+        let request = request?;
+
+        let method = request.method().to_string();
+        let url = request.url().to_string();
+
+        match mode {
+            Mode::Replay(exchanges) => {
+                let mut exchanges = exchanges.lock().expect("To lock the replay");
+                let position = exchanges
+                    .iter()
+                    .position(|exchange| exchange.method == method && exchange.url == url)
+                    .unwrap_or_else(|| panic!("To find a recorded response for {method} {url}"));
+
+                Ok(to_response(exchanges.remove(position)))
+            }
+            Mode::Record(file) => {
+                let request_headers = redacted(request.headers());
+                let request_body = request
+                    .body()
+                    .and_then(reqwest::Body::as_bytes)
+                    .and_then(to_json);
+
+                let response = client.execute(request).await?;
+                let status = response.status().as_u16();
+                let response_headers = redacted(response.headers());
+                let response_body = response.bytes().await?;
+
+                let exchange = Exchange {
+                    method,
+                    url,
+                    request_headers,
+                    request_body,
+                    status,
+                    response_headers,
+                    response_body: to_json(&response_body),
+                };
+
+                let line = serde_json::to_string(&exchange).expect("To serialize the exchange");
+                writeln!(file.lock().expect("To lock the recording"), "{line}")
+                    .expect("To write to the recording");
+
+                Ok(to_response(exchange))
+            }
+        }
+    }
+}
+
+/// `headers` as strings, with the values of `SECRET_HEADERS` replaced
+fn redacted(headers: &reqwest::header::HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            // NOTE: Header names are always lowercase in a `HeaderMap`
+            let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                "[redacted]"
+            } else {
+                value.to_str().unwrap_or_default()
+            };
+
+            (name.to_string(), value.to_owned())
+        })
+        .collect()
+}
+
+/// Body as JSON, or as a string when it isn't JSON
+fn to_json(body: &[u8]) -> Option<Value> {
+    if body.is_empty() {
+        return None;
+    }
+
+    Some(
+        serde_json::from_slice(body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned())),
+    )
+}
+
+fn to_response(exchange: Exchange) -> reqwest::Response {
+    let mut builder = http::Response::builder().status(exchange.status);
+    // NOTE: The body is serialized again, so its length might differ from the original
+    for (name, value) in &exchange.response_headers {
+        if name != "content-length" && name != "transfer-encoding" {
+            builder = builder.header(name, value);
+        }
+    }
+
+    let body = match exchange.response_body {
+        None => vec![],
+        Some(Value::String(text)) => text.into_bytes(),
+        Some(value) => serde_json::to_vec(&value).expect("To serialize the recorded body"),
+    };
+
+    builder
+        .body(body)
+        .expect("To rebuild the recorded response")
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use serde_json::json;
+
+    use super::{redacted, replay, Mode, MODE};
+    use crate::{client, get_models, in_project, models, upload_payload};
+
+    #[test]
+    fn redacts_credentials_of_every_service() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", HeaderValue::from_static("ApiKey secret"));
+        headers.insert("PRIVATE-TOKEN", HeaderValue::from_static("glpat-secret"));
+        headers.insert("Accept", HeaderValue::from_static("application/json"));
+
+        let headers = redacted(&headers);
+        assert_eq!(headers["authorization"], "[redacted]");
+        assert_eq!(headers["private-token"], "[redacted]");
+        assert_eq!(headers["accept"], "application/json");
+    }
+
+    #[tokio::test]
+    async fn replays_a_recorded_session() {
+        replay(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/replay.jsonl"));
+        assert_eq!(super::project_id(), Some(42));
+
+        let client = client();
+        in_project(42, async {
+            let categories = get_models::<models::Category>(&client, "categories").await;
+            assert_eq!(
+                categories
+                    .iter()
+                    .map(|category| category.name.as_str())
+                    .collect::<Vec<&str>>(),
+                ["Programming", "Art"]
+            );
+
+            let payload = json!({ "title": "Footstep sounds on grass", "categoryId": 1 });
+            let work_item_id = upload_payload(&client, "Footstep sounds on grass", &payload).await;
+            assert_eq!(work_item_id, 1001);
+        })
+        .await;
+
+        let Some(Mode::Replay(exchanges)) = MODE.get() else {
+            panic!("To be replaying");
+        };
+        assert!(exchanges.lock().expect("To lock the replay").is_empty());
+    }
+}
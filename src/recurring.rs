@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+use clap::{Args, Subcommand};
+use serde::{Deserialize, Deserializer};
+
+use crate::config::Config;
+use crate::schedule::Schedule;
+use crate::{
+    client, create_tickets, current_project_id, get_available_boards, get_current_milestone,
+    output, paths, Arguments, CreateArguments,
+};
+
+const STATE_FILE_NAME: &str = "recurring.json";
+
+#[derive(Subcommand, Debug)]
+pub enum RecurringCommand {
+    /// Create the recurring tickets that are due, meant to be run by a cron job or systemd timer
+    Run(RecurringRunArguments),
+}
+
+#[derive(Args, Debug)]
+pub struct RecurringRunArguments {
+    /// File with the recurring ticket definitions
+    #[arg(short, long, default_value = "recurring.toml")]
+    file: PathBuf,
+
+    /// Create tags used by the templates that don't exist on Hack'n'Plan yet
+    #[arg(long)]
+    create_missing_tags: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Definitions {
+    recurring: Vec<Recurring>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Recurring {
+    /// Identifies the definition, so the same period never gets two copies
+    name: String,
+    #[serde(deserialize_with = "deserialize_schedule")]
+    schedule: Schedule,
+    /// Tickets in the same format as the files passed to `hnp create`
+    template: String,
+    /// Board for the tickets, defaults to the board of the current milestone
+    board: Option<String>,
+}
+
+fn deserialize_schedule<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Schedule, D::Error> {
+    let expression = String::deserialize(deserializer)?;
+    Schedule::parse(&expression).map_err(serde::de::Error::custom)
+}
+
+pub async fn run(arguments: &Arguments, command: &RecurringCommand, config: &Config) {
+    match command {
+        RecurringCommand::Run(run_arguments) => run_due(arguments, run_arguments, config).await,
+    }
+}
+
+async fn run_due(arguments: &Arguments, run_arguments: &RecurringRunArguments, config: &Config) {
+    let contents = fs::read_to_string(&run_arguments.file).unwrap_or_else(|_| {
+        panic!(
+            "To read recurring tickets from {}",
+            run_arguments.file.display()
+        )
+    });
+    let definitions: Definitions = toml::from_str(&contents).unwrap_or_else(|error| {
+        panic!(
+            "To parse recurring tickets in {}: {error}",
+            run_arguments.file.display()
+        )
+    });
+
+    let now = config.calendar.now();
+    let mut state = load_state();
+
+    let due = definitions
+        .recurring
+        .iter()
+        .filter_map(|recurring| {
+            let period = recurring.schedule.last_before(now)?;
+            let created_until = state
+                .get(&state_key(&recurring.name))
+                .and_then(|date| NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S").ok());
+
+            created_until
+                .is_none_or(|created_until| created_until < period)
+                .then_some((recurring, period))
+        })
+        .collect::<Vec<(&Recurring, NaiveDateTime)>>();
+
+    if due.is_empty() {
+        output::info("No recurring tickets are due");
+        return;
+    }
+
+    let client = client();
+    let available_boards = get_available_boards(&client).await;
+    let current_milestone = get_current_milestone(&client, config).await;
+
+    let create_arguments = CreateArguments {
+        default_category: None,
+        create_missing_tags: run_arguments.create_missing_tags,
+        fail_on_missing_tags: !run_arguments.create_missing_tags,
+        milestone: None,
+        check_capacity: false,
+        auto_assign: None,
+        suggest_estimates: false,
+        apply_suggestions: false,
+        stage: None,
+        scan_description: false,
+        skip: 0,
+        limit: None,
+        batch_size: 1,
+        only: None,
+        pick: false,
+        // NOTE: Recurring tickets are created by a cron job, nobody is there to confirm them
+        yes: true,
+        annotate: None,
+        title_template: None,
+        input_format: None,
+        files: vec![],
+    };
+
+    for (recurring, period) in due {
+        let board_id = match &recurring.board {
+            Some(board) => {
+                available_boards
+                    .iter()
+                    .find(|(_, name, _)| name.eq_ignore_ascii_case(board))
+                    .unwrap_or_else(|| panic!("To find a board for \"{board}\""))
+                    .0
+            }
+            None => {
+                let (milestone_id, milestone) = current_milestone.as_ref().unwrap_or_else(|| {
+                    panic!(
+                        "To find a current milestone for \"{}\", or give it a board",
+                        recurring.name
+                    )
+                });
+
+                available_boards
+                    .iter()
+                    .find(|(_, _, board_milestone_id)| *board_milestone_id == Some(*milestone_id))
+                    .unwrap_or_else(|| panic!("To find a board in milestone \"{milestone}\""))
+                    .0
+            }
+        };
+
+        output::info(format!(
+            "Creating \"{}\" for {}",
+            recurring.name,
+            period.format("%Y-%m-%d %H:%M")
+        ));
+
+        create_tickets(
+            arguments,
+            &create_arguments,
+            config,
+            &recurring.template,
+            &format!("recurring \"{}\"", recurring.name),
+            1,
+            Some(board_id),
+        )
+        .await;
+
+        if !arguments.dry_run {
+            // NOTE: Saved after every definition, so a failure later on can't cause duplicates
+            state.insert(
+                state_key(&recurring.name),
+                period.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            );
+            save_state(&state);
+        }
+    }
+}
+
+fn state_key(name: &str) -> String {
+    format!("{PROJECT_ID}/{name}", PROJECT_ID = current_project_id())
+}
+
+/// Path of the file remembering until when each recurring definition was created
+fn state_path() -> PathBuf {
+    paths::data_dir()
+        .expect("To find the user's data directory")
+        .join(STATE_FILE_NAME)
+}
+
+fn load_state() -> HashMap<String, String> {
+    let path = state_path();
+    if !path.exists() {
+        return HashMap::new();
+    }
+
+    let contents = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("To read recurring state from {}", path.display()));
+
+    serde_json::from_str(&contents)
+        .unwrap_or_else(|error| panic!("To parse recurring state in {}: {error}", path.display()))
+}
+
+fn save_state(state: &HashMap<String, String>) {
+    let path = state_path();
+    fs::create_dir_all(path.parent().expect("state file to be in a directory"))
+        .unwrap_or_else(|_| panic!("To create directory for {}", path.display()));
+
+    fs::write(
+        &path,
+        serde_json::to_string_pretty(state).expect("To serialize recurring state"),
+    )
+    .unwrap_or_else(|_| panic!("To write recurring state to {}", path.display()));
+}
@@ -0,0 +1,37 @@
+use clap::Subcommand;
+
+use crate::config::Config;
+use crate::{accuracy, changelog, critical_path, roadmap, stale, Arguments};
+
+#[derive(Subcommand, Debug)]
+pub enum ReportCommand {
+    /// Render milestones and their work items as a static HTML page
+    Roadmap(roadmap::RoadmapArguments),
+    /// Compare the estimates of a milestone's work items to the time logged on them, to
+    /// calibrate future estimates
+    Accuracy(accuracy::AccuracyArguments),
+    /// List the open work items nobody touched in a while, per assignee, and optionally tag them
+    /// or remind their assignees
+    Stale(stale::StaleArguments),
+    /// Find the longest chain of dependencies by estimated work left in a milestone, the work
+    /// items that gate its due date
+    CriticalPath(critical_path::CriticalPathArguments),
+    /// List the completed work items of a milestone by category or tag, as release notes
+    Changelog(changelog::ChangelogArguments),
+}
+
+pub async fn run(arguments: &Arguments, command: &ReportCommand, config: &Config) {
+    match command {
+        ReportCommand::Roadmap(roadmap_arguments) => roadmap::run(roadmap_arguments, config).await,
+        ReportCommand::Accuracy(accuracy_arguments) => accuracy::run(accuracy_arguments).await,
+        ReportCommand::Stale(stale_arguments) => {
+            stale::run(arguments, stale_arguments, config).await
+        }
+        ReportCommand::CriticalPath(critical_path_arguments) => {
+            critical_path::run(critical_path_arguments, config).await
+        }
+        ReportCommand::Changelog(changelog_arguments) => {
+            changelog::run(changelog_arguments, config).await
+        }
+    }
+}
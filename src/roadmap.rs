@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::config::Config;
+use crate::filter::Filter;
+use crate::i18n::Message;
+use crate::models::{self, WorkItem};
+use crate::{client, get_available_boards, get_models, get_work_items, output, Id};
+
+/// Stylesheet used without `--theme`, its colors are CSS variables so a theme can override just
+/// those
+const DEFAULT_STYLESHEET: &str = include_str!("roadmap.css");
+
+#[derive(Args, Debug)]
+pub struct RoadmapArguments {
+    /// Directory to write `index.html` and `style.css` to, it's created when missing
+    #[arg(long)]
+    out: PathBuf,
+
+    /// Only the work items matching this filter, e.g. `-tag:internal`
+    #[arg(short, long)]
+    filter: Option<String>,
+
+    /// Heading of the page
+    #[arg(long, default_value = "Roadmap")]
+    title: String,
+
+    /// Stylesheet to use instead of the default one
+    #[arg(long, value_name = "CSS")]
+    theme: Option<PathBuf>,
+}
+
+pub async fn run(roadmap_arguments: &RoadmapArguments, config: &Config) {
+    let filter = roadmap_arguments.filter.as_deref().map(|filter| {
+        Filter::parse(filter).unwrap_or_else(|error| {
+            output::error(format!("Invalid filter: {error}"));
+            output::exit_with_error();
+        })
+    });
+
+    let stylesheet = match &roadmap_arguments.theme {
+        Some(theme) => fs::read_to_string(theme)
+            .unwrap_or_else(|_| panic!("To read theme {}", theme.display())),
+        None => DEFAULT_STYLESHEET.to_owned(),
+    };
+
+    let client = client();
+    let spinner = output::spinner(Message::FetchingMilestonesAndWorkItems);
+    let mut milestones = get_models::<models::Milestone>(&client, "milestones").await;
+    let available_boards = get_available_boards(&client).await;
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    // NOTE: Milestones run in order of their dates, the ones without dates go last
+    milestones.sort_by(|a, b| {
+        let key = |milestone: &models::Milestone| {
+            (
+                milestone
+                    .due_date
+                    .clone()
+                    .filter(|date| !date.is_empty())
+                    .is_none(),
+                milestone.start_date.clone().unwrap_or_default(),
+                milestone.due_date.clone().unwrap_or_default(),
+            )
+        };
+        key(a).cmp(&key(b))
+    });
+
+    let milestone_of = |work_item: &WorkItem| {
+        let board_id = work_item.board.as_ref()?.board_id;
+        available_boards
+            .iter()
+            .find(|(id, _, _)| *id == board_id)
+            .and_then(|(_, _, milestone_id)| *milestone_id)
+    };
+
+    let mut work_items_by_milestone: BTreeMap<Option<Id>, Vec<&WorkItem>> = BTreeMap::new();
+    for work_item in work_items.iter().filter(|work_item| {
+        filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(work_item))
+    }) {
+        work_items_by_milestone
+            .entry(milestone_of(work_item))
+            .or_default()
+            .push(work_item);
+    }
+
+    let mut sections = vec![];
+    for milestone in &milestones {
+        if let Some(work_items) = work_items_by_milestone.get(&Some(milestone.milestone_id)) {
+            sections.push(section(
+                &milestone.name,
+                dates(milestone).as_deref(),
+                work_items,
+            ));
+        }
+    }
+    if let Some(work_items) = work_items_by_milestone.get(&None) {
+        sections.push(section("Not planned yet", None, work_items));
+    }
+
+    let page = format!(
+        concat!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n",
+            "<meta charset=\"utf-8\">\n",
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+            "<title>{title}</title>\n",
+            "<link rel=\"stylesheet\" href=\"style.css\">\n",
+            "</head>\n<body>\n<main>\n",
+            "<header>\n<h1>{title}</h1>\n<p>Updated {updated}</p>\n</header>\n",
+            "{sections}",
+            "</main>\n</body>\n</html>\n"
+        ),
+        title = escape(&roadmap_arguments.title),
+        updated = config.calendar.today().format("%B %-d, %Y"),
+        sections = sections.join(""),
+    );
+
+    let out = &roadmap_arguments.out;
+    fs::create_dir_all(out).unwrap_or_else(|_| panic!("To create directory {}", out.display()));
+    fs::write(out.join("index.html"), page)
+        .unwrap_or_else(|_| panic!("To write {}", out.join("index.html").display()));
+    fs::write(out.join("style.css"), stylesheet)
+        .unwrap_or_else(|_| panic!("To write {}", out.join("style.css").display()));
+
+    output::success(format!(
+        "Wrote the roadmap with {} milestone(s) to {}",
+        sections.len(),
+        out.join("index.html").display()
+    ));
+}
+
+fn dates(milestone: &models::Milestone) -> Option<String> {
+    let date = |date: &Option<String>| {
+        date.as_deref()
+            .and_then(|date| date.get(..10))
+            .and_then(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            .map(|date| date.format("%B %-d, %Y").to_string())
+    };
+
+    match (date(&milestone.start_date), date(&milestone.due_date)) {
+        (Some(start), Some(due)) => Some(format!("{start} – {due}")),
+        (None, Some(due)) => Some(format!("Until {due}")),
+        (Some(start), None) => Some(format!("From {start}")),
+        (None, None) => None,
+    }
+}
+
+/// A milestone with its progress bar and work items grouped by category
+fn section(name: &str, dates: Option<&str>, work_items: &[&WorkItem]) -> String {
+    let completed = work_items
+        .iter()
+        .filter(|work_item| work_item.is_completed())
+        .count();
+    let percentage = completed * 100 / work_items.len().max(1);
+
+    let mut categories: BTreeMap<String, Vec<&WorkItem>> = BTreeMap::new();
+    for work_item in work_items {
+        let category = work_item
+            .category
+            .as_ref()
+            .map_or("Other".to_owned(), |category| category.name.clone());
+        categories.entry(category).or_default().push(work_item);
+    }
+
+    let mut html = format!("<section class=\"milestone\">\n<h2>{}</h2>\n", escape(name));
+    if let Some(dates) = dates {
+        html.push_str(&format!("<p class=\"dates\">{}</p>\n", escape(dates)));
+    }
+    html.push_str(&format!(
+        "<div class=\"progress\"><span style=\"width: {percentage}%\"></span></div>\n"
+    ));
+    html.push_str(&format!(
+        "<p class=\"summary\">{completed} of {} done ({percentage}%)</p>\n",
+        work_items.len()
+    ));
+
+    for (category, work_items) in categories {
+        html.push_str(&format!(
+            "<div class=\"category\">\n<h3>{}</h3>\n<ul>\n",
+            escape(&category)
+        ));
+
+        for work_item in work_items {
+            let stage = work_item
+                .stage
+                .as_ref()
+                .map(|stage| stage.name.as_str())
+                .unwrap_or_default();
+            html.push_str(&format!(
+                "<li{}><span class=\"title\">{}</span><span class=\"stage\">{}</span></li>\n",
+                if work_item.is_completed() {
+                    " class=\"completed\""
+                } else {
+                    ""
+                },
+                escape(&work_item.title),
+                escape(stage)
+            ));
+        }
+
+        html.push_str("</ul>\n</div>\n");
+    }
+
+    html.push_str("</section>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, HashMap};
+
+use clap::Args;
+use serde_json::json;
+
+use crate::clone::{clone_work_items, find_milestone, set_stage, CloneOptions};
+use crate::i18n::{Action, Message};
+use crate::models::{Stage, WorkItem, WorkLog};
+use crate::{
+    client, get_available_boards, get_available_milestones, get_json, get_models, get_work_items,
+    output, patch_json, Arguments, Id,
+};
+
+#[derive(Args, Debug)]
+pub struct RolloverArguments {
+    /// Milestone whose unfinished work items roll over
+    #[arg(long, value_name = "MILESTONE")]
+    from: String,
+
+    /// Milestone to roll them over to
+    #[arg(long, value_name = "MILESTONE")]
+    to: String,
+
+    /// Board of --to to put them on, needed when it has more than one board and none has the name
+    /// of the board they're on now
+    #[arg(long, value_name = "BOARD")]
+    board: Option<String>,
+
+    /// Only roll over the work items in these stages instead of all that aren't completed
+    #[arg(long, value_name = "STAGE", value_delimiter = ',')]
+    stages: Vec<String>,
+
+    /// Clone the work items into --to with their remaining estimate and complete the originals,
+    /// instead of moving them, so --from keeps a record of what was planned
+    #[arg(long)]
+    clone: bool,
+
+    /// Roll over without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// Moves the unfinished work items of a milestone to the next one, keeping their assignees and
+/// estimates
+pub async fn run(arguments: &Arguments, rollover_arguments: &RolloverArguments) {
+    let client = client();
+
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let available_milestones = get_available_milestones(&client).await;
+    let available_boards = get_available_boards(&client).await;
+    let stages = get_models::<Stage>(&client, "stages").await;
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    let from_id = find_milestone(&rollover_arguments.from, &available_milestones);
+    let to_id = find_milestone(&rollover_arguments.to, &available_milestones);
+    let to_boards = available_boards
+        .iter()
+        .filter(|(_, _, milestone_id)| *milestone_id == Some(to_id))
+        .collect::<Vec<&(Id, String, Option<Id>)>>();
+
+    let stage_ids = rollover_arguments
+        .stages
+        .iter()
+        .map(|name| {
+            stages
+                .iter()
+                .find(|stage| stage.name.eq_ignore_ascii_case(name.trim()))
+                .unwrap_or_else(|| panic!("To find a stage for \"{name}\""))
+                .stage_id
+        })
+        .collect::<Vec<Id>>();
+
+    let board_of = |id: Id| {
+        available_boards
+            .iter()
+            .find(|(board_id, _, _)| *board_id == id)
+    };
+    let to_roll = work_items
+        .iter()
+        .filter(|work_item| {
+            work_item.board.as_ref().is_some_and(|board| {
+                board_of(board.board_id)
+                    .is_some_and(|(_, _, milestone_id)| *milestone_id == Some(from_id))
+            })
+        })
+        .filter(|work_item| !work_item.is_completed())
+        .filter(|work_item| {
+            stage_ids.is_empty()
+                || work_item
+                    .stage
+                    .as_ref()
+                    .is_some_and(|stage| stage_ids.contains(&stage.stage_id))
+        })
+        .collect::<Vec<&WorkItem>>();
+
+    if to_roll.is_empty() {
+        output::success(format!(
+            "Nothing to roll over from \"{}\"",
+            rollover_arguments.from
+        ));
+        return;
+    }
+
+    // NOTE: Boards are matched by name first, so e.g. "Art" in one sprint rolls into "Art" in
+    // the next
+    let target_board = |work_item: &WorkItem| -> Id {
+        if let Some(board) = &rollover_arguments.board {
+            return to_boards
+                .iter()
+                .find(|(_, name, _)| name.eq_ignore_ascii_case(board))
+                .unwrap_or_else(|| {
+                    panic!("To find board \"{board}\" in \"{}\"", rollover_arguments.to)
+                })
+                .0;
+        }
+
+        let current = work_item.board.as_ref().map(|board| board.name.as_str());
+        match to_boards
+            .iter()
+            .find(|(_, name, _)| current.is_some_and(|current| name.eq_ignore_ascii_case(current)))
+        {
+            Some((id, _, _)) => *id,
+            None if to_boards.len() == 1 => to_boards[0].0,
+            None if to_boards.is_empty() => {
+                panic!("To find a board in \"{}\"", rollover_arguments.to)
+            }
+            None => {
+                output::error(format!(
+                    "\"{}\" has more than one board, pick one with --board",
+                    rollover_arguments.to
+                ));
+                output::exit_with_error();
+            }
+        }
+    };
+
+    let mut board_ids = HashMap::new();
+    for work_item in &to_roll {
+        let board_id = target_board(work_item);
+        let board_name = &board_of(board_id).expect("target board to be available").1;
+        output::info(format!(
+            "{} #{} {} to \"{board_name}\"",
+            if rollover_arguments.clone {
+                "Cloning"
+            } else {
+                "Moving"
+            },
+            work_item.work_item_id,
+            work_item.title
+        ));
+        board_ids.insert(work_item.work_item_id, board_id);
+    }
+
+    if arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if rollover_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::RolledOver));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmRollover {
+            work_items: to_roll.len(),
+            milestone: &rollover_arguments.to,
+        })
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::RolledOver));
+        return;
+    }
+
+    if !rollover_arguments.clone {
+        let progress = output::progress(to_roll.len(), "Moving work items");
+        for work_item in &to_roll {
+            let id = work_item.work_item_id;
+            let path = format!("/workitems/{id}");
+            patch_json(&client, &path, &json!({ "boardId": board_ids[&id] })).await;
+            progress.inc(1);
+        }
+        progress.finish_and_clear();
+
+        output::success(format!(
+            "Moved {} work item(s) to \"{}\"",
+            to_roll.len(),
+            rollover_arguments.to
+        ));
+        return;
+    }
+
+    let completed_stage = stages
+        .iter()
+        .find(|stage| stage.status == "completed")
+        .expect("To have a stage for completed work items");
+
+    // NOTE: The clones only get the part of the estimate that hasn't been logged yet
+    let mut estimates = HashMap::new();
+    for work_item in &to_roll {
+        let id = work_item.work_item_id;
+        let work_logs: Vec<WorkLog> =
+            serde_json::from_value(get_json(&client, &format!("/workitems/{id}/worklogs")).await)
+                .unwrap_or_else(|error| panic!("To deserialize work logs: {error}"));
+        let logged = work_logs.iter().map(|work_log| work_log.value).sum::<f32>();
+
+        if let Some(estimate) = work_item.estimated_cost {
+            estimates.insert(id, (estimate - logged).max(0.0));
+        }
+    }
+
+    let options = CloneOptions {
+        sub_tasks: true,
+        reset_stages: false,
+        estimates,
+        from_project: None,
+    };
+
+    // NOTE: clone_work_items puts everything on one board, so the work items go per board
+    let mut by_board: BTreeMap<Id, Vec<&WorkItem>> = BTreeMap::new();
+    for work_item in &to_roll {
+        by_board
+            .entry(board_ids[&work_item.work_item_id])
+            .or_default()
+            .push(work_item);
+    }
+
+    let mut cloned = 0;
+    for (board_id, on_board) in &by_board {
+        cloned += clone_work_items(&client, on_board, Some(*board_id), &options)
+            .await
+            .len();
+    }
+
+    for work_item in &to_roll {
+        set_stage(&client, work_item.work_item_id, completed_stage.stage_id).await;
+    }
+
+    output::success(format!(
+        "Cloned {} work item(s) to \"{}\" and completed the originals in \"{}\"",
+        cloned, rollover_arguments.to, rollover_arguments.from
+    ));
+}
@@ -0,0 +1,251 @@
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Timelike};
+
+/// Cron expression (`minute hour day-of-month month day-of-week`) deciding when a recurring
+/// ticket is due. Fields support `*`, lists (`1,15`), ranges (`1-5`), steps (`*/2`) and names
+/// (`mon`, `jan`), and `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are accepted
+/// as shorthands.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    /// Like cron, a day matches either field when both days are restricted
+    days_restricted: bool,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Schedule, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            expression => expression,
+        };
+
+        let fields = expression.split_whitespace().collect::<Vec<&str>>();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(format!(
+                "\"{expression}\" should have 5 fields: minute hour day-of-month month day-of-week"
+            ));
+        };
+
+        let mut days_of_week = parse_field(days_of_week, 0, 7, &DAY_NAMES)?;
+        // NOTE: Both 0 and 7 are Sunday
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+
+        Ok(Schedule {
+            minutes: parse_field(minutes, 0, 59, &[])?,
+            hours: parse_field(hours, 0, 23, &[])?,
+            days_of_month: parse_field(days_of_month, 1, 31, &[])?,
+            months: parse_field(months, 1, 12, &MONTH_NAMES)?,
+            days_of_week,
+            days_restricted: !["*", "?"].contains(&fields[2]) && !["*", "?"].contains(&fields[4]),
+        })
+    }
+
+    /// Last moment at or before `now` the schedule fired, if that's in its current period. That
+    /// period is as long as the coarsest field the schedule restricts, so a schedule for the 31st
+    /// of the month doesn't fire in a month without one.
+    pub fn last_before(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let period = self.period();
+
+        for days_ago in 0..=period.num_days() {
+            let date = (now - Duration::days(days_ago)).date();
+            if !self.matches_date(date) {
+                continue;
+            }
+
+            let latest_hour = if days_ago == 0 { now.hour() } else { 23 };
+            for hour in (0..=latest_hour).rev() {
+                if !self.hours[hour as usize] {
+                    continue;
+                }
+
+                let latest_minute = if days_ago == 0 && hour == now.hour() {
+                    now.minute()
+                } else {
+                    59
+                };
+
+                if let Some(minute) = (0..=latest_minute)
+                    .rev()
+                    .find(|minute| self.minutes[*minute as usize])
+                {
+                    return NaiveTime::from_hms_opt(hour, minute, 0)
+                        .map(|time| date.and_time(time))
+                        .filter(|fired| now - *fired < period);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// How long each period of the schedule is: a year when it restricts the months, a month
+    /// for the days of the month, a week for the days of the week, a day for the hours and an
+    /// hour otherwise
+    fn period(&self) -> Duration {
+        let restricts = |allowed: &[bool]| allowed.iter().any(|allowed| !allowed);
+
+        if restricts(&self.months[1..]) {
+            Duration::days(366)
+        } else if restricts(&self.days_of_week) {
+            Duration::days(7)
+        } else if restricts(&self.days_of_month[1..]) {
+            Duration::days(31)
+        } else if restricts(&self.hours) {
+            Duration::days(1)
+        } else {
+            Duration::hours(1)
+        }
+    }
+
+    fn matches_date(&self, date: chrono::NaiveDate) -> bool {
+        if !self.months[date.month() as usize] {
+            return false;
+        }
+
+        let day_of_month = self.days_of_month[date.day() as usize];
+        let day_of_week = self.days_of_week[date.weekday().num_days_from_sunday() as usize];
+
+        if self.days_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+}
+
+/// Which values between `min` and `max` the cron `field` allows, indexed by value
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max as usize + 1];
+
+    let value = |value: &str| -> Result<u32, String> {
+        let lowercase = value.to_lowercase();
+        let number = match names.iter().position(|name| *name == lowercase) {
+            // NOTE: Month names start at 1, day names at 0 (Sunday)
+            Some(position) => position as u32 + min,
+            None => value
+                .parse::<u32>()
+                .map_err(|_| format!("\"{value}\" in \"{field}\" is not a valid value"))?,
+        };
+
+        if number < min || number > max {
+            return Err(format!(
+                "\"{value}\" in \"{field}\" should be between {min} and {max}"
+            ));
+        }
+
+        Ok(number)
+    };
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<usize>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("\"{step}\" in \"{field}\" is not a valid step"))?,
+            ),
+            None => (part, 1),
+        };
+
+        let (start, end) = match range {
+            "*" | "?" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+
+        // NOTE: Only days of the week wrap around, `fri-mon` is the weekend
+        let numbers = if start <= end {
+            (start..=end).collect::<Vec<u32>>()
+        } else if names == DAY_NAMES {
+            (start..=max).chain(min..=end).collect()
+        } else {
+            return Err(format!(
+                "\"{range}\" in \"{field}\" should go from low to high"
+            ));
+        };
+
+        for number in numbers.into_iter().step_by(step) {
+            allowed[number as usize] = true;
+        }
+    }
+
+    Ok(allowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::Schedule;
+
+    fn at(moment: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(moment, "%Y-%m-%d %H:%M").expect("To parse the moment")
+    }
+
+    fn last_before(expression: &str, now: &str) -> Option<NaiveDateTime> {
+        Schedule::parse(expression)
+            .expect("To parse the schedule")
+            .last_before(at(now))
+    }
+
+    #[test]
+    fn wraps_ranges_of_weekdays() {
+        // NOTE: October 16th 2026 is a Friday
+        assert_eq!(
+            last_before("0 9 * * mon-sun", "2026-10-16 10:00"),
+            Some(at("2026-10-16 09:00"))
+        );
+        assert_eq!(
+            last_before("0 9 * * fri-mon", "2026-10-15 10:00"),
+            Some(at("2026-10-12 09:00"))
+        );
+        assert_eq!(
+            last_before("0 9 * * 6-0", "2026-10-16 10:00"),
+            Some(at("2026-10-11 09:00"))
+        );
+    }
+
+    #[test]
+    fn rejects_other_ranges_from_high_to_low() {
+        assert!(Schedule::parse("0 17-9 * * *").is_err());
+        assert!(Schedule::parse("0 9 * dec-jan *").is_err());
+    }
+
+    #[test]
+    fn only_fires_in_the_current_period() {
+        assert_eq!(
+            last_before("0 9 * * mon", "2026-10-16 10:00"),
+            Some(at("2026-10-12 09:00"))
+        );
+        assert_eq!(
+            last_before("0 0 1 * *", "2026-10-16 10:00"),
+            Some(at("2026-10-01 00:00"))
+        );
+
+        // NOTE: November has no 31st, so in December the last one is a period too far back
+        assert_eq!(last_before("0 0 31 * *", "2026-12-15 10:00"), None);
+        assert_eq!(
+            last_before("30 9 * * *", "2026-10-16 09:00"),
+            Some(at("2026-10-15 09:30"))
+        );
+    }
+}
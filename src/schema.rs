@@ -0,0 +1,59 @@
+use serde_json::{json, Value};
+
+/// JSON Schema of a resolved ticket, the JSON `--dry-run` prints for every ticket before it
+/// would be uploaded. Keep it in line with the fields of `Ticket`.
+pub fn ticket() -> Value {
+    let id = json!({ "type": "integer" });
+    let ids = json!({ "type": "array", "items": { "type": "integer" } });
+    let date = json!({
+        "type": "string",
+        "description": "YYYY-MM-DDT00:00:00, or empty when not set",
+        "pattern": r"^(\d{4}-\d{2}-\d{2}T00:00:00)?$"
+    });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "hnp resolved ticket",
+        "description": "A ticket with its markup resolved to Hack'n'Plan ids, as `hnp --dry-run` prints it",
+        "type": "object",
+        "properties": {
+            "title": { "type": "string" },
+            "description": { "type": "string" },
+            "parentId": { "type": "integer", "description": "Story to put the work item below, 0 for none" },
+            "isStory": { "type": "boolean" },
+            "categoryId": id,
+            "estimatedCost": { "type": "number", "minimum": 0, "description": "Hours or points, depending on the project" },
+            "importanceLevelId": id,
+            "boardId": { "type": "integer", "description": "0 for the backlog" },
+            "startDate": date,
+            "dueDate": date,
+            "assignedUserIds": ids,
+            "tagIds": ids,
+            "subTasks": {
+                "type": "array",
+                "description": "Titles of the sub tasks, sub tasks with an assignee or estimate become child work items instead",
+                "items": { "type": "string" }
+            },
+            "dependencyIds": ids,
+            "designElementId": id,
+            "stageId": { "type": "integer", "description": "Stage to move the work item to after creating it" }
+        },
+        "required": [
+            "title",
+            "description",
+            "parentId",
+            "isStory",
+            "categoryId",
+            "estimatedCost",
+            "importanceLevelId",
+            "boardId",
+            "startDate",
+            "dueDate",
+            "assignedUserIds",
+            "tagIds",
+            "subTasks",
+            "dependencyIds"
+        ],
+        "additionalProperties": false
+    })
+}
@@ -0,0 +1,204 @@
+use std::path::Path;
+
+use rhai::{Array, Dynamic, Engine, Map, Scope};
+
+use crate::{core, Id, Ticket};
+
+/// Runs the Rhai script at `path` once for every ticket, with the ticket as the `ticket` map.
+/// Categories, tags, assignees, importance levels and boards are in there by name:
+///
+/// ```text
+/// if ticket.title.contains("crash") {
+///     ticket.tags.push("stability");
+///     ticket.importance = "Urgent";
+/// }
+/// ```
+pub fn transform(path: &Path, tickets: &mut [Ticket], metadata: &core::Metadata) {
+    let engine = Engine::new();
+    let script = engine
+        .compile_file(path.to_path_buf())
+        .unwrap_or_else(|error| panic!("To compile {}: {error}", path.display()));
+
+    for ticket in tickets {
+        let mut scope = Scope::new();
+        scope.push("ticket", to_map(ticket, metadata));
+
+        engine
+            .run_ast_with_scope(&mut scope, &script)
+            .unwrap_or_else(|error| {
+                panic!(
+                    "To run {} for \"{}\": {error}",
+                    path.display(),
+                    ticket.title
+                )
+            });
+
+        let map = scope
+            .get_value::<Map>("ticket")
+            .unwrap_or_else(|| panic!("To still have a `ticket` map after {}", path.display()));
+        from_map(ticket, &map, metadata);
+    }
+}
+
+fn to_map(ticket: &Ticket, metadata: &core::Metadata) -> Map {
+    let name = |names: &[(Id, String)], id: Id| {
+        names
+            .iter()
+            .find(|(available_id, _)| *available_id == id)
+            .map(|(_, name)| name.to_owned())
+            .unwrap_or_default()
+    };
+
+    let mut map = Map::new();
+    map.insert("title".into(), ticket.title.clone().into());
+    map.insert("description".into(), ticket.description.clone().into());
+    map.insert(
+        "category".into(),
+        name(&metadata.categories, ticket.category_id).into(),
+    );
+    map.insert(
+        "tags".into(),
+        ticket
+            .tag_ids
+            .iter()
+            .map(|id| Dynamic::from(name(&metadata.tags, *id)))
+            .collect::<Array>()
+            .into(),
+    );
+    map.insert(
+        "assignees".into(),
+        metadata
+            .users
+            .iter()
+            .filter(|(id, _, _)| ticket.assigned_user_ids.contains(id))
+            .map(|(_, _, username)| Dynamic::from(username.to_owned()))
+            .collect::<Array>()
+            .into(),
+    );
+    map.insert("estimate".into(), (ticket.estimated_cost as f64).into());
+    map.insert(
+        "importance".into(),
+        metadata
+            .importance_levels
+            .iter()
+            .find(|(id, _, _)| *id == ticket.importance_level_id)
+            .map(|(_, name, _)| name.to_owned())
+            .unwrap_or_default()
+            .into(),
+    );
+    map.insert(
+        "board".into(),
+        metadata
+            .boards
+            .iter()
+            .find(|(id, _, _)| *id == ticket.board_id)
+            .map(|(_, name, _)| name.to_owned())
+            .unwrap_or_default()
+            .into(),
+    );
+
+    map
+}
+
+/// Takes over what the script changed in `map`, looking the names up in `metadata`
+fn from_map(ticket: &mut Ticket, map: &Map, metadata: &core::Metadata) {
+    let text = |field: &str| {
+        map.get(field)
+            .and_then(|value| value.clone().into_string().ok())
+            .unwrap_or_else(|| panic!("To have text in ticket.{field} of \"{}\"", ticket.title))
+    };
+    let texts = |field: &str| {
+        map.get(field)
+            .and_then(|value| value.clone().try_cast::<Array>())
+            .map(|values| {
+                values
+                    .into_iter()
+                    .filter_map(|value| value.into_string().ok())
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_else(|| panic!("To have a list in ticket.{field} of \"{}\"", ticket.title))
+    };
+    let id = |names: &[(Id, String)], kind: &str, name: &str| {
+        names
+            .iter()
+            .find(|(_, available)| available.eq_ignore_ascii_case(name))
+            .map(|(id, _)| *id)
+            .unwrap_or_else(|| panic!("To find a {kind} for \"{name}\" set by the script"))
+    };
+
+    let title = text("title");
+    let description = text("description");
+    let category_id = id(&metadata.categories, "category", &text("category"));
+    let tag_ids = texts("tags")
+        .iter()
+        .map(|tag| id(&metadata.tags, "tag", tag))
+        .collect::<Vec<Id>>();
+    let assigned_user_ids = texts("assignees")
+        .iter()
+        .map(|user_name| crate::find_user(user_name, &metadata.users).0)
+        .collect::<Vec<Id>>();
+    let estimate = map
+        .get("estimate")
+        .and_then(|value| {
+            value
+                .as_float()
+                .ok()
+                .or_else(|| value.as_int().ok().map(|estimate| estimate as f64))
+        })
+        .unwrap_or_else(|| {
+            panic!(
+                "To have a number in ticket.estimate of \"{}\"",
+                ticket.title
+            )
+        });
+
+    let importance = text("importance");
+    let importance_level_id = if importance.is_empty() {
+        ticket.importance_level_id
+    } else {
+        metadata
+            .importance_levels
+            .iter()
+            .find(|(_, name, _)| name.eq_ignore_ascii_case(&importance))
+            .map(|(id, _, _)| *id)
+            .unwrap_or_else(|| {
+                panic!("To find an importance level for \"{importance}\" set by the script")
+            })
+    };
+
+    // NOTE: An empty board leaves the ticket in the backlog
+    let board = text("board");
+    let board_id = if board.is_empty() {
+        0
+    } else {
+        metadata
+            .boards
+            .iter()
+            .find(|(_, name, _)| name.eq_ignore_ascii_case(&board))
+            .map(|(id, _, _)| *id)
+            .unwrap_or_else(|| panic!("To find a board for \"{board}\" set by the script"))
+    };
+
+    ticket.title = title;
+    ticket.description = description;
+    ticket.category_id = category_id;
+    ticket.tag_ids = tag_ids;
+    ticket.assigned_user_ids = assigned_user_ids;
+    ticket.estimated_cost = estimate as f32;
+    ticket.importance_level_id = importance_level_id;
+    ticket.board_id = board_id;
+
+    // NOTE: What the script sets wins over the same fields from ```json blocks and hooks
+    for field in [
+        "title",
+        "description",
+        "categoryId",
+        "tagIds",
+        "assignedUserIds",
+        "estimatedCost",
+        "importanceLevelId",
+        "boardId",
+    ] {
+        ticket.overrides.remove(field);
+    }
+}
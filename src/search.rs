@@ -0,0 +1,151 @@
+use clap::Args;
+use console::style;
+use regex::Regex;
+
+use crate::i18n::Message;
+use crate::models::WorkItem;
+use crate::{client, get_work_items, output};
+
+/// Characters of a description line shown around the first match
+const SNIPPET_LENGTH: usize = 80;
+
+#[derive(Args, Debug)]
+pub struct SearchArguments {
+    /// Words to look for in the titles and descriptions, all of them have to appear
+    #[arg(required = true, value_name = "WORDS")]
+    words: Vec<String>,
+
+    /// Only print the ids of the matching work items, one per line, for piping into
+    /// `hnp bulk --ids -`
+    #[arg(short = 'q', long)]
+    ids_only: bool,
+}
+
+/// Lists the work items whose title or description contains every word, matches in the title
+/// first
+pub async fn run(search_arguments: &SearchArguments) {
+    let words = search_arguments
+        .words
+        .iter()
+        .flat_map(|words| words.split_whitespace())
+        .collect::<Vec<&str>>();
+    let matchers = words
+        .iter()
+        .map(|word| {
+            Regex::new(&format!("(?i){}", regex::escape(word))).expect("To compile a search word")
+        })
+        .collect::<Vec<Regex>>();
+    let any_word = Regex::new(&format!(
+        "(?i){}",
+        words
+            .iter()
+            .map(|word| regex::escape(word))
+            .collect::<Vec<String>>()
+            .join("|")
+    ))
+    .expect("To compile the search words");
+
+    // NOTE: The API can't search work items, so all of them are fetched and searched here
+    let client = client();
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    let mut results = work_items
+        .iter()
+        .filter_map(|work_item| {
+            let description = work_item.description.as_deref().unwrap_or_default();
+            let in_title = matchers
+                .iter()
+                .all(|matcher| matcher.is_match(&work_item.title));
+            let matches = matchers
+                .iter()
+                .all(|matcher| matcher.is_match(&work_item.title) || matcher.is_match(description));
+
+            matches.then_some((!in_title, work_item))
+        })
+        .collect::<Vec<(bool, &WorkItem)>>();
+    results.sort_by_key(|(in_description, work_item)| (*in_description, work_item.work_item_id));
+
+    if search_arguments.ids_only {
+        for (_, work_item) in &results {
+            println!("{}", work_item.work_item_id);
+        }
+        return;
+    }
+
+    if results.is_empty() {
+        output::info(format!("No work items contain \"{}\"", words.join(" ")));
+        return;
+    }
+
+    for (_, work_item) in &results {
+        let details = [
+            work_item
+                .category
+                .as_ref()
+                .map(|category| category.name.as_str()),
+            work_item.stage.as_ref().map(|stage| stage.name.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&str>>()
+        .join(", ");
+
+        println!(
+            "{} {} {}",
+            style(format!("#{}", work_item.work_item_id)).dim(),
+            highlight(&work_item.title, &any_word),
+            style(format!("({details})")).dim()
+        );
+
+        let snippet = work_item
+            .description
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .find(|line| any_word.is_match(line))
+            .map(|line| snippet(line.trim(), &any_word));
+        if let Some(snippet) = snippet {
+            println!("    {}", highlight(&snippet, &any_word));
+        }
+    }
+
+    output::info(format!("Found {} work item(s)", results.len()));
+}
+
+/// `text` with everything `matcher` matches in bold yellow
+fn highlight(text: &str, matcher: &Regex) -> String {
+    matcher
+        .replace_all(text, |captures: &regex::Captures| {
+            style(&captures[0]).yellow().bold().to_string()
+        })
+        .into_owned()
+}
+
+/// The part of `line` around the first match, with `…` where it's cut off
+fn snippet(line: &str, matcher: &Regex) -> String {
+    let characters = line.chars().count();
+    if characters <= SNIPPET_LENGTH {
+        return line.to_owned();
+    }
+
+    let first_match = matcher
+        .find(line)
+        .map(|found| line[..found.start()].chars().count())
+        .unwrap_or_default();
+    let start = first_match
+        .saturating_sub(SNIPPET_LENGTH / 3)
+        .min(characters - SNIPPET_LENGTH);
+    let end = start + SNIPPET_LENGTH;
+
+    format!(
+        "{}{}{}",
+        if start > 0 { "…" } else { "" },
+        line.chars()
+            .skip(start)
+            .take(SNIPPET_LENGTH)
+            .collect::<String>(),
+        if end < characters { "…" } else { "" }
+    )
+}
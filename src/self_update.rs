@@ -0,0 +1,171 @@
+use std::env::consts::{ARCH, EXE_SUFFIX, OS};
+use std::fs;
+
+use clap::Args;
+use minisign_verify::{PublicKey, Signature};
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::i18n::{Action, Message};
+use crate::{client, output, Arguments};
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/bram-dingelstad/hnp-cli/releases/latest";
+
+/// Minisign key the checksums of the releases are signed with, set when building a release
+// NOTE: Without it there's no way to tell a release apart from anything else on GitHub, so
+// builds without the key refuse to update
+const PUBLIC_KEY: Option<&str> = option_env!("HNP_RELEASE_PUBLIC_KEY");
+
+/// Release asset with the SHA-256 checksum of every binary, signed in `SHA256SUMS.minisig`
+const CHECKSUMS: &str = "SHA256SUMS";
+
+#[derive(Args, Debug)]
+pub struct SelfUpdateArguments {
+    /// Only tell whether there's a newer version
+    #[arg(long)]
+    check: bool,
+
+    /// Update without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// Replaces the running binary with the latest release on GitHub, after checking it against the
+/// signed checksums of the release
+pub async fn run(arguments: &Arguments, self_update_arguments: &SelfUpdateArguments) {
+    let client = client();
+
+    let spinner = output::spinner("Checking GitHub for a new version of hnp");
+    let release: Release = client
+        .get(LATEST_RELEASE_URL)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|error| panic!("To fetch the latest release from GitHub: {error}"))
+        .json()
+        .await
+        .unwrap_or_else(|error| panic!("To deserialize the latest release: {error}"));
+    spinner.finish_and_clear();
+
+    let current = Version::parse(env!("CARGO_PKG_VERSION")).expect("To have a semver version");
+    let latest = Version::parse(release.tag_name.trim_start_matches('v'))
+        .unwrap_or_else(|_| panic!("To read release \"{}\" as a version", release.tag_name));
+
+    if latest <= current {
+        output::success(format!("hnp {current} is the latest version"));
+        return;
+    }
+
+    output::info(format!("hnp {latest} is available, this is {current}"));
+    if self_update_arguments.check || arguments.dry_run {
+        return;
+    }
+
+    // NOTE: Release binaries are named after the platform Rust reports, e.g.
+    // `hnp-x86_64-windows.exe` or `hnp-aarch64-macos`
+    let binary_name = format!("hnp-{ARCH}-{OS}{EXE_SUFFIX}");
+    let (Some(binary), Some(checksums), Some(signature)) = (
+        release.asset(&binary_name),
+        release.asset(CHECKSUMS),
+        release.asset(&format!("{CHECKSUMS}.minisig")),
+    ) else {
+        output::error(format!(
+            "hnp {latest} has no signed build for {OS} on {ARCH}, download it from {}",
+            release.html_url
+        ));
+        output::exit_with_error();
+    };
+
+    let Some(public_key) = PUBLIC_KEY else {
+        output::error(format!(
+            "This build of hnp can't verify releases, download hnp {latest} from {}",
+            release.html_url
+        ));
+        output::exit_with_error();
+    };
+
+    let confirmed = if self_update_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Updated));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmSelfUpdate(&latest))
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Updated));
+        return;
+    }
+
+    let spinner = output::spinner(format!("Downloading hnp {latest}"));
+    let checksums = download(&client, checksums).await;
+    let signature = download(&client, signature).await;
+    let contents = download(&client, binary).await;
+    spinner.finish_and_clear();
+
+    let public_key = PublicKey::from_base64(public_key)
+        .expect("To read the public key the releases are signed with");
+    let verified = Signature::decode(&String::from_utf8_lossy(&signature))
+        .and_then(|signature| public_key.verify(&checksums, &signature, false));
+    if verified.is_err() {
+        output::error(format!(
+            "The signature of the checksums of hnp {latest} is invalid, nothing was updated"
+        ));
+        output::exit_with_error();
+    }
+
+    let expected = String::from_utf8_lossy(&checksums)
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim().trim_start_matches('*') == binary_name)
+        .map(|(checksum, _)| checksum.to_lowercase());
+    if expected != Some(format!("{:x}", Sha256::digest(&contents))) {
+        output::error(format!(
+            "The download of {binary_name} doesn't match its checksum, nothing was updated"
+        ));
+        output::exit_with_error();
+    }
+
+    let path = std::env::temp_dir().join(format!("{binary_name}-{latest}"));
+    fs::write(&path, contents).unwrap_or_else(|_| panic!("To write {}", path.display()));
+    self_replace::self_replace(&path)
+        .unwrap_or_else(|error| panic!("To replace the hnp binary: {error}"));
+    let _ = fs::remove_file(&path);
+
+    output::success(format!("Updated hnp to {latest}"));
+}
+
+async fn download(client: &reqwest::Client, asset: &Asset) -> Vec<u8> {
+    client
+        .get(&asset.browser_download_url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .unwrap_or_else(|error| panic!("To download {}: {error}", asset.name))
+        .bytes()
+        .await
+        .unwrap_or_else(|error| panic!("To download {}: {error}", asset.name))
+        .to_vec()
+}
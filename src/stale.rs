@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use clap::Args;
+use serde_json::{json, Value};
+
+use crate::bulk::{self, Changes};
+use crate::config::Config;
+use crate::i18n::{Action, Message};
+use crate::models::{Comment, WorkItem, WorkLog};
+use crate::{
+    client, get_available_tags, get_json, get_work_items, normalize_name, output, post_json,
+    Arguments,
+};
+
+#[derive(Args, Debug)]
+pub struct StaleArguments {
+    /// Days without a change, comment or logged time before a work item counts as stale
+    #[arg(long, default_value_t = 30)]
+    days: u64,
+
+    /// Tag the stale work items, with `stale` unless another tag is given. The tag is created
+    /// when it doesn't exist yet.
+    #[arg(
+        long,
+        value_name = "TAG",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "stale"
+    )]
+    tag: Option<String>,
+
+    /// Post a comment on the stale work items to remind their assignees, `{days}` is replaced by
+    /// the days since they were last touched
+    #[arg(
+        long,
+        value_name = "TEXT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "This hasn't been touched in {days} days, is it still relevant?"
+    )]
+    remind: Option<String>,
+
+    /// Tag and comment without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+/// Lists the open work items nobody touched in `--days`, grouped by assignee, and optionally
+/// tags them or posts a reminder on them
+pub async fn run(arguments: &Arguments, stale_arguments: &StaleArguments, config: &Config) {
+    let client = client();
+    let today = config.calendar.today();
+    let cutoff = today - chrono::Days::new(stale_arguments.days);
+
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    // NOTE: The update date covers stage changes and edits, comments and logged time are only
+    // fetched for the work items that look stale by it
+    let candidates = work_items
+        .iter()
+        .filter(|work_item| !work_item.is_completed())
+        .filter(|work_item| {
+            config
+                .calendar
+                .day_of(&work_item.update_date)
+                .is_none_or(|date| date < cutoff)
+        })
+        .collect::<Vec<&WorkItem>>();
+
+    let progress = output::progress(candidates.len(), "Checking comments and logged time");
+    let mut stale = vec![];
+    for work_item in candidates {
+        let id = work_item.work_item_id;
+        let comments: Vec<Comment> = serde_json::from_value(items(
+            get_json(&client, &format!("/workitems/{id}/comments")).await,
+        ))
+        .unwrap_or_else(|error| panic!("To deserialize comments: {error}"));
+        let work_logs: Vec<WorkLog> =
+            serde_json::from_value(get_json(&client, &format!("/workitems/{id}/worklogs")).await)
+                .unwrap_or_else(|error| panic!("To deserialize work logs: {error}"));
+
+        let last_touched = comments
+            .iter()
+            .map(|comment| config.calendar.day_of(&comment.creation_date))
+            .chain(
+                work_logs
+                    .iter()
+                    .map(|work_log| config.calendar.day_of(&work_log.creation_date)),
+            )
+            .chain([config.calendar.day_of(&work_item.update_date)])
+            .flatten()
+            .max();
+
+        if last_touched.is_none_or(|date| date < cutoff) {
+            stale.push((work_item, last_touched));
+        }
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    if stale.is_empty() {
+        output::success(format!(
+            "Every open work item was touched in the last {} days",
+            stale_arguments.days
+        ));
+        return;
+    }
+
+    let mut by_assignee: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (work_item, last_touched) in &stale {
+        let line = format!(
+            "#{} {} ({})",
+            work_item.work_item_id,
+            work_item.title,
+            last_touched.map_or("never touched".to_owned(), |date| format!(
+                "{} days",
+                (today - date).num_days()
+            ))
+        );
+
+        let mut assignees = work_item
+            .assigned_users
+            .iter()
+            .map(|assigned_user| format!("@{}", assigned_user.user.username))
+            .collect::<Vec<String>>();
+        if assignees.is_empty() {
+            assignees.push("Unassigned".to_owned());
+        }
+
+        for assignee in assignees {
+            by_assignee.entry(assignee).or_default().push(line.clone());
+        }
+    }
+
+    for (assignee, lines) in &by_assignee {
+        println!("{assignee}");
+        for line in lines {
+            println!("    {line}");
+        }
+    }
+    println!();
+    output::info(format!(
+        "{} work item(s) weren't touched in the last {} days",
+        stale.len(),
+        stale_arguments.days
+    ));
+
+    if (stale_arguments.tag.is_none() && stale_arguments.remind.is_none()) || arguments.dry_run {
+        return;
+    }
+
+    let confirmed = if stale_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Changed));
+        output::exit_with_error();
+    } else {
+        output::confirm(Message::ConfirmStale {
+            work_items: stale.len(),
+            tag: stale_arguments.tag.is_some(),
+            remind: stale_arguments.remind.is_some(),
+        })
+    };
+
+    if !confirmed {
+        output::info(Message::NothingWas(Action::Changed));
+        return;
+    }
+
+    let mut tag = None;
+    if let Some(name) = &stale_arguments.tag {
+        let existing = get_available_tags(&client)
+            .await
+            .into_iter()
+            .find(|(_, available_name)| normalize_name(available_name) == normalize_name(name));
+
+        tag = Some(match existing {
+            Some(existing) => existing,
+            None => (
+                post_json(&client, "/tags", &json!({ "name": name }), "tagId").await,
+                name.to_owned(),
+            ),
+        });
+    }
+
+    let progress = output::progress(stale.len(), "Updating stale work items");
+    for (work_item, last_touched) in &stale {
+        let id = work_item.work_item_id;
+
+        if let Some(tag) = tag
+            .as_ref()
+            .filter(|(tag_id, _)| !work_item.tag_ids().contains(tag_id))
+        {
+            let changes = Changes {
+                add_tags: vec![tag.clone()],
+                ..Default::default()
+            };
+            bulk::apply(&client, id, &changes).await;
+        }
+
+        if let Some(remind) = &stale_arguments.remind {
+            let days = last_touched.map_or("many".to_owned(), |date| {
+                (today - date).num_days().to_string()
+            });
+            let path = format!("/workitems/{id}/comments");
+            post_json(
+                &client,
+                &path,
+                &json!(remind.replace("{days}", &days)),
+                "id",
+            )
+            .await;
+        }
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    output::success(format!("Updated {} stale work item(s)", stale.len()));
+}
+
+/// The items of a paged response, or the response itself when it isn't paged
+fn items(value: Value) -> Value {
+    match value {
+        Value::Object(mut object) => object.remove("items").unwrap_or_default(),
+        value => value,
+    }
+}
@@ -0,0 +1,197 @@
+use chrono::{Datelike, NaiveDate};
+use clap::Args;
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::i18n::Message;
+use crate::models::{Stage, WorkItem};
+use crate::recording::SendRecorded;
+use crate::{
+    api_endpoint, client, core, get_available_users, get_models, get_work_items, output, Id,
+    API_KEY,
+};
+
+#[derive(Args, Debug)]
+pub struct StandupArguments {
+    /// Whose work items to summarize, `@me` being the owner of the API key
+    #[arg(long, default_value = "@me", value_name = "@USER")]
+    user: String,
+
+    /// Format the summary as Markdown
+    #[arg(long, conflicts_with = "slack")]
+    markdown: bool,
+
+    /// Format the summary for Slack
+    #[arg(long)]
+    slack: bool,
+}
+
+/// The user the API key belongs to, which isn't scoped to a project
+#[derive(Deserialize)]
+struct CurrentUser {
+    username: String,
+}
+
+/// Prints what the user completed on the previous working day, is working on today and is blocked
+/// on, ready to paste into chat
+pub async fn run(standup_arguments: &StandupArguments, config: &Config) {
+    let client = client();
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let user = find_user(&client, &standup_arguments.user).await;
+    let stages = get_models::<Stage>(&client, "stages").await;
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    // NOTE: The stage of a work item lacks `isUnblocker`, so it's looked up in the stages
+    let stage = |work_item: &WorkItem| {
+        let stage_id = work_item.stage.as_ref()?.stage_id;
+        stages.iter().find(|stage| stage.stage_id == stage_id)
+    };
+    // NOTE: A dependency stops blocking once it reaches a stage that unblocks, or is gone
+    let blockers = |work_item: &WorkItem| {
+        work_item
+            .dependency_ids
+            .iter()
+            .filter_map(|dependency_id| {
+                work_items
+                    .iter()
+                    .find(|candidate| candidate.work_item_id == *dependency_id)
+            })
+            .filter(|dependency| {
+                !dependency.is_completed()
+                    && !stage(dependency).is_some_and(|stage| stage.is_unblocker)
+            })
+            .collect::<Vec<&WorkItem>>()
+    };
+
+    let yesterday = previous_working_day(config);
+    let mut completed = vec![];
+    let mut in_progress = vec![];
+    let mut blocked = vec![];
+    for work_item in work_items
+        .iter()
+        .filter(|work_item| !work_item.is_story && work_item.assigned_user_ids().contains(&user.0))
+    {
+        if work_item.is_completed() {
+            if config.calendar.day_of(&work_item.update_date) == Some(yesterday) {
+                completed.push(line(work_item));
+            }
+            continue;
+        }
+
+        let blockers = blockers(work_item);
+        let is_blocked_stage =
+            stage(work_item).is_some_and(|stage| stage.name.to_lowercase().contains("block"));
+        if !blockers.is_empty() {
+            blocked.push(format!(
+                "{} (waiting on {})",
+                line(work_item),
+                blockers
+                    .into_iter()
+                    .map(line)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        } else if is_blocked_stage {
+            blocked.push(line(work_item));
+        } else if stage(work_item).is_some_and(|stage| stage.status == "started") {
+            in_progress.push(line(work_item));
+        }
+    }
+
+    let sections = [
+        (
+            if config.calendar.today() - yesterday == chrono::Duration::days(1) {
+                "Completed yesterday".to_owned()
+            } else {
+                format!("Completed {}", yesterday.format("%A"))
+            },
+            completed,
+        ),
+        ("In progress today".to_owned(), in_progress),
+        ("Blocked".to_owned(), blocked),
+    ];
+
+    println!(
+        "{}",
+        if standup_arguments.markdown {
+            format(&sections, |heading| format!("**{heading}**"), "-")
+        } else if standup_arguments.slack {
+            format(&sections, |heading| format!("*{heading}*"), "•")
+        } else {
+            format(&sections, |heading| format!("{heading}:"), "-")
+        }
+    );
+}
+
+/// The user `user_name` refers to, asking Hack'n'Plan who owns the API key for `@me`
+async fn find_user(client: &reqwest::Client, user_name: &str) -> (Id, String, String) {
+    let user_name = user_name.trim_start_matches('@');
+    let available_users = get_available_users(client).await;
+
+    let user_name = if user_name.eq_ignore_ascii_case("me") {
+        client
+            .get(format!(
+                "{API_ENDPOINT}/users/me",
+                API_ENDPOINT = api_endpoint()
+            ))
+            .header(
+                "Authorization",
+                format!("ApiKey {API_KEY}", API_KEY = *API_KEY),
+            )
+            .send_recorded()
+            .await
+            .and_then(|response| response.error_for_status())
+            .expect("To get the current user from Hack'n'Plan")
+            .json::<CurrentUser>()
+            .await
+            .expect("To deserialize the current user")
+            .username
+    } else {
+        user_name.to_owned()
+    };
+
+    available_users
+        .iter()
+        .find(|(_, _, username)| username.eq_ignore_ascii_case(&user_name))
+        .cloned()
+        .unwrap_or_else(|| panic!("{}", core::user_not_found(&user_name, &available_users)))
+}
+
+/// The last day before today that falls within the working week of the calendar
+fn previous_working_day(config: &Config) -> NaiveDate {
+    let today = config.calendar.today();
+
+    (1..=7)
+        .map(|days| today - chrono::Days::new(days))
+        .find(|day| (day.weekday().num_days_from_monday() as f32) < config.calendar.days_per_week)
+        .unwrap_or(today - chrono::Days::new(1))
+}
+
+fn line(work_item: &WorkItem) -> String {
+    format!("#{} {}", work_item.work_item_id, work_item.title)
+}
+
+/// `sections` below their heading, a bullet per work item
+fn format(
+    sections: &[(String, Vec<String>)],
+    heading: impl Fn(&str) -> String,
+    bullet: &str,
+) -> String {
+    sections
+        .iter()
+        .map(|(title, lines)| {
+            let lines = if lines.is_empty() {
+                format!("{bullet} Nothing")
+            } else {
+                lines
+                    .iter()
+                    .map(|line| format!("{bullet} {line}"))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            };
+            format!("{}\n{lines}", heading(title))
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use chrono::{Datelike, NaiveDate};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::history::Run;
+use crate::{current_project_id, output, paths, Id};
+
+const FILE_NAME: &str = "stats.jsonl";
+
+/// Whether `stats.enabled` is on in the config, set before anything gets created
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// What a single run created, a line in the stats file
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    /// Day of the run, like `2024-03-18`
+    date: String,
+    project_id: Id,
+    work_items: usize,
+    /// Work items that had an estimate and the hours they added up to
+    estimated_work_items: usize,
+    estimated_hours: f32,
+}
+
+#[derive(Args, Debug)]
+pub struct StatsArguments {
+    /// How many weeks back to show, including this one
+    #[arg(long, default_value_t = 8)]
+    weeks: u32,
+
+    /// Count the work items created in every project instead of this one
+    #[arg(long)]
+    all_projects: bool,
+}
+
+pub fn set(enabled: bool) {
+    ENABLED.set(enabled).expect("To only set the stats once");
+}
+
+/// Stats file with a JSON line per run, `~/.local/share/hnp/stats.jsonl` on Linux
+fn path() -> PathBuf {
+    paths::data_dir()
+        .expect("To find the user's data directory")
+        .join(FILE_NAME)
+}
+
+/// Adds what `run` created to the stats file, when the stats are turned on. Nothing but the
+/// counts is kept and nothing leaves this machine.
+pub fn record(run: &Run) {
+    if !ENABLED.get().copied().unwrap_or_default() || run.work_item_ids.is_empty() {
+        return;
+    }
+
+    let estimates = run
+        .payloads
+        .iter()
+        .filter_map(|payload| payload["estimatedCost"].as_f64())
+        .filter(|estimate| *estimate > 0.0)
+        .collect::<Vec<f64>>();
+    let entry = Entry {
+        date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        project_id: run.project_id,
+        work_items: run.work_item_ids.len(),
+        estimated_work_items: estimates.len(),
+        estimated_hours: estimates.iter().sum::<f64>() as f32,
+    };
+
+    // NOTE: Stats are nice to have, so failing to write them doesn't fail the run
+    let path = path();
+    if let Some(directory) = path.parent() {
+        let _ = fs::create_dir_all(directory);
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        let line = serde_json::to_string(&entry).expect("To serialize stats");
+        let _ = writeln!(file, "{line}");
+    }
+}
+
+/// Prints the work items created per week and their average estimate from the stats file
+pub fn run(stats_arguments: &StatsArguments) {
+    let entries = fs::read_to_string(path())
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+        .filter(|entry| stats_arguments.all_projects || entry.project_id == current_project_id())
+        .collect::<Vec<Entry>>();
+
+    if entries.is_empty() {
+        if ENABLED.get().copied().unwrap_or_default() {
+            output::info("No work items were created since the stats were turned on");
+        } else {
+            output::info(
+                "There are no stats yet, turn them on with `enabled = true` under `[stats]` in \
+                 the config",
+            );
+        }
+        return;
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let first_week = today
+        - chrono::Duration::weeks(i64::from(stats_arguments.weeks.saturating_sub(1)))
+        - chrono::Duration::days(i64::from(today.weekday().num_days_from_monday()));
+
+    // NOTE: Weeks without work items are listed as well, so gaps in the planning show
+    let mut weeks: BTreeMap<NaiveDate, (usize, usize, f32)> = BTreeMap::new();
+    let mut week = first_week;
+    while week <= today {
+        weeks.insert(week, (0, 0, 0.0));
+        week += chrono::Duration::weeks(1);
+    }
+    for entry in &entries {
+        let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") else {
+            continue;
+        };
+        if date < first_week {
+            continue;
+        }
+
+        let monday =
+            date - chrono::Duration::days(i64::from(date.weekday().num_days_from_monday()));
+        let (work_items, estimated_work_items, estimated_hours) =
+            weeks.entry(monday).or_insert((0, 0, 0.0));
+        *work_items += entry.work_items;
+        *estimated_work_items += entry.estimated_work_items;
+        *estimated_hours += entry.estimated_hours;
+    }
+
+    println!(
+        "{:<10} {:>10} {:>10} {:>10}",
+        "Week", "Items", "Estimated", "Average"
+    );
+    for (monday, (work_items, estimated_work_items, estimated_hours)) in &weeks {
+        let week = monday.iso_week();
+        println!(
+            "{:<10} {:>10} {:>10} {:>10}",
+            format!("{}-W{:02}", week.year(), week.week()),
+            work_items,
+            format!("{estimated_hours:.1}h"),
+            average(*estimated_hours, *estimated_work_items)
+        );
+    }
+
+    let work_items = weeks
+        .values()
+        .map(|(work_items, _, _)| work_items)
+        .sum::<usize>();
+    let estimated_work_items = weeks
+        .values()
+        .map(|(_, estimated_work_items, _)| estimated_work_items)
+        .sum::<usize>();
+    let estimated_hours = weeks
+        .values()
+        .map(|(_, _, estimated_hours)| estimated_hours)
+        .sum::<f32>();
+    println!();
+    output::info(format!(
+        "Created {work_items} work item(s) in the last {} week(s), {:.1} per week, estimated at \
+         {} on average",
+        weeks.len(),
+        work_items as f32 / weeks.len() as f32,
+        average(estimated_hours, estimated_work_items)
+    ));
+}
+
+/// Hours per estimated work item, `—` without any
+fn average(estimated_hours: f32, estimated_work_items: usize) -> String {
+    if estimated_work_items > 0 {
+        format!("{:.1}h", estimated_hours / estimated_work_items as f32)
+    } else {
+        "—".to_owned()
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use crate::config::{Config, EstimateMode};
+use crate::models::WorkItem;
+use crate::{estimate_with_sub_tasks, get_work_items, output, Ticket};
+
+/// How many of the most similar work items an estimate is suggested from
+const SIMILAR: usize = 5;
+
+/// Words too common in titles to tell work items apart
+const STOP_WORDS: [&str; 8] = ["the", "and", "for", "with", "from", "into", "when", "that"];
+
+/// Suggests an estimate for the tickets without one, the median of the work items in the same
+/// category with the most title words in common. Gives the tickets that estimate when `apply`.
+pub async fn estimates(
+    client: &reqwest::Client,
+    tickets: &mut [Ticket],
+    config: &Config,
+    apply: bool,
+) {
+    if tickets
+        .iter()
+        .all(|ticket| estimate_with_sub_tasks(ticket) > 0.0)
+    {
+        return;
+    }
+
+    let spinner = output::spinner("Fetching work items to suggest estimates from");
+    let work_items = get_work_items::<WorkItem>(client).await;
+    spinner.finish_and_clear();
+
+    let unit = match config.estimates.mode {
+        EstimateMode::Hours => "h",
+        EstimateMode::Points => " points",
+    };
+
+    for ticket in tickets
+        .iter_mut()
+        .filter(|ticket| estimate_with_sub_tasks(ticket) == 0.0)
+    {
+        let title_words = words(&ticket.title);
+        let mut similar = work_items
+            .iter()
+            .filter(|work_item| {
+                work_item
+                    .category
+                    .as_ref()
+                    .is_some_and(|category| category.category_id == ticket.category_id)
+            })
+            .filter_map(|work_item| {
+                let estimate = work_item
+                    .estimated_cost
+                    .filter(|estimate| *estimate > 0.0)?;
+                let other = words(&work_item.title);
+                let shared = title_words.intersection(&other).count();
+                // NOTE: Words in common relative to all words of both, so long titles don't win
+                // by size alone
+                let similarity = shared as f32 / title_words.union(&other).count().max(1) as f32;
+
+                (shared > 0).then_some((similarity, estimate, work_item))
+            })
+            .collect::<Vec<(f32, f32, &WorkItem)>>();
+
+        if similar.is_empty() {
+            output::info(format!(
+                "No similar work items to suggest an estimate for \"{}\" from",
+                ticket.title
+            ));
+            continue;
+        }
+
+        similar.sort_by(|(a, _, _), (b, _, _)| b.total_cmp(a));
+        similar.truncate(SIMILAR);
+
+        let mut estimates = similar
+            .iter()
+            .map(|(_, estimate, _)| *estimate)
+            .collect::<Vec<f32>>();
+        estimates.sort_by(f32::total_cmp);
+        let middle = estimates.len() / 2;
+        let median = if estimates.len() % 2 == 0 {
+            (estimates[middle - 1] + estimates[middle]) / 2.0
+        } else {
+            estimates[middle]
+        };
+
+        let sources = similar
+            .iter()
+            .map(|(_, estimate, work_item)| {
+                format!("#{} ({estimate}{unit})", work_item.work_item_id)
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        if apply {
+            ticket.estimated_cost = median;
+            output::info(format!(
+                "Estimated \"{}\" at ~{median}{unit}, the median of {sources}",
+                ticket.title
+            ));
+        } else {
+            output::info(format!(
+                "Suggesting ~{median}{unit} for \"{}\", the median of {sources}",
+                ticket.title
+            ));
+        }
+    }
+}
+
+/// Lowercase words of `title` that say something about the work
+fn words(title: &str) -> HashSet<String> {
+    title
+        .split(|character: char| !character.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
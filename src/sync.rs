@@ -0,0 +1,525 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use clap::Args;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::bulk::{self, Changes};
+use crate::config::Config;
+use crate::i18n::{Action, Message};
+use crate::models::{self, WorkItem};
+use crate::{
+    client, clone, find_board, get_models, get_work_items, history, input, output, parse_tickets,
+    text, upload_with_children, Arguments, CreateArguments, Id, Ticket, TICKET_SEPARATOR,
+};
+
+/// Remembers which work items the directory had, so deleted files can be noticed
+const MANIFEST_FILE_NAME: &str = ".hnp-sync.json";
+
+lazy_static! {
+    /// `<!-- hnp: 1234 -->` on the first line of a file links it to work item #1234
+    static ref HEADER_MATCHER: Regex =
+        Regex::new(r"^<!--\s*hnp:\s*(?P<id>\d+)\s*-->[ \t]*\r?\n?").expect("Header Regex to compile");
+}
+
+#[derive(Args, Debug)]
+pub struct SyncArguments {
+    /// Directory with a `.md` file per ticket, named after the ticket (e.g. `fix-jump-sound.md`)
+    directory: PathBuf,
+
+    /// Create tags that don't exist on Hack'n'Plan yet without asking
+    #[arg(long)]
+    create_missing_tags: bool,
+
+    /// Also honor tags, estimates, importance and due dates written in the description
+    #[arg(long)]
+    scan_description: bool,
+
+    /// Create, update and archive without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Compare every file with its work item, also the ones that didn't change since the last
+    /// sync. Overwrites changes made on Hack'n'Plan to those as well.
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    /// File name by work item id, as of the last sync
+    work_items: BTreeMap<Id, String>,
+    /// Hash of what each work item was synced from, see [`content_hash`]
+    #[serde(default)]
+    hashes: BTreeMap<Id, String>,
+}
+
+/// A ticket file in the synced directory
+struct TicketFile {
+    path: PathBuf,
+    /// From the header, `None` for files that weren't synced before
+    work_item_id: Option<Id>,
+    /// Everything below the header
+    contents: String,
+    board: Option<String>,
+}
+
+pub async fn run(arguments: &Arguments, sync_arguments: &SyncArguments, config: &Config) {
+    let directory = &sync_arguments.directory;
+    let manifest_path = directory.join(MANIFEST_FILE_NAME);
+    let manifest = fs::read_to_string(&manifest_path)
+        .ok()
+        .map(|contents| {
+            serde_json::from_str::<Manifest>(&contents)
+                .unwrap_or_else(|error| panic!("To parse {}: {error}", manifest_path.display()))
+        })
+        .unwrap_or_default();
+
+    let files = read_ticket_files(directory);
+
+    let client = client();
+
+    let spinner = output::spinner(Message::FetchingWorkItems);
+    let work_items = get_work_items::<WorkItem>(&client).await;
+    spinner.finish_and_clear();
+
+    let find_work_item = |id: Id| {
+        work_items
+            .iter()
+            .find(|work_item| work_item.work_item_id == id)
+    };
+
+    // NOTE: A header pointing at a work item that no longer exists is treated as a new file
+    let files = files
+        .into_iter()
+        .map(|mut file| {
+            if let Some(id) = file.work_item_id {
+                if find_work_item(id).is_none() {
+                    output::warn(format!(
+                        "Work item #{id} of {} doesn't exist anymore, creating it again",
+                        file.path.display()
+                    ));
+                    file.work_item_id = None;
+                }
+            }
+
+            file
+        })
+        .collect::<Vec<TicketFile>>();
+
+    let create_arguments = CreateArguments {
+        default_category: None,
+        create_missing_tags: sync_arguments.create_missing_tags,
+        fail_on_missing_tags: false,
+        milestone: None,
+        check_capacity: false,
+        auto_assign: None,
+        suggest_estimates: false,
+        apply_suggestions: false,
+        stage: None,
+        scan_description: sync_arguments.scan_description,
+        skip: 0,
+        limit: None,
+        batch_size: 1,
+        only: None,
+        pick: false,
+        yes: sync_arguments.yes,
+        annotate: None,
+        title_template: None,
+        input_format: None,
+        files: vec![],
+    };
+
+    let texts = files
+        .iter()
+        .map(|file| file.contents.as_str())
+        .collect::<Vec<&str>>();
+    let (mut tickets, metadata) = parse_tickets(
+        arguments,
+        &create_arguments,
+        config,
+        &client,
+        &texts,
+        &[],
+        None,
+    )
+    .await;
+
+    // NOTE: A board in the title wins over the one in the front matter, like with `hnp create`
+    for (file, ticket) in files.iter().zip(&mut tickets) {
+        if let Some(board) = file.board.as_ref().filter(|_| ticket.board_id == 0) {
+            ticket.board_id = find_board(&client, board).await;
+        }
+    }
+
+    let name = |items: &[(Id, String)], id: Id| {
+        let name = items
+            .iter()
+            .find(|(item_id, _)| *item_id == id)
+            .map(|(_, name)| name.clone())
+            .unwrap_or_default();
+
+        (id, name)
+    };
+    let users = metadata
+        .users
+        .iter()
+        .map(|(id, _, username)| (*id, username.clone()))
+        .collect::<Vec<(Id, String)>>();
+    let importance_levels = metadata
+        .importance_levels
+        .iter()
+        .map(|(id, name, _)| (*id, name.clone()))
+        .collect::<Vec<(Id, String)>>();
+
+    let mut to_create: Vec<(&TicketFile, &Ticket)> = vec![];
+    let mut to_update: Vec<(&TicketFile, &WorkItem, Changes)> = vec![];
+    let mut hashes = BTreeMap::new();
+    let mut unchanged = 0;
+    for (file, ticket) in files.iter().zip(&tickets) {
+        let Some(work_item) = file.work_item_id.and_then(find_work_item) else {
+            to_create.push((file, ticket));
+            continue;
+        };
+
+        // NOTE: Files that are the same as at the last sync are left alone without comparing,
+        // which also keeps changes made on Hack'n'Plan since then
+        let hash = content_hash(ticket);
+        let is_unchanged = manifest.hashes.get(&work_item.work_item_id) == Some(&hash);
+        hashes.insert(work_item.work_item_id, hash);
+        if is_unchanged && !sync_arguments.force {
+            unchanged += 1;
+            continue;
+        }
+
+        let tag_ids = work_item.tag_ids();
+        let user_ids = work_item.assigned_user_ids();
+
+        let changes = Changes {
+            title: Some(ticket.title.clone()).filter(|title| *title != work_item.title),
+            description: Some(ticket.description.clone()).filter(|description| {
+                description.trim() != work_item.description.as_deref().unwrap_or_default().trim()
+            }),
+            estimated_cost: Some(ticket.estimated_cost).filter(|estimated_cost| {
+                (estimated_cost - work_item.estimated_cost.unwrap_or_default()).abs() > f32::EPSILON
+            }),
+            add_tags: ticket
+                .tag_ids
+                .iter()
+                .filter(|id| !tag_ids.contains(id))
+                .map(|id| name(&metadata.tags, *id))
+                .collect(),
+            remove_tags: tag_ids
+                .iter()
+                .filter(|id| !ticket.tag_ids.contains(id))
+                .map(|id| name(&metadata.tags, *id))
+                .collect(),
+            assign: ticket
+                .assigned_user_ids
+                .iter()
+                .filter(|id| !user_ids.contains(id))
+                .map(|id| name(&users, *id))
+                .collect(),
+            unassign: user_ids
+                .iter()
+                .filter(|id| !ticket.assigned_user_ids.contains(id))
+                .map(|id| name(&users, *id))
+                .collect(),
+            importance_level: Some(name(&importance_levels, ticket.importance_level_id)).filter(
+                |(id, _)| {
+                    work_item
+                        .importance_level
+                        .as_ref()
+                        .map(|importance_level| importance_level.importance_level_id)
+                        != Some(*id)
+                },
+            ),
+            category: Some(name(&metadata.categories, ticket.category_id)).filter(|(id, _)| {
+                work_item
+                    .category
+                    .as_ref()
+                    .map(|category| category.category_id)
+                    != Some(*id)
+            }),
+        };
+
+        if !changes.is_empty() {
+            to_update.push((file, work_item, changes));
+        }
+    }
+
+    // NOTE: Work items of files that were deleted since the last sync, unless already completed
+    let synced_ids = files
+        .iter()
+        .filter_map(|file| file.work_item_id)
+        .collect::<Vec<Id>>();
+    let to_archive = manifest
+        .work_items
+        .iter()
+        .filter(|(id, _)| !synced_ids.contains(id))
+        .filter_map(|(id, file_name)| Some((find_work_item(*id)?, file_name)))
+        .filter(|(work_item, _)| !work_item.is_completed())
+        .collect::<Vec<(&WorkItem, &String)>>();
+
+    for (file, ticket) in &to_create {
+        output::info(format!(
+            "Create \"{}\" from {}",
+            ticket.title,
+            file.path.display()
+        ));
+    }
+    for (file, work_item, changes) in &to_update {
+        output::info(format!(
+            "Update #{} {} from {}",
+            work_item.work_item_id,
+            work_item.title,
+            file.path.display()
+        ));
+
+        for line in changes.describe() {
+            println!("    {line}");
+        }
+    }
+    for (work_item, file_name) in &to_archive {
+        output::info(format!(
+            "Archive #{} {}, {file_name} was deleted",
+            work_item.work_item_id, work_item.title
+        ));
+    }
+
+    if unchanged > 0 {
+        output::info(format!(
+            "Skipping {unchanged} file(s) that didn't change since the last sync, use --force to compare them too"
+        ));
+    }
+
+    if to_create.is_empty() && to_update.is_empty() && to_archive.is_empty() {
+        output::info(Message::NothingToSync(files.len()));
+
+        // NOTE: Files synced before hashes were kept get theirs now
+        if manifest.hashes != hashes && !arguments.dry_run {
+            write_manifest(
+                &manifest_path,
+                &Manifest {
+                    work_items: manifest.work_items,
+                    hashes,
+                },
+            );
+        }
+        return;
+    }
+
+    if arguments.dry_run {
+        return;
+    }
+
+    if !(to_create.is_empty() && to_update.is_empty()) {
+        let confirmed = confirm(
+            arguments,
+            sync_arguments,
+            Message::ConfirmSync {
+                create: to_create.len(),
+                update: to_update.len(),
+            },
+        );
+
+        if !confirmed {
+            output::info(Message::NothingWas(Action::Changed));
+            return;
+        }
+    }
+
+    let run = history::Run::new(&format!("sync {}", directory.display()));
+    if !to_create.is_empty() {
+        run.start();
+    }
+    let run = Mutex::new(run);
+
+    let mut work_item_files = BTreeMap::new();
+    for file in &files {
+        if let Some(id) = file.work_item_id {
+            work_item_files.insert(id, file_name(&file.path));
+        }
+    }
+
+    let progress = output::progress(to_create.len() + to_update.len(), "Syncing tickets");
+    for (file, ticket) in &to_create {
+        let work_item_id = upload_with_children(&client, ticket, &run).await;
+        write_header(file, work_item_id);
+        work_item_files.insert(work_item_id, file_name(&file.path));
+        hashes.insert(work_item_id, content_hash(ticket));
+
+        progress.suspend(|| {
+            output::success(Message::CreatedWorkItem {
+                id: work_item_id,
+                title: &ticket.title,
+            })
+        });
+        progress.inc(1);
+    }
+    for (_, work_item, changes) in &to_update {
+        bulk::apply(&client, work_item.work_item_id, changes).await;
+
+        progress.suspend(|| {
+            output::success(Message::UpdatedWorkItem {
+                id: work_item.work_item_id,
+                title: &work_item.title,
+            })
+        });
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    let mut run = run
+        .into_inner()
+        .expect("To have recorded every upload in the run history");
+    if !to_create.is_empty() {
+        run.finish();
+        output::info(Message::UndoCreated(&run.id));
+    }
+
+    if !to_archive.is_empty() {
+        let confirmed = confirm(
+            arguments,
+            sync_arguments,
+            Message::ConfirmArchive(to_archive.len()),
+        );
+
+        if confirmed {
+            let stages = get_models::<models::Stage>(&client, "stages").await;
+            let stage = stages
+                .iter()
+                .find(|stage| stage.status == "completed")
+                .expect("To find a stage for completed work items");
+
+            for (work_item, _) in &to_archive {
+                clone::set_stage(&client, work_item.work_item_id, stage.stage_id).await;
+                output::success(Message::ArchivedWorkItem {
+                    id: work_item.work_item_id,
+                    title: &work_item.title,
+                    stage: &stage.name,
+                });
+            }
+        } else {
+            // NOTE: They're left out of the manifest either way, so this isn't asked every sync
+            output::info("The work items of deleted files were left as they are");
+        }
+    }
+
+    write_manifest(
+        &manifest_path,
+        &Manifest {
+            work_items: work_item_files,
+            hashes,
+        },
+    );
+}
+
+fn write_manifest(path: &Path, manifest: &Manifest) {
+    fs::write(
+        path,
+        serde_json::to_string_pretty(manifest).expect("To serialize the sync manifest"),
+    )
+    .unwrap_or_else(|_| panic!("To write {}", path.display()));
+}
+
+/// Hash of everything `ticket` would send, so changes to the config that resolve the markup
+/// differently count as well. FNV-1a, as it has to stay the same between builds.
+fn content_hash(ticket: &Ticket) -> String {
+    let hash = ticket
+        .payload()
+        .to_string()
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+
+    format!("{hash:016x}")
+}
+
+fn confirm(arguments: &Arguments, sync_arguments: &SyncArguments, question: Message) -> bool {
+    if sync_arguments.yes {
+        true
+    } else if arguments.non_interactive {
+        output::error(Message::NonInteractiveWithoutYes(Action::Changed));
+        output::exit_with_error();
+    } else {
+        output::confirm(question)
+    }
+}
+
+/// The `.md` files in `directory` by name, each holding a single ticket
+fn read_ticket_files(directory: &Path) -> Vec<TicketFile> {
+    let mut paths = fs::read_dir(directory)
+        .unwrap_or_else(|_| panic!("To read directory {}", directory.display()))
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|extension| extension == "md")
+                && !file_name(path).starts_with('.')
+        })
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            output::set_context(Some(path.display().to_string()));
+
+            let contents = text::read(&path);
+            let (work_item_id, contents) = match HEADER_MATCHER.captures(&contents) {
+                Some(captures) => (
+                    captures["id"].parse().ok(),
+                    &contents[captures.get(0).expect("Header to match").end()..],
+                ),
+                None => (None, contents.as_str()),
+            };
+
+            let (front_matter, contents) =
+                input::FrontMatter::parse(contents).unwrap_or_else(|error| panic!("{error}"));
+            let contents = front_matter.apply(contents);
+
+            let tickets = TICKET_SEPARATOR
+                .split(&contents)
+                .filter(|text| !text.trim().is_empty())
+                .count();
+            if tickets != 1 {
+                panic!("file to contain exactly one ticket, found {tickets}");
+            }
+
+            output::set_context(None);
+
+            TicketFile {
+                path,
+                work_item_id,
+                contents,
+                board: front_matter.board,
+            }
+        })
+        .collect()
+}
+
+/// Links `file` to `work_item_id` by putting the header above its contents, replacing the old one
+fn write_header(file: &TicketFile, work_item_id: Id) {
+    let (contents, encoding) = text::read_as_is(&file.path);
+
+    let contents = HEADER_MATCHER.replace(&contents, "");
+
+    text::write(
+        &file.path,
+        &format!("<!-- hnp: {work_item_id} -->\n{contents}"),
+        encoding,
+    );
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::Path;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16_LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16_BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// Encodings hnp reads text files in, and writes them back in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Utf8,
+    Utf8WithBom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Encoding of `bytes` going by its byte order mark, UTF-8 without one
+    fn detect(bytes: &[u8]) -> Encoding {
+        if bytes.starts_with(UTF8_BOM) {
+            Encoding::Utf8WithBom
+        } else if bytes.starts_with(UTF16_LE_BOM) {
+            Encoding::Utf16Le
+        } else if bytes.starts_with(UTF16_BE_BOM) {
+            Encoding::Utf16Be
+        } else {
+            Encoding::Utf8
+        }
+    }
+
+    /// `text` in this encoding, byte order mark included
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        match self {
+            Encoding::Utf8 => text.as_bytes().to_vec(),
+            Encoding::Utf8WithBom => [UTF8_BOM, text.as_bytes()].concat(),
+            Encoding::Utf16Le => UTF16_LE_BOM
+                .iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect(),
+            Encoding::Utf16Be => UTF16_BE_BOM
+                .iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+                .collect(),
+        }
+    }
+}
+
+/// `bytes` as text, without the byte order mark editors on Windows like to put in front. UTF-16
+/// is recognized by its byte order mark, anything else has to be UTF-8.
+pub fn decode(bytes: &[u8]) -> Option<String> {
+    let utf16 = |rest: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units = rest
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect::<Vec<u16>>();
+        String::from_utf16(&units).ok()
+    };
+
+    match Encoding::detect(bytes) {
+        Encoding::Utf8 => String::from_utf8(bytes.to_vec()).ok(),
+        Encoding::Utf8WithBom => String::from_utf8(bytes[UTF8_BOM.len()..].to_vec()).ok(),
+        Encoding::Utf16Le => utf16(&bytes[UTF16_LE_BOM.len()..], u16::from_le_bytes),
+        Encoding::Utf16Be => utf16(&bytes[UTF16_BE_BOM.len()..], u16::from_be_bytes),
+    }
+}
+
+/// Name of the encoding of `bytes`, when it's one hnp can read
+pub fn encoding(bytes: &[u8]) -> Option<&'static str> {
+    decode(bytes)?;
+
+    Some(match Encoding::detect(bytes) {
+        Encoding::Utf8 => "UTF-8",
+        Encoding::Utf8WithBom => "UTF-8 with a byte order mark",
+        Encoding::Utf16Le => "UTF-16 LE",
+        Encoding::Utf16Be => "UTF-16 BE",
+    })
+}
+
+/// Contents of the text file at `path` as they are with the encoding to write them back in
+pub fn read_as_is(path: &Path) -> (String, Encoding) {
+    let bytes = fs::read(path).unwrap_or_else(|_| panic!("To read {}", path.display()));
+    let contents =
+        decode(&bytes).unwrap_or_else(|| panic!("To read {} as UTF-8 or UTF-16", path.display()));
+
+    (contents, Encoding::detect(&bytes))
+}
+
+/// Writes `contents` to the text file at `path` in `encoding`, the one it was read in
+pub fn write(path: &Path, contents: &str, encoding: Encoding) {
+    fs::write(path, encoding.encode(contents))
+        .unwrap_or_else(|_| panic!("To write to {}", path.display()));
+}
+
+/// Contents of the text file at `path` with `\n` line endings, whatever encoding and line
+/// endings it was saved with
+pub fn read(path: &Path) -> String {
+    read_as_is(path).0.replace("\r\n", "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, Encoding};
+
+    #[test]
+    fn writes_back_in_the_encoding_it_read() {
+        let text = "Fix the jump\r\n===\r\nÉén keer <!-- hnp: 1001 -->\r\n";
+
+        for encoding in [
+            Encoding::Utf8,
+            Encoding::Utf8WithBom,
+            Encoding::Utf16Le,
+            Encoding::Utf16Be,
+        ] {
+            let bytes = encoding.encode(text);
+
+            assert_eq!(Encoding::detect(&bytes), encoding);
+            assert_eq!(decode(&bytes).as_deref(), Some(text));
+        }
+    }
+
+    #[test]
+    fn keeps_the_byte_order_mark() {
+        assert_eq!(Encoding::Utf8WithBom.encode("a"), b"\xEF\xBB\xBFa");
+        assert_eq!(Encoding::Utf16Le.encode("a"), b"\xFF\xFEa\x00");
+        assert_eq!(Encoding::Utf16Be.encode("a"), b"\xFE\xFF\x00a");
+    }
+}
@@ -0,0 +1,152 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::models::Stage;
+use crate::{input, output};
+
+lazy_static! {
+    static ref TASK_MATCHER: Regex = Regex::new(concat!(
+        r"^(?<completed>x[ \t]+(?:\d{4}-\d{2}-\d{2}[ \t]+)?)?",
+        r"(?:\((?<priority>[A-Z])\)[ \t]+)?",
+        r"(?:\d{4}-\d{2}-\d{2}[ \t]+)?",
+        r"(?<text>.*)$"
+    ))
+    .expect("todo.txt task Regex to compile");
+    static ref PROJECT_MATCHER: Regex =
+        Regex::new(r"(?:^|[ \t])\+(?<name>\S+)").expect("Project Regex to compile");
+    static ref CONTEXT_MATCHER: Regex =
+        Regex::new(r"(?:^|[ \t])@(?<name>\S+)").expect("Context Regex to compile");
+    static ref KEY_VALUE_MATCHER: Regex = Regex::new(r"(?:^|[ \t])(?<key>due|t|pri):(?<value>\S+)")
+        .expect("Key value Regex to compile");
+}
+
+/// Converts a todo.txt file, a task per line, to tickets in hnp's own syntax.
+///
+/// Priorities become importance levels in the order Hack'n'Plan lists them (`(A)` is the first,
+/// letters past the last level get the last one), `+project` becomes the category, `@context`
+/// becomes a tag, `due:` and the threshold date `t:` become due and start dates, and completed
+/// tasks go to the first completed stage.
+pub fn to_tickets(contents: &str, stages: &[Stage], importance_levels: usize) -> String {
+    let completed_stage = stages.iter().find(|stage| stage.status == "completed");
+
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let captures = TASK_MATCHER.captures(line.trim())?;
+            let text = &captures["text"];
+
+            let mut tokens = vec![];
+            tokens.extend(
+                PROJECT_MATCHER
+                    .captures_iter(text)
+                    .map(|project| input::hash_tag(&project["name"])),
+            );
+            tokens.extend(
+                CONTEXT_MATCHER
+                    .captures_iter(text)
+                    .map(|context| input::hash_tag(&context["name"])),
+            );
+
+            // NOTE: Completed tasks keep their priority as `pri:A`, as they can't start with it
+            let mut priority = captures.name("priority").map(|priority| priority.as_str());
+            for key_value in KEY_VALUE_MATCHER.captures_iter(text) {
+                let value = key_value.name("value").map_or("", |value| value.as_str());
+                match &key_value["key"] {
+                    "due" => tokens.push(format!("due:{value}")),
+                    "t" => tokens.push(format!("start:{value}")),
+                    _ => priority = priority.or(Some(value)),
+                }
+            }
+
+            if let Some(priority) = priority.and_then(|priority| priority.chars().next()) {
+                let position = (priority as usize)
+                    .saturating_sub('A' as usize)
+                    .min(importance_levels.saturating_sub(1));
+                tokens.push(format!("!{}", position + 1));
+            }
+
+            if captures.name("completed").is_some() {
+                match completed_stage {
+                    Some(stage) => tokens.push(format!("stage:\"{}\"", stage.name)),
+                    None => output::warn(format!(
+                        "No completed stage for the completed task \"{text}\", it stays open"
+                    )),
+                }
+            }
+
+            let title = PROJECT_MATCHER.replace_all(text, "");
+            let title = CONTEXT_MATCHER.replace_all(&title, "");
+            let title = KEY_VALUE_MATCHER.replace_all(&title, "");
+
+            Some(format!("{} {}\n===\n", title.trim(), tokens.join(" ")))
+        })
+        .collect::<Vec<String>>()
+        .join("---\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_tickets;
+    use crate::models::Stage;
+
+    fn stages() -> Vec<Stage> {
+        [(1, "Planned", "created"), (4, "Completed", "completed")]
+            .into_iter()
+            .map(|(stage_id, name, status)| Stage {
+                stage_id,
+                name: name.to_owned(),
+                status: status.to_owned(),
+                is_unblocker: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn turns_priorities_into_importance_levels() {
+        assert_eq!(
+            to_tickets("(A) Fix jump", &stages(), 4),
+            "Fix jump !1\n===\n"
+        );
+        assert_eq!(
+            to_tickets("(C) Fix jump", &stages(), 4),
+            "Fix jump !3\n===\n"
+        );
+    }
+
+    #[test]
+    fn gives_priorities_past_the_last_level_the_last_level() {
+        assert_eq!(
+            to_tickets("(Z) Fix jump", &stages(), 4),
+            "Fix jump !4\n===\n"
+        );
+    }
+
+    #[test]
+    fn completes_done_tasks_with_their_kept_priority() {
+        assert_eq!(
+            to_tickets("x 2024-01-01 Fix jump pri:B", &stages(), 4),
+            "Fix jump !2 stage:\"Completed\"\n===\n"
+        );
+    }
+
+    #[test]
+    fn moves_projects_and_contexts_out_of_the_title() {
+        assert_eq!(
+            to_tickets("Fix +programming jump @phone", &stages(), 4),
+            "Fix jump #programming #phone\n===\n"
+        );
+    }
+
+    #[test]
+    fn turns_thresholds_into_start_dates() {
+        assert_eq!(
+            to_tickets(
+                "Fix jump t:2024-02-01 due:2024-03-01\nShip it",
+                &stages(),
+                4
+            ),
+            "Fix jump start:2024-02-01 due:2024-03-01\n===\n---\nShip it \n===\n"
+        );
+    }
+}
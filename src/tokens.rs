@@ -0,0 +1,70 @@
+use crate::config::{CustomToken, TokenField};
+
+/// Value produced by one of the custom tokens from the config, e.g. tag "platform-ps5"
+#[derive(Debug)]
+pub struct CustomTokenMatch {
+    pub field: TokenField,
+    pub value: String,
+}
+
+/// Runs every custom token over `title`, expanding capture groups (`$1`, `${name}`) into values
+pub fn match_custom_tokens(title: &str, tokens: &[CustomToken]) -> Vec<CustomTokenMatch> {
+    tokens
+        .iter()
+        .flat_map(|token| {
+            token.pattern.captures_iter(title).map(|captures| {
+                let mut value = String::new();
+                captures.expand(&token.value, &mut value);
+
+                CustomTokenMatch {
+                    field: token.field,
+                    value: value.trim().to_owned(),
+                }
+            })
+        })
+        .collect::<Vec<CustomTokenMatch>>()
+}
+
+pub fn strip_custom_tokens(title: &str, tokens: &[CustomToken]) -> String {
+    tokens.iter().fold(title.to_owned(), |title, token| {
+        token.pattern.replace_all(&title, "").into_owned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{match_custom_tokens, strip_custom_tokens};
+    use crate::config::{CustomToken, TokenField};
+
+    fn token(pattern: &str, field: &str, value: &str) -> CustomToken {
+        toml::from_str(&format!(
+            "pattern = '{pattern}'\nfield = \"{field}\"\nvalue = \"{value}\""
+        ))
+        .expect("To parse the custom token")
+    }
+
+    #[test]
+    fn expands_capture_groups_into_values() {
+        let tokens = [
+            token(r"\bMS(\d+)\b", "milestone", "$1"),
+            token(r"\bplatform:(?<name>\w+)", "tag", "platform-${name}"),
+        ];
+
+        let matches = match_custom_tokens("Fix the jump MS12 platform:ps5", &tokens)
+            .into_iter()
+            .map(|token| (token.field, token.value))
+            .collect::<Vec<(TokenField, String)>>();
+
+        assert_eq!(
+            matches,
+            [
+                (TokenField::Milestone, "12".to_owned()),
+                (TokenField::Tag, "platform-ps5".to_owned())
+            ]
+        );
+        assert_eq!(
+            strip_custom_tokens("Fix the jump MS12 platform:ps5", &tokens).trim(),
+            "Fix the jump"
+        );
+    }
+}
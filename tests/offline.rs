@@ -0,0 +1,51 @@
+//! Runs `hnp` on the example tickets against an exported metadata snapshot, without network or an
+//! API key, like CI can
+
+use std::process::{Command, Output};
+
+/// `hnp create example_tasks.txt --offline` with `arguments`, against `tests/fixtures/metadata.json`
+fn create_offline(arguments: &[&str]) -> Output {
+    // NOTE: An empty home, so no config or history of the user is picked up
+    let home = env!("CARGO_TARGET_TMPDIR");
+
+    Command::new(env!("CARGO_BIN_EXE_hnp"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["create", "example_tasks.txt", "--offline"])
+        .args(["--metadata", "tests/fixtures/metadata.json"])
+        .args(arguments)
+        .env_remove("HACKNPLAN_API_KEY")
+        .env_remove("HACKNPLAN_PROJECT_ID")
+        .env_remove("RUST_BACKTRACE")
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home)
+        .env("XDG_DATA_HOME", home)
+        .env("XDG_CACHE_HOME", home)
+        .env("LANG", "C")
+        .output()
+        .expect("To run hnp")
+}
+
+#[test]
+fn parses_the_example_tasks_offline() {
+    let output = create_offline(&["--limit", "1"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains(r#""title": "✨ Example task""#), "{stdout}");
+    // NOTE: #bug, !urgent and @name resolve to the ids in the snapshot
+    assert!(stdout.contains(r#""categoryId": 2"#), "{stdout}");
+    assert!(stdout.contains(r#""importanceLevelId": 1"#), "{stdout}");
+    assert!(stdout.contains(r#""estimatedCost": 8.0"#), "{stdout}");
+}
+
+#[test]
+fn reports_the_example_ticket_without_a_category() {
+    let output = create_offline(&[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("Another issue that will fail because u didnt specify a category"),
+        "{stderr}"
+    );
+}